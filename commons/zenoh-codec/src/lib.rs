@@ -18,6 +18,7 @@ mod common;
 mod core;
 mod scouting;
 mod transport;
+mod version;
 mod zenoh;
 
 use zenoh_protocol::{core::Reliability, zenoh::ReplyContext};
@@ -32,10 +33,21 @@ pub trait RCodec<Message, Buffer> {
     fn read(self, buffer: Buffer) -> Result<Message, Self::Error>;
 }
 
-#[derive(Clone, Copy, Default)]
+#[derive(Clone, Copy, Default, Debug, PartialEq, Eq)]
 #[non_exhaustive]
 pub struct Zenoh060;
 
+/// The codec of a version of the zenoh protocol, as negotiated at the establishment of a transport.
+///
+/// Messages are encoded by [`Zenoh060`], which implements the latest version of the protocol:
+/// the features the negotiated version lacks are left out when writing and refused when reading.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct Zenoh060Version {
+    pub version: u8,
+    pub codec: Zenoh060,
+}
+
 #[derive(Clone, Copy, Default)]
 #[non_exhaustive]
 pub struct Zenoh060Header {
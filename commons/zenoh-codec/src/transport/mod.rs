@@ -17,6 +17,7 @@ mod init;
 mod join;
 mod keepalive;
 mod open;
+mod pingpong;
 
 use crate::{RCodec, WCodec, Zenoh060, Zenoh060Header};
 use zenoh_buffers::{
@@ -47,6 +48,8 @@ where
             TransportBody::Join(b) => self.write(&mut *writer, b),
             TransportBody::Close(b) => self.write(&mut *writer, b),
             TransportBody::KeepAlive(b) => self.write(&mut *writer, b),
+            TransportBody::Ping(b) => self.write(&mut *writer, b),
+            TransportBody::Pong(b) => self.write(&mut *writer, b),
             TransportBody::Frame(b) => self.write(&mut *writer, b),
         }
     }
@@ -87,6 +90,13 @@ where
             tmsg::id::JOIN => TransportBody::Join(codec.read(&mut *reader)?),
             tmsg::id::CLOSE => TransportBody::Close(codec.read(&mut *reader)?),
             tmsg::id::KEEP_ALIVE => TransportBody::KeepAlive(codec.read(&mut *reader)?),
+            tmsg::id::PING_PONG => {
+                if imsg::has_flag(codec.header, tmsg::flag::P) {
+                    TransportBody::Ping(codec.read(&mut *reader)?)
+                } else {
+                    TransportBody::Pong(codec.read(&mut *reader)?)
+                }
+            }
             tmsg::id::PRIORITY | tmsg::id::FRAME => TransportBody::Frame(codec.read(&mut *reader)?),
             _ => return Err(DidntRead),
         };
@@ -0,0 +1,127 @@
+//
+// Copyright (c) 2022 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+use crate::{RCodec, WCodec, Zenoh060, Zenoh060Header};
+use zenoh_buffers::{
+    reader::{DidntRead, Reader},
+    writer::{DidntWrite, Writer},
+};
+use zenoh_protocol::{
+    common::imsg,
+    core::ZInt,
+    transport::{tmsg, Ping, Pong},
+};
+
+// Ping
+impl<W> WCodec<&Ping, &mut W> for Zenoh060
+where
+    W: Writer,
+{
+    type Output = Result<(), DidntWrite>;
+
+    fn write(self, writer: &mut W, x: &Ping) -> Self::Output {
+        // Header
+        let header = tmsg::id::PING_PONG | tmsg::flag::P;
+        self.write(&mut *writer, header)?;
+
+        // Body
+        self.write(&mut *writer, x.hash)?;
+        Ok(())
+    }
+}
+
+impl<R> RCodec<Ping, &mut R> for Zenoh060
+where
+    R: Reader,
+{
+    type Error = DidntRead;
+
+    fn read(self, reader: &mut R) -> Result<Ping, Self::Error> {
+        let codec = Zenoh060Header {
+            header: self.read(&mut *reader)?,
+            ..Default::default()
+        };
+        codec.read(reader)
+    }
+}
+
+impl<R> RCodec<Ping, &mut R> for Zenoh060Header
+where
+    R: Reader,
+{
+    type Error = DidntRead;
+
+    fn read(self, reader: &mut R) -> Result<Ping, Self::Error> {
+        if imsg::mid(self.header) != tmsg::id::PING_PONG
+            || !imsg::has_flag(self.header, tmsg::flag::P)
+        {
+            return Err(DidntRead);
+        }
+
+        let hash: ZInt = self.codec.read(&mut *reader)?;
+
+        Ok(Ping { hash })
+    }
+}
+
+// Pong
+impl<W> WCodec<&Pong, &mut W> for Zenoh060
+where
+    W: Writer,
+{
+    type Output = Result<(), DidntWrite>;
+
+    fn write(self, writer: &mut W, x: &Pong) -> Self::Output {
+        // Header
+        let header = tmsg::id::PING_PONG;
+        self.write(&mut *writer, header)?;
+
+        // Body
+        self.write(&mut *writer, x.hash)?;
+        Ok(())
+    }
+}
+
+impl<R> RCodec<Pong, &mut R> for Zenoh060
+where
+    R: Reader,
+{
+    type Error = DidntRead;
+
+    fn read(self, reader: &mut R) -> Result<Pong, Self::Error> {
+        let codec = Zenoh060Header {
+            header: self.read(&mut *reader)?,
+            ..Default::default()
+        };
+        codec.read(reader)
+    }
+}
+
+impl<R> RCodec<Pong, &mut R> for Zenoh060Header
+where
+    R: Reader,
+{
+    type Error = DidntRead;
+
+    fn read(self, reader: &mut R) -> Result<Pong, Self::Error> {
+        if imsg::mid(self.header) != tmsg::id::PING_PONG
+            || imsg::has_flag(self.header, tmsg::flag::P)
+        {
+            return Err(DidntRead);
+        }
+
+        let hash: ZInt = self.codec.read(&mut *reader)?;
+
+        Ok(Pong { hash })
+    }
+}
@@ -0,0 +1,180 @@
+//
+// Copyright (c) 2022 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+use crate::{RCodec, WCodec, Zenoh060, Zenoh060Version};
+use alloc::borrow::Cow;
+use zenoh_buffers::{
+    reader::{BacktrackableReader, DidntRead, Reader},
+    writer::{DidntWrite, Writer},
+};
+use zenoh_protocol::{
+    defaults::{MIN_VERSION, VERSION},
+    transport::{Frame, FramePayload, TransportBody, TransportMessage},
+    zenoh::{ZenohBody, ZenohMessage},
+};
+
+// The versions of the protocol introducing each feature
+const PING_PONG_VERSION: u8 = 0x08;
+const LINK_WEIGHTS_VERSION: u8 = 0x08;
+
+impl Zenoh060Version {
+    /// Returns the codec of the given version of the protocol, if supported.
+    pub const fn new(version: u8) -> Option<Self> {
+        if version >= MIN_VERSION && version <= VERSION {
+            Some(Self {
+                version,
+                codec: Zenoh060,
+            })
+        } else {
+            None
+        }
+    }
+
+    /// Returns the codec of the latest version of the protocol.
+    pub const fn latest() -> Self {
+        Self {
+            version: VERSION,
+            codec: Zenoh060,
+        }
+    }
+
+    /// Whether this version has the ping and pong transport messages.
+    pub const fn has_ping_pong(&self) -> bool {
+        self.version >= PING_PONG_VERSION
+    }
+
+    /// Whether this version has the link weights of the link states.
+    pub const fn has_link_weights(&self) -> bool {
+        self.version >= LINK_WEIGHTS_VERSION
+    }
+
+    /// Whether the given zenoh message can be exchanged with this version as is.
+    pub fn is_supported(&self, x: &ZenohMessage) -> bool {
+        match &x.body {
+            ZenohBody::LinkStateList(l) => {
+                self.has_link_weights() || l.link_states.iter().all(|ls| ls.weights.is_none())
+            }
+            _ => true,
+        }
+    }
+
+    fn is_transport_supported(&self, x: &TransportMessage) -> bool {
+        match &x.body {
+            TransportBody::Ping(_) | TransportBody::Pong(_) => self.has_ping_pong(),
+            TransportBody::Frame(Frame {
+                payload: FramePayload::Messages { messages },
+                ..
+            }) => messages.iter().all(|m| self.is_supported(m)),
+            _ => true,
+        }
+    }
+
+    // Leaves out of a zenoh message the features this version lacks
+    fn downgrade<'a>(&self, x: &'a ZenohMessage) -> Cow<'a, ZenohMessage> {
+        if self.is_supported(x) {
+            return Cow::Borrowed(x);
+        }
+        let mut x = x.clone();
+        if let ZenohBody::LinkStateList(l) = &mut x.body {
+            for ls in l.link_states.iter_mut() {
+                ls.weights = None;
+            }
+        }
+        Cow::Owned(x)
+    }
+}
+
+impl Default for Zenoh060Version {
+    fn default() -> Self {
+        Self::latest()
+    }
+}
+
+// TransportMessage
+impl<W> WCodec<&TransportMessage, &mut W> for Zenoh060Version
+where
+    W: Writer,
+{
+    type Output = Result<(), DidntWrite>;
+
+    fn write(self, writer: &mut W, x: &TransportMessage) -> Self::Output {
+        match &x.body {
+            TransportBody::Ping(_) | TransportBody::Pong(_) if !self.has_ping_pong() => {
+                Err(DidntWrite)
+            }
+            TransportBody::Frame(Frame {
+                channel,
+                sn,
+                payload: FramePayload::Messages { messages },
+            }) if !self.is_transport_supported(x) => {
+                let frame = TransportMessage {
+                    body: TransportBody::Frame(Frame {
+                        channel: *channel,
+                        sn: *sn,
+                        payload: FramePayload::Messages {
+                            messages: messages
+                                .iter()
+                                .map(|m| self.downgrade(m).into_owned())
+                                .collect(),
+                        },
+                    }),
+                    ..x.clone()
+                };
+                self.codec.write(&mut *writer, &frame)
+            }
+            _ => self.codec.write(&mut *writer, x),
+        }
+    }
+}
+
+impl<R> RCodec<TransportMessage, &mut R> for Zenoh060Version
+where
+    R: Reader + BacktrackableReader,
+{
+    type Error = DidntRead;
+
+    fn read(self, reader: &mut R) -> Result<TransportMessage, Self::Error> {
+        let x: TransportMessage = self.codec.read(&mut *reader)?;
+        if !self.is_transport_supported(&x) {
+            return Err(DidntRead);
+        }
+        Ok(x)
+    }
+}
+
+// ZenohMessage
+impl<W> WCodec<&ZenohMessage, &mut W> for Zenoh060Version
+where
+    W: Writer,
+{
+    type Output = Result<(), DidntWrite>;
+
+    fn write(self, writer: &mut W, x: &ZenohMessage) -> Self::Output {
+        self.codec.write(&mut *writer, self.downgrade(x).as_ref())
+    }
+}
+
+impl<R> RCodec<ZenohMessage, &mut R> for Zenoh060Version
+where
+    R: Reader,
+{
+    type Error = DidntRead;
+
+    fn read(self, reader: &mut R) -> Result<ZenohMessage, Self::Error> {
+        let x: ZenohMessage = self.codec.read(&mut *reader)?;
+        if !self.is_supported(&x) {
+            return Err(DidntRead);
+        }
+        Ok(x)
+    }
+}
@@ -42,6 +42,9 @@ where
         if x.locators.is_some() {
             options |= zmsg::link_state::LOC;
         }
+        if x.weights.is_some() {
+            options |= zmsg::link_state::WGT;
+        }
         self.write(&mut *writer, options)?;
 
         // Body
@@ -61,6 +64,14 @@ where
         for l in x.links.iter() {
             self.write(&mut *writer, *l)?;
         }
+        if let Some(weights) = x.weights.as_ref() {
+            if weights.len() != x.links.len() {
+                return Err(DidntWrite);
+            }
+            for w in weights.iter() {
+                self.write(&mut *writer, *w)?;
+            }
+        }
 
         Ok(())
     }
@@ -100,6 +111,16 @@ where
             let l: ZInt = self.read(&mut *reader)?;
             links.push(l);
         }
+        let weights = if imsg::has_option(options, zmsg::link_state::WGT) {
+            let mut weights: Vec<ZInt> = Vec::with_capacity(len);
+            for _ in 0..len {
+                let w: ZInt = self.read(&mut *reader)?;
+                weights.push(w);
+            }
+            Some(weights)
+        } else {
+            None
+        };
 
         Ok(LinkState {
             psid,
@@ -108,6 +129,7 @@ where
             whatami,
             locators,
             links,
+            weights,
         })
     }
 }
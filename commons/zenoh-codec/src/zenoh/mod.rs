@@ -152,6 +152,8 @@ where
                 reliability: self.reliability,
             },
             routing_context,
+            ttl: None,
+            express: false,
         })
     }
 }
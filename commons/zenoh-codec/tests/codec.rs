@@ -225,6 +225,12 @@ fn codec_keep_alive() {
     run!(KeepAlive, KeepAlive::rand());
 }
 
+#[test]
+fn codec_ping_pong() {
+    run!(Ping, Ping::rand());
+    run!(Pong, Pong::rand());
+}
+
 #[test]
 fn codec_frame_header() {
     run!(FrameHeader, FrameHeader::rand());
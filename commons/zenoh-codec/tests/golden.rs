@@ -0,0 +1,161 @@
+//
+// Copyright (c) 2022 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+// The exact bytes of the messages of each supported version of the protocol: any change
+// of the wire format of a released version breaks the interoperability with its peers.
+use zenoh_buffers::{reader::HasReader, writer::HasWriter, ZBuf};
+use zenoh_codec::*;
+use zenoh_protocol::{core::*, transport::*, zenoh::*};
+
+const V07: u8 = 0x07;
+const V08: u8 = 0x08;
+
+fn codec(version: u8) -> Zenoh060Version {
+    Zenoh060Version::new(version).unwrap()
+}
+
+fn write_transport(version: u8, x: &TransportMessage) -> Option<Vec<u8>> {
+    let mut bytes = vec![];
+    let mut writer = bytes.writer();
+    codec(version).write(&mut writer, x).ok()?;
+    Some(bytes)
+}
+
+fn read_transport(version: u8, bytes: &[u8]) -> Option<TransportMessage> {
+    let zbuf = ZBuf::from(bytes.to_vec());
+    let mut reader = zbuf.reader();
+    codec(version).read(&mut reader).ok()
+}
+
+fn write_zenoh(version: u8, x: &ZenohMessage) -> Vec<u8> {
+    let mut bytes = vec![];
+    let mut writer = bytes.writer();
+    codec(version).write(&mut writer, x).unwrap();
+    bytes
+}
+
+fn read_zenoh(version: u8, bytes: &[u8]) -> Option<ZenohMessage> {
+    let zbuf = ZBuf::from(bytes.to_vec());
+    let mut reader = zbuf.reader();
+    codec(version).read(&mut reader).ok()
+}
+
+fn data() -> ZenohMessage {
+    ZenohMessage::make_data(
+        WireExpr::from(42).with_suffix("a/b"),
+        ZBuf::from(vec![1, 2, 3]),
+        Channel {
+            priority: Priority::default(),
+            reliability: Reliability::Reliable,
+        },
+        CongestionControl::Block,
+        None,
+        None,
+        None,
+        None,
+    )
+}
+
+fn link_state_list(weights: Option<Vec<ZInt>>) -> ZenohMessage {
+    ZenohMessage::make_link_state_list(
+        vec![LinkState {
+            psid: 1,
+            sn: 2,
+            zid: None,
+            whatami: None,
+            locators: None,
+            links: vec![3, 4],
+            weights,
+        }],
+        None,
+    )
+}
+
+#[test]
+fn golden_versions() {
+    assert!(Zenoh060Version::new(V07 - 1).is_none());
+    assert!(Zenoh060Version::new(V07).is_some());
+    assert!(Zenoh060Version::new(V08).is_some());
+    assert!(Zenoh060Version::new(V08 + 1).is_none());
+    assert_eq!(Zenoh060Version::latest().version, V08);
+}
+
+#[test]
+fn golden_keep_alive() {
+    let x = TransportMessage::make_keep_alive(None, None);
+    let bytes = [0x08];
+    for version in [V07, V08] {
+        assert_eq!(write_transport(version, &x).unwrap(), bytes);
+        assert_eq!(read_transport(version, &bytes).unwrap(), x);
+    }
+}
+
+#[test]
+fn golden_frame() {
+    let x = TransportMessage::make_frame(
+        Channel {
+            priority: Priority::default(),
+            reliability: Reliability::Reliable,
+        },
+        7,
+        FramePayload::Messages {
+            messages: vec![data()],
+        },
+        None,
+    );
+    let bytes = [
+        0x2a, 0x07, 0x8c, 0x2a, 0x03, 0x61, 0x2f, 0x62, 0x03, 0x01, 0x02, 0x03,
+    ];
+    for version in [V07, V08] {
+        assert_eq!(write_transport(version, &x).unwrap(), bytes);
+        assert_eq!(read_transport(version, &bytes).unwrap(), x);
+    }
+}
+
+#[test]
+fn golden_ping_pong() {
+    let ping = TransportMessage::make_ping(0x2a, None);
+    let pong = TransportMessage::make_pong(0x2a, None);
+    let ping_bytes = [0x29, 0x2a];
+    let pong_bytes = [0x09, 0x2a];
+    assert_eq!(write_transport(V08, &ping).unwrap(), ping_bytes);
+    assert_eq!(write_transport(V08, &pong).unwrap(), pong_bytes);
+    assert_eq!(read_transport(V08, &ping_bytes).unwrap(), ping);
+    assert_eq!(read_transport(V08, &pong_bytes).unwrap(), pong);
+
+    // The ping and pong messages don't exist in the older versions
+    assert!(write_transport(V07, &ping).is_none());
+    assert!(write_transport(V07, &pong).is_none());
+    assert!(read_transport(V07, &ping_bytes).is_none());
+    assert!(read_transport(V07, &pong_bytes).is_none());
+}
+
+#[test]
+fn golden_link_state_list() {
+    let x = link_state_list(None);
+    let bytes = [0x1c, 0x10, 0x01, 0x00, 0x01, 0x02, 0x02, 0x03, 0x04];
+    for version in [V07, V08] {
+        assert_eq!(write_zenoh(version, &x), bytes);
+        assert_eq!(read_zenoh(version, &bytes).unwrap().body, x.body);
+    }
+
+    // The link weights are left out of the older versions
+    let x = link_state_list(Some(vec![10, 20]));
+    let bytes_weights = [
+        0x1c, 0x10, 0x01, 0x08, 0x01, 0x02, 0x02, 0x03, 0x04, 0x0a, 0x14,
+    ];
+    assert_eq!(write_zenoh(V08, &x), bytes_weights);
+    assert_eq!(read_zenoh(V08, &bytes_weights).unwrap().body, x.body);
+    assert_eq!(write_zenoh(V07, &x), bytes);
+    assert!(read_zenoh(V07, &bytes_weights).is_none());
+}
@@ -97,6 +97,35 @@ pub mod routing {
     }
 }
 
+impl Default for ConnectionRetryConf {
+    fn default() -> Self {
+        Self {
+            period_init_ms: 1000,
+            period_max_ms: 4000,
+            period_increase_factor: 2.,
+            jitter: 0.,
+            max_attempts: 0,
+            endpoints: vec![],
+        }
+    }
+}
+
+impl Default for ScoutingConf {
+    fn default() -> Self {
+        Self {
+            timeout: None,
+            delay: None,
+            multicast: ScoutingMulticastConf::default(),
+            gossip: GossipConf::default(),
+            // Don't retry forever connecting to a scouted peer that might have definitely left
+            retry: ConnectionRetryConf {
+                max_attempts: 3,
+                ..Default::default()
+            },
+        }
+    }
+}
+
 impl Default for TransportUnicastConf {
     fn default() -> Self {
         Self {
@@ -104,6 +133,28 @@ impl Default for TransportUnicastConf {
             accept_pending: Some(100),
             max_sessions: Some(1000),
             max_links: Some(1),
+            scheduling: LinkSchedulingConf::default(),
+            rtt: RttConf::default(),
+            health: LinkHealthConf::default(),
+        }
+    }
+}
+
+impl Default for RttConf {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            interval: Some(1000),
+        }
+    }
+}
+
+impl Default for LinkHealthConf {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            interval: Some(500),
+            timeout: Some(2000),
         }
     }
 }
@@ -132,6 +183,7 @@ impl Default for LinkTxConf {
             lease: Some(10000),
             keep_alive: Some(4),
             batch_size: Some(u16::MAX),
+            max_message_size: Some(2_usize.pow(30)),
             queue: QueueConf::default(),
             threads: Some(num),
         }
@@ -143,6 +195,8 @@ impl Default for QueueConf {
         Self {
             size: QueueSizeConf::default(),
             backoff: Some(100),
+            adaptive_backoff: Some(false),
+            real_time_keep_last: Some(false),
         }
     }
 }
@@ -176,6 +230,15 @@ impl Default for LinkRxConf {
     }
 }
 
+impl Default for AuthReloadConf {
+    fn default() -> Self {
+        Self {
+            period_ms: 1000,
+            close_revoked: false,
+        }
+    }
+}
+
 impl Default for SharedMemoryConf {
     fn default() -> Self {
         Self { enabled: true }
@@ -116,14 +116,15 @@ validated_struct::validator! {
         pub connect: #[derive(Default)]
         ConnectConfig {
             pub endpoints: Vec<EndPoint>,
+            /// How to retry connecting to the endpoints, either initially or after the session has been closed.
+            pub retry: ConnectionRetryConf,
         },
         /// Which endpoints to listen on. `zenohd` will add `tcp/[::]:7447` to these locators if left empty.
         pub listen: #[derive(Default)]
         ListenConfig {
             pub endpoints: Vec<EndPoint>,
         },
-        pub scouting: #[derive(Default)]
-        ScoutingConf {
+        pub scouting: ScoutingConf {
             /// In client mode, the period dedicated to scouting for a router before failing. In milliseconds.
             timeout: Option<u64>,
             /// In peer mode, the period dedicated to scouting remote peers before attempting other operations. In milliseconds.
@@ -158,6 +159,8 @@ validated_struct::validator! {
                 #[serde(deserialize_with = "treat_error_as_none")]
                 autoconnect: Option<ModeDependentValue<WhatAmIMatcher>>,
             },
+            /// How to retry connecting to a scouted peer after the session with it has been closed.
+            pub retry: ConnectionRetryConf,
         },
 
         /// Configuration of data messages timestamps management.
@@ -213,6 +216,35 @@ validated_struct::validator! {
                 max_sessions: Option<usize>,
                 /// Maximum number of unicast incoming links per transport session (default: 1)
                 max_links: Option<usize>,
+                /// How the messages are scheduled on the links of a transport session having several links.
+                pub scheduling: #[derive(Default)]
+                LinkSchedulingConf {
+                    /// The scheduling policy: "first_fit" (default), "active_backup", "round_robin" or "weighted".
+                    policy: LinkSchedulingPolicy,
+                    /// The weights of the links, matched by locator, the port being optional (default weight: 1).
+                    weights: Vec<LinkWeightConf>,
+                    /// The links the messages of a given priority are pinned to, matched by locator, the port being optional.
+                    priorities: Vec<LinkPriorityConf>,
+                },
+                /// Round-trip time measurement on the links of a transport session.
+                pub rtt: RttConf {
+                    /// Whether ping messages are periodically sent on each link to measure its round-trip time and jitter (default: false).
+                    /// The measured round-trip times are also advertised to the routing, which then takes them into account when computing the routing trees.
+                    /// Enable it only if all the zenoh nodes of the system support ping messages and link weights.
+                    enabled: bool,
+                    /// Interval in milliseconds between two pings on a link (default: 1000).
+                    interval: Option<ZInt>,
+                },
+                /// Fast failure detection of the links of a transport session, besides their lease.
+                pub health: LinkHealthConf {
+                    /// Whether ping messages are periodically sent on each link, for it to be closed when nothing is received on it for `timeout` (default: false).
+                    /// The peers not supporting ping messages keep on being monitored by their lease only.
+                    enabled: bool,
+                    /// Interval in milliseconds between two pings on a link (default: 500).
+                    interval: Option<ZInt>,
+                    /// Duration in milliseconds without receiving anything on a link after which it is closed, if shorter than the lease (default: 2000).
+                    timeout: Option<ZInt>,
+                },
             },
             pub multicast: TransportMulticastConf {
                 /// Link join interval duration in milliseconds (default: 2500)
@@ -237,6 +269,9 @@ validated_struct::validator! {
                     keep_alive: Option<usize>,
                     /// Zenoh's MTU equivalent (default: 2^16-1)
                     batch_size: Option<u16>,
+                    /// Maximum size of the messages being fragmented at sender end (default: 1GiB).
+                    /// Messages that are larger than the configured size will be dropped instead of being fragmented.
+                    max_message_size: Option<usize>,
                     pub queue: QueueConf {
                         /// The size of each priority queue indicates the number of batches a given queue can contain.
                         /// The amount of memory being allocated for each queue is then SIZE_XXX * BATCH_SIZE.
@@ -255,7 +290,14 @@ validated_struct::validator! {
                         } where (queue_size_validator),
                         /// The initial exponential backoff time in nanoseconds to allow the batching to eventually progress.
                         /// Higher values lead to a more aggressive batching but it will introduce additional latency.
-                        backoff: Option<ZInt>
+                        backoff: Option<ZInt>,
+                        /// If true, the backoff is scaled by the observed utilization of the link: the messages are
+                        /// sent right away on an idle link, while they are batched up to the full backoff on a busy one.
+                        adaptive_backoff: Option<bool>,
+                        /// If true, the real_time queue keeps only the last message of each key expression while congested:
+                        /// instead of waiting or being dropped, such a message replaces the one of the same key expression
+                        /// still waiting to be serialized, if any. Requires qos to be enabled.
+                        real_time_keep_last: Option<bool>,
                     },
                     // Number of threads used for TX
                     threads: Option<usize>,
@@ -306,6 +348,28 @@ validated_struct::validator! {
                     key_size: Option<usize>,
                     known_keys_file: Option<String>,
                 },
+                /// How the credentials are reloaded at runtime, when the files they are stored in change
+                /// or when the authentication configuration is changed through the admin space.
+                pub reload: AuthReloadConf {
+                    /// The period in milliseconds at which the user-password dictionary and the known keys
+                    /// files are checked for changes. 0 disables the check.
+                    period_ms: u64,
+                    /// Whether to close the transports of the peers whose credentials have been revoked.
+                    close_revoked: bool,
+                },
+                /// The authentication of incoming links by the properties of the links themselves.
+                pub link: #[derive(Default)]
+                LinkAuthConf {
+                    /// The IP networks (e.g. "192.168.1.0/24") the remote address of incoming links must belong to.
+                    /// Any address is accepted if empty, otherwise links without IP address (e.g. unix sockets) are rejected
+                    /// unless `allow_non_ip_links` is true.
+                    allowed_networks: Vec<String>,
+                    /// Whether to accept the links without IP address (e.g. unix sockets) when `allowed_networks` is not empty.
+                    allow_non_ip_links: bool,
+                    /// The DNS names one of which the certificate presented by the remote end of incoming links must be valid for,
+                    /// as per its subject alternative names. Any link is accepted if empty, otherwise links without certificate are rejected.
+                    allowed_cert_names: Vec<String>,
+                },
             },
         },
         /// Configuration of the admin space.
@@ -348,6 +412,127 @@ impl Default for PermissionsConf {
     }
 }
 
+/// How to retry connecting to an endpoint: with an exponential backoff and some jitter,
+/// up to a maximum number of attempts.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ConnectionRetryConf {
+    /// The period in milliseconds before the first retry.
+    pub period_init_ms: u64,
+    /// The maximum period in milliseconds between two retries.
+    pub period_max_ms: u64,
+    /// The factor the period is multiplied by after each failed attempt.
+    pub period_increase_factor: f64,
+    /// The ratio of the period randomly added to or subtracted from it, between 0 and 1.
+    pub jitter: f64,
+    /// The maximum number of attempts, 0 meaning unlimited.
+    pub max_attempts: usize,
+    /// The policies overriding this one for some endpoints.
+    pub endpoints: Vec<EndPointRetryConf>,
+}
+
+impl ConnectionRetryConf {
+    /// The policy to apply to the given endpoint or locator:
+    /// this one overridden by the first [`EndPointRetryConf`] matching it, if any.
+    pub fn for_endpoint(&self, endpoint: &str) -> ConnectionRetryConf {
+        let mut conf = self.clone();
+        conf.endpoints.clear();
+        if let Some(o) = self
+            .endpoints
+            .iter()
+            .find(|o| endpoint.starts_with(&o.endpoint))
+        {
+            conf.period_init_ms = o.period_init_ms.unwrap_or(conf.period_init_ms);
+            conf.period_max_ms = o.period_max_ms.unwrap_or(conf.period_max_ms);
+            conf.period_increase_factor = o
+                .period_increase_factor
+                .unwrap_or(conf.period_increase_factor);
+            conf.jitter = o.jitter.unwrap_or(conf.jitter);
+            conf.max_attempts = o.max_attempts.unwrap_or(conf.max_attempts);
+        }
+        conf
+    }
+}
+
+/// Overrides the [`ConnectionRetryConf`] of the endpoints starting with `endpoint` (e.g. `tcp/192.168.1.10`).
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct EndPointRetryConf {
+    pub endpoint: String,
+    #[serde(default)]
+    pub period_init_ms: Option<u64>,
+    #[serde(default)]
+    pub period_max_ms: Option<u64>,
+    #[serde(default)]
+    pub period_increase_factor: Option<f64>,
+    #[serde(default)]
+    pub jitter: Option<f64>,
+    #[serde(default)]
+    pub max_attempts: Option<usize>,
+}
+
+/// The policy used to choose the link of a unicast transport session a message is sent on.
+/// Whatever the policy, the links matching the reliability of the message are preferred.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LinkSchedulingPolicy {
+    /// The first established link.
+    #[default]
+    FirstFit,
+    /// The link with the highest weight, the other ones being used only when it fails.
+    ActiveBackup,
+    /// All the links in turn.
+    RoundRobin,
+    /// All the links in turn, proportionally to their weight.
+    Weighted,
+}
+
+/// The weight of the links whose source or destination locator starts with `locator` (e.g. `tcp/192.168.1.10`).
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct LinkWeightConf {
+    pub locator: String,
+    pub weight: usize,
+}
+
+/// Pins the messages of `priority` to the links whose source or destination locator starts with `locator`,
+/// as long as such a link is established.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct LinkPriorityConf {
+    pub priority: PriorityConf,
+    pub locator: String,
+}
+
+/// The name of a [`Priority`] in the configuration.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PriorityConf {
+    Control,
+    RealTime,
+    InteractiveHigh,
+    InteractiveLow,
+    DataHigh,
+    Data,
+    DataLow,
+    Background,
+}
+
+impl From<PriorityConf> for Priority {
+    fn from(priority: PriorityConf) -> Self {
+        match priority {
+            PriorityConf::Control => Priority::Control,
+            PriorityConf::RealTime => Priority::RealTime,
+            PriorityConf::InteractiveHigh => Priority::InteractiveHigh,
+            PriorityConf::InteractiveLow => Priority::InteractiveLow,
+            PriorityConf::DataHigh => Priority::DataHigh,
+            PriorityConf::Data => Priority::Data,
+            PriorityConf::DataLow => Priority::DataLow,
+            PriorityConf::Background => Priority::Background,
+        }
+    }
+}
+
 fn set_true() -> bool {
     true
 }
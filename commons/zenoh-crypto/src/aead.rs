@@ -0,0 +1,144 @@
+//
+// Copyright (c) 2022 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+use super::{hmac, BlockCipher, PseudoRng};
+use aes::cipher::{generic_array::GenericArray, BlockEncrypt, KeyInit};
+use aes::Aes128;
+use rand::RngCore;
+use std::convert::TryInto;
+use zenoh_result::{bail, ZResult};
+
+/// Authenticated encryption with associated data.
+///
+/// The payload is encrypted with AES-128 in counter mode and authenticated with a
+/// HMAC-SHA3-256 tag (encrypt-then-MAC) computed over the associated data, the nonce
+/// and the ciphertext. The encryption and authentication keys are both derived from
+/// the master key. The output is `nonce || ciphertext || tag`.
+pub struct AeadCipher {
+    inner: Aes128,
+    mac_key: Vec<u8>,
+}
+
+impl AeadCipher {
+    pub const KEY_SIZE: usize = BlockCipher::BLOCK_SIZE;
+    pub const NONCE_SIZE: usize = BlockCipher::BLOCK_SIZE;
+    pub const TAG_SIZE: usize = 16;
+    pub const OVERHEAD: usize = Self::NONCE_SIZE + Self::TAG_SIZE;
+
+    pub fn new(key: [u8; Self::KEY_SIZE]) -> ZResult<AeadCipher> {
+        let enc_key = hmac::sign(&key, b"zenoh-aead-enc")?;
+        let mac_key = hmac::sign(&key, b"zenoh-aead-mac")?;
+        Ok(AeadCipher {
+            inner: Aes128::new(GenericArray::from_slice(&enc_key[..Self::KEY_SIZE])),
+            mac_key,
+        })
+    }
+
+    pub fn encrypt(&self, plaintext: &[u8], aad: &[u8], prng: &mut PseudoRng) -> ZResult<Vec<u8>> {
+        let mut nonce = [0_u8; Self::NONCE_SIZE];
+        prng.fill_bytes(&mut nonce);
+
+        let mut bytes = Vec::with_capacity(plaintext.len() + Self::OVERHEAD);
+        bytes.extend_from_slice(&nonce);
+        bytes.extend_from_slice(plaintext);
+        self.apply_keystream(&nonce, &mut bytes[Self::NONCE_SIZE..]);
+        let tag = self.tag(aad, &bytes)?;
+        bytes.extend_from_slice(&tag);
+        Ok(bytes)
+    }
+
+    pub fn decrypt(&self, bytes: &[u8], aad: &[u8]) -> ZResult<Vec<u8>> {
+        if bytes.len() < Self::OVERHEAD {
+            bail!("Invalid bytes length to decrypt: {}", bytes.len());
+        }
+
+        let (data, tag) = bytes.split_at(bytes.len() - Self::TAG_SIZE);
+        let expected = self.tag(aad, data)?;
+        // Compare in constant time
+        let diff = expected
+            .iter()
+            .zip(tag.iter())
+            .fold(0_u8, |acc, (e, t)| acc | (e ^ t));
+        if diff != 0 {
+            bail!("Invalid authentication tag");
+        }
+
+        let (nonce, ciphertext) = data.split_at(Self::NONCE_SIZE);
+        let mut plaintext = ciphertext.to_vec();
+        self.apply_keystream(nonce, &mut plaintext);
+        Ok(plaintext)
+    }
+
+    fn apply_keystream(&self, nonce: &[u8], bytes: &mut [u8]) {
+        let mut counter = u128::from_be_bytes(nonce.try_into().unwrap());
+        for chunk in bytes.chunks_mut(BlockCipher::BLOCK_SIZE) {
+            let mut block = GenericArray::from(counter.to_be_bytes());
+            self.inner.encrypt_block(&mut block);
+            chunk
+                .iter_mut()
+                .zip(block.iter())
+                .for_each(|(b, k)| *b ^= k);
+            counter = counter.wrapping_add(1);
+        }
+    }
+
+    fn tag(&self, aad: &[u8], data: &[u8]) -> ZResult<Vec<u8>> {
+        let mut input = Vec::with_capacity(8 + aad.len() + data.len());
+        input.extend_from_slice(&(aad.len() as u64).to_be_bytes());
+        input.extend_from_slice(aad);
+        input.extend_from_slice(data);
+        let mut tag = hmac::sign(&self.mac_key, &input)?;
+        tag.truncate(Self::TAG_SIZE);
+        Ok(tag)
+    }
+}
+
+mod tests {
+    #[test]
+    fn aead() {
+        use super::{AeadCipher, PseudoRng};
+        use rand::{RngCore, SeedableRng};
+
+        let mut prng = PseudoRng::from_entropy();
+        let mut key = [0_u8; AeadCipher::KEY_SIZE];
+        prng.fill_bytes(&mut key);
+        let cipher = AeadCipher::new(key).unwrap();
+
+        for len in [0, 1, 15, 16, 17, 1_024] {
+            let mut clear = vec![0_u8; len];
+            prng.fill_bytes(&mut clear);
+            let encrypted = cipher.encrypt(&clear, b"demo/example", &mut prng).unwrap();
+            assert_eq!(encrypted.len(), len + AeadCipher::OVERHEAD);
+            if len > 0 {
+                assert_ne!(&encrypted[AeadCipher::NONCE_SIZE..][..len], &clear[..]);
+            }
+            let decrypted = cipher.decrypt(&encrypted, b"demo/example").unwrap();
+            assert_eq!(decrypted, clear);
+
+            // Wrong associated data
+            assert!(cipher.decrypt(&encrypted, b"demo/other").is_err());
+            // Tampered bytes
+            let mut tampered = encrypted.clone();
+            tampered[AeadCipher::NONCE_SIZE] ^= 0x01;
+            assert!(cipher.decrypt(&tampered, b"demo/example").is_err());
+            // Wrong key
+            let mut other = [0_u8; AeadCipher::KEY_SIZE];
+            prng.fill_bytes(&mut other);
+            let other = AeadCipher::new(other).unwrap();
+            assert!(other.decrypt(&encrypted, b"demo/example").is_err());
+        }
+
+        // Truncated input
+        assert!(cipher.decrypt(&[0_u8; 8], b"").is_err());
+    }
+}
@@ -24,3 +24,78 @@ pub fn sign(key: &[u8], data: &[u8]) -> ZResult<Vec<u8>> {
 pub fn digest(data: &[u8]) -> Vec<u8> {
     Sha3_256::digest(data).as_slice().to_vec()
 }
+
+/// Derive a key from a password with PBKDF2, using HMAC-SHA3-256 as pseudorandom function.
+/// The derived key is as long as the output of the hash function.
+pub fn pbkdf2(password: &[u8], salt: &[u8], iterations: u32) -> ZResult<Vec<u8>> {
+    let mut key = vec![0; Sha3_256::output_size()];
+    pbkdf2_with::<Hmac<Sha3_256>>(password, salt, iterations, &mut key)?;
+    Ok(key)
+}
+
+// PBKDF2 as per RFC 8018, with the given HMAC as pseudorandom function
+fn pbkdf2_with<M>(password: &[u8], salt: &[u8], iterations: u32, key: &mut [u8]) -> ZResult<()>
+where
+    M: Mac + hmac::digest::KeyInit + Clone,
+{
+    let prf = <M as hmac::digest::KeyInit>::new_from_slice(password)?;
+    for (i, block) in key.chunks_mut(M::output_size()).enumerate() {
+        let mut u = prf.clone();
+        u.update(salt);
+        u.update(&(i as u32 + 1).to_be_bytes());
+        let mut u = u.finalize().into_bytes();
+        let mut t = u.clone();
+        for _ in 1..iterations {
+            let mut next = prf.clone();
+            next.update(&u);
+            u = next.finalize().into_bytes();
+            t.iter_mut().zip(u.iter()).for_each(|(t, u)| *t ^= u);
+        }
+        block.copy_from_slice(&t[..block.len()]);
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use sha2::Sha256;
+
+    // The PBKDF2-HMAC-SHA256 test vectors of RFC 7914, section 11
+    #[test]
+    fn pbkdf2_known_answers() {
+        let vectors: [(&[u8], &[u8], u32, &str); 2] = [
+            (
+                b"passwd",
+                b"salt",
+                1,
+                "55ac046e56e3089fec1691c22544b605f94185216dde0465e68b9d57c20dacbc\
+                 49ca9cccf179b645991664b39d77ef317c71b845b1e30bd509112041d3a19783",
+            ),
+            (
+                b"Password",
+                b"NaCl",
+                80_000,
+                "4ddcd8f60b98be21830cee5ef22701f9641a4418d04c0414aeff08876b34ab56\
+                 a1d425a1225833549adb841b51c9b3176a272bdebba1d078478f62b397f33c8d",
+            ),
+        ];
+        for (password, salt, iterations, expected) in vectors {
+            let mut key = [0; 64];
+            pbkdf2_with::<Hmac<Sha256>>(password, salt, iterations, &mut key).unwrap();
+            assert_eq!(hex(&key), expected);
+        }
+    }
+
+    #[test]
+    fn pbkdf2_sha3() {
+        // A single iteration is a single HMAC of the salt followed by the block index
+        let key = pbkdf2(b"password", b"salt", 1).unwrap();
+        assert_eq!(key, sign(b"password", b"salt\x00\x00\x00\x01").unwrap());
+        assert_ne!(key, pbkdf2(b"password", b"salt", 2).unwrap());
+    }
+
+    fn hex(bytes: &[u8]) -> String {
+        bytes.iter().map(|b| format!("{b:02x}")).collect()
+    }
+}
@@ -12,7 +12,7 @@
 //   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
 //
 use crate::core::{CowStr, ZInt};
-use alloc::{borrow::Cow, string::String};
+use alloc::{borrow::Cow, format, string::String};
 use core::{
     convert::TryFrom,
     fmt::{self, Debug},
@@ -46,6 +46,14 @@ mod consts {
     ];
 }
 
+/// The start of the suffix of an [`KnownEncoding::AppCustom`] encoding carrying
+/// the numeric id of a custom encoding (e.g. `application/custom;id=42`).
+pub const CUSTOM_ID_SUFFIX: &str = ";id=";
+
+/// The suffix of an [`KnownEncoding::AppOctetStream`] encoding marking a payload
+/// encrypted end-to-end (e.g. `application/octet-stream;e2e`).
+pub const ENCRYPTED_SUFFIX: &str = ";e2e";
+
 #[repr(u8)]
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
 #[cfg_attr(feature = "defmt", derive(defmt::Format))]
@@ -183,6 +191,34 @@ impl Encoding {
             Encoding::WithSuffix(_, s) => s.as_ref(),
         }
     }
+
+    /// Creates the encoding identifying the custom encoding with the given numeric id.
+    pub fn custom(id: ZInt) -> Self {
+        Encoding::WithSuffix(
+            KnownEncoding::AppCustom,
+            Cow::from(format!("{CUSTOM_ID_SUFFIX}{id}")).into(),
+        )
+    }
+
+    /// Returns the numeric id of the custom encoding identified by this encoding, if any.
+    pub fn custom_id(&self) -> Option<ZInt> {
+        match self {
+            Encoding::WithSuffix(KnownEncoding::AppCustom, s) => {
+                s.strip_prefix(CUSTOM_ID_SUFFIX)?.parse().ok()
+            }
+            _ => None,
+        }
+    }
+
+    /// Creates the encoding marking a payload encrypted end-to-end.
+    pub fn encrypted() -> Self {
+        Encoding::WithSuffix(KnownEncoding::AppOctetStream, ENCRYPTED_SUFFIX.into())
+    }
+
+    /// Returns `true` if this encoding marks a payload encrypted end-to-end.
+    pub fn is_encrypted(&self) -> bool {
+        matches!(self, Encoding::WithSuffix(KnownEncoding::AppOctetStream, s) if s.as_ref() == ENCRYPTED_SUFFIX)
+    }
 }
 
 impl Encoding {
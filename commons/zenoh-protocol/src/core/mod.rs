@@ -57,7 +57,7 @@ pub use wire_expr::WireExpr;
 mod cowstr;
 pub use cowstr::CowStr;
 mod encoding;
-pub use encoding::{Encoding, KnownEncoding};
+pub use encoding::{Encoding, KnownEncoding, CUSTOM_ID_SUFFIX, ENCRYPTED_SUFFIX};
 
 pub mod locator;
 pub use locator::Locator;
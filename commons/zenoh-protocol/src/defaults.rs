@@ -18,7 +18,11 @@ use super::core::ZInt;
 // +-+-+-+-+-+-+-+-+
 // | v_maj | v_min |
 // +-------+-------+
-pub const VERSION: u8 = 0x07;
+// - 0x07: zenoh 0.6.0
+// - 0x08: adds the ping and pong transport messages, and the link weights of the link states
+pub const VERSION: u8 = 0x08;
+// The oldest version still supported, negotiated with the peers running older versions of zenoh.
+pub const MIN_VERSION: u8 = 0x07;
 
 // The default sequence number resolution takes 4 bytes on the wire.
 // Given the VLE encoding of ZInt, 4 bytes result in 28 useful bits.
@@ -17,6 +17,7 @@ mod init;
 mod join;
 mod keepalive;
 mod open;
+mod pingpong;
 
 use crate::{
     common::Attachment,
@@ -29,6 +30,7 @@ pub use init::*;
 pub use join::*;
 pub use keepalive::*;
 pub use open::*;
+pub use pingpong::*;
 use zenoh_buffers::ZSlice;
 
 pub mod tmsg {
@@ -138,6 +140,8 @@ pub enum TransportBody {
     Join(Join),
     Close(Close),
     KeepAlive(KeepAlive),
+    Ping(Ping),
+    Pong(Pong),
     Frame(Frame),
 }
 
@@ -280,6 +284,24 @@ impl TransportMessage {
         }
     }
 
+    pub fn make_ping(hash: ZInt, attachment: Option<Attachment>) -> TransportMessage {
+        TransportMessage {
+            body: TransportBody::Ping(Ping { hash }),
+            attachment,
+            #[cfg(feature = "stats")]
+            size: None,
+        }
+    }
+
+    pub fn make_pong(hash: ZInt, attachment: Option<Attachment>) -> TransportMessage {
+        TransportMessage {
+            body: TransportBody::Pong(Pong { hash }),
+            attachment,
+            #[cfg(feature = "stats")]
+            size: None,
+        }
+    }
+
     pub fn make_frame(
         channel: Channel,
         sn: ZInt,
@@ -310,7 +332,7 @@ impl TransportMessage {
             None
         };
 
-        let body = match rng.gen_range(0..8) {
+        let body = match rng.gen_range(0..10) {
             0 => TransportBody::InitSyn(InitSyn::rand()),
             1 => TransportBody::InitAck(InitAck::rand()),
             2 => TransportBody::OpenSyn(OpenSyn::rand()),
@@ -319,6 +341,8 @@ impl TransportMessage {
             5 => TransportBody::Close(Close::rand()),
             6 => TransportBody::KeepAlive(KeepAlive::rand()),
             7 => TransportBody::Frame(Frame::rand()),
+            8 => TransportBody::Ping(Ping::rand()),
+            9 => TransportBody::Pong(Pong::rand()),
             _ => unreachable!(),
         };
 
@@ -16,7 +16,7 @@ use alloc::vec::Vec;
 
 //  7 6 5 4 3 2 1 0
 // +-+-+-+-+-+-+-+-+
-// ~X|X|X|X|X|L|W|P~
+// ~X|X|X|X|G|L|W|P~
 // +-+-+-+-+-+-+-+-+
 // ~     psid      ~
 // +---------------+
@@ -30,6 +30,8 @@ use alloc::vec::Vec;
 // +---------------+
 // ~    [links]    ~
 // +---------------+
+// ~   [weights]   ~ if G == 1 -- One weight for each link
+// +---------------+
 #[derive(Debug, Clone, PartialEq, Eq)]
 #[cfg_attr(feature = "defmt", derive(defmt::Format))]
 pub struct LinkState {
@@ -39,6 +41,7 @@ pub struct LinkState {
     pub whatami: Option<WhatAmI>,
     pub locators: Option<Vec<Locator>>,
     pub links: Vec<ZInt>,
+    pub weights: Option<Vec<ZInt>>,
 }
 
 impl LinkState {
@@ -72,6 +75,11 @@ impl LinkState {
         };
         let n = rng.gen_range(MIN..=MAX);
         let links = (0..n).map(|_| rng.gen()).collect::<Vec<ZInt>>();
+        let weights = if rng.gen_bool(0.5) {
+            Some((0..n).map(|_| rng.gen()).collect::<Vec<ZInt>>())
+        } else {
+            None
+        };
 
         Self {
             psid,
@@ -80,6 +88,7 @@ impl LinkState {
             whatami,
             locators,
             links,
+            weights,
         }
     }
 }
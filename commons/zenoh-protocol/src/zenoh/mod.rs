@@ -26,7 +26,7 @@ use crate::{
     },
 };
 use alloc::{string::String, vec::Vec};
-use core::fmt;
+use core::{fmt, time::Duration};
 pub use data::*;
 pub use declare::*;
 pub use linkstate::*;
@@ -128,6 +128,7 @@ pub mod zmsg {
         pub const PID: ZInt = 1; // 0x01
         pub const WAI: ZInt = 1 << 1; // 0x02
         pub const LOC: ZInt = 1 << 2; // 0x04
+        pub const WGT: ZInt = 1 << 3; // 0x08
     }
 
     pub mod conduit {
@@ -211,6 +212,12 @@ pub struct ZenohMessage {
     pub channel: Channel,
     pub routing_context: Option<RoutingContext>,
     pub attachment: Option<Attachment>,
+    /// The maximum time this message may wait in the local transmission queues before being
+    /// serialized, after which it is dropped. It is local and never serialized on the wire.
+    pub ttl: Option<Duration>,
+    /// Whether this message is sent right away instead of waiting in the local transmission
+    /// queues to be batched with the following ones. It is local and never serialized on the wire.
+    pub express: bool,
     #[cfg(feature = "stats")]
     pub size: Option<core::num::NonZeroUsize>,
 }
@@ -226,6 +233,8 @@ impl ZenohMessage {
             channel: zmsg::default_channel::DECLARE,
             routing_context,
             attachment,
+            ttl: None,
+            express: false,
             #[cfg(feature = "stats")]
             size: None,
         }
@@ -254,6 +263,8 @@ impl ZenohMessage {
             channel,
             routing_context,
             attachment,
+            ttl: None,
+            express: false,
             #[cfg(feature = "stats")]
             size: None,
         }
@@ -273,6 +284,8 @@ impl ZenohMessage {
             channel,
             routing_context: None,
             attachment,
+            ttl: None,
+            express: false,
             #[cfg(feature = "stats")]
             size: None,
         }
@@ -295,6 +308,8 @@ impl ZenohMessage {
             channel: zmsg::default_channel::PULL,
             routing_context: None,
             attachment,
+            ttl: None,
+            express: false,
             #[cfg(feature = "stats")]
             size: None,
         }
@@ -324,6 +339,8 @@ impl ZenohMessage {
             channel: zmsg::default_channel::QUERY,
             routing_context,
             attachment,
+            ttl: None,
+            express: false,
             #[cfg(feature = "stats")]
             size: None,
         }
@@ -338,6 +355,8 @@ impl ZenohMessage {
             channel: zmsg::default_channel::LINK_STATE_LIST,
             routing_context: None,
             attachment,
+            ttl: None,
+            express: false,
             #[cfg(feature = "stats")]
             size: None,
         }
@@ -436,6 +455,8 @@ impl ZenohMessage {
             channel,
             routing_context,
             attachment,
+            ttl: None,
+            express: false,
         }
     }
 }
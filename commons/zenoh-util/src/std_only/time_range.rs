@@ -248,6 +248,9 @@ impl TimeExpr {
     pub fn resolve_at(&self, now: SystemTime) -> SystemTime {
         match self {
             TimeExpr::Fixed(t) => *t,
+            TimeExpr::Now { offset_secs } if *offset_secs < 0.0 => now
+                .checked_sub(std::time::Duration::from_secs_f64(-offset_secs))
+                .unwrap_or(SystemTime::UNIX_EPOCH),
             TimeExpr::Now { offset_secs } => now + std::time::Duration::from_secs_f64(*offset_secs),
         }
     }
@@ -421,6 +424,15 @@ mod tests {
         assert!("2020-11-05".parse::<TimeExpr>().is_err());
     }
 
+    #[test]
+    fn test_resolve_time_expr() {
+        let now = SystemTime::now();
+        let expr: TimeExpr = "now(-1h)".parse().unwrap();
+        assert_eq!(expr.resolve_at(now), now - Duration::from_secs(3600));
+        let expr: TimeExpr = "now(1m)".parse().unwrap();
+        assert_eq!(expr.resolve_at(now), now + Duration::from_secs(60));
+    }
+
     #[test]
     fn test_parse_duration() {
         assert_eq!(parse_duration("0").unwrap(), 0.0);
@@ -44,6 +44,7 @@ use zenoh_result::{zerror, ZResult};
 /*            GENERAL                */
 /*************************************/
 #[derive(Clone, Debug, Serialize, Hash, PartialEq, Eq)]
+#[non_exhaustive]
 pub struct Link {
     pub src: Locator,
     pub dst: Locator,
@@ -51,6 +52,38 @@ pub struct Link {
     pub mtu: u16,
     pub is_reliable: bool,
     pub is_streamed: bool,
+    #[serde(skip)]
+    pub auth_id: Option<LinkAuthId>,
+}
+
+/// The identity the remote end of a link has proven while establishing it,
+/// i.e. the certificate it presented during a TLS or QUIC handshake.
+#[derive(Clone, Debug, Hash, PartialEq, Eq)]
+pub struct LinkAuthId {
+    certificate: Vec<u8>,
+}
+
+impl LinkAuthId {
+    /// The identity proven by a DER encoded end-entity certificate.
+    pub fn from_certificate(certificate: Vec<u8>) -> LinkAuthId {
+        LinkAuthId { certificate }
+    }
+
+    /// The DER encoded end-entity certificate of this identity.
+    pub fn certificate(&self) -> &[u8] {
+        &self.certificate
+    }
+
+    /// Whether the certificate is valid for the given DNS name, as per its subject alternative names.
+    pub fn is_valid_for_name(&self, name: &str) -> bool {
+        let name = match webpki::DnsNameRef::try_from_ascii_str(name) {
+            Ok(name) => name,
+            Err(_) => return false,
+        };
+        webpki::EndEntityCert::try_from(self.certificate.as_slice())
+            .and_then(|cert| cert.verify_is_valid_for_dns_name(name))
+            .is_ok()
+    }
 }
 
 #[async_trait]
@@ -78,6 +111,7 @@ impl From<&LinkUnicast> for Link {
             mtu: link.get_mtu(),
             is_reliable: link.is_reliable(),
             is_streamed: link.is_streamed(),
+            auth_id: link.get_auth_id().cloned(),
         }
     }
 }
@@ -97,6 +131,7 @@ impl From<&LinkMulticast> for Link {
             mtu: link.get_mtu(),
             is_reliable: link.is_reliable(),
             is_streamed: false,
+            auth_id: None,
         }
     }
 }
@@ -141,6 +176,9 @@ pub trait LinkUnicastTrait: Send + Sync {
     fn get_dst(&self) -> &Locator;
     fn is_reliable(&self) -> bool;
     fn is_streamed(&self) -> bool;
+    fn get_auth_id(&self) -> Option<&LinkAuthId> {
+        None
+    }
     async fn write(&self, buffer: &[u8]) -> ZResult<usize>;
     async fn write_all(&self, buffer: &[u8]) -> ZResult<()>;
     async fn read(&self, buffer: &mut [u8]) -> ZResult<usize>;
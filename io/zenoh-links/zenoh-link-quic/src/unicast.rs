@@ -31,8 +31,9 @@ use std::sync::{Arc, RwLock};
 use std::time::Duration;
 use zenoh_core::{zasynclock, zread, zwrite};
 use zenoh_link_commons::{
-    LinkManagerUnicastTrait, LinkUnicast, LinkUnicastTrait, NewLinkChannelSender,
+    LinkAuthId, LinkManagerUnicastTrait, LinkUnicast, LinkUnicastTrait, NewLinkChannelSender,
 };
+use zenoh_protocol::core::endpoint::Config;
 use zenoh_protocol::core::{EndPoint, Locator};
 use zenoh_result::{bail, zerror, ZResult};
 use zenoh_sync::Signal;
@@ -42,6 +43,8 @@ pub struct LinkUnicastQuic {
     src_addr: SocketAddr,
     src_locator: Locator,
     dst_locator: Locator,
+    // The identity proven by the certificate of the remote end, if any
+    auth_id: Option<LinkAuthId>,
     send: AsyncMutex<quinn::SendStream>,
     recv: AsyncMutex<quinn::RecvStream>,
 }
@@ -54,12 +57,20 @@ impl LinkUnicastQuic {
         send: quinn::SendStream,
         recv: quinn::RecvStream,
     ) -> LinkUnicastQuic {
+        // Extract the identity from the certificate of the remote end, if any
+        let auth_id = connection
+            .peer_identity()
+            .and_then(|id| id.downcast::<Vec<rustls::Certificate>>().ok())
+            .and_then(|certs| certs.first().cloned())
+            .map(|cert| LinkAuthId::from_certificate(cert.0));
+
         // Build the Quic object
         LinkUnicastQuic {
             connection,
             src_addr,
             src_locator: Locator::new(QUIC_LOCATOR_PREFIX, src_addr.to_string(), "").unwrap(),
             dst_locator,
+            auth_id,
             send: AsyncMutex::new(send),
             recv: AsyncMutex::new(recv),
         }
@@ -149,6 +160,11 @@ impl LinkUnicastTrait for LinkUnicastQuic {
     fn is_streamed(&self) -> bool {
         true
     }
+
+    #[inline(always)]
+    fn get_auth_id(&self) -> Option<&LinkAuthId> {
+        self.auth_id.as_ref()
+    }
 }
 
 impl Drop for LinkUnicastQuic {
@@ -259,10 +275,32 @@ impl LinkManagerUnicastTrait for LinkManagerUnicastQuic {
             root_cert_store.add(c).map_err(|e| zerror!("{}", e))?;
         }
 
-        let mut client_crypto = rustls::ClientConfig::builder()
+        let client_auth: bool = match epconf.get(TLS_CLIENT_AUTH) {
+            Some(value) => value.parse()?,
+            None => TLS_CLIENT_AUTH_DEFAULT.parse().unwrap(),
+        };
+        let client_crypto = rustls::ClientConfig::builder()
             .with_safe_defaults()
-            .with_root_certificates(root_cert_store)
-            .with_no_client_auth();
+            .with_root_certificates(root_cert_store);
+        let mut client_crypto = if client_auth {
+            let certificates = load_certificates(
+                &epconf,
+                TLS_CLIENT_CERTIFICATE_RAW,
+                TLS_CLIENT_CERTIFICATE_FILE,
+            )
+            .await?;
+            let private_key = load_private_key(
+                &epconf,
+                TLS_CLIENT_PRIVATE_KEY_RAW,
+                TLS_CLIENT_PRIVATE_KEY_FILE,
+            )
+            .await?;
+            client_crypto
+                .with_single_cert(certificates, private_key)
+                .map_err(|e| zerror!("Invalid QUIC client certificate: {}", e))?
+        } else {
+            client_crypto.with_no_client_auth()
+        };
         client_crypto.alpn_protocols = ALPN_QUIC_HTTP.iter().map(|&x| x.into()).collect();
 
         let ip_addr: IpAddr = if addr.is_ipv4() {
@@ -351,10 +389,36 @@ impl LinkManagerUnicastTrait for LinkManagerUnicastQuic {
         );
 
         // Server config
-        let mut server_crypto = rustls::ServerConfig::builder()
-            .with_safe_defaults()
-            .with_no_client_auth()
-            .with_single_cert(certificates, private_key)?;
+        let client_auth: bool = match epconf.get(TLS_CLIENT_AUTH) {
+            Some(value) => value.parse()?,
+            None => TLS_CLIENT_AUTH_DEFAULT.parse().unwrap(),
+        };
+        let server_crypto = rustls::ServerConfig::builder().with_safe_defaults();
+        let server_crypto = if client_auth {
+            // The client certificates are verified against the root CA certificates
+            let mut root_cert_store = rustls::RootCertStore::empty();
+            let certificates = load_certificates(
+                &epconf,
+                TLS_ROOT_CA_CERTIFICATE_RAW,
+                TLS_ROOT_CA_CERTIFICATE_FILE,
+            )
+            .await
+            .map_err(|e| {
+                zerror!(
+                    "Missing root certificates while client authentication is enabled: {}",
+                    e
+                )
+            })?;
+            for c in certificates.iter() {
+                root_cert_store.add(c).map_err(|e| zerror!("{}", e))?;
+            }
+            server_crypto.with_client_cert_verifier(
+                rustls::server::AllowAnyAuthenticatedClient::new(root_cert_store),
+            )
+        } else {
+            server_crypto.with_no_client_auth()
+        };
+        let mut server_crypto = server_crypto.with_single_cert(certificates, private_key)?;
         server_crypto.alpn_protocols = ALPN_QUIC_HTTP.iter().map(|&x| x.into()).collect();
         let mut server_config = quinn::ServerConfig::with_crypto(Arc::new(server_crypto));
 
@@ -467,6 +531,57 @@ impl LinkManagerUnicastTrait for LinkManagerUnicastQuic {
     }
 }
 
+async fn load_certificates(
+    config: &Config<'_>,
+    raw_key: &str,
+    file_key: &str,
+) -> ZResult<Vec<rustls::Certificate>> {
+    let f = if let Some(value) = config.get(raw_key) {
+        value.as_bytes().to_vec()
+    } else if let Some(value) = config.get(file_key) {
+        async_std::fs::read(value)
+            .await
+            .map_err(|e| zerror!("Invalid QUIC certificate file: {}", e))?
+    } else {
+        bail!("No QUIC certificate has been provided.");
+    };
+    let certificates = rustls_pemfile::certs(&mut BufReader::new(f.as_slice()))
+        .map_err(|e| zerror!("Invalid QUIC certificate file: {}", e))?
+        .drain(..)
+        .map(rustls::Certificate)
+        .collect::<Vec<rustls::Certificate>>();
+    if certificates.is_empty() {
+        bail!("No QUIC certificate has been provided.");
+    }
+    Ok(certificates)
+}
+
+async fn load_private_key(
+    config: &Config<'_>,
+    raw_key: &str,
+    file_key: &str,
+) -> ZResult<rustls::PrivateKey> {
+    let f = if let Some(value) = config.get(raw_key) {
+        value.as_bytes().to_vec()
+    } else if let Some(value) = config.get(file_key) {
+        async_std::fs::read(value)
+            .await
+            .map_err(|e| zerror!("Invalid QUIC private key file: {}", e))?
+    } else {
+        bail!("No QUIC private key has been provided.");
+    };
+    rustls_pemfile::read_all(&mut BufReader::new(f.as_slice()))
+        .map_err(|e| zerror!("Invalid QUIC private key file: {}", e))?
+        .drain(..)
+        .find_map(|x| match x {
+            rustls_pemfile::Item::RSAKey(k)
+            | rustls_pemfile::Item::PKCS8Key(k)
+            | rustls_pemfile::Item::ECKey(k) => Some(rustls::PrivateKey(k)),
+            _ => None,
+        })
+        .ok_or_else(|| zerror!("No QUIC private key has been provided.").into())
+}
+
 async fn accept_task(
     endpoint: quinn::Endpoint,
     active: Arc<AtomicBool>,
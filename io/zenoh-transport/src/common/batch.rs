@@ -17,7 +17,7 @@ use zenoh_buffers::{
     writer::{BacktrackableWriter, DidntWrite, HasWriter, Writer},
     BBuf, ZBufReader,
 };
-use zenoh_codec::{WCodec, Zenoh060};
+use zenoh_codec::{WCodec, Zenoh060Version};
 use zenoh_protocol::{
     core::{Channel, Reliability, ZInt},
     transport::{FrameHeader, FrameKind, TransportMessage},
@@ -95,6 +95,11 @@ pub(crate) struct WBatch {
     buffer: BBuf,
     // It is a streamed batch
     is_streamed: bool,
+    // Each zenoh message is serialized in its own frame, for the receiver to reorder
+    // the messages striped over several links according to their SN
+    is_striped: bool,
+    // The codec of the protocol version negotiated with the peer
+    codec: Zenoh060Version,
     // The current frame being serialized: BestEffort/Reliable
     current_frame: CurrentFrame,
     // The latest SN
@@ -109,6 +114,8 @@ impl WBatch {
         let mut batch = Self {
             buffer: BBuf::with_capacity(size as usize),
             is_streamed,
+            is_striped: false,
+            codec: Zenoh060Version::latest(),
             current_frame: CurrentFrame::None,
             latest_sn: LatestSn {
                 reliable: None,
@@ -124,6 +131,18 @@ impl WBatch {
         batch
     }
 
+    /// Serialize each zenoh message in its own frame.
+    pub(crate) fn striped(mut self, is_striped: bool) -> Self {
+        self.is_striped = is_striped;
+        self
+    }
+
+    /// Serialize the messages with the codec of the given protocol version.
+    pub(crate) fn codec(mut self, codec: Zenoh060Version) -> Self {
+        self.codec = codec;
+        self
+    }
+
     /// Verify that the [`SerializationBatch`][SerializationBatch] has no serialized bytes.
     #[inline(always)]
     pub(crate) fn is_empty(&self) -> bool {
@@ -194,7 +213,7 @@ impl Encode<&TransportMessage> for &mut WBatch {
         let mut writer = self.buffer.writer();
         let mark = writer.mark();
 
-        let codec = Zenoh060::default();
+        let codec = self.codec;
         codec.write(&mut writer, message).map_err(|e| {
             // Revert the write operation
             writer.rewind(mark);
@@ -234,12 +253,16 @@ impl Encode<&ZenohMessage> for &mut WBatch {
             // We are not serializing on the right frame.
             return Err(WError::NewFrame);
         };
+        if self.is_striped {
+            // Each message has its own frame.
+            return Err(WError::NewFrame);
+        }
 
         // Mark the write operation
         let mut writer = self.buffer.writer();
         let mark = writer.mark();
 
-        let codec = Zenoh060::default();
+        let codec = self.codec;
         codec.write(&mut writer, message).map_err(|_| {
             // Revert the write operation
             writer.rewind(mark);
@@ -263,14 +286,14 @@ impl Encode<(&ZenohMessage, Channel, ZInt)> for &mut WBatch {
         let mut writer = self.buffer.writer();
         let mark = writer.mark();
 
-        let codec = Zenoh060::default();
+        let codec = self.codec;
         // Write the frame header
         let frame = FrameHeader {
             channel,
             sn,
             kind: FrameKind::Messages,
         };
-        codec.write(&mut writer, &frame).map_err(|e| {
+        codec.codec.write(&mut writer, &frame).map_err(|e| {
             // Revert the write operation
             writer.rewind(mark);
             e
@@ -308,7 +331,7 @@ impl Encode<(&mut ZBufReader<'_>, Channel, ZInt)> for &mut WBatch {
         let (reader, channel, sn) = message;
 
         let mut writer = self.buffer.writer();
-        let codec = Zenoh060::default();
+        let codec = self.codec.codec;
 
         // Mark the buffer for the writing operation
         let mark = writer.mark();
@@ -13,9 +13,14 @@
 //
 use super::defragmentation::DefragBuffer;
 use super::seq_num::{SeqNum, SeqNumGenerator};
+use std::collections::HashMap;
 use std::sync::{Arc, Mutex};
+use std::time::Instant;
 use zenoh_core::zlock;
-use zenoh_protocol::core::{ConduitSn, Reliability, ZInt};
+use zenoh_protocol::{
+    core::{ConduitSn, Reliability, ZInt},
+    transport::FramePayload,
+};
 use zenoh_result::ZResult;
 
 #[derive(Debug)]
@@ -40,6 +45,12 @@ impl TransportChannelTx {
 pub(crate) struct TransportChannelRx {
     pub(crate) sn: SeqNum,
     pub(crate) defrag: DefragBuffer,
+    // The frames received ahead of a missing one, by sequence number
+    pub(crate) pending: HashMap<ZInt, FramePayload>,
+    // The size of the pending frames
+    pub(crate) pending_bytes: usize,
+    // Since when the missing frame is waited for
+    pub(crate) pending_since: Option<Instant>,
 }
 
 impl TransportChannelRx {
@@ -50,7 +61,13 @@ impl TransportChannelRx {
     ) -> ZResult<TransportChannelRx> {
         let sn = SeqNum::make(0, sn_resolution)?;
         let defrag = DefragBuffer::make(reliability, sn_resolution, defrag_buff_size)?;
-        let tch = TransportChannelRx { sn, defrag };
+        let tch = TransportChannelRx {
+            sn,
+            defrag,
+            pending: HashMap::new(),
+            pending_bytes: 0,
+            pending_since: None,
+        };
         Ok(tch)
     }
 
@@ -63,6 +80,9 @@ impl TransportChannelRx {
         };
 
         self.sn.set(sn)?;
+        self.pending.clear();
+        self.pending_bytes = 0;
+        self.pending_since = None;
         self.defrag.sync(sn)
     }
 }
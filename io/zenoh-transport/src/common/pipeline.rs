@@ -17,24 +17,26 @@ use crate::common::batch::WError;
 use super::batch::{Encode, WBatch};
 use super::conduit::{TransportChannelTx, TransportConduitTx};
 use async_std::prelude::FutureExt;
-use flume::{bounded, Receiver, Sender};
+use flume::{bounded, Receiver, RecvTimeoutError, Sender};
 use ringbuffer_spsc::{RingBuffer, RingBufferReader, RingBufferWriter};
-use std::sync::atomic::{AtomicBool, AtomicU16, Ordering};
+use std::collections::VecDeque;
+use std::convert::TryFrom;
+use std::sync::atomic::{AtomicBool, AtomicU16, AtomicUsize, Ordering};
 use std::sync::{Arc, Mutex, MutexGuard};
 use std::thread;
-use std::time::Duration;
+use std::time::{Duration, Instant};
 use zenoh_buffers::{
     reader::{HasReader, Reader},
     writer::HasWriter,
-    ZBuf,
+    SplitBuffer, ZBuf,
 };
-use zenoh_codec::{WCodec, Zenoh060};
+use zenoh_codec::{WCodec, Zenoh060Version};
 use zenoh_config::QueueSizeConf;
 use zenoh_core::zlock;
 use zenoh_protocol::{
-    core::{Channel, Priority},
+    core::{Channel, Priority, WireExpr},
     transport::TransportMessage,
-    zenoh::ZenohMessage,
+    zenoh::{ZenohBody, ZenohMessage},
 };
 
 // It's faster to work directly with nanoseconds.
@@ -43,6 +45,8 @@ type NanoSeconds = u32;
 
 const RBLEN: usize = QueueSizeConf::MAX;
 const TSLOT: NanoSeconds = 100;
+// The maximum number of key expressions whose last message is kept in keep-last mode
+const KEEP_LAST_MAX: usize = 256;
 
 // Inner structure to reuse serialization batches
 struct StageInRefill {
@@ -58,6 +62,10 @@ impl StageInRefill {
     fn wait(&self) -> bool {
         self.n_ref_r.recv().is_ok()
     }
+
+    fn wait_deadline(&self, deadline: Instant) -> Result<(), RecvTimeoutError> {
+        self.n_ref_r.recv_deadline(deadline)
+    }
 }
 
 // Inner structure to link the initial stage with the final stage of the pipeline
@@ -107,16 +115,161 @@ impl StageInMutex {
     }
 }
 
+// The messages kept by a queue in keep-last mode while no batch is available,
+// at most one per key expression and in the order they will be serialized.
+// Once KEEP_LAST_MAX key expressions are kept, the oldest message is dropped.
+struct StageInKeepLast {
+    priority: Priority,
+    batch_size: u16,
+    msgs: VecDeque<(WireExpr<'static>, ZenohMessage, Option<Instant>, bool)>,
+    is_empty: Arc<AtomicBool>,
+}
+
+impl StageInKeepLast {
+    // The key of the messages replacing each other in keep-last mode
+    fn key(&self, msg: &ZenohMessage) -> Option<WireExpr<'static>> {
+        match &msg.body {
+            // Messages that may need to be fragmented are never kept
+            ZenohBody::Data(data)
+                if data.reply_context.is_none()
+                    && data.payload.len() < self.batch_size as usize =>
+            {
+                Some(data.key.clone())
+            }
+            _ => None,
+        }
+    }
+
+    // Keep the message in place of the previous one kept for the same key expression
+    fn keep(
+        &mut self,
+        key: WireExpr<'static>,
+        msg: &ZenohMessage,
+        deadline: Option<Instant>,
+        is_express: bool,
+    ) {
+        self.msgs.retain(|(k, _, _, _)| *k != key);
+        if self.msgs.len() >= KEEP_LAST_MAX {
+            self.msgs.pop_front();
+        }
+        self.msgs
+            .push_back((key, msg.clone(), deadline, is_express));
+        self.is_empty.store(false, Ordering::Relaxed);
+    }
+}
+
+#[inline]
+fn is_expired(deadline: Option<Instant>) -> bool {
+    deadline.map_or(false, |d| Instant::now() >= d)
+}
+
 // This is the initial stage of the pipeline where messages are serliazed on
 struct StageIn {
     s_ref: StageInRefill,
     s_out: StageInOut,
     mutex: StageInMutex,
     fragbuf: ZBuf,
+    frag_max_size: usize,
+    codec: Zenoh060Version,
+    keep_last: Option<StageInKeepLast>,
+    expired: Arc<AtomicUsize>,
 }
 
 impl StageIn {
-    fn push_zenoh_message(&mut self, msg: &mut ZenohMessage, priority: Priority) -> bool {
+    fn push_zenoh_message(
+        &mut self,
+        msg: &mut ZenohMessage,
+        priority: Priority,
+        deadline: Option<Instant>,
+        is_express: bool,
+    ) -> bool {
+        // The message may have expired while waiting for the queue
+        if is_expired(deadline) {
+            self.expired.fetch_add(1, Ordering::Relaxed);
+            return false;
+        }
+
+        if self.keep_last.is_none() {
+            return self.push_zenoh_message_inner(msg, priority, deadline, is_express, false, true);
+        }
+        // Serialize the kept messages first to preserve the order of the messages
+        let is_flushed = self.flush_kept(true);
+        match self.keep_last.as_ref().and_then(|kl| kl.key(msg)) {
+            Some(key) if !is_flushed => {
+                self.keep_last
+                    .as_mut()
+                    .unwrap()
+                    .keep(key, msg, deadline, is_express);
+                true
+            }
+            Some(_) => {
+                self.push_zenoh_message_inner(msg, priority, deadline, is_express, true, true)
+            }
+            None => self.push_zenoh_message_inner(msg, priority, deadline, is_express, false, true),
+        }
+    }
+
+    // Serialize the kept messages as long as batches are available. Returns true if
+    // no message is kept anymore. When not blocking, the messages are only serialized
+    // if this does not require to wait for a batch.
+    fn flush_kept(&mut self, is_blocking: bool) -> bool {
+        let (priority, mut msgs) = match self.keep_last.as_mut() {
+            Some(kl) if !kl.msgs.is_empty() => (kl.priority, std::mem::take(&mut kl.msgs)),
+            _ => return true,
+        };
+        while let Some((_, mut msg, deadline, is_express)) = msgs.pop_front() {
+            if is_expired(deadline) {
+                self.expired.fetch_add(1, Ordering::Relaxed);
+                continue;
+            }
+            self.push_zenoh_message_inner(
+                &mut msg,
+                priority,
+                deadline,
+                is_express,
+                true,
+                is_blocking,
+            );
+            let kl = self.keep_last.as_mut().unwrap();
+            if !kl.msgs.is_empty() {
+                // No batch is available: the message has been kept again
+                kl.msgs.extend(msgs);
+                return false;
+            }
+        }
+        self.keep_last
+            .as_ref()
+            .unwrap()
+            .is_empty
+            .store(true, Ordering::Relaxed);
+        true
+    }
+
+    // Send the current batch right away instead of waiting for more messages
+    fn flush(&mut self) {
+        self.flush_kept(false);
+        let mut c_guard = self.mutex.current();
+        if let Some(batch) = c_guard.take() {
+            if batch.is_empty() {
+                *c_guard = Some(batch);
+            } else {
+                self.s_out.move_batch(batch);
+            }
+        }
+    }
+
+    // Serialize the message. When keeping, a message that would otherwise wait for a batch
+    // or be dropped is kept instead. When not blocking, a message whose serialization
+    // would wait for a batch is kept instead.
+    fn push_zenoh_message_inner(
+        &mut self,
+        msg: &mut ZenohMessage,
+        priority: Priority,
+        deadline: Option<Instant>,
+        is_express: bool,
+        is_keeping: bool,
+        is_blocking: bool,
+    ) -> bool {
         // Lock the current serialization batch.
         let mut c_guard = self.mutex.current();
 
@@ -124,7 +277,7 @@ impl StageIn {
         let is_droppable = msg.is_droppable();
 
         macro_rules! zgetbatch_rets {
-            ($fragment:expr) => {
+            ($fragment:expr, $restore:block) => {
                 loop {
                     match c_guard.take() {
                         Some(batch) => break batch,
@@ -135,12 +288,29 @@ impl StageIn {
                             }
                             None => {
                                 drop(c_guard);
-                                if !$fragment && is_droppable {
+                                if !$fragment && is_keeping {
+                                    // Keep the message until a batch is available
+                                    $restore
+                                    let kl = self.keep_last.as_mut().unwrap();
+                                    let key = kl.key(msg).unwrap();
+                                    kl.keep(key, msg, deadline, is_express);
+                                    return true;
+                                } else if !$fragment && is_droppable {
                                     // We are in the congestion scenario
                                     // The yield is to avoid the writing task to spin
                                     // indefinitely and monopolize the CPU usage.
                                     thread::yield_now();
                                     return false;
+                                } else if let (false, Some(deadline)) = ($fragment, deadline) {
+                                    // Drop the message if no batch is available before its deadline
+                                    match self.s_ref.wait_deadline(deadline) {
+                                        Ok(_) => {}
+                                        Err(RecvTimeoutError::Timeout) => {
+                                            self.expired.fetch_add(1, Ordering::Relaxed);
+                                            return false;
+                                        }
+                                        Err(RecvTimeoutError::Disconnected) => return false,
+                                    }
                                 } else {
                                     if !self.s_ref.wait() {
                                         return false;
@@ -156,16 +326,22 @@ impl StageIn {
 
         macro_rules! zretok {
             ($batch:expr) => {{
-                let bytes = $batch.len();
-                *c_guard = Some($batch);
-                drop(c_guard);
-                self.s_out.notify(bytes);
+                if is_express {
+                    // Send the batch right away instead of waiting for more messages
+                    self.s_out.move_batch($batch);
+                    drop(c_guard);
+                } else {
+                    let bytes = $batch.len();
+                    *c_guard = Some($batch);
+                    drop(c_guard);
+                    self.s_out.notify(bytes);
+                }
                 return true;
             }};
         }
 
         // Get the current serialization batch.
-        let mut batch = zgetbatch_rets!(false);
+        let mut batch = zgetbatch_rets!(false, {});
         // Attempt the serialization on the current batch
         let e = match batch.encode(&*msg) {
             Ok(_) => zretok!(batch),
@@ -194,7 +370,10 @@ impl StageIn {
         if !batch.is_empty() {
             // Move out existing batch
             self.s_out.move_batch(batch);
-            batch = zgetbatch_rets!(false);
+            batch = zgetbatch_rets!(false, {
+                // The message is serialized again once a batch is available
+                tch.sn.set(sn).unwrap();
+            });
         }
 
         // Attempt a second serialization on fully empty batch
@@ -207,19 +386,42 @@ impl StageIn {
         // Reinsert the current batch for fragmentation.
         *c_guard = Some(batch);
 
+        if !is_blocking {
+            // Fragmenting may wait for batches: keep the message until it can block
+            tch.sn.set(sn).unwrap();
+            drop(tch);
+            drop(c_guard);
+            let kl = self.keep_last.as_mut().unwrap();
+            let key = kl.key(msg).unwrap();
+            kl.keep(key, msg, deadline, is_express);
+            return true;
+        }
+
         // Take the expandable buffer and serialize the totality of the message
         self.fragbuf.clear();
 
         let mut writer = self.fragbuf.writer();
-        let codec = Zenoh060::default();
+        let codec = self.codec;
         codec.write(&mut writer, &*msg).unwrap();
 
+        if self.fragbuf.len() > self.frag_max_size {
+            // Restore the sequence number
+            tch.sn.set(sn).unwrap();
+            log::warn!(
+                "Zenoh message dropped because it is larger than the maximum fragmented size: {} > {}",
+                self.fragbuf.len(),
+                self.frag_max_size
+            );
+            self.fragbuf.clear();
+            return false;
+        }
+
         // Fragment the whole message
         let mut reader = self.fragbuf.reader();
         while reader.can_read() {
             // Get the current serialization batch
             // Treat all messages as non-droppable once we start fragmenting
-            batch = zgetbatch_rets!(true);
+            batch = zgetbatch_rets!(true, {});
 
             // Serialize the message fragmnet
             match batch.encode((&mut reader, channel, sn)) {
@@ -317,6 +519,7 @@ enum Pull {
 // Inner structure to keep track and signal backoff operations
 #[derive(Clone)]
 struct Backoff {
+    slot: NanoSeconds,
     retry_time: NanoSeconds,
     last_bytes: u16,
     bytes: Arc<AtomicU16>,
@@ -324,8 +527,9 @@ struct Backoff {
 }
 
 impl Backoff {
-    fn new(bytes: Arc<AtomicU16>, backoff: Arc<AtomicBool>) -> Self {
+    fn new(slot: NanoSeconds, bytes: Arc<AtomicU16>, backoff: Arc<AtomicBool>) -> Self {
         Self {
+            slot,
             retry_time: 0,
             last_bytes: 0,
             bytes,
@@ -335,7 +539,7 @@ impl Backoff {
 
     fn next(&mut self) {
         if self.retry_time == 0 {
-            self.retry_time = TSLOT;
+            self.retry_time = self.slot;
             self.backoff.store(true, Ordering::Relaxed);
         } else {
             self.retry_time *= 2;
@@ -348,6 +552,54 @@ impl Backoff {
     }
 }
 
+// Estimation of the fraction of time spent writing the pulled batches on the link,
+// used in adaptive mode to scale the backoff: batching only pays off on a busy link.
+struct LinkUtilization {
+    ratio: f64,
+    busy: Duration,
+    pulled: Option<Instant>,
+    waiting: Instant,
+}
+
+impl LinkUtilization {
+    // The weight of the last sample in the moving average
+    const ALPHA: f64 = 0.125;
+
+    fn new() -> Self {
+        Self {
+            ratio: 0.0,
+            busy: Duration::ZERO,
+            pulled: None,
+            waiting: Instant::now(),
+        }
+    }
+
+    // A pull has started: the link is done writing the last pulled batch. A pull cancelled
+    // by a timeout does not count as busy time when pulling again.
+    fn waiting(&mut self) {
+        if let Some(pulled) = self.pulled.take() {
+            let now = Instant::now();
+            self.busy = now.saturating_duration_since(pulled);
+            self.waiting = now;
+        }
+    }
+
+    // A batch has been pulled and is about to be written on the link
+    fn pulled(&mut self) {
+        let now = Instant::now();
+        let total = self.busy + now.saturating_duration_since(self.waiting);
+        if !total.is_zero() {
+            let sample = self.busy.as_secs_f64() / total.as_secs_f64();
+            self.ratio += Self::ALPHA * (sample - self.ratio);
+        }
+        self.pulled = Some(now);
+    }
+
+    fn scale(&self, slot: NanoSeconds) -> NanoSeconds {
+        ((slot as f64 * self.ratio) as NanoSeconds).max(1)
+    }
+}
+
 // Inner structure to link the final stage with the initial stage of the pipeline
 struct StageOutIn {
     s_out_r: RingBufferReader<WBatch, RBLEN>,
@@ -433,6 +685,7 @@ impl StageOutRefill {
 struct StageOut {
     s_in: StageOutIn,
     s_ref: StageOutRefill,
+    kept_is_empty: Option<Arc<AtomicBool>>,
 }
 
 impl StageOut {
@@ -441,6 +694,13 @@ impl StageOut {
         self.s_in.try_pull()
     }
 
+    #[inline]
+    fn has_kept(&self) -> bool {
+        self.kept_is_empty
+            .as_ref()
+            .map_or(false, |e| !e.load(Ordering::Relaxed))
+    }
+
     #[inline]
     fn refill(&mut self, batch: WBatch) {
         self.s_ref.refill(batch);
@@ -464,18 +724,32 @@ impl StageOut {
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub(crate) struct TransmissionPipelineConf {
     pub(crate) is_streamed: bool,
+    pub(crate) is_striped: bool,
     pub(crate) batch_size: u16,
     pub(crate) queue_size: [usize; Priority::NUM],
     pub(crate) backoff: Duration,
+    // The maximum size of the messages being fragmented, larger ones are dropped
+    pub(crate) frag_max_size: usize,
+    // The codec of the protocol version negotiated with the peer
+    pub(crate) codec: Zenoh060Version,
+    // Whether the backoff is scaled by the observed utilization of the link
+    pub(crate) adaptive_backoff: bool,
+    // Whether the RealTime queue keeps only the last message per key expression when congested
+    pub(crate) keep_last: bool,
 }
 
 impl Default for TransmissionPipelineConf {
     fn default() -> Self {
         Self {
             is_streamed: false,
+            is_striped: false,
             batch_size: u16::MAX,
             queue_size: [1; Priority::NUM],
             backoff: Duration::from_micros(1),
+            frag_max_size: usize::MAX,
+            codec: Zenoh060Version::latest(),
+            adaptive_backoff: false,
+            keep_last: false,
         }
     }
 }
@@ -502,6 +776,14 @@ impl TransmissionPipeline {
         // This is a MPSC channel
         let (n_out_w, n_out_r) = bounded(1);
 
+        // The number of messages dropped because of their expired deadline
+        let expired = Arc::new(AtomicUsize::new(0));
+
+        // The initial backoff time
+        let slot = NanoSeconds::try_from(config.backoff.as_nanos())
+            .unwrap_or(NanoSeconds::MAX)
+            .max(1);
+
         for (prio, num) in size_iter.enumerate() {
             assert!(*num != 0 && *num <= RBLEN);
 
@@ -511,7 +793,11 @@ impl TransmissionPipeline {
             // Fill the refill ring buffer with batches
             for _ in 0..*num {
                 assert!(s_ref_w
-                    .push(WBatch::new(config.batch_size, config.is_streamed))
+                    .push(
+                        WBatch::new(config.batch_size, config.is_streamed)
+                            .striped(config.is_striped)
+                            .codec(config.codec)
+                    )
                     .is_none());
             }
             // Create the channel for notifying that new batches are in the refill ring buffer
@@ -525,6 +811,17 @@ impl TransmissionPipeline {
             let bytes = Arc::new(AtomicU16::new(0));
             let backoff = Arc::new(AtomicBool::new(false));
 
+            // Only the RealTime queue can keep the last message per key expression
+            let keep_last =
+                (config.keep_last && conduit.len() > 1 && prio == Priority::RealTime as usize)
+                    .then(|| StageInKeepLast {
+                        priority: Priority::RealTime,
+                        batch_size: config.batch_size,
+                        msgs: VecDeque::new(),
+                        is_empty: Arc::new(AtomicBool::new(true)),
+                    });
+            let kept_is_empty = keep_last.as_ref().map(|kl| kl.is_empty.clone());
+
             stage_in.push(Mutex::new(StageIn {
                 s_ref: StageInRefill { n_ref_r, s_ref_r },
                 s_out: StageInOut {
@@ -538,6 +835,10 @@ impl TransmissionPipeline {
                     conduit: conduit[prio].clone(),
                 },
                 fragbuf: ZBuf::default(),
+                frag_max_size: config.frag_max_size,
+                codec: config.codec,
+                keep_last,
+                expired: expired.clone(),
             }));
 
             // The stage out for this priority
@@ -545,21 +846,28 @@ impl TransmissionPipeline {
                 s_in: StageOutIn {
                     s_out_r,
                     current,
-                    backoff: Backoff::new(bytes, backoff),
+                    backoff: Backoff::new(slot, bytes, backoff),
                 },
                 s_ref: StageOutRefill { n_ref_w, s_ref_w },
+                kept_is_empty,
             });
         }
 
         let active = Arc::new(AtomicBool::new(true));
+        let stage_in: Arc<[Mutex<StageIn>]> = stage_in.into_boxed_slice().into();
         let producer = TransmissionPipelineProducer {
-            stage_in: stage_in.into_boxed_slice().into(),
+            stage_in: stage_in.clone(),
             active: active.clone(),
         };
         let consumer = TransmissionPipelineConsumer {
+            stage_in,
             stage_out: stage_out.into_boxed_slice(),
             n_out_r,
             active,
+            slot,
+            utilization: config.adaptive_backoff.then(LinkUtilization::new),
+            #[cfg(feature = "stats")]
+            expired,
         };
 
         (producer, consumer)
@@ -582,9 +890,12 @@ impl TransmissionPipelineProducer {
         } else {
             (0, Priority::default())
         };
+        // The deadline is computed before waiting for the queue
+        let deadline = msg.ttl.map(|ttl| Instant::now() + ttl);
+        let express = msg.express;
         // Lock the channel. We are the only one that will be writing on it.
         let mut queue = zlock!(self.stage_in[idx]);
-        queue.push_zenoh_message(&mut msg, priority)
+        queue.push_zenoh_message(&mut msg, priority, deadline, express)
     }
 
     #[inline]
@@ -600,6 +911,22 @@ impl TransmissionPipelineProducer {
         queue.push_transport_message(msg)
     }
 
+    /// Sends right away the messages waiting in the queue of the given priority,
+    /// or in all the queues if no priority is given.
+    pub(crate) fn flush(&self, priority: Option<Priority>) {
+        match priority {
+            // If the queue is not QoS, it means that we only have one priority with index 0.
+            Some(priority) if self.stage_in.len() > 1 => {
+                zlock!(self.stage_in[priority as usize]).flush();
+            }
+            _ => {
+                for queue in self.stage_in.iter() {
+                    zlock!(queue).flush();
+                }
+            }
+        }
+    }
+
     pub(crate) fn disable(&self) {
         self.active.store(false, Ordering::Relaxed);
 
@@ -616,20 +943,47 @@ impl TransmissionPipelineProducer {
 }
 
 pub(crate) struct TransmissionPipelineConsumer {
+    // Used to serialize the messages kept while no batch was available
+    stage_in: Arc<[Mutex<StageIn>]>,
     // A single Mutex for all the priority queues
     stage_out: Box<[StageOut]>,
     n_out_r: Receiver<()>,
     active: Arc<AtomicBool>,
+    slot: NanoSeconds,
+    utilization: Option<LinkUtilization>,
+    #[cfg(feature = "stats")]
+    expired: Arc<AtomicUsize>,
 }
 
 impl TransmissionPipelineConsumer {
     pub(crate) async fn pull(&mut self) -> Option<(WBatch, usize)> {
+        // In adaptive mode, scale the backoff with the utilization of the link
+        let slot = match self.utilization.as_mut() {
+            Some(u) => {
+                u.waiting();
+                u.scale(self.slot)
+            }
+            None => self.slot,
+        };
         while self.active.load(Ordering::Relaxed) {
             // Calculate the backoff maximum
             let mut bo = NanoSeconds::MAX;
             for (prio, queue) in self.stage_out.iter_mut().enumerate() {
+                queue.s_in.backoff.slot = slot;
+                if queue.has_kept() {
+                    // Never wait for the producers: retry later if one of them is pushing
+                    match self.stage_in[prio].try_lock() {
+                        Ok(mut s_in) => {
+                            s_in.flush_kept(false);
+                        }
+                        Err(_) => bo = bo.min(TSLOT),
+                    }
+                }
                 match queue.try_pull() {
                     Pull::Some(batch) => {
+                        if let Some(u) = self.utilization.as_mut() {
+                            u.pulled();
+                        }
                         return Some((batch, prio));
                     }
                     Pull::Backoff(b) => {
@@ -655,6 +1009,13 @@ impl TransmissionPipelineConsumer {
         self.stage_out[priority].refill(batch);
     }
 
+    /// Returns the number of messages dropped because of their expired deadline
+    /// since the last call.
+    #[cfg(feature = "stats")]
+    pub(crate) fn take_expired(&self) -> usize {
+        self.expired.swap(0, Ordering::Relaxed)
+    }
+
     pub(crate) fn drain(&mut self) -> Vec<(WBatch, usize)> {
         // Drain the remaining batches
         let mut batches = vec![];
@@ -685,7 +1046,7 @@ mod tests {
     use super::*;
     use async_std::{prelude::FutureExt, task};
     use std::{
-        convert::TryFrom,
+        convert::{TryFrom, TryInto},
         sync::{
             atomic::{AtomicUsize, Ordering},
             Arc,
@@ -709,17 +1070,26 @@ mod tests {
 
     const CONFIG: TransmissionPipelineConf = TransmissionPipelineConf {
         is_streamed: true,
+        is_striped: false,
         batch_size: BATCH_SIZE,
         queue_size: [1; Priority::NUM],
         backoff: Duration::from_micros(1),
+        frag_max_size: usize::MAX,
+        codec: Zenoh060Version::latest(),
+        adaptive_backoff: false,
+        keep_last: false,
     };
 
     #[test]
     fn tx_pipeline_flow() {
-        fn schedule(queue: TransmissionPipelineProducer, num_msg: usize, payload_size: usize) {
+        fn schedule(
+            queue: TransmissionPipelineProducer,
+            num_msg: usize,
+            payload_size: usize,
+            start: Instant,
+        ) {
             // Send reliable messages
-            let key = "test".into();
-            let payload = ZBuf::from(vec![0_u8; payload_size]);
+            let key: WireExpr = "test".into();
             let data_info = None;
             let routing_context = None;
             let reply_context = None;
@@ -730,34 +1100,44 @@ mod tests {
             };
             let congestion_control = CongestionControl::Block;
 
-            let message = ZenohMessage::make_data(
-                key,
-                payload,
-                channel,
-                congestion_control,
-                data_info,
-                routing_context,
-                reply_context,
-                attachment,
-            );
-
             println!(
                 "Pipeline Flow [>>>]: Sending {num_msg} messages with payload size of {payload_size} bytes"
             );
-            for i in 0..num_msg {
-                println!("Pipeline Flow [>>>]: Pushed {} msgs", i + 1);
-                queue.push_zenoh_message(message.clone());
+            for _ in 0..num_msg {
+                // Timestamp the payload to measure the latency
+                let mut payload = vec![0_u8; payload_size];
+                let sent = start.elapsed().as_nanos() as u64;
+                payload[..8].copy_from_slice(&sent.to_le_bytes());
+
+                let message = ZenohMessage::make_data(
+                    key.clone(),
+                    ZBuf::from(payload),
+                    channel,
+                    congestion_control,
+                    data_info.clone(),
+                    routing_context,
+                    reply_context.clone(),
+                    attachment.clone(),
+                );
+                queue.push_zenoh_message(message);
             }
         }
 
-        async fn consume(mut queue: TransmissionPipelineConsumer, num_msg: usize) {
+        async fn consume(
+            mut queue: TransmissionPipelineConsumer,
+            num_msg: usize,
+            start: Instant,
+        ) -> TransmissionPipelineConsumer {
             let mut batches: usize = 0;
             let mut bytes: usize = 0;
             let mut msgs: usize = 0;
             let mut fragments: usize = 0;
+            // The latency of the messages that have not been fragmented
+            let mut latencies: Vec<Duration> = vec![];
 
             while msgs != num_msg {
                 let (batch, priority) = queue.pull().await.unwrap();
+                let received = start.elapsed();
                 batches += 1;
                 bytes += batch.len() as usize;
                 // Create a ZBuf for deserialization starting from the batch
@@ -769,36 +1149,60 @@ mod tests {
                 loop {
                     let res: Result<TransportMessage, DidntRead> = codec.read(&mut reader);
                     match res {
-                        Ok(msg) => {
-                            match msg.body {
-                                TransportBody::Frame(Frame { payload, .. }) => match payload {
-                                    FramePayload::Messages { messages } => {
-                                        msgs += messages.len();
-                                    }
-                                    FramePayload::Fragment { is_final, .. } => {
-                                        fragments += 1;
-                                        if is_final {
-                                            msgs += 1;
+                        Ok(msg) => match msg.body {
+                            TransportBody::Frame(Frame { payload, .. }) => match payload {
+                                FramePayload::Messages { messages } => {
+                                    msgs += messages.len();
+                                    for m in messages {
+                                        if let ZenohBody::Data(data) = m.body {
+                                            let payload = data.payload.contiguous();
+                                            let sent = u64::from_le_bytes(
+                                                payload[..8].try_into().unwrap(),
+                                            );
+                                            latencies.push(
+                                                received.saturating_sub(Duration::from_nanos(sent)),
+                                            );
                                         }
                                     }
-                                },
-                                _ => {
-                                    msgs += 1;
                                 }
+                                FramePayload::Fragment { is_final, .. } => {
+                                    fragments += 1;
+                                    if is_final {
+                                        msgs += 1;
+                                    }
+                                }
+                            },
+                            _ => {
+                                msgs += 1;
                             }
-                            println!("Pipeline Flow [<<<]: Pulled {} msgs", msgs + 1);
-                        }
+                        },
                         Err(_) => break,
                     }
                 }
-                println!("Pipeline Flow [+++]: Refill {} msgs", msgs + 1);
                 // Reinsert the batch
                 queue.refill(batch, priority);
             }
 
+            let elapsed = start.elapsed();
             println!(
                 "Pipeline Flow [<<<]: Received {msgs} messages, {bytes} bytes, {batches} batches, {fragments} fragments"
             );
+            println!(
+                "Pipeline Flow [<<<]: Throughput of {:.0} msg/s, {:.3} MB/s",
+                msgs as f64 / elapsed.as_secs_f64(),
+                bytes as f64 / elapsed.as_secs_f64() / 1_000_000.0
+            );
+            if !latencies.is_empty() {
+                latencies.sort();
+                let sum: Duration = latencies.iter().sum();
+                println!(
+                    "Pipeline Flow [<<<]: Latency of {:?} on average, {:?} median, {:?} max",
+                    sum / latencies.len() as u32,
+                    latencies[latencies.len() / 2],
+                    latencies[latencies.len() - 1]
+                );
+            }
+            queue
         }
 
         // Pipeline conduits
@@ -811,33 +1215,119 @@ mod tests {
         // Payload size of the messages
         let payload_sizes = [8, 64, 512, 4_096, 8_192, 32_768, 262_144, 2_097_152];
 
+        // The batching modes
+        let configs = [
+            ("fixed", TransmissionPipelineConf::default()),
+            (
+                "adaptive",
+                TransmissionPipelineConf {
+                    adaptive_backoff: true,
+                    ..TransmissionPipelineConf::default()
+                },
+            ),
+        ];
+
         task::block_on(async {
-            for ps in payload_sizes.iter() {
-                if ZInt::try_from(*ps).is_err() {
-                    break;
+            for (mode, config) in configs.iter() {
+                for ps in payload_sizes.iter() {
+                    if ZInt::try_from(*ps).is_err() {
+                        break;
+                    }
+
+                    // Compute the number of messages to send
+                    let num_msg = max_msgs.min(bytes / ps);
+
+                    println!("Pipeline Flow [---]: {mode} backoff");
+                    let (producer, consumer) =
+                        TransmissionPipeline::make(config.clone(), conduits.as_slice());
+
+                    let start = Instant::now();
+                    let t_c = task::spawn(consume(consumer, num_msg, start));
+
+                    // The producer blocks while no batch is available
+                    let c_ps = *ps;
+                    let t_s = task::spawn_blocking(move || {
+                        schedule(producer, num_msg, c_ps, start);
+                    });
+
+                    let res = t_c.join(t_s).timeout(TIMEOUT).await;
+                    assert!(res.is_ok());
+
+                    // The fixed backoff is the configured one, while the adaptive backoff
+                    // is scaled down by the observed utilization of the link
+                    let (consumer, _) = res.unwrap();
+                    let slot = consumer.stage_out[0].s_in.backoff.slot;
+                    match consumer.utilization.as_ref() {
+                        None => {
+                            assert!(!config.adaptive_backoff);
+                            assert_eq!(slot, consumer.slot);
+                        }
+                        Some(u) => {
+                            assert!(config.adaptive_backoff);
+                            assert!((0.0..=1.0).contains(&u.ratio));
+                            assert!(slot <= consumer.slot);
+                        }
+                    }
                 }
+            }
+        });
+    }
 
-                // Compute the number of messages to send
-                let num_msg = max_msgs.min(bytes / ps);
+    #[test]
+    fn tx_pipeline_express_flush() {
+        let message = ZenohMessage::make_data(
+            "test".into(),
+            ZBuf::from(vec![0_u8; 8]),
+            Channel {
+                priority: Priority::Control,
+                reliability: Reliability::Reliable,
+            },
+            CongestionControl::Block,
+            None,
+            None,
+            None,
+            None,
+        );
+        // Whether a batch is ready to be sent without waiting for the backoff
+        let is_sent = |consumer: &mut TransmissionPipelineConsumer| match consumer.stage_out[0]
+            .s_in
+            .s_out_r
+            .pull()
+        {
+            Some(batch) => {
+                consumer.refill(batch, 0);
+                true
+            }
+            None => false,
+        };
 
-                let (producer, consumer) = TransmissionPipeline::make(
-                    TransmissionPipelineConf::default(),
-                    conduits.as_slice(),
-                );
+        let tct = TransportConduitTx::make(SEQ_NUM_RES).unwrap();
+        let conduits = vec![tct];
+        let (producer, mut consumer) = TransmissionPipeline::make(CONFIG, conduits.as_slice());
+        let push = |express: bool| {
+            let mut message = message.clone();
+            message.express = express;
+            producer.push_zenoh_message(message)
+        };
 
-                let t_c = task::spawn(async move {
-                    consume(consumer, num_msg).await;
-                });
+        // A message waits in the current batch to be batched with the following ones
+        assert!(push(false));
+        assert!(!is_sent(&mut consumer));
 
-                let c_ps = *ps;
-                let t_s = task::spawn(async move {
-                    schedule(producer, num_msg, c_ps);
-                });
+        // An express message is sent right away along with the messages batched before it
+        assert!(push(true));
+        assert!(is_sent(&mut consumer));
+        assert!(!is_sent(&mut consumer));
 
-                let res = t_c.join(t_s).timeout(TIMEOUT).await;
-                assert!(res.is_ok());
-            }
-        });
+        // Flushing sends the current batch right away
+        assert!(push(false));
+        assert!(!is_sent(&mut consumer));
+        producer.flush(None);
+        assert!(is_sent(&mut consumer));
+
+        // Flushing an empty queue sends nothing
+        producer.flush(Some(Priority::Control));
+        assert!(!is_sent(&mut consumer));
     }
 
     #[test]
@@ -938,6 +1428,201 @@ mod tests {
         });
     }
 
+    #[test]
+    fn tx_pipeline_expired() {
+        // Make sure to put only one message per batch
+        let payload_size = (CONFIG.batch_size / 2) as usize;
+        let message = ZenohMessage::make_data(
+            "test".into(),
+            ZBuf::from(vec![0_u8; payload_size]),
+            Channel {
+                priority: Priority::Control,
+                reliability: Reliability::Reliable,
+            },
+            CongestionControl::Block,
+            None,
+            None,
+            None,
+            None,
+        );
+
+        let tct = TransportConduitTx::make(SEQ_NUM_RES).unwrap();
+        let conduits = vec![tct];
+        let (producer, mut consumer) = TransmissionPipeline::make(CONFIG, conduits.as_slice());
+        let push = |ttl: Duration| {
+            let mut message = message.clone();
+            message.ttl = Some(ttl);
+            producer.push_zenoh_message(message)
+        };
+
+        // The first message fills the only batch of the queue
+        assert!(push(SLEEP));
+
+        // The second message waits for a batch until its deadline and is dropped
+        let now = Instant::now();
+        assert!(!push(SLEEP));
+        assert!(now.elapsed() >= SLEEP);
+
+        // An already expired message is dropped without waiting
+        assert!(!push(Duration::ZERO));
+
+        #[cfg(feature = "stats")]
+        assert_eq!(consumer.take_expired(), 2);
+
+        // Once the batch is sent, a message is pushed before its deadline
+        task::block_on(async {
+            let (batch, priority) = consumer.pull().timeout(TIMEOUT).await.unwrap().unwrap();
+            consumer.refill(batch, priority);
+        });
+        assert!(push(SLEEP));
+    }
+
+    #[test]
+    fn tx_pipeline_keep_last() {
+        // Make sure to put only one message per batch
+        let payload_size = (CONFIG.batch_size / 2) as usize;
+        let make = |key: &str, id: u8| {
+            let mut payload = vec![0_u8; payload_size];
+            payload[0] = id;
+            ZenohMessage::make_data(
+                key.to_string().into(),
+                ZBuf::from(payload),
+                Channel {
+                    priority: Priority::RealTime,
+                    reliability: Reliability::Reliable,
+                },
+                CongestionControl::Block,
+                None,
+                None,
+                None,
+                None,
+            )
+        };
+
+        let conduits = (0..Priority::NUM)
+            .map(|_| TransportConduitTx::make(SEQ_NUM_RES).unwrap())
+            .collect::<Vec<_>>();
+        let config = TransmissionPipelineConf {
+            is_streamed: false,
+            keep_last: true,
+            ..CONFIG
+        };
+        let (producer, mut consumer) = TransmissionPipeline::make(config, conduits.as_slice());
+
+        // None of these pushes blocks: once the only batch is full, the last message
+        // of each key expression is kept until a batch is available
+        assert!(producer.push_zenoh_message(make("test/a", 1)));
+        assert!(producer.push_zenoh_message(make("test/a", 2)));
+        assert!(producer.push_zenoh_message(make("test/b", 3)));
+        assert!(producer.push_zenoh_message(make("test/a", 4)));
+
+        task::block_on(async {
+            let mut ids = vec![];
+            while ids.len() < 3 {
+                let (batch, priority) = consumer.pull().timeout(TIMEOUT).await.unwrap().unwrap();
+                let mut reader = batch.as_bytes().reader();
+                let codec = Zenoh060::default();
+                while let Ok(msg) = codec.read(&mut reader) {
+                    let msg: TransportMessage = msg;
+                    if let TransportBody::Frame(Frame {
+                        payload: FramePayload::Messages { messages },
+                        ..
+                    }) = msg.body
+                    {
+                        for m in messages {
+                            if let ZenohBody::Data(data) = m.body {
+                                ids.push(data.payload.contiguous()[0]);
+                            }
+                        }
+                    }
+                }
+                consumer.refill(batch, priority);
+            }
+            assert_eq!(ids, vec![1, 3, 4]);
+        });
+    }
+
+    #[test]
+    fn tx_pipeline_keep_last_max() {
+        let make = |key: String| {
+            ZenohMessage::make_data(
+                key.into(),
+                ZBuf::from(vec![0_u8; 8]),
+                Channel {
+                    priority: Priority::RealTime,
+                    reliability: Reliability::Reliable,
+                },
+                CongestionControl::Block,
+                None,
+                None,
+                None,
+                None,
+            )
+        };
+
+        let mut kl = StageInKeepLast {
+            priority: Priority::RealTime,
+            batch_size: CONFIG.batch_size,
+            msgs: VecDeque::new(),
+            is_empty: Arc::new(AtomicBool::new(true)),
+        };
+        for i in 0..KEEP_LAST_MAX + 2 {
+            let msg = make(format!("test/{i}"));
+            kl.keep(kl.key(&msg).unwrap(), &msg, None, false);
+        }
+
+        // The messages of the oldest key expressions are dropped
+        assert_eq!(kl.msgs.len(), KEEP_LAST_MAX);
+        let key = |i: usize| WireExpr::from(format!("test/{i}"));
+        assert_eq!(kl.msgs.front().unwrap().0, key(2));
+        assert_eq!(kl.msgs.back().unwrap().0, key(KEEP_LAST_MAX + 1));
+
+        // Replacing the message of a kept key expression drops nothing
+        let msg = make("test/2".to_string());
+        kl.keep(kl.key(&msg).unwrap(), &msg, None, false);
+        assert_eq!(kl.msgs.len(), KEEP_LAST_MAX);
+        assert_eq!(kl.msgs.front().unwrap().0, key(3));
+        assert_eq!(kl.msgs.back().unwrap().0, key(2));
+    }
+
+    #[test]
+    fn tx_pipeline_frag_max_size() {
+        let make = |payload_size: usize| {
+            ZenohMessage::make_data(
+                "test".into(),
+                ZBuf::from(vec![0_u8; payload_size]),
+                Channel {
+                    priority: Priority::Control,
+                    reliability: Reliability::Reliable,
+                },
+                CongestionControl::Block,
+                None,
+                None,
+                None,
+                None,
+            )
+        };
+
+        let tct = TransportConduitTx::make(SEQ_NUM_RES).unwrap();
+        let conduits = vec![tct];
+        let config = TransmissionPipelineConf {
+            frag_max_size: 2 * CONFIG.batch_size as usize,
+            ..CONFIG
+        };
+        let (producer, mut consumer) = TransmissionPipeline::make(config, conduits.as_slice());
+
+        // A message larger than the maximum fragmented size is dropped before being fragmented
+        assert!(!producer.push_zenoh_message(make(4 * CONFIG.batch_size as usize)));
+
+        // The following messages are still sent
+        assert!(producer.push_zenoh_message(make(8)));
+        producer.flush(None);
+        task::block_on(async {
+            let (batch, _) = consumer.pull().timeout(TIMEOUT).await.unwrap().unwrap();
+            assert!(!batch.is_empty());
+        });
+    }
+
     #[test]
     #[ignore]
     fn tx_pipeline_thr() {
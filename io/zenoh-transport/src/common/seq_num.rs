@@ -130,7 +130,6 @@ impl SeqNum {
     /// # Arguments
     ///
     /// * `value` -  The sequence number which should be checked for gap computation.
-    #[cfg(test)] // @TODO: remove once reliability is implemented
     pub(crate) fn gap(&self, value: ZInt) -> ZResult<ZInt> {
         if value >= self.resolution {
             bail!("The sequence number value must be smaller than the resolution")
@@ -20,6 +20,14 @@ macro_rules! stats_struct {
         $field_vis:vis $field_name:ident,
         )*
      }
+     $(
+     extra {
+        $(
+        $(#[$extra_meta:meta])*
+        $extra_vis:vis $extra_name:ident: $extra_ty:ty,
+        )*
+     }
+     )?
     ) => {
         paste::paste! {
             $(#[$meta])*
@@ -28,6 +36,10 @@ macro_rules! stats_struct {
                 $(#[$field_meta:meta])*
                 $field_vis $field_name: usize,
                 )*
+                $($(
+                $(#[$extra_meta])*
+                $extra_vis $extra_name: $extra_ty,
+                )*)?
             }
 
             struct [<$struct_name Atomic>] {
@@ -41,6 +53,7 @@ macro_rules! stats_struct {
                 fn snapshot(&self) -> $struct_name {
                     $struct_name {
                         $($field_name: self.[<get_ $field_name>](),)*
+                        $($($extra_name: Default::default(),)*)?
                     }
                 }
 
@@ -28,13 +28,14 @@ use std::sync::Arc;
 use std::sync::RwLock;
 use std::time::Duration;
 use zenoh_cfg_properties::{config::*, Properties};
-use zenoh_config::{Config, QueueConf, QueueSizeConf};
+use zenoh_codec::Zenoh060Version;
+use zenoh_config::{Config, LinkTxConf, QueueConf, QueueSizeConf};
 use zenoh_core::zparse;
 use zenoh_crypto::{BlockCipher, PseudoRng};
 use zenoh_link::NewLinkChannelSender;
 use zenoh_protocol::{
     core::{EndPoint, Locator, Priority, WhatAmI, ZInt, ZenohId},
-    defaults::{BATCH_SIZE, SEQ_NUM_RES, VERSION},
+    defaults::{BATCH_SIZE, MIN_VERSION, SEQ_NUM_RES, VERSION},
 };
 use zenoh_result::{bail, ZResult};
 #[cfg(feature = "shared-memory")]
@@ -93,12 +94,16 @@ use zenoh_shm::SharedMemoryReader;
 
 pub struct TransportManagerConfig {
     pub version: u8,
+    pub min_version: u8,
     pub zid: ZenohId,
     pub whatami: WhatAmI,
     pub sn_resolution: ZInt,
     pub batch_size: u16,
     pub queue_size: [usize; Priority::NUM],
     pub queue_backoff: Duration,
+    pub queue_adaptive_backoff: bool,
+    pub queue_keep_last: bool,
+    pub frag_max_size: usize,
     pub defrag_buff_size: usize,
     pub link_rx_buffer_size: usize,
     pub unicast: TransportManagerConfigUnicast,
@@ -120,12 +125,16 @@ pub struct TransportManagerParams {
 
 pub struct TransportManagerBuilder {
     version: u8,
+    min_version: u8,
     zid: ZenohId,
     whatami: WhatAmI,
     sn_resolution: ZInt,
     batch_size: u16,
     queue_size: QueueSizeConf,
     queue_backoff: Duration,
+    queue_adaptive_backoff: bool,
+    queue_keep_last: bool,
+    frag_max_size: usize,
     defrag_buff_size: usize,
     link_rx_buffer_size: usize,
     unicast: TransportManagerBuilderUnicast,
@@ -135,6 +144,18 @@ pub struct TransportManagerBuilder {
 }
 
 impl TransportManagerBuilder {
+    /// Set the latest version of the protocol negotiated with the peers.
+    pub fn version(mut self, version: u8) -> Self {
+        self.version = version;
+        self
+    }
+
+    /// Set the oldest version of the protocol negotiated with the peers.
+    pub fn min_version(mut self, min_version: u8) -> Self {
+        self.min_version = min_version;
+        self
+    }
+
     pub fn zid(mut self, zid: ZenohId) -> Self {
         self.zid = zid;
         self
@@ -165,6 +186,21 @@ impl TransportManagerBuilder {
         self
     }
 
+    pub fn queue_adaptive_backoff(mut self, queue_adaptive_backoff: bool) -> Self {
+        self.queue_adaptive_backoff = queue_adaptive_backoff;
+        self
+    }
+
+    pub fn queue_keep_last(mut self, queue_keep_last: bool) -> Self {
+        self.queue_keep_last = queue_keep_last;
+        self
+    }
+
+    pub fn frag_max_size(mut self, frag_max_size: usize) -> Self {
+        self.frag_max_size = frag_max_size;
+        self
+    }
+
     pub fn defrag_buff_size(mut self, defrag_buff_size: usize) -> Self {
         self.defrag_buff_size = defrag_buff_size;
         self
@@ -210,9 +246,28 @@ impl TransportManagerBuilder {
                 .unwrap(),
         );
         self = self.batch_size(config.transport().link().tx().batch_size().unwrap());
+        self = self.frag_max_size(config.transport().link().tx().max_message_size().unwrap());
         self = self.defrag_buff_size(config.transport().link().rx().max_message_size().unwrap());
         self = self.link_rx_buffer_size(config.transport().link().rx().buffer_size().unwrap());
         self = self.queue_size(config.transport().link().tx().queue().size().clone());
+        self = self.queue_adaptive_backoff(
+            config
+                .transport()
+                .link()
+                .tx()
+                .queue()
+                .adaptive_backoff()
+                .unwrap(),
+        );
+        self = self.queue_keep_last(
+            config
+                .transport()
+                .link()
+                .tx()
+                .queue()
+                .real_time_keep_last()
+                .unwrap(),
+        );
         self = self.tx_threads(config.transport().link().tx().threads().unwrap());
 
         let (c, errors) = zenoh_link::LinkConfigurator::default()
@@ -242,6 +297,19 @@ impl TransportManagerBuilder {
     }
 
     pub fn build(self, handler: Arc<dyn TransportEventHandler>) -> ZResult<TransportManager> {
+        for version in [self.min_version, self.version] {
+            if Zenoh060Version::new(version).is_none() {
+                bail!("Unsupported protocol version: {}", version);
+            }
+        }
+        if self.min_version > self.version {
+            bail!(
+                "Invalid protocol versions: min version {} is greater than version {}",
+                self.min_version,
+                self.version
+            );
+        }
+
         let unicast = self.unicast.build()?;
         let multicast = self.multicast.build()?;
 
@@ -257,12 +325,16 @@ impl TransportManagerBuilder {
 
         let config = TransportManagerConfig {
             version: self.version,
+            min_version: self.min_version,
             zid: self.zid,
             whatami: self.whatami,
             sn_resolution: self.sn_resolution,
             batch_size: self.batch_size,
             queue_size,
             queue_backoff: self.queue_backoff,
+            queue_adaptive_backoff: self.queue_adaptive_backoff,
+            queue_keep_last: self.queue_keep_last,
+            frag_max_size: self.frag_max_size,
             defrag_buff_size: self.defrag_buff_size,
             link_rx_buffer_size: self.link_rx_buffer_size,
             unicast: unicast.config,
@@ -287,14 +359,20 @@ impl Default for TransportManagerBuilder {
     fn default() -> Self {
         let queue = QueueConf::default();
         let backoff = queue.backoff().unwrap();
+        let adaptive_backoff = queue.adaptive_backoff().unwrap();
+        let keep_last = queue.real_time_keep_last().unwrap();
         Self {
             version: VERSION,
+            min_version: MIN_VERSION,
             zid: ZenohId::rand(),
             whatami: ZN_MODE_DEFAULT.parse().unwrap(),
             sn_resolution: SEQ_NUM_RES,
             batch_size: BATCH_SIZE,
             queue_size: queue.size,
             queue_backoff: Duration::from_nanos(backoff),
+            queue_adaptive_backoff: adaptive_backoff,
+            queue_keep_last: keep_last,
+            frag_max_size: LinkTxConf::default().max_message_size().unwrap(),
             defrag_buff_size: zparse!(ZN_DEFRAG_BUFF_SIZE_DEFAULT).unwrap(),
             link_rx_buffer_size: zparse!(ZN_LINK_RX_BUFF_SIZE_DEFAULT).unwrap(),
             endpoint: HashMap::new(),
@@ -26,7 +26,7 @@ use std::convert::TryInto;
 use std::sync::Arc;
 use std::time::{Duration, Instant};
 use zenoh_buffers::reader::{HasReader, Reader};
-use zenoh_codec::{RCodec, Zenoh060};
+use zenoh_codec::{RCodec, Zenoh060Version};
 use zenoh_core::zlock;
 use zenoh_link::{LinkMulticast, Locator};
 use zenoh_protocol::{
@@ -95,9 +95,14 @@ impl TransportLinkMulticast {
         if self.handle_tx.is_none() {
             let tpc = TransmissionPipelineConf {
                 is_streamed: false,
+                is_striped: false,
                 batch_size: config.batch_size.min(self.link.get_mtu()),
                 queue_size: self.transport.manager.config.queue_size,
                 backoff: self.transport.manager.config.queue_backoff,
+                frag_max_size: self.transport.manager.config.frag_max_size,
+                codec: Zenoh060Version::new(config.version).unwrap_or_default(),
+                adaptive_backoff: self.transport.manager.config.queue_adaptive_backoff,
+                keep_last: self.transport.manager.config.queue_keep_last,
             };
             // The pipeline
             let (producer, consumer) = TransmissionPipeline::make(tpc, &conduit_tx);
@@ -226,6 +231,8 @@ async fn tx_task(
     let keep_alive = config.join_interval / config.keep_alive as u32;
     let mut last_join = Instant::now().checked_sub(config.join_interval).unwrap();
     loop {
+        #[cfg(feature = "stats")]
+        stats.inc_tx_z_expired(pipeline.take_expired());
         match pull(&mut pipeline, keep_alive)
             .race(join(last_join, config.join_interval))
             .await
@@ -348,8 +355,8 @@ async fn rx_task(
         Ok(Action::Stop)
     }
 
-    // The codec
-    let codec = Zenoh060::default();
+    // The codec: the messages of older versions of the protocol are a subset of the latest one
+    let codec = Zenoh060Version::latest();
 
     // The pool of buffers
     let mtu = link.get_mtu() as usize;
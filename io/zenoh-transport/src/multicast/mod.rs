@@ -30,7 +30,11 @@ use std::{
 use transport::{TransportMulticastConfig, TransportMulticastInner};
 use zenoh_core::zread;
 use zenoh_link::Link;
-use zenoh_protocol::{core::ZInt, transport::tmsg, zenoh::ZenohMessage};
+use zenoh_protocol::{
+    core::{Priority, ZInt},
+    transport::tmsg,
+    zenoh::ZenohMessage,
+};
 use zenoh_result::{zerror, ZResult};
 
 /*************************************/
@@ -47,6 +51,7 @@ stats_struct! {
         pub tx_t_msgs,
         pub tx_z_msgs,
         pub tx_z_dropped,
+        pub tx_z_expired,
         pub tx_z_data_msgs,
         pub tx_z_data_payload_bytes,
         pub tx_z_data_reply_msgs,
@@ -140,6 +145,15 @@ impl TransportMulticast {
         Ok(())
     }
 
+    /// Sends right away the messages waiting to be batched in the transmission queues of the
+    /// given priority, or in all the transmission queues if no priority is given.
+    #[inline(always)]
+    pub fn flush(&self, priority: Option<Priority>) -> ZResult<()> {
+        let transport = self.get_transport()?;
+        transport.flush(priority);
+        Ok(())
+    }
+
     #[inline(always)]
     pub fn handle_message(&self, message: ZenohMessage) -> ZResult<()> {
         self.schedule(message)
@@ -160,12 +160,15 @@ impl TransportMulticastInner {
             return Ok(());
         }
 
-        if join.version != self.manager.config.version {
+        if join.version < self.manager.config.min_version
+            || join.version > self.manager.config.version
+        {
             log::debug!(
-                "Ingoring Join on {} from peer: {}. Unsupported version: {}. Expected: {}.",
+                "Ingoring Join on {} from peer: {}. Unsupported version: {}. Expected: {}..={}.",
                 locator,
                 join.zid,
                 join.version,
+                self.manager.config.min_version,
                 self.manager.config.version,
             );
             return Ok(());
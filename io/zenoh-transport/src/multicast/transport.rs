@@ -262,8 +262,9 @@ impl TransportMulticastInner {
         match guard.as_mut() {
             Some(l) => {
                 assert!(!self.conduit_tx.is_empty());
+                // Join and send with the oldest supported version, understood by all the peers
                 let config = TransportLinkMulticastConfig {
-                    version: self.manager.config.version,
+                    version: self.manager.config.min_version,
                     zid: self.manager.config.zid,
                     whatami: self.manager.config.whatami,
                     lease: self.manager.config.multicast.lease,
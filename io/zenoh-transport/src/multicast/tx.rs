@@ -13,6 +13,7 @@
 //
 use super::transport::TransportMulticastInner;
 use zenoh_core::zread;
+use zenoh_protocol::core::Priority;
 #[cfg(feature = "stats")]
 use zenoh_protocol::zenoh::ZenohBody;
 use zenoh_protocol::zenoh::ZenohMessage;
@@ -88,4 +89,11 @@ impl TransportMulticastInner {
 
         res
     }
+
+    pub(super) fn flush(&self, priority: Option<Priority>) {
+        let pipeline = zread!(self.link).as_ref().and_then(|l| l.pipeline.clone());
+        if let Some(pl) = pipeline {
+            pl.flush(priority);
+        }
+    }
 }
@@ -82,6 +82,8 @@ impl<P: 'static + Primitives> TransportPeerEventHandler for DeMux<P> {
                         payload,
                         msg.channel,
                         congestion_control,
+                        msg.ttl,
+                        msg.express,
                         data_info,
                         msg.routing_context,
                     );
@@ -16,6 +16,7 @@ mod mux;
 
 pub use demux::*;
 pub use mux::*;
+use std::time::Duration;
 use zenoh_buffers::ZBuf;
 use zenoh_protocol::{
     core::{
@@ -48,12 +49,15 @@ pub trait Primitives: Send + Sync {
     );
     fn forget_queryable(&self, key_expr: &WireExpr, routing_context: Option<RoutingContext>);
 
+    #[allow(clippy::too_many_arguments)]
     fn send_data(
         &self,
         key_expr: &WireExpr,
         payload: ZBuf,
         channel: Channel,
         cogestion_control: CongestionControl,
+        ttl: Option<Duration>,
+        express: bool,
         data_info: Option<DataInfo>,
         routing_context: Option<RoutingContext>,
     );
@@ -132,6 +136,8 @@ impl Primitives for DummyPrimitives {
         _payload: ZBuf,
         _channel: Channel,
         _cogestion_control: CongestionControl,
+        _ttl: Option<Duration>,
+        _express: bool,
         _info: Option<DataInfo>,
         _routing_context: Option<RoutingContext>,
     ) {
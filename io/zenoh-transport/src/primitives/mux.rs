@@ -13,6 +13,7 @@
 //
 use super::super::TransportUnicast;
 use super::Primitives;
+use std::time::Duration;
 use zenoh_buffers::ZBuf;
 use zenoh_protocol::{
     core::{
@@ -134,10 +135,12 @@ impl Primitives for Mux {
         payload: ZBuf,
         channel: Channel,
         cogestion_control: CongestionControl,
+        ttl: Option<Duration>,
+        express: bool,
         data_info: Option<DataInfo>,
         routing_context: Option<RoutingContext>,
     ) {
-        let _ = self.handler.handle_message(ZenohMessage::make_data(
+        let mut msg = ZenohMessage::make_data(
             key_expr.to_owned(),
             payload,
             channel,
@@ -146,7 +149,10 @@ impl Primitives for Mux {
             routing_context,
             None,
             None,
-        ));
+        );
+        msg.ttl = ttl;
+        msg.express = express;
+        let _ = self.handler.handle_message(msg);
     }
 
     fn send_query(
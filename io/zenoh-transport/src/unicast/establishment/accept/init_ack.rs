@@ -15,6 +15,7 @@ use super::{init_syn, AResult};
 use crate::{
     unicast::establishment::{
         authenticator::AuthenticatedPeerLink, Cookie, EstablishmentProperties, Zenoh060Cookie,
+        STRIPING_PROPERTY, VERSIONS_PROPERTY,
     },
     TransportManager,
 };
@@ -57,16 +58,34 @@ pub(super) async fn send(
 
     // Create the cookie
     let mut cookie = Cookie {
+        version: input.version,
         whatami: input.whatami,
         zid: input.zid,
         sn_resolution: agreed_sn_resolution,
         is_qos: input.is_qos,
+        is_striped: input.is_peer_striped,
         nonce: zasynclock!(manager.prng).gen_range(0..agreed_sn_resolution),
         properties: EstablishmentProperties::new(),
     };
 
     // Build the attachment from the authenticators
     let mut ps_attachment = EstablishmentProperties::new();
+    if input.is_versions {
+        ps_attachment
+            .insert(Property {
+                key: VERSIONS_PROPERTY,
+                value: vec![input.version],
+            })
+            .map_err(|e| (e, Some(tmsg::close_reason::UNSUPPORTED)))?;
+    }
+    if manager.config.unicast.scheduling.is_striped() {
+        ps_attachment
+            .insert(Property {
+                key: STRIPING_PROPERTY,
+                value: vec![],
+            })
+            .map_err(|e| (e, Some(tmsg::close_reason::UNSUPPORTED)))?;
+    }
     let mut ps_cookie = EstablishmentProperties::new();
     for pa in zasyncread!(manager.state.unicast.peer_authenticator).iter() {
         let (mut att, mut cke) = pa
@@ -13,7 +13,10 @@ use std::convert::TryFrom;
 // Contributors:
 //   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
 //
-use super::super::{AuthenticatedPeerLink, EstablishmentProperties};
+use super::super::{
+    negotiate_version, AuthenticatedPeerLink, EstablishmentProperties, STRIPING_PROPERTY,
+    VERSIONS_PROPERTY,
+};
 use super::AResult;
 use crate::TransportManager;
 use zenoh_link::LinkUnicast;
@@ -29,10 +32,13 @@ use zenoh_result::zerror;
 
 // Read and eventually accept an InitSyn
 pub(super) struct Output {
+    pub(super) version: u8,
+    pub(super) is_versions: bool,
     pub(super) whatami: WhatAmI,
     pub(super) zid: ZenohId,
     pub(super) sn_resolution: ZInt,
     pub(super) is_qos: bool,
+    pub(super) is_peer_striped: bool,
     pub(super) init_syn_properties: EstablishmentProperties,
 }
 pub(super) async fn recv(
@@ -83,28 +89,41 @@ pub(super) async fn recv(
         None => auth_link.peer_id = Some(init_syn.zid),
     }
 
-    // Check if the version is supported
-    if init_syn.version != manager.config.version {
-        let e = zerror!(
-            "Rejecting InitSyn on {} because of unsupported Zenoh version from peer: {}",
-            link,
-            init_syn.zid
-        );
-        return Err((e.into(), Some(tmsg::close_reason::INVALID)));
-    }
-
     // Validate the InitSyn with the peer authenticators
-    let init_syn_properties: EstablishmentProperties = match msg.attachment.take() {
+    let mut init_syn_properties: EstablishmentProperties = match msg.attachment.take() {
         Some(att) => EstablishmentProperties::try_from(&att)
             .map_err(|e| (e, Some(tmsg::close_reason::INVALID)))?,
         None => EstablishmentProperties::new(),
     };
 
+    // Choose the latest version supported by both peers, an opener running an older
+    // version of zenoh only supporting the version of its InitSyn
+    let versions = init_syn_properties.remove(VERSIONS_PROPERTY);
+    let is_versions = versions.is_some();
+    let versions = versions.map_or_else(|| vec![init_syn.version], |p| p.value);
+    let version = match negotiate_version(manager, &versions) {
+        Some(version) => version,
+        None => {
+            let e = zerror!(
+                "Rejecting InitSyn on {} because of unsupported Zenoh version from peer {}: {:?}",
+                link,
+                init_syn.zid,
+                versions
+            );
+            return Err((e.into(), Some(tmsg::close_reason::INVALID)));
+        }
+    };
+
+    let is_peer_striped = init_syn_properties.remove(STRIPING_PROPERTY).is_some();
+
     let output = Output {
+        version,
+        is_versions,
         whatami: init_syn.whatami,
         zid: init_syn.zid,
         sn_resolution: init_syn.sn_resolution,
         is_qos: init_syn.is_qos,
+        is_peer_striped,
         init_syn_properties,
     };
     Ok(output)
@@ -54,11 +54,13 @@ pub(crate) async fn accept_link(
     // Initialize the transport
     let zid = output.cookie.zid;
     let input = super::InputInit {
+        version: output.cookie.version,
         zid: output.cookie.zid,
         whatami: output.cookie.whatami,
         sn_resolution: output.cookie.sn_resolution,
         is_shm: output.is_shm,
         is_qos: output.cookie.is_qos,
+        is_peer_striped: output.cookie.is_striped,
     };
     let transport = step!(transport_init(manager, input)
         .await
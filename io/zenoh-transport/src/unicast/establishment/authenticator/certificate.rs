@@ -0,0 +1,81 @@
+//
+// Copyright (c) 2022 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+use super::{LinkAuthenticator, LinkAuthenticatorId, LinkUnicastAuthenticatorTrait, ZenohId};
+use async_trait::async_trait;
+use std::sync::Arc;
+use zenoh_config::Config;
+use zenoh_link::Link;
+use zenoh_result::{bail, ZResult};
+
+/// Accepts the incoming links whose remote end presented a certificate valid for one of the
+/// allowed DNS names, as per its subject alternative names. The certificate itself is validated
+/// by the link (e.g. TLS or QUIC with client authentication).
+pub struct CertificateAuthenticator {
+    allowed: Vec<String>,
+}
+
+impl CertificateAuthenticator {
+    pub fn new(allowed: Vec<String>) -> ZResult<CertificateAuthenticator> {
+        for name in allowed.iter() {
+            if webpki::DnsNameRef::try_from_ascii_str(name).is_err() {
+                bail!("Invalid certificate name: {}", name);
+            }
+        }
+        Ok(CertificateAuthenticator { allowed })
+    }
+
+    pub async fn from_config(config: &Config) -> ZResult<Option<CertificateAuthenticator>> {
+        let names = config.transport().auth().link().allowed_cert_names();
+        if names.is_empty() {
+            return Ok(None);
+        }
+        log::debug!("Certificate link authentication is enabled: {:?}", names);
+        CertificateAuthenticator::new(names.clone()).map(Some)
+    }
+}
+
+#[async_trait]
+impl LinkUnicastAuthenticatorTrait for CertificateAuthenticator {
+    fn id(&self) -> LinkAuthenticatorId {
+        LinkAuthenticatorId::Certificate
+    }
+
+    async fn close(&self) {}
+
+    async fn handle_new_link(&self, link: &Link) -> ZResult<Option<ZenohId>> {
+        match link.auth_id.as_ref() {
+            Some(id) if self.allowed.iter().any(|n| id.is_valid_for_name(n)) => Ok(None),
+            Some(_) => bail!(
+                "Rejected link {}: certificate not valid for {:?}",
+                link,
+                self.allowed
+            ),
+            None => bail!("Rejected link {}: no certificate", link),
+        }
+    }
+
+    async fn handle_link_err(&self, _link: &Link) {}
+}
+
+impl From<Arc<CertificateAuthenticator>> for LinkAuthenticator {
+    fn from(v: Arc<CertificateAuthenticator>) -> LinkAuthenticator {
+        LinkAuthenticator(v)
+    }
+}
+
+impl From<CertificateAuthenticator> for LinkAuthenticator {
+    fn from(v: CertificateAuthenticator) -> LinkAuthenticator {
+        Self::from(Arc::new(v))
+    }
+}
@@ -11,6 +11,8 @@
 // Contributors:
 //   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
 //
+mod certificate;
+mod network;
 #[cfg(feature = "auth_pubkey")]
 mod pubkey;
 #[cfg(feature = "shared-memory")]
@@ -20,6 +22,8 @@ mod userpassword;
 
 use crate::unicast::establishment::Cookie;
 use async_trait::async_trait;
+pub use certificate::*;
+pub use network::*;
 #[cfg(feature = "auth_pubkey")]
 pub use pubkey::*;
 #[cfg(feature = "shared-memory")]
@@ -43,13 +47,27 @@ use zenoh_result::ZResult;
 #[repr(u8)]
 pub enum LinkAuthenticatorId {
     Reserved = 0,
+    Network = 1,
+    Certificate = 2,
 }
 
 pub struct LinkAuthenticator(Arc<dyn LinkUnicastAuthenticatorTrait + Send + Sync>);
 
 impl LinkAuthenticator {
-    pub(crate) async fn from_config(_config: &Config) -> ZResult<HashSet<LinkAuthenticator>> {
-        Ok(HashSet::new())
+    pub(crate) async fn from_config(config: &Config) -> ZResult<HashSet<LinkAuthenticator>> {
+        let mut las = HashSet::new();
+
+        let mut res = NetworkAuthenticator::from_config(config).await?;
+        if let Some(la) = res.take() {
+            las.insert(la.into());
+        }
+
+        let mut res = CertificateAuthenticator::from_config(config).await?;
+        if let Some(la) = res.take() {
+            las.insert(la.into());
+        }
+
+        Ok(las)
     }
 }
 
@@ -311,6 +329,16 @@ pub trait PeerAuthenticatorTrait: Send + Sync {
     /// * `peerd_id` - The [`ZenohId`][ZenohId] of the transport being closed.
     ///
     async fn handle_close(&self, peer_id: &ZenohId);
+
+    /// Reload the credentials of the authenticator from the configuration, e.g. after the files
+    /// they are stored in have changed. Return the peers whose credentials have been revoked.
+    ///
+    /// # Arguments
+    /// * `config` - The configuration to reload the credentials from
+    ///
+    async fn reload(&self, _config: &Config) -> ZResult<Vec<ZenohId>> {
+        Ok(vec![])
+    }
 }
 
 /*************************************/
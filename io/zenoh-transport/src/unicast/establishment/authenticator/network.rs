@@ -0,0 +1,171 @@
+//
+// Copyright (c) 2022 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+use super::{LinkAuthenticator, LinkAuthenticatorId, LinkUnicastAuthenticatorTrait, ZenohId};
+use async_trait::async_trait;
+use std::fmt;
+use std::net::{IpAddr, Ipv4Addr, SocketAddr};
+use std::str::FromStr;
+use std::sync::Arc;
+use zenoh_config::Config;
+use zenoh_link::Link;
+use zenoh_result::{bail, zerror, Error as ZError, ZResult};
+
+/// An IP network, e.g. 192.168.1.0/24 or fd00::/8
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct IpNetwork {
+    addr: IpAddr,
+    prefix: u8,
+}
+
+impl IpNetwork {
+    pub fn new(addr: IpAddr, prefix: u8) -> ZResult<IpNetwork> {
+        let max = match addr {
+            IpAddr::V4(_) => 32,
+            IpAddr::V6(_) => 128,
+        };
+        if prefix > max {
+            bail!("Invalid prefix length for {}: {}", addr, prefix);
+        }
+        Ok(IpNetwork { addr, prefix })
+    }
+
+    pub fn contains(&self, addr: &IpAddr) -> bool {
+        // The peers connecting over IPv4 to a dual-stack listener have an IPv4-mapped IPv6 address
+        let addr = match addr {
+            IpAddr::V6(v6) => match v6.octets() {
+                [0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0xff, 0xff, a, b, c, d] => {
+                    IpAddr::V4(Ipv4Addr::new(a, b, c, d))
+                }
+                _ => *addr,
+            },
+            IpAddr::V4(_) => *addr,
+        };
+        match (self.addr, addr) {
+            (IpAddr::V4(net), IpAddr::V4(addr)) => {
+                let mask = u32::MAX.checked_shl(32 - self.prefix as u32).unwrap_or(0);
+                u32::from(net) & mask == u32::from(addr) & mask
+            }
+            (IpAddr::V6(net), IpAddr::V6(addr)) => {
+                let mask = u128::MAX.checked_shl(128 - self.prefix as u32).unwrap_or(0);
+                u128::from(net) & mask == u128::from(addr) & mask
+            }
+            _ => false,
+        }
+    }
+}
+
+impl FromStr for IpNetwork {
+    type Err = ZError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (addr, prefix) = match s.split_once('/') {
+            Some((addr, prefix)) => (addr, Some(prefix)),
+            None => (s, None),
+        };
+        let addr: IpAddr = addr
+            .parse()
+            .map_err(|e| zerror!("Invalid IP network {}: {}", s, e))?;
+        let prefix = match prefix {
+            Some(prefix) => prefix
+                .parse()
+                .map_err(|e| zerror!("Invalid IP network {}: {}", s, e))?,
+            None if addr.is_ipv4() => 32,
+            None => 128,
+        };
+        IpNetwork::new(addr, prefix)
+    }
+}
+
+impl fmt::Display for IpNetwork {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}/{}", self.addr, self.prefix)
+    }
+}
+
+/// Accepts the incoming links whose remote IP address belongs to one of the allowed networks.
+/// The links without IP address (e.g. unix sockets) are rejected unless explicitly allowed.
+pub struct NetworkAuthenticator {
+    allowed: Vec<IpNetwork>,
+    allow_non_ip: bool,
+}
+
+impl NetworkAuthenticator {
+    pub fn new(allowed: Vec<IpNetwork>) -> NetworkAuthenticator {
+        NetworkAuthenticator {
+            allowed,
+            allow_non_ip: false,
+        }
+    }
+
+    pub fn allow_non_ip(mut self, allow_non_ip: bool) -> Self {
+        self.allow_non_ip = allow_non_ip;
+        self
+    }
+
+    pub async fn from_config(config: &Config) -> ZResult<Option<NetworkAuthenticator>> {
+        let c = config.transport().auth().link();
+        let networks = c.allowed_networks();
+        if networks.is_empty() {
+            return Ok(None);
+        }
+        let allowed = networks
+            .iter()
+            .map(|n| n.parse())
+            .collect::<ZResult<Vec<IpNetwork>>>()?;
+        log::debug!("Network link authentication is enabled: {:?}", allowed);
+        Ok(Some(
+            NetworkAuthenticator::new(allowed).allow_non_ip(*c.allow_non_ip_links()),
+        ))
+    }
+}
+
+#[async_trait]
+impl LinkUnicastAuthenticatorTrait for NetworkAuthenticator {
+    fn id(&self) -> LinkAuthenticatorId {
+        LinkAuthenticatorId::Network
+    }
+
+    async fn close(&self) {}
+
+    async fn handle_new_link(&self, link: &Link) -> ZResult<Option<ZenohId>> {
+        let addr = match link.dst.address().as_str().parse::<SocketAddr>() {
+            Ok(addr) => addr.ip(),
+            Err(_) if self.allow_non_ip => return Ok(None),
+            Err(_) => bail!("Rejected link {}: no IP address", link),
+        };
+        if self.allowed.iter().any(|n| n.contains(&addr)) {
+            Ok(None)
+        } else {
+            bail!(
+                "Rejected link {}: {} is not in an allowed network",
+                link,
+                addr
+            )
+        }
+    }
+
+    async fn handle_link_err(&self, _link: &Link) {}
+}
+
+impl From<Arc<NetworkAuthenticator>> for LinkAuthenticator {
+    fn from(v: Arc<NetworkAuthenticator>) -> LinkAuthenticator {
+        LinkAuthenticator(v)
+    }
+}
+
+impl From<NetworkAuthenticator> for LinkAuthenticator {
+    fn from(v: NetworkAuthenticator) -> LinkAuthenticator {
+        Self::from(Arc::new(v))
+    }
+}
@@ -15,6 +15,7 @@ use super::{
     AuthenticatedPeerLink, PeerAuthenticator, PeerAuthenticatorId, PeerAuthenticatorTrait,
 };
 use crate::unicast::establishment::Cookie;
+use async_std::fs;
 use async_std::sync::Mutex;
 use async_trait::async_trait;
 use rand::SeedableRng;
@@ -328,9 +329,21 @@ impl PubKeyAuthenticator {
     }
 
     pub async fn from_config(config: &Config) -> ZResult<Option<PubKeyAuthenticator>> {
-        let c = config.transport().auth().pubkey();
+        let authenticator = match Self::from_config_keys(config)? {
+            Some(authenticator) => authenticator,
+            None => return Ok(None),
+        };
+
+        if let Some(file) = config.transport().auth().pubkey().known_keys_file() {
+            zasynclock!(authenticator.state).known_keys = Some(load_known_keys(file).await?);
+            log::debug!("PubKey known keys have been configured");
+        }
+
+        Ok(Some(authenticator))
+    }
 
-        // @TODO: support PubKey keys import
+    fn from_config_keys(config: &Config) -> ZResult<Option<PubKeyAuthenticator>> {
+        let c = config.transport().auth().pubkey();
 
         // First, check if PEM keys are provided
         match (c.public_key_pem(), c.private_key_pem()) {
@@ -670,6 +683,49 @@ impl PeerAuthenticatorTrait for PubKeyAuthenticator {
     async fn handle_close(&self, peer_id: &ZenohId) {
         zasynclock!(self.state).authenticated.remove(peer_id);
     }
+
+    async fn reload(&self, config: &Config) -> ZResult<Vec<ZenohId>> {
+        let file = match config.transport().auth().pubkey().known_keys_file() {
+            Some(file) => file,
+            None => return Ok(vec![]),
+        };
+        let known_keys = load_known_keys(file).await?;
+        log::debug!("PubKey known keys have been reloaded");
+
+        // The peers whose public key is no longer known are revoked
+        let mut guard = zasynclock!(self.state);
+        let revoked = guard
+            .authenticated
+            .iter()
+            .filter(|(_, key)| {
+                key.as_ref()
+                    .map_or(false, |key| !known_keys.iter().any(|x| x == key))
+            })
+            .map(|(zid, _)| *zid)
+            .collect();
+        guard.known_keys = Some(known_keys);
+
+        Ok(revoked)
+    }
+}
+
+// Load a file containing a sequence of PEM encoded RSA public keys
+async fn load_known_keys(path: &str) -> ZResult<Vec<ZPublicKey>> {
+    const PEM_END: &str = "-----END RSA PUBLIC KEY-----";
+
+    let content = fs::read_to_string(path)
+        .await
+        .map_err(|e| zerror!("Invalid PubKey known keys file: {}", e))?;
+    content
+        .split_inclusive(PEM_END)
+        .map(str::trim)
+        .filter(|pem| !pem.is_empty())
+        .map(|pem| {
+            RsaPublicKey::from_pkcs1_pem(pem)
+                .map(ZPublicKey::from)
+                .map_err(|e| zerror!("Rsa Public Key: {}", e).into())
+        })
+        .collect()
 }
 
 //noinspection ALL
@@ -20,7 +20,11 @@ use async_std::fs;
 use async_std::sync::{Mutex, RwLock};
 use async_trait::async_trait;
 use std::collections::{HashMap, HashSet};
+use std::convert::TryFrom;
+use std::fmt;
+use std::str::FromStr;
 use std::sync::Arc;
+use subtle::ConstantTimeEq;
 use zenoh_buffers::{
     reader::{DidntRead, HasReader, Reader},
     writer::{DidntWrite, HasWriter, Writer},
@@ -30,9 +34,15 @@ use zenoh_codec::{RCodec, WCodec, Zenoh060};
 use zenoh_config::Config;
 use zenoh_core::{zasynclock, zasyncread, zasyncwrite};
 use zenoh_crypto::hmac;
-use zenoh_result::{bail, zerror, ZResult};
+use zenoh_result::{bail, zerror, Error as ZError, ZResult};
 
 const USRPWD_VERSION: ZInt = 1;
+const SCRAM_PREFIX: &str = "$scram-sha3-256$";
+const SCRAM_SALT_LEN: usize = 16;
+// The PBKDF2 iterations of the plaintext passwords and of the unknown users
+const SCRAM_ITERATIONS: u32 = 10_000;
+// The maximum PBKDF2 iterations a peer is allowed to ask for
+const SCRAM_MAX_ITERATIONS: u32 = 1_000_000;
 
 /// # Attachment decorator
 ///
@@ -62,8 +72,11 @@ const USRPWD_VERSION: ZInt = 1;
 /// +-+-+-+---------+
 /// ~    version    ~
 /// +---------------+
+/// ~     user      ~ if present
+/// +---------------+
 struct InitSynProperty {
     version: ZInt,
+    user: Option<Vec<u8>>,
 }
 
 impl<W> WCodec<&InitSynProperty, &mut W> for Zenoh060
@@ -74,6 +87,9 @@ where
 
     fn write(self, writer: &mut W, x: &InitSynProperty) -> Self::Output {
         self.write(&mut *writer, x.version)?;
+        if let Some(user) = x.user.as_ref() {
+            self.write(&mut *writer, user.as_slice())?;
+        }
         Ok(())
     }
 }
@@ -86,7 +102,13 @@ where
 
     fn read(self, reader: &mut R) -> Result<InitSynProperty, Self::Error> {
         let version: ZInt = self.read(&mut *reader)?;
-        Ok(InitSynProperty { version })
+        // The user is appended by the peers supporting hashed passwords
+        let user: Option<Vec<u8>> = if reader.can_read() {
+            Some(self.read(&mut *reader)?)
+        } else {
+            None
+        };
+        Ok(InitSynProperty { version, user })
     }
 }
 
@@ -99,8 +121,13 @@ where
 /// +-+-+-+---------+
 /// ~     nonce     ~
 /// +---------------+
+/// ~  iterations   ~ if the user is present in the InitSyn
+/// +---------------+
+/// ~     salt      ~ if the user is present in the InitSyn
+/// +---------------+
 struct InitAckProperty {
     nonce: ZInt,
+    scram: Option<(ZInt, Vec<u8>)>,
 }
 
 impl<W> WCodec<&InitAckProperty, &mut W> for Zenoh060
@@ -111,6 +138,10 @@ where
 
     fn write(self, writer: &mut W, x: &InitAckProperty) -> Self::Output {
         self.write(&mut *writer, x.nonce)?;
+        if let Some((iterations, salt)) = x.scram.as_ref() {
+            self.write(&mut *writer, *iterations)?;
+            self.write(&mut *writer, salt.as_slice())?;
+        }
         Ok(())
     }
 }
@@ -123,7 +154,14 @@ where
 
     fn read(self, reader: &mut R) -> Result<InitAckProperty, Self::Error> {
         let nonce: ZInt = self.read(&mut *reader)?;
-        Ok(InitAckProperty { nonce })
+        let scram = if reader.can_read() {
+            let iterations: ZInt = self.read(&mut *reader)?;
+            let salt: Vec<u8> = self.read(&mut *reader)?;
+            Some((iterations, salt))
+        } else {
+            None
+        };
+        Ok(InitAckProperty { nonce, scram })
     }
 }
 
@@ -136,7 +174,7 @@ where
 /// +-+-+-+---------+
 /// ~     user      ~
 /// +---------------+
-/// ~     hash      ~
+/// ~     hash      ~ the client proof if the InitAck has a salt
 /// +---------------+
 struct OpenSynProperty {
     user: Vec<u8>,
@@ -169,6 +207,150 @@ where
     }
 }
 
+/*************************************/
+/*              SCRAM                */
+/*************************************/
+// The client key and the stored key of a password as per SCRAM (RFC 5802), with PBKDF2-HMAC-SHA3-256
+fn scram_keys(password: &[u8], salt: &[u8], iterations: u32) -> ZResult<(Vec<u8>, Vec<u8>)> {
+    let salted_password = hmac::pbkdf2(password, salt, iterations)?;
+    let client_key = hmac::sign(&salted_password, b"Client Key")?;
+    let stored_key = hmac::digest(&client_key);
+    Ok((client_key, stored_key))
+}
+
+// The client signature of the user and of the nonce
+fn scram_signature(stored_key: &[u8], user: &[u8], nonce: ZInt) -> ZResult<Vec<u8>> {
+    let mut message = user.to_vec();
+    message.extend_from_slice(&nonce.to_le_bytes());
+    hmac::sign(stored_key, &message)
+}
+
+fn xor(a: &[u8], b: &[u8]) -> Vec<u8> {
+    a.iter().zip(b.iter()).map(|(a, b)| a ^ b).collect()
+}
+
+/*************************************/
+/*            Password               */
+/*************************************/
+/// A password of the user-password dictionary: either in plaintext or hashed as a SCRAM stored key
+/// with PBKDF2-HMAC-SHA3-256, formatted as `$scram-sha3-256$<iterations>$<hex salt>$<hex stored key>`.
+#[derive(Clone, PartialEq, Eq)]
+pub enum Password {
+    Plain(Vec<u8>),
+    Hashed {
+        iterations: u32,
+        salt: Vec<u8>,
+        stored_key: Vec<u8>,
+    },
+}
+
+impl Password {
+    /// Hash a password with a random salt.
+    pub fn hash(password: &[u8], iterations: u32) -> ZResult<Password> {
+        if iterations == 0 || iterations > SCRAM_MAX_ITERATIONS {
+            bail!("Invalid number of PBKDF2 iterations: {}", iterations);
+        }
+        let salt = rand::random::<[u8; SCRAM_SALT_LEN]>().to_vec();
+        let (_, stored_key) = scram_keys(password, &salt, iterations)?;
+        Ok(Password::Hashed {
+            iterations,
+            salt,
+            stored_key,
+        })
+    }
+
+    /// Check a plaintext password against this one, in a time independent of their content.
+    pub fn verify(&self, password: &[u8]) -> bool {
+        match self {
+            Password::Plain(plain) => hmac::digest(plain).ct_eq(&hmac::digest(password)).into(),
+            Password::Hashed {
+                iterations,
+                salt,
+                stored_key,
+            } => match scram_keys(password, salt, *iterations) {
+                Ok((_, key)) => key.ct_eq(stored_key).into(),
+                Err(_) => false,
+            },
+        }
+    }
+}
+
+impl From<Vec<u8>> for Password {
+    fn from(password: Vec<u8>) -> Password {
+        match std::str::from_utf8(&password).map(Password::from_str) {
+            Ok(Ok(password)) => password,
+            _ => Password::Plain(password),
+        }
+    }
+}
+
+impl FromStr for Password {
+    type Err = ZError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let hashed = match s.strip_prefix(SCRAM_PREFIX) {
+            Some(hashed) => hashed,
+            None => return Ok(Password::Plain(s.as_bytes().to_vec())),
+        };
+        let mut fields = hashed.split('$');
+        match (fields.next(), fields.next(), fields.next(), fields.next()) {
+            (Some(iterations), Some(salt), Some(stored_key), None) => {
+                let iterations: u32 = iterations
+                    .parse()
+                    .map_err(|e| zerror!("Invalid hashed password iterations: {}", e))?;
+                if iterations == 0 || iterations > SCRAM_MAX_ITERATIONS {
+                    bail!("Invalid hashed password iterations: {}", iterations);
+                }
+                let salt = hex::decode(salt)
+                    .map_err(|e| zerror!("Invalid hashed password salt: {}", e))?;
+                let stored_key = hex::decode(stored_key)
+                    .map_err(|e| zerror!("Invalid hashed password stored key: {}", e))?;
+                if stored_key.len() != hmac::digest(&[]).len() {
+                    bail!("Invalid hashed password stored key length");
+                }
+                Ok(Password::Hashed {
+                    iterations,
+                    salt,
+                    stored_key,
+                })
+            }
+            _ => bail!("Invalid hashed password format"),
+        }
+    }
+}
+
+impl fmt::Display for Password {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Password::Plain(password) => write!(f, "{}", String::from_utf8_lossy(password)),
+            Password::Hashed {
+                iterations,
+                salt,
+                stored_key,
+            } => write!(
+                f,
+                "{}{}${}${}",
+                SCRAM_PREFIX,
+                iterations,
+                hex::encode(salt),
+                hex::encode(stored_key)
+            ),
+        }
+    }
+}
+
+async fn load_dictionary(path: &str) -> ZResult<HashMap<Vec<u8>, Password>> {
+    let content = fs::read_to_string(path)
+        .await
+        .map_err(|e| zerror!("Invalid user-password dictionary file: {}", e))?;
+    let mut lookup = HashMap::new();
+    let mut ps = Properties::from(content);
+    for (user, password) in ps.drain() {
+        lookup.insert(user.into(), password.parse()?);
+    }
+    Ok(lookup)
+}
+
 /*************************************/
 /*          Authenticator            */
 /*************************************/
@@ -177,13 +359,24 @@ struct Credentials {
     password: Vec<u8>,
 }
 
+// A user of the dictionary with the SCRAM parameters it is authenticated with
+struct User {
+    password: Password,
+    iterations: u32,
+    salt: Vec<u8>,
+    stored_key: Vec<u8>,
+}
+
 struct Authenticated {
-    credentials: Credentials,
+    user: Vec<u8>,
+    password: Password,
     links: HashSet<(Locator, Locator)>,
 }
 
 pub struct UserPasswordAuthenticator {
-    lookup: RwLock<HashMap<Vec<u8>, Vec<u8>>>,
+    // The secret the salts of the plaintext passwords and of the unknown users are derived from
+    secret: Vec<u8>,
+    lookup: RwLock<HashMap<Vec<u8>, User>>,
     credentials: Option<Credentials>,
     authenticated: Mutex<HashMap<ZenohId, Authenticated>>,
 }
@@ -197,16 +390,69 @@ impl UserPasswordAuthenticator {
             user: cr.0,
             password: cr.1,
         });
-        UserPasswordAuthenticator {
-            lookup: RwLock::new(lookup),
+        let mut authenticator = UserPasswordAuthenticator {
+            secret: rand::random::<[u8; 32]>().to_vec(),
+            lookup: RwLock::new(HashMap::new()),
             credentials,
             authenticated: Mutex::new(HashMap::new()),
+        };
+        for (user, password) in lookup.into_iter() {
+            match authenticator.user(&user, password.into()) {
+                Ok(u) => {
+                    authenticator.lookup.get_mut().insert(user, u);
+                }
+                Err(e) => log::error!(
+                    "Invalid password of user {}: {}",
+                    String::from_utf8_lossy(&user),
+                    e
+                ),
+            }
         }
+        authenticator
+    }
+
+    // The salt of a plaintext password or of an unknown user, the same for a given user
+    fn salt(&self, user: &[u8]) -> ZResult<Vec<u8>> {
+        let mut salt = hmac::sign(&self.secret, user)?;
+        salt.truncate(SCRAM_SALT_LEN);
+        Ok(salt)
+    }
+
+    fn user(&self, user: &[u8], password: Password) -> ZResult<User> {
+        let (iterations, salt, stored_key) = match &password {
+            Password::Plain(plain) => {
+                let salt = self.salt(user)?;
+                let (_, stored_key) = scram_keys(plain, &salt, SCRAM_ITERATIONS)?;
+                (SCRAM_ITERATIONS, salt, stored_key)
+            }
+            Password::Hashed {
+                iterations,
+                salt,
+                stored_key,
+            } => (*iterations, salt.clone(), stored_key.clone()),
+        };
+        Ok(User {
+            password,
+            iterations,
+            salt,
+            stored_key,
+        })
+    }
+
+    fn users(&self, dictionary: HashMap<Vec<u8>, Password>) -> ZResult<HashMap<Vec<u8>, User>> {
+        dictionary
+            .into_iter()
+            .map(|(user, password)| {
+                let u = self.user(&user, password)?;
+                Ok((user, u))
+            })
+            .collect()
     }
 
     pub async fn add_user(&self, user: Vec<u8>, password: Vec<u8>) -> ZResult<()> {
+        let u = self.user(&user, password.into())?;
         let mut guard = zasyncwrite!(self.lookup);
-        guard.insert(user, password);
+        guard.insert(user, u);
         Ok(())
     }
 
@@ -219,16 +465,10 @@ impl UserPasswordAuthenticator {
     pub async fn from_config(config: &Config) -> ZResult<Option<UserPasswordAuthenticator>> {
         let c = config.transport().auth().usrpwd();
 
-        let mut lookup: HashMap<Vec<u8>, Vec<u8>> = HashMap::new();
+        let mut dictionary: HashMap<Vec<u8>, Password> = HashMap::new();
         if let Some(dict) = c.dictionary_file() {
-            let content = fs::read_to_string(dict)
-                .await
-                .map_err(|e| zerror!("Invalid user-password dictionary file: {}", e))?;
             // Populate the user-password dictionary
-            let mut ps = Properties::from(content);
-            for (user, password) in ps.drain() {
-                lookup.insert(user.into(), password.into());
-            }
+            dictionary = load_dictionary(dict).await?;
             log::debug!("User-password dictionary has been configured");
         }
 
@@ -240,9 +480,11 @@ impl UserPasswordAuthenticator {
             }
         }
 
-        if !lookup.is_empty() || credentials.is_some() {
+        if !dictionary.is_empty() || credentials.is_some() {
             log::debug!("User-password authentication is enabled");
-            Ok(Some(UserPasswordAuthenticator::new(lookup, credentials)))
+            let mut authenticator = UserPasswordAuthenticator::new(HashMap::new(), credentials);
+            *authenticator.lookup.get_mut() = authenticator.users(dictionary)?;
+            Ok(Some(authenticator))
         } else {
             Ok(None)
         }
@@ -271,6 +513,7 @@ impl PeerAuthenticatorTrait for UserPasswordAuthenticator {
 
         let init_syn_property = InitSynProperty {
             version: USRPWD_VERSION,
+            user: self.credentials.as_ref().map(|cr| cr.user.clone()),
         };
         let mut wbuf = vec![];
         let codec = Zenoh060::default();
@@ -309,9 +552,20 @@ impl PeerAuthenticatorTrait for UserPasswordAuthenticator {
             bail!("Rejected InitSyn with invalid attachment on link: {}", link)
         }
 
+        // Tell the peers sending their user how to derive their SCRAM keys. The unknown users
+        // get a salt and a number of iterations as well, not to be told apart from the known ones.
+        let scram = match init_syn_property.user.as_ref() {
+            Some(user) => match zasyncread!(self.lookup).get(user) {
+                Some(u) => Some((u.iterations as ZInt, u.salt.clone())),
+                None => Some((SCRAM_ITERATIONS as ZInt, self.salt(user)?)),
+            },
+            None => None,
+        };
+
         // Create the InitAck attachment
         let init_ack_property = InitAckProperty {
             nonce: cookie.nonce,
+            scram,
         };
         let mut wbuf = vec![];
         let mut writer = wbuf.writer();
@@ -320,7 +574,8 @@ impl PeerAuthenticatorTrait for UserPasswordAuthenticator {
             .map_err(|_| zerror!("Error in encoding InitAck for UsrPwd on link: {}", link))?;
         let attachment = wbuf;
 
-        Ok((Some(attachment), None))
+        // The user the proof of the OpenSyn is expected for
+        Ok((Some(attachment), init_syn_property.user))
     }
 
     async fn handle_init_ack(
@@ -353,9 +608,22 @@ impl PeerAuthenticatorTrait for UserPasswordAuthenticator {
             )
         })?;
 
-        // Create the HMAC of the password using the nonce received as a key (it's a challenge)
-        let key = init_ack_property.nonce.to_le_bytes();
-        let hmac = hmac::sign(&key, &credentials.password)?;
+        let nonce = init_ack_property.nonce;
+        let hmac = match init_ack_property.scram {
+            // Prove the knowledge of the client key without disclosing it
+            Some((iterations, salt)) => {
+                let iterations = u32::try_from(iterations)
+                    .ok()
+                    .filter(|i| (1..=SCRAM_MAX_ITERATIONS).contains(i))
+                    .ok_or_else(|| zerror!("Invalid UsrPwd iterations on link: {}", link))?;
+                let (client_key, stored_key) =
+                    scram_keys(&credentials.password, &salt, iterations)?;
+                let signature = scram_signature(&stored_key, &credentials.user, nonce)?;
+                xor(&client_key, &signature)
+            }
+            // Create the HMAC of the password using the nonce received as a key (it's a challenge)
+            None => hmac::sign(&nonce.to_le_bytes(), &credentials.password)?,
+        };
         // Create the OpenSyn attachment
         let open_syn_property = OpenSynProperty {
             user: credentials.user.clone(),
@@ -378,7 +646,7 @@ impl PeerAuthenticatorTrait for UserPasswordAuthenticator {
         cookie: &Cookie,
         property: (Option<Vec<u8>>, Option<Vec<u8>>),
     ) -> ZResult<Option<Vec<u8>>> {
-        let (attachment, _cookie) = property;
+        let (attachment, scram_user) = property;
         let a = attachment.ok_or_else(|| {
             zerror!(
                 "Received OpenSyn with no UsrPwd attachment on link: {}",
@@ -396,36 +664,53 @@ impl PeerAuthenticatorTrait for UserPasswordAuthenticator {
             )
         })?;
         let password = match zasyncread!(self.lookup).get(&open_syn_property.user) {
-            Some(password) => password.clone(),
+            Some(u) => match scram_user {
+                // Verify the client key recovered from the proof against the stored key
+                Some(user) if user == open_syn_property.user => {
+                    let signature = scram_signature(&u.stored_key, &user, cookie.nonce)?;
+                    let client_key = xor(&open_syn_property.hmac, &signature);
+                    if open_syn_property.hmac.len() != signature.len()
+                        || !bool::from(hmac::digest(&client_key).ct_eq(&u.stored_key))
+                    {
+                        bail!("Received OpenSyn with invalid password on link: {}", link)
+                    }
+                    u.password.clone()
+                }
+                Some(_) => bail!("Received OpenSyn with invalid user on link: {}", link),
+                // The peers not sending their user in the InitSyn sign their password in plaintext
+                None => match &u.password {
+                    Password::Plain(plain) => {
+                        let hmac = hmac::sign(&cookie.nonce.to_le_bytes(), plain)?;
+                        if !bool::from(hmac.ct_eq(&open_syn_property.hmac)) {
+                            bail!("Received OpenSyn with invalid password on link: {}", link)
+                        }
+                        u.password.clone()
+                    }
+                    Password::Hashed { .. } => {
+                        bail!("Received OpenSyn with invalid password on link: {}", link)
+                    }
+                },
+            },
             None => bail!("Received OpenSyn with invalid user on link: {}", link),
         };
 
-        // Create the HMAC of the password using the nonce received as challenge
-        let key = cookie.nonce.to_le_bytes();
-        let hmac = hmac::sign(&key, &password)?;
-        if hmac != open_syn_property.hmac {
-            bail!("Received OpenSyn with invalid password on link: {}", link)
-        }
-
         // Check PID validity
         let mut guard = zasynclock!(self.authenticated);
         match guard.get_mut(&cookie.zid) {
             Some(auth) => {
-                if open_syn_property.user != auth.credentials.user
-                    || password != auth.credentials.password
-                {
+                if open_syn_property.user != auth.user || password != auth.password {
                     bail!("Received OpenSyn with invalid password on link: {}", link)
                 }
                 auth.links.insert((link.src.clone(), link.dst.clone()));
             }
             None => {
-                let credentials = Credentials {
+                let mut links = HashSet::new();
+                links.insert((link.src.clone(), link.dst.clone()));
+                let auth = Authenticated {
                     user: open_syn_property.user,
                     password,
+                    links,
                 };
-                let mut links = HashSet::new();
-                links.insert((link.src.clone(), link.dst.clone()));
-                let auth = Authenticated { credentials, links };
                 guard.insert(cookie.zid, auth);
             }
         }
@@ -460,6 +745,29 @@ impl PeerAuthenticatorTrait for UserPasswordAuthenticator {
     async fn handle_close(&self, peer_id: &ZenohId) {
         zasynclock!(self.authenticated).remove(peer_id);
     }
+
+    async fn reload(&self, config: &Config) -> ZResult<Vec<ZenohId>> {
+        let dict = match config.transport().auth().usrpwd().dictionary_file() {
+            Some(dict) => dict,
+            None => return Ok(vec![]),
+        };
+        let lookup = self.users(load_dictionary(dict).await?)?;
+        log::debug!("User-password dictionary has been reloaded");
+
+        // The peers whose user has been removed or whose password has changed are revoked
+        let revoked = zasynclock!(self.authenticated)
+            .iter()
+            .filter(|(_, auth)| {
+                lookup
+                    .get(&auth.user)
+                    .map_or(true, |u| u.password != auth.password)
+            })
+            .map(|(zid, _)| *zid)
+            .collect();
+        *zasyncwrite!(self.lookup) = lookup;
+
+        Ok(revoked)
+    }
 }
 
 //noinspection ALL
@@ -22,10 +22,12 @@ use zenoh_protocol::core::{Property, WhatAmI, ZInt, ZenohId};
 
 #[derive(Clone, Debug, PartialEq, Eq)]
 pub struct Cookie {
+    pub version: u8,
     pub whatami: WhatAmI,
     pub zid: ZenohId,
     pub sn_resolution: ZInt,
     pub is_qos: bool,
+    pub is_striped: bool,
     pub nonce: ZInt,
     pub properties: EstablishmentProperties,
 }
@@ -37,12 +39,15 @@ where
     type Output = Result<(), DidntWrite>;
 
     fn write(self, writer: &mut W, x: &Cookie) -> Self::Output {
+        self.write(&mut *writer, x.version)?;
         let wai: ZInt = x.whatami.into();
         self.write(&mut *writer, wai)?;
         self.write(&mut *writer, &x.zid)?;
         self.write(&mut *writer, x.sn_resolution)?;
         let is_qos = u8::from(x.is_qos);
         self.write(&mut *writer, is_qos)?;
+        let is_striped = u8::from(x.is_striped);
+        self.write(&mut *writer, is_striped)?;
         self.write(&mut *writer, x.nonce)?;
         self.write(&mut *writer, x.properties.as_slice())?;
 
@@ -57,12 +62,15 @@ where
     type Error = DidntRead;
 
     fn read(self, reader: &mut R) -> Result<Cookie, Self::Error> {
+        let version: u8 = self.read(&mut *reader)?;
         let wai: ZInt = self.read(&mut *reader)?;
         let whatami = WhatAmI::try_from(wai).ok_or(DidntRead)?;
         let zid: ZenohId = self.read(&mut *reader)?;
         let sn_resolution: ZInt = self.read(&mut *reader)?;
         let is_qos: u8 = self.read(&mut *reader)?;
         let is_qos = is_qos == 1;
+        let is_striped: u8 = self.read(&mut *reader)?;
+        let is_striped = is_striped == 1;
         let nonce: ZInt = self.read(&mut *reader)?;
         let mut ps: Vec<Property> = self.read(&mut *reader)?;
         let mut properties = EstablishmentProperties::new();
@@ -71,10 +79,12 @@ where
         }
 
         let cookie = Cookie {
+            version,
             whatami,
             zid,
             sn_resolution,
             is_qos,
+            is_striped,
             nonce,
             properties,
         };
@@ -133,10 +143,12 @@ impl Cookie {
         let mut rng = rand::thread_rng();
 
         Self {
+            version: rng.gen(),
             whatami: WhatAmI::rand(),
             zid: ZenohId::default(),
             sn_resolution: rng.gen(),
             is_qos: rng.gen_bool(0.5),
+            is_striped: rng.gen_bool(0.5),
             nonce: rng.gen(),
             properties: EstablishmentProperties::rand(),
         }
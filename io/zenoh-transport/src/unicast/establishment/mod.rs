@@ -56,15 +56,39 @@ pub(super) async fn close_link(
     }
 }
 
+/*************************************/
+/*             VERSION               */
+/*************************************/
+// The establishment property listing the protocol versions supported by the opener in the
+// InitSyn, and holding the version chosen by the acceptor in the InitAck. It is ignored by
+// the peers running older versions of zenoh, which only support the version of the InitSyn.
+const VERSIONS_PROPERTY: ZInt = 0x10;
+
+// The latest of the given protocol versions also supported by the manager
+fn negotiate_version(manager: &TransportManager, versions: &[u8]) -> Option<u8> {
+    versions
+        .iter()
+        .copied()
+        .filter(|v| (manager.config.min_version..=manager.config.version).contains(v))
+        .max()
+}
+
+// The establishment property announcing that the messages are striped over the links of the
+// transport, for the peer to reorder them. It is sent in the InitSyn by an opener and in the
+// InitAck by an acceptor striping its messages.
+const STRIPING_PROPERTY: ZInt = 0x11;
+
 /*************************************/
 /*            TRANSPORT              */
 /*************************************/
 pub(super) struct InputInit {
+    pub(super) version: u8,
     pub(super) zid: ZenohId,
     pub(super) whatami: WhatAmI,
     pub(super) sn_resolution: ZInt,
     pub(super) is_shm: bool,
     pub(super) is_qos: bool,
+    pub(super) is_peer_striped: bool,
 }
 async fn transport_init(
     manager: &TransportManager,
@@ -74,11 +98,13 @@ async fn transport_init(
     let initial_sn_tx = zasynclock!(manager.prng).gen_range(0..input.sn_resolution);
 
     let config = TransportConfigUnicast {
+        version: input.version,
         peer: input.zid,
         whatami: input.whatami,
         sn_resolution: input.sn_resolution,
         is_shm: input.is_shm,
         is_qos: input.is_qos,
+        is_peer_striped: input.is_peer_striped,
         initial_sn_tx,
     };
 
@@ -13,7 +13,8 @@
 //
 use crate::unicast::establishment::open::OResult;
 use crate::unicast::establishment::{
-    authenticator::AuthenticatedPeerLink, EstablishmentProperties,
+    authenticator::AuthenticatedPeerLink, negotiate_version, EstablishmentProperties,
+    STRIPING_PROPERTY, VERSIONS_PROPERTY,
 };
 use crate::TransportManager;
 use std::convert::TryFrom;
@@ -34,11 +35,13 @@ use crate::unicast::establishment::authenticator::PeerAuthenticatorId;
 /*              OPEN                 */
 /*************************************/
 pub(super) struct Output {
+    pub(super) version: u8,
     pub(super) zid: ZenohId,
     pub(super) whatami: WhatAmI,
     pub(super) sn_resolution: ZInt,
     pub(super) is_qos: bool,
     pub(super) is_shm: bool,
+    pub(super) is_peer_striped: bool,
     pub(super) cookie: ZSlice,
     pub(super) open_syn_attachment: Option<Attachment>,
 }
@@ -46,7 +49,7 @@ pub(super) async fn recv(
     link: &LinkUnicast,
     manager: &TransportManager,
     auth_link: &mut AuthenticatedPeerLink,
-    _input: super::init_syn::Output,
+    input: super::init_syn::Output,
 ) -> OResult<Output> {
     // Wait to read an InitAck
     let mut messages = link
@@ -118,6 +121,27 @@ pub(super) async fn recv(
         None => EstablishmentProperties::new(),
     };
 
+    // An acceptor running an older version of zenoh doesn't choose the version
+    let version = match init_ack_properties.remove(VERSIONS_PROPERTY) {
+        Some(p) => match p.value.as_slice() {
+            [version] if negotiate_version(manager, &[*version]).is_some() => *version,
+            _ => {
+                return Err((
+                    zerror!(
+                        "Rejecting InitAck on {}. Invalid version: {:?}",
+                        link,
+                        p.value
+                    )
+                    .into(),
+                    Some(tmsg::close_reason::INVALID),
+                ));
+            }
+        },
+        None => input.version,
+    };
+
+    let is_peer_striped = init_ack_properties.remove(STRIPING_PROPERTY).is_some();
+
     #[allow(unused_mut)]
     let mut is_shm = false;
     let mut ps_attachment = EstablishmentProperties::new();
@@ -171,11 +195,13 @@ pub(super) async fn recv(
     };
 
     let output = Output {
+        version,
         zid: init_ack.zid,
         whatami: init_ack.whatami,
         sn_resolution,
         is_qos: init_ack.is_qos,
         is_shm,
+        is_peer_striped,
         cookie: init_ack.cookie,
         open_syn_attachment,
     };
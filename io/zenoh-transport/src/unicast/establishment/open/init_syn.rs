@@ -13,7 +13,8 @@
 //
 use super::OResult;
 use crate::unicast::establishment::{
-    authenticator::AuthenticatedPeerLink, EstablishmentProperties,
+    authenticator::AuthenticatedPeerLink, EstablishmentProperties, STRIPING_PROPERTY,
+    VERSIONS_PROPERTY,
 };
 use crate::TransportManager;
 use std::convert::TryFrom;
@@ -28,7 +29,9 @@ use zenoh_protocol::{
 /*************************************/
 /*              OPEN                 */
 /*************************************/
-pub(super) struct Output;
+pub(super) struct Output {
+    pub(super) version: u8,
+}
 
 pub(super) async fn send(
     link: &LinkUnicast,
@@ -36,6 +39,25 @@ pub(super) async fn send(
     auth_link: &mut AuthenticatedPeerLink,
 ) -> OResult<Output> {
     let mut ps_attachment = EstablishmentProperties::new();
+    // The InitSyn is sent with the oldest supported version, for older acceptors to accept it,
+    // while the newer acceptors choose among the listed versions
+    let version = manager.config.min_version;
+    if manager.config.version > version {
+        ps_attachment
+            .insert(Property {
+                key: VERSIONS_PROPERTY,
+                value: (version..=manager.config.version).collect(),
+            })
+            .map_err(|e| (e, Some(tmsg::close_reason::UNSUPPORTED)))?;
+    }
+    if manager.config.unicast.scheduling.is_striped() {
+        ps_attachment
+            .insert(Property {
+                key: STRIPING_PROPERTY,
+                value: vec![],
+            })
+            .map_err(|e| (e, Some(tmsg::close_reason::UNSUPPORTED)))?;
+    }
     for pa in zasyncread!(manager.state.unicast.peer_authenticator).iter() {
         let mut att = pa
             .get_init_syn_properties(auth_link, &manager.config.zid)
@@ -61,7 +83,7 @@ pub(super) async fn send(
     };
 
     let message = TransportMessage::make_init_syn(
-        manager.config.version,
+        version,
         manager.config.whatami,
         manager.config.zid,
         manager.config.sn_resolution,
@@ -73,6 +95,6 @@ pub(super) async fn send(
         .await
         .map_err(|e| (e, Some(tmsg::close_reason::GENERIC)))?;
 
-    let output = Output;
+    let output = Output { version };
     Ok(output)
 }
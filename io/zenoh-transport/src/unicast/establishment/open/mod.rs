@@ -62,11 +62,13 @@ pub(crate) async fn open_link(
 
     let zid = output.zid;
     let input = InputInit {
+        version: output.version,
         zid,
         whatami: output.whatami,
         sn_resolution: output.sn_resolution,
         is_shm: output.is_shm,
         is_qos: output.is_qos,
+        is_peer_striped: output.is_peer_striped,
     };
     let transport = step!(super::transport_init(manager, input).await);
 
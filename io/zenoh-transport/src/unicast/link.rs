@@ -12,7 +12,9 @@
 //   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
 //
 use super::common::conduit::TransportConduitTx;
+use super::rtt::LinkRttEstimator;
 use super::transport::TransportUnicastInner;
+use super::LinkHealth;
 #[cfg(feature = "stats")]
 use super::TransportUnicastStatsAtomic;
 use crate::common::pipeline::{
@@ -23,13 +25,14 @@ use crate::TransportExecutor;
 use async_std::prelude::FutureExt;
 use async_std::task;
 use async_std::task::JoinHandle;
+use std::convert::TryFrom;
 use std::sync::Arc;
 use std::time::Duration;
 use zenoh_buffers::reader::{HasReader, Reader};
 use zenoh_buffers::ZSlice;
-use zenoh_codec::{RCodec, Zenoh060};
+use zenoh_codec::RCodec;
 use zenoh_link::{LinkUnicast, LinkUnicastDirection};
-use zenoh_protocol::transport::TransportMessage;
+use zenoh_protocol::{core::Priority, transport::TransportMessage};
 use zenoh_result::{bail, zerror, ZResult};
 use zenoh_sync::{RecyclingObjectPool, Signal};
 
@@ -41,12 +44,16 @@ pub(super) struct TransportLinkUnicast {
     pub(super) link: LinkUnicast,
     // The transmission pipeline
     pub(super) pipeline: Option<TransmissionPipelineProducer>,
+    // The round-trip time of the link
+    pub(super) rtt: Arc<LinkRttEstimator>,
     // The transport this link is associated to
     transport: TransportUnicastInner,
-    // The signals to stop TX/RX tasks
+    // The signals to stop TX/RX/ping tasks
     handle_tx: Option<Arc<async_executor::Task<()>>>,
     signal_rx: Signal,
     handle_rx: Option<Arc<JoinHandle<()>>>,
+    signal_ping: Signal,
+    handle_ping: Option<Arc<JoinHandle<()>>>,
 }
 
 impl TransportLinkUnicast {
@@ -60,9 +67,12 @@ impl TransportLinkUnicast {
             transport,
             link,
             pipeline: None,
+            rtt: Arc::new(LinkRttEstimator::new()),
             handle_tx: None,
             signal_rx: Signal::new(),
             handle_rx: None,
+            signal_ping: Signal::new(),
+            handle_ping: None,
         }
     }
 }
@@ -78,13 +88,52 @@ impl TransportLinkUnicast {
         if self.handle_tx.is_none() {
             let config = TransmissionPipelineConf {
                 is_streamed: self.link.is_streamed(),
+                is_striped: self
+                    .transport
+                    .config
+                    .manager
+                    .config
+                    .unicast
+                    .scheduling
+                    .is_striped(),
                 batch_size: batch_size.min(self.link.get_mtu()),
                 queue_size: self.transport.config.manager.config.queue_size,
                 backoff: self.transport.config.manager.config.queue_backoff,
+                frag_max_size: self.transport.config.manager.config.frag_max_size,
+                codec: self.transport.config.codec,
+                adaptive_backoff: self.transport.config.manager.config.queue_adaptive_backoff,
+                keep_last: self.transport.config.manager.config.queue_keep_last,
             };
             // The pipeline
             let (producer, consumer) = TransmissionPipeline::make(config, conduit_tx);
-            self.pipeline = Some(producer);
+            self.pipeline = Some(producer.clone());
+
+            // Spawn the ping task, if the peer's version of the protocol has the ping messages:
+            // either to measure the RTT of each priority, or to monitor the health of the link
+            let rtt_interval = self.transport.config.manager.config.unicast.rtt_interval;
+            let health_interval = self.health().map(|h| h.interval);
+            let interval = match (rtt_interval, health_interval) {
+                (Some(rtt), Some(health)) => Some(rtt.min(health)),
+                (rtt, health) => rtt.or(health),
+            };
+            if let Some(interval) = interval.filter(|_| self.transport.config.codec.has_ping_pong())
+            {
+                let priorities = if !self.transport.is_qos() {
+                    vec![Priority::default()]
+                } else if rtt_interval.is_some() {
+                    (0..Priority::NUM as u8)
+                        .filter_map(|p| Priority::try_from(p).ok())
+                        .collect()
+                } else {
+                    vec![Priority::Control]
+                };
+                let c_rtt = self.rtt.clone();
+                let c_signal = self.signal_ping.clone();
+                let handle = task::spawn(async move {
+                    ping_task(producer, c_rtt, interval, priorities, c_signal).await
+                });
+                self.handle_ping = Some(Arc::new(handle));
+            }
 
             // Spawn the TX task
             let c_link = self.link.clone();
@@ -110,13 +159,28 @@ impl TransportLinkUnicast {
     }
 
     pub(super) fn stop_tx(&mut self) {
+        self.signal_ping.trigger();
         if let Some(pl) = self.pipeline.as_ref() {
             pl.disable();
         }
     }
 
+    // The fast failure detection of the link, if the peer's version of the protocol has the ping messages
+    fn health(&self) -> Option<LinkHealth> {
+        self.transport
+            .config
+            .manager
+            .config
+            .unicast
+            .health
+            .filter(|_| self.transport.config.codec.has_ping_pong())
+    }
+
     pub(super) fn start_rx(&mut self, lease: Duration) {
         if self.handle_rx.is_none() {
+            // The link is closed when nothing is received on it for its lease,
+            // or as soon as the health check timeout expires
+            let lease = self.health().map_or(lease, |h| h.timeout.min(lease));
             // Spawn the RX task
             let c_link = self.link.clone();
             let c_transport = self.transport.clone();
@@ -159,6 +223,11 @@ impl TransportLinkUnicast {
         }
 
         self.stop_tx();
+        if let Some(handle) = self.handle_ping.take() {
+            // Safety: it is safe to unwrap the Arc since we have the ownership of the whole link
+            let handle_ping = Arc::try_unwrap(handle).unwrap();
+            handle_ping.await;
+        }
         if let Some(handle) = self.handle_tx.take() {
             // Safety: it is safe to unwrap the Arc since we have the ownership of the whole link
             let handle_tx = Arc::try_unwrap(handle).unwrap();
@@ -179,6 +248,8 @@ async fn tx_task(
     #[cfg(feature = "stats")] stats: Arc<TransportUnicastStatsAtomic>,
 ) -> ZResult<()> {
     loop {
+        #[cfg(feature = "stats")]
+        stats.inc_tx_z_expired(pipeline.take_expired());
         match pipeline.pull().timeout(keep_alive).await {
             Ok(res) => match res {
                 Some((batch, priority)) => {
@@ -231,6 +302,21 @@ async fn tx_task(
     Ok(())
 }
 
+async fn ping_task(
+    pipeline: TransmissionPipelineProducer,
+    rtt: Arc<LinkRttEstimator>,
+    interval: Duration,
+    priorities: Vec<Priority>,
+    signal: Signal,
+) {
+    while signal.wait().timeout(interval).await.is_err() {
+        for priority in priorities.iter() {
+            let message = TransportMessage::make_ping(rtt.ping(*priority), None);
+            pipeline.push_transport_message(message, *priority);
+        }
+    }
+}
+
 async fn rx_task_stream(
     link: LinkUnicast,
     transport: TransportUnicastInner,
@@ -257,7 +343,7 @@ async fn rx_task_stream(
         Ok(Action::Stop)
     }
 
-    let codec = Zenoh060::default();
+    let codec = transport.config.codec;
 
     // The pool of buffers
     let mtu = link.get_mtu() as usize;
@@ -326,7 +412,7 @@ async fn rx_task_dgram(
         Ok(Action::Stop)
     }
 
-    let codec = Zenoh060::default();
+    let codec = transport.config.codec;
 
     // The pool of buffers
     let mtu = link.get_mtu() as usize;
@@ -21,14 +21,17 @@ use async_std::prelude::FutureExt;
 use async_std::sync::{Mutex as AsyncMutex, RwLock as AsyncRwLock};
 use async_std::task;
 use std::collections::{HashMap, HashSet};
+use std::convert::TryFrom;
+use std::str::FromStr;
 use std::sync::{Arc, Mutex};
 use std::time::Duration;
 use zenoh_cfg_properties::config::*;
-use zenoh_config::Config;
+use zenoh_codec::Zenoh060Version;
+use zenoh_config::{Config, LinkSchedulingConf, LinkSchedulingPolicy};
 use zenoh_core::{zasynclock, zasyncread, zasyncwrite, zlock, zparse};
 use zenoh_link::*;
 use zenoh_protocol::{
-    core::{locator::LocatorProtocol, ZenohId},
+    core::{locator::LocatorProtocol, Locator, Priority, ZenohId},
     transport::tmsg,
 };
 use zenoh_result::{bail, zerror, ZResult};
@@ -43,11 +46,69 @@ pub struct TransportManagerConfigUnicast {
     pub accept_pending: usize,
     pub max_sessions: usize,
     pub max_links: usize,
+    pub scheduling: LinkScheduling,
+    pub rtt_interval: Option<Duration>,
+    pub health: Option<LinkHealth>,
     pub is_qos: bool,
     #[cfg(feature = "shared-memory")]
     pub is_shm: bool,
 }
 
+/// The link scheduling configuration, with its locators parsed.
+#[derive(Clone, Debug, Default)]
+pub struct LinkScheduling {
+    pub policy: LinkSchedulingPolicy,
+    pub weights: Vec<(Locator, usize)>,
+    pub priorities: Vec<(Priority, Locator)>,
+}
+
+impl LinkScheduling {
+    /// Whether the messages are striped over the links, and thus need to be reordered by the receiver.
+    pub fn is_striped(&self) -> bool {
+        matches!(
+            self.policy,
+            LinkSchedulingPolicy::RoundRobin | LinkSchedulingPolicy::Weighted
+        )
+    }
+}
+
+impl TryFrom<&LinkSchedulingConf> for LinkScheduling {
+    type Error = zenoh_result::Error;
+
+    fn try_from(conf: &LinkSchedulingConf) -> ZResult<Self> {
+        let parse = |locator: &str| {
+            Locator::from_str(locator).map_err(|e| {
+                zerror!(
+                    "Invalid locator in the link scheduling configuration: {}: {}",
+                    locator,
+                    e
+                )
+            })
+        };
+        let mut weights = Vec::with_capacity(conf.weights().len());
+        for w in conf.weights() {
+            weights.push((parse(&w.locator)?, w.weight));
+        }
+        let mut priorities = Vec::with_capacity(conf.priorities().len());
+        for p in conf.priorities() {
+            priorities.push((Priority::from(p.priority), parse(&p.locator)?));
+        }
+        Ok(LinkScheduling {
+            policy: *conf.policy(),
+            weights,
+            priorities,
+        })
+    }
+}
+
+/// The fast failure detection of the links: pings are sent on a link every `interval`,
+/// for it to be closed when nothing is received on it for `timeout`, if shorter than its lease.
+#[derive(Clone, Copy, Debug)]
+pub struct LinkHealth {
+    pub interval: Duration,
+    pub timeout: Duration,
+}
+
 pub struct TransportManagerStateUnicast {
     // Incoming uninitialized transports
     pub(super) incoming: Arc<AsyncMutex<usize>>,
@@ -78,6 +139,9 @@ pub struct TransportManagerBuilderUnicast {
     pub(super) accept_pending: usize,
     pub(super) max_sessions: usize,
     pub(super) max_links: usize,
+    pub(super) scheduling: LinkSchedulingConf,
+    pub(super) rtt_interval: Option<Duration>,
+    pub(super) health: Option<LinkHealth>,
     pub(super) is_qos: bool,
     #[cfg(feature = "shared-memory")]
     pub(super) is_shm: bool,
@@ -116,6 +180,21 @@ impl TransportManagerBuilderUnicast {
         self
     }
 
+    pub fn scheduling(mut self, scheduling: LinkSchedulingConf) -> Self {
+        self.scheduling = scheduling;
+        self
+    }
+
+    pub fn rtt_interval(mut self, rtt_interval: Option<Duration>) -> Self {
+        self.rtt_interval = rtt_interval;
+        self
+    }
+
+    pub fn health(mut self, health: Option<LinkHealth>) -> Self {
+        self.health = health;
+        self
+    }
+
     pub fn peer_authenticator(mut self, peer_authenticator: HashSet<PeerAuthenticator>) -> Self {
         self.peer_authenticator = peer_authenticator;
         self
@@ -148,6 +227,17 @@ impl TransportManagerBuilderUnicast {
         self = self.accept_pending(config.transport().unicast().accept_pending().unwrap());
         self = self.max_sessions(config.transport().unicast().max_sessions().unwrap());
         self = self.max_links(config.transport().unicast().max_links().unwrap());
+        self = self.scheduling(config.transport().unicast().scheduling().clone());
+        let rtt = config.transport().unicast().rtt();
+        self = self.rtt_interval(
+            rtt.enabled()
+                .then(|| Duration::from_millis(rtt.interval().unwrap())),
+        );
+        let health = config.transport().unicast().health();
+        self = self.health(health.enabled().then(|| LinkHealth {
+            interval: Duration::from_millis(health.interval().unwrap()),
+            timeout: Duration::from_millis(health.timeout().unwrap()),
+        }));
         self = self.qos(*config.transport().qos().enabled());
 
         #[cfg(feature = "shared-memory")]
@@ -171,6 +261,9 @@ impl TransportManagerBuilderUnicast {
             accept_pending: self.accept_pending,
             max_sessions: self.max_sessions,
             max_links: self.max_links,
+            scheduling: LinkScheduling::try_from(&self.scheduling)?,
+            rtt_interval: self.rtt_interval,
+            health: self.health,
             is_qos: self.is_qos,
             #[cfg(feature = "shared-memory")]
             is_shm: self.is_shm,
@@ -221,6 +314,9 @@ impl Default for TransportManagerBuilderUnicast {
             accept_pending: zparse!(ZN_OPEN_INCOMING_PENDING_DEFAULT).unwrap(),
             max_sessions: zparse!(ZN_MAX_SESSIONS_UNICAST_DEFAULT).unwrap(),
             max_links: zparse!(ZN_MAX_LINKS_DEFAULT).unwrap(),
+            scheduling: LinkSchedulingConf::default(),
+            rtt_interval: None,
+            health: None,
             is_qos: zparse!(ZN_QOS_DEFAULT).unwrap(),
             #[cfg(feature = "shared-memory")]
             is_shm: zparse!(ZN_SHM_DEFAULT).unwrap(),
@@ -369,6 +465,17 @@ impl TransportManager {
                     return Err(e.into());
                 }
 
+                if transport.config.codec.version != config.version {
+                    let e = zerror!(
+                        "Transport with peer {} already exist. Invalid version: {}. Execpted: {}.",
+                        config.peer,
+                        config.version,
+                        transport.config.codec.version
+                    );
+                    log::trace!("{}", e);
+                    return Err(e.into());
+                }
+
                 if transport.config.sn_resolution != config.sn_resolution {
                     let e = zerror!(
                     "Transport with peer {} already exist. Invalid sn resolution: {}. Execpted: {}.",
@@ -416,14 +523,23 @@ impl TransportManager {
                 }
 
                 // Create the transport
+                let codec = Zenoh060Version::new(config.version).ok_or_else(|| {
+                    zerror!(
+                        "Unsupported version {} for transport with peer: {}",
+                        config.version,
+                        config.peer
+                    )
+                })?;
                 let stc = TransportUnicastConfig {
                     manager: self.clone(),
                     zid: config.peer,
+                    codec,
                     whatami: config.whatami,
                     sn_resolution: config.sn_resolution,
                     initial_sn_tx: config.initial_sn_tx,
                     is_shm: config.is_shm,
                     is_qos: config.is_qos,
+                    is_peer_striped: config.is_peer_striped,
                 };
                 let a_t = Arc::new(TransportUnicastInner::make(stc)?);
 
@@ -432,8 +548,9 @@ impl TransportManager {
                 guard.insert(config.peer, a_t);
 
                 log::debug!(
-                    "New transport opened with {}: whatami {}, sn resolution {}, initial sn {:?}, shm: {}, qos: {}",
+                    "New transport opened with {}: version {:#04x}, whatami {}, sn resolution {}, initial sn {:?}, shm: {}, qos: {}",
                     config.peer,
+                    config.version,
                     config.whatami,
                     config.sn_resolution,
                     config.initial_sn_tx,
@@ -508,6 +625,31 @@ impl TransportManager {
         Ok(())
     }
 
+    /// Reload the credentials of the peer authenticators from the configuration and, if configured,
+    /// close the transports of the peers whose credentials have been revoked.
+    /// Return the peers whose credentials have been revoked.
+    pub async fn reload_peer_authenticators(&self, config: &Config) -> ZResult<Vec<ZenohId>> {
+        let mut revoked = vec![];
+        for pa in zasyncread!(self.state.unicast.peer_authenticator).iter() {
+            for zid in pa.reload(config).await? {
+                if !revoked.contains(&zid) {
+                    revoked.push(zid);
+                }
+            }
+        }
+
+        if *config.transport().auth().reload().close_revoked() {
+            for zid in revoked.iter() {
+                if let Some(transport) = self.get_transport_unicast(zid) {
+                    log::debug!("Closing transport with peer {}: credentials revoked", zid);
+                    let _ = transport.close().await;
+                }
+            }
+        }
+
+        Ok(revoked)
+    }
+
     pub(crate) async fn handle_new_link_unicast(&self, link: LinkUnicast) {
         let mut guard = zasynclock!(self.state.unicast.incoming);
         if *guard >= self.config.unicast.accept_pending {
@@ -549,6 +691,7 @@ impl TransportManager {
                 }
                 Err(e) => {
                     log::debug!("{}", e);
+                    let _ = link.close().await;
                     let mut guard = zasynclock!(self.state.unicast.incoming);
                     *guard -= 1;
                     return;
@@ -14,6 +14,7 @@
 pub mod establishment;
 pub(crate) mod link;
 pub(crate) mod manager;
+pub(crate) mod rtt;
 pub(crate) mod rx;
 pub(crate) mod transport;
 pub(crate) mod tx;
@@ -23,12 +24,13 @@ use super::common;
 use super::common::stats::stats_struct;
 use super::{TransportPeer, TransportPeerEventHandler};
 pub use manager::*;
+pub use rtt::{LinkRtt, RttStats};
 use std::fmt;
 use std::sync::{Arc, Weak};
 use transport::TransportUnicastInner;
 use zenoh_link::Link;
 use zenoh_protocol::{
-    core::{WhatAmI, ZInt, ZenohId},
+    core::{Priority, WhatAmI, ZInt, ZenohId},
     transport::tmsg,
     zenoh::ZenohMessage,
 };
@@ -48,6 +50,7 @@ stats_struct! {
         pub tx_t_msgs,
         pub tx_z_msgs,
         pub tx_z_dropped,
+        pub tx_z_expired,
         pub tx_z_data_msgs,
         pub tx_z_data_payload_bytes,
         pub tx_z_data_reply_msgs,
@@ -73,6 +76,10 @@ stats_struct! {
         pub rx_z_unit_reply_msgs,
         pub rx_bytes,
     }
+    extra {
+        /// The round-trip time statistics of the links measuring it.
+        pub links: Vec<LinkRtt>,
+    }
 }
 
 /*************************************/
@@ -81,11 +88,13 @@ stats_struct! {
 #[derive(Clone, Copy)]
 pub(crate) struct TransportConfigUnicast {
     pub(crate) peer: ZenohId,
+    pub(crate) version: u8,
     pub(crate) whatami: WhatAmI,
     pub(crate) sn_resolution: ZInt,
     pub(crate) initial_sn_tx: ZInt,
     pub(crate) is_shm: bool,
     pub(crate) is_qos: bool,
+    pub(crate) is_peer_striped: bool,
 }
 
 /// [`TransportUnicast`] is the transport handler returned
@@ -113,6 +122,13 @@ impl TransportUnicast {
         Ok(transport.get_whatami())
     }
 
+    /// Returns the version of the protocol negotiated with the peer.
+    #[inline(always)]
+    pub fn get_version(&self) -> ZResult<u8> {
+        let transport = self.get_inner()?;
+        Ok(transport.get_version())
+    }
+
     #[inline(always)]
     pub fn get_sn_resolution(&self) -> ZResult<ZInt> {
         let transport = self.get_inner()?;
@@ -163,6 +179,13 @@ impl TransportUnicast {
             .collect())
     }
 
+    /// The round-trip time statistics of the links of this transport measuring it.
+    #[inline(always)]
+    pub fn get_link_rtts(&self) -> ZResult<Vec<LinkRtt>> {
+        let transport = self.get_inner()?;
+        Ok(transport.get_link_rtts())
+    }
+
     #[inline(always)]
     pub fn schedule(&self, message: ZenohMessage) -> ZResult<()> {
         let transport = self.get_inner()?;
@@ -170,6 +193,15 @@ impl TransportUnicast {
         Ok(())
     }
 
+    /// Sends right away the messages waiting to be batched in the transmission queues of the
+    /// given priority, or in all the transmission queues if no priority is given.
+    #[inline(always)]
+    pub fn flush(&self, priority: Option<Priority>) -> ZResult<()> {
+        let transport = self.get_inner()?;
+        transport.flush(priority);
+        Ok(())
+    }
+
     #[inline(always)]
     pub async fn close_link(&self, link: &Link) -> ZResult<()> {
         let transport = self.get_inner()?;
@@ -200,7 +232,10 @@ impl TransportUnicast {
 
     #[cfg(feature = "stats")]
     pub fn get_stats(&self) -> ZResult<TransportUnicastStats> {
-        Ok(self.get_inner()?.stats.snapshot())
+        let transport = self.get_inner()?;
+        let mut stats = transport.stats.snapshot();
+        stats.links = transport.get_link_rtts();
+        Ok(stats)
     }
 }
 
@@ -225,6 +260,7 @@ impl fmt::Debug for TransportUnicast {
                 .debug_struct("Transport Unicast")
                 .field("zid", &transport.get_zid())
                 .field("whatami", &transport.get_whatami())
+                .field("version", &transport.get_version())
                 .field("sn_resolution", &transport.get_sn_resolution())
                 .field("is_qos", &transport.is_qos())
                 .field("is_shm", &transport.is_shm())
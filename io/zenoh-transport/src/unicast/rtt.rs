@@ -0,0 +1,178 @@
+//
+// Copyright (c) 2022 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+use serde::{Deserialize, Serialize};
+use std::convert::TryFrom;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use zenoh_core::zlock;
+use zenoh_link::LinkUnicast;
+use zenoh_protocol::core::{Locator, Priority, ZInt};
+
+// The priority of a ping is encoded in the lowest bits of its hash,
+// the time it has been sent at in the remaining ones.
+const PRIORITY_BITS: u32 = 3;
+const PRIORITY_MASK: ZInt = (1 << PRIORITY_BITS) - 1;
+
+/// The round-trip time statistics of a link, expressed in microseconds.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Deserialize, Serialize)]
+pub struct RttStats {
+    /// The number of round-trip times measured so far.
+    pub samples: usize,
+    /// The last measured round-trip time.
+    pub last_us: u64,
+    /// The smoothed round-trip time, as defined in RFC 6298.
+    pub smoothed_us: u64,
+    /// The smoothed variation between consecutive round-trip times, as defined in RFC 3550.
+    pub jitter_us: u64,
+    /// The lowest measured round-trip time.
+    pub min_us: u64,
+    /// The highest measured round-trip time.
+    pub max_us: u64,
+}
+
+impl RttStats {
+    /// The smoothed round-trip time, if any has been measured.
+    pub fn smoothed(&self) -> Option<Duration> {
+        (self.samples > 0).then(|| Duration::from_micros(self.smoothed_us))
+    }
+
+    /// The jitter of the round-trip time, if any has been measured.
+    pub fn jitter(&self) -> Option<Duration> {
+        (self.samples > 0).then(|| Duration::from_micros(self.jitter_us))
+    }
+
+    fn update(&mut self, rtt_us: u64) {
+        if self.samples == 0 {
+            self.smoothed_us = rtt_us;
+            self.min_us = rtt_us;
+            self.max_us = rtt_us;
+        } else {
+            let delta = rtt_us.abs_diff(self.last_us);
+            self.jitter_us = (self.jitter_us * 15 + delta) / 16;
+            self.smoothed_us = (self.smoothed_us * 7 + rtt_us) / 8;
+            self.min_us = self.min_us.min(rtt_us);
+            self.max_us = self.max_us.max(rtt_us);
+        }
+        self.last_us = rtt_us;
+        self.samples += 1;
+    }
+}
+
+/// The round-trip time statistics of a link of a unicast transport.
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize, Serialize)]
+pub struct LinkRtt {
+    pub src: Locator,
+    pub dst: Locator,
+    /// The statistics over the pings of all the priorities.
+    pub rtt: RttStats,
+    /// The statistics of the pings of each priority, indexed by priority.
+    /// A ping is subject to the queueing delay of its priority on the sender side.
+    pub priorities: Vec<RttStats>,
+}
+
+#[derive(Default)]
+struct LinkRttState {
+    rtt: RttStats,
+    priorities: [RttStats; Priority::NUM],
+}
+
+pub(super) struct LinkRttEstimator {
+    epoch: Instant,
+    state: Mutex<LinkRttState>,
+}
+
+impl LinkRttEstimator {
+    pub(super) fn new() -> Self {
+        Self {
+            epoch: Instant::now(),
+            state: Mutex::new(LinkRttState::default()),
+        }
+    }
+
+    fn now(&self) -> ZInt {
+        self.epoch.elapsed().as_micros() as ZInt
+    }
+
+    /// The hash of a ping of the given priority sent now.
+    pub(super) fn ping(&self, priority: Priority) -> ZInt {
+        (self.now() << PRIORITY_BITS) | priority as ZInt
+    }
+
+    /// Account for the pong echoing the given hash.
+    pub(super) fn pong(&self, hash: ZInt) {
+        let now = self.now();
+        let sent = hash >> PRIORITY_BITS;
+        if sent > now {
+            log::trace!("Ignoring pong from the future: {}", hash);
+            return;
+        }
+        let priority = match Priority::try_from((hash & PRIORITY_MASK) as u8) {
+            Ok(priority) => priority,
+            Err(_) => return,
+        };
+
+        let rtt = now - sent;
+        let mut guard = zlock!(self.state);
+        guard.rtt.update(rtt);
+        guard.priorities[priority as usize].update(rtt);
+    }
+
+    pub(super) fn stats(&self, link: &LinkUnicast) -> LinkRtt {
+        let guard = zlock!(self.state);
+        LinkRtt {
+            src: link.get_src().clone(),
+            dst: link.get_dst().clone(),
+            rtt: guard.rtt,
+            priorities: guard.priorities.to_vec(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rtt_stats() {
+        let mut stats = RttStats::default();
+        assert_eq!(stats.smoothed(), None);
+
+        stats.update(1_000);
+        assert_eq!(stats.smoothed(), Some(Duration::from_micros(1_000)));
+        assert_eq!(stats.jitter(), Some(Duration::ZERO));
+
+        stats.update(1_800);
+        assert_eq!(stats.samples, 2);
+        assert_eq!(stats.last_us, 1_800);
+        assert_eq!(stats.smoothed_us, 1_100);
+        assert_eq!(stats.jitter_us, 50);
+        assert_eq!((stats.min_us, stats.max_us), (1_000, 1_800));
+    }
+
+    #[test]
+    fn rtt_ping_pong() {
+        let estimator = LinkRttEstimator::new();
+        let hash = estimator.ping(Priority::DataHigh);
+        std::thread::sleep(Duration::from_millis(10));
+        estimator.pong(hash);
+        // A pong sent in the future is discarded
+        estimator.pong(estimator.ping(Priority::DataHigh) + (1 << 40));
+
+        let guard = zlock!(estimator.state);
+        assert_eq!(guard.rtt.samples, 1);
+        assert!(guard.rtt.last_us >= 10_000);
+        assert_eq!(guard.priorities[Priority::DataHigh as usize], guard.rtt);
+        assert_eq!(guard.priorities[Priority::Data as usize].samples, 0);
+    }
+}
@@ -15,19 +15,56 @@ use super::common::conduit::TransportChannelRx;
 use super::transport::TransportUnicastInner;
 use async_std::task;
 use std::sync::MutexGuard;
-#[cfg(feature = "stats")]
+use std::time::{Duration, Instant};
 use zenoh_buffers::SplitBuffer;
 use zenoh_core::{zlock, zread};
 use zenoh_link::LinkUnicast;
-#[cfg(feature = "stats")]
 use zenoh_protocol::zenoh::ZenohBody;
 use zenoh_protocol::{
     core::{Priority, Reliability, ZInt, ZenohId},
-    transport::{tmsg, Close, Frame, FramePayload, KeepAlive, TransportBody, TransportMessage},
+    transport::{
+        tmsg, Close, Frame, FramePayload, KeepAlive, Ping, Pong, TransportBody, TransportMessage,
+    },
     zenoh::ZenohMessage,
 };
 use zenoh_result::{bail, zerror, ZResult};
 
+// The bounds of the frames kept per channel while waiting for a missing one, when the peer stripes the messages.
+// A reliable frame is only missing while in flight on another link, or lost with a closed link,
+// while a best effort frame might have been dropped: don't wait too long for it.
+struct ReorderBounds {
+    frames: usize,
+    bytes: usize,
+    timeout: Duration,
+}
+
+const RX_REORDER_RELIABLE: ReorderBounds = ReorderBounds {
+    frames: 16_384,
+    bytes: 16 * 1024 * 1024,
+    timeout: Duration::from_secs(1),
+};
+const RX_REORDER_BEST_EFFORT: ReorderBounds = ReorderBounds {
+    frames: 64,
+    bytes: 1024 * 1024,
+    timeout: Duration::from_millis(50),
+};
+// The approximate overhead of a message kept for reordering, besides its payload
+const RX_REORDER_MSG_OVERHEAD: usize = 64;
+
+// The approximate size of a frame kept for reordering
+fn frame_size(payload: &FramePayload) -> usize {
+    match payload {
+        FramePayload::Fragment { buffer, .. } => buffer.len(),
+        FramePayload::Messages { messages } => messages
+            .iter()
+            .map(|msg| match &msg.body {
+                ZenohBody::Data(data) => data.payload.len() + RX_REORDER_MSG_OVERHEAD,
+                _ => RX_REORDER_MSG_OVERHEAD,
+            })
+            .sum(),
+    }
+}
+
 /*************************************/
 /*            TRANSPORT RX           */
 /*************************************/
@@ -120,11 +157,38 @@ impl TransportUnicastInner {
         Ok(())
     }
 
+    fn handle_ping(&self, link: &LinkUnicast, hash: ZInt) -> ZResult<()> {
+        // Reply on the same link with the highest priority to measure the RTT as precisely as possible
+        let priority = if self.is_qos() {
+            Priority::Control
+        } else {
+            Priority::default()
+        };
+        let guard = zread!(self.links);
+        if let Some(pl) = guard
+            .iter()
+            .find(|l| &l.link == link)
+            .and_then(|l| l.pipeline.as_ref())
+        {
+            pl.push_transport_message(TransportMessage::make_pong(hash, None), priority);
+        }
+        Ok(())
+    }
+
+    fn handle_pong(&self, link: &LinkUnicast, hash: ZInt) -> ZResult<()> {
+        let guard = zread!(self.links);
+        if let Some(l) = guard.iter().find(|l| &l.link == link) {
+            l.rtt.pong(hash);
+        }
+        Ok(())
+    }
+
     fn handle_frame(
         &self,
         sn: ZInt,
         payload: FramePayload,
         mut guard: MutexGuard<'_, TransportChannelRx>,
+        bounds: &ReorderBounds,
     ) -> ZResult<()> {
         let precedes = guard.sn.precedes(sn)?;
         if !precedes {
@@ -142,18 +206,104 @@ impl TransportUnicastInner {
             return Ok(());
         }
 
+        // The frames striped over several links may be received out of order:
+        // keep them until the missing ones are received
+        if guard.sn.gap(sn)? > 1 {
+            guard.pending_bytes += frame_size(&payload);
+            guard.pending.insert(sn, payload);
+            let since = *guard.pending_since.get_or_insert_with(Instant::now);
+            if self.config.is_peer_striped
+                && zread!(self.links).len() > 1
+                && guard.pending.len() <= bounds.frames
+                && guard.pending_bytes <= bounds.bytes
+                && since.elapsed() <= bounds.timeout
+            {
+                return Ok(());
+            }
+            // Give up waiting for the missing frames
+            return self.flush_frames(&mut guard);
+        }
+
+        self.deliver_frame(sn, payload, &mut guard)?;
+        // Deliver the frames that were waiting for this one
+        loop {
+            let next = (guard.sn.get() + 1) % guard.sn.resolution();
+            match guard.pending.remove(&next) {
+                Some(payload) => {
+                    guard.pending_bytes -= frame_size(&payload);
+                    self.deliver_frame(next, payload, &mut guard)?
+                }
+                None => break,
+            }
+        }
+        // Another frame is missing if some are still pending
+        guard.pending_since = (!guard.pending.is_empty()).then(Instant::now);
+        Ok(())
+    }
+
+    // Delivers the pending frames in order, skipping the missing ones
+    fn flush_frames(&self, channel: &mut TransportChannelRx) -> ZResult<()> {
+        channel.pending_bytes = 0;
+        channel.pending_since = None;
+        let mut pending = Vec::with_capacity(channel.pending.len());
+        for (sn, payload) in channel.pending.drain() {
+            pending.push((channel.sn.gap(sn)?, sn, payload));
+        }
+        pending.sort_by_key(|(gap, _, _)| *gap);
+        for (_, sn, payload) in pending {
+            self.deliver_frame(sn, payload, channel)?;
+        }
+        Ok(())
+    }
+
+    // Delivers the frames kept for reordering, e.g. when a link is removed: the missing frames may have been lost with it
+    pub(super) fn flush_pending_frames(&self) {
+        self.flush_frames_if(|_, _| true)
+    }
+
+    // Delivers the frames kept for reordering for longer than the timeout, in case no frame was received since
+    fn flush_expired_frames(&self) {
+        self.flush_frames_if(|channel, bounds| {
+            channel
+                .pending_since
+                .map_or(false, |since| since.elapsed() > bounds.timeout)
+        })
+    }
+
+    fn flush_frames_if(&self, condition: impl Fn(&TransportChannelRx, &ReorderBounds) -> bool) {
+        for c in self.conduit_rx.iter() {
+            for (channel, bounds) in [
+                (&c.reliable, &RX_REORDER_RELIABLE),
+                (&c.best_effort, &RX_REORDER_BEST_EFFORT),
+            ] {
+                let mut guard = zlock!(channel);
+                if !guard.pending.is_empty() && condition(&guard, bounds) {
+                    if let Err(e) = self.flush_frames(&mut guard) {
+                        log::debug!("Transport: {}. {}", self.config.zid, e);
+                    }
+                }
+            }
+        }
+    }
+
+    fn deliver_frame(
+        &self,
+        sn: ZInt,
+        payload: FramePayload,
+        channel: &mut TransportChannelRx,
+    ) -> ZResult<()> {
         // Set will always return OK because we have already checked
         // with precedes() that the sn has the right resolution
-        let _ = guard.sn.set(sn);
+        let _ = channel.sn.set(sn);
         match payload {
             FramePayload::Fragment { buffer, is_final } => {
-                if guard.defrag.is_empty() {
-                    let _ = guard.defrag.sync(sn);
+                if channel.defrag.is_empty() {
+                    let _ = channel.defrag.sync(sn);
                 }
-                guard.defrag.push(sn, buffer)?;
+                channel.defrag.push(sn, buffer)?;
                 if is_final {
                     // When shared-memory feature is disabled, msg does not need to be mutable
-                    let msg = guard.defrag.defragment().ok_or_else(|| {
+                    let msg = channel.defrag.defragment().ok_or_else(|| {
                         zerror!("Transport: {}. Defragmentation error.", self.config.zid)
                     })?;
                     self.trigger_callback(msg)
@@ -192,10 +342,15 @@ impl TransportUnicastInner {
                 };
 
                 match channel.reliability {
-                    Reliability::Reliable => self.handle_frame(sn, payload, zlock!(c.reliable)),
-                    Reliability::BestEffort => {
-                        self.handle_frame(sn, payload, zlock!(c.best_effort))
+                    Reliability::Reliable => {
+                        self.handle_frame(sn, payload, zlock!(c.reliable), &RX_REORDER_RELIABLE)
                     }
+                    Reliability::BestEffort => self.handle_frame(
+                        sn,
+                        payload,
+                        zlock!(c.best_effort),
+                        &RX_REORDER_BEST_EFFORT,
+                    ),
                 }
             }
             TransportBody::Close(Close {
@@ -203,7 +358,14 @@ impl TransportUnicastInner {
                 reason,
                 link_only,
             }) => self.handle_close(link, zid, reason, link_only),
-            TransportBody::KeepAlive(KeepAlive { .. }) => Ok(()),
+            TransportBody::KeepAlive(KeepAlive { .. }) => {
+                if self.config.is_peer_striped {
+                    self.flush_expired_frames();
+                }
+                Ok(())
+            }
+            TransportBody::Ping(Ping { hash }) => self.handle_ping(link, hash),
+            TransportBody::Pong(Pong { hash }) => self.handle_pong(link, hash),
             _ => {
                 log::debug!(
                     "Transport: {}. Message handling not implemented: {:?}",
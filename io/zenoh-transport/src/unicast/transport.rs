@@ -14,11 +14,14 @@
 use super::super::{TransportExecutor, TransportManager, TransportPeerEventHandler};
 use super::common::conduit::{TransportConduitRx, TransportConduitTx};
 use super::link::TransportLinkUnicast;
+use super::rtt::LinkRtt;
 #[cfg(feature = "stats")]
 use super::TransportUnicastStatsAtomic;
 use async_std::sync::{Mutex as AsyncMutex, MutexGuard as AsyncMutexGuard};
+use std::sync::atomic::AtomicUsize;
 use std::sync::{Arc, RwLock};
 use std::time::Duration;
+use zenoh_codec::Zenoh060Version;
 use zenoh_core::{zasynclock, zread, zwrite};
 use zenoh_link::{Link, LinkUnicast, LinkUnicastDirection};
 use zenoh_protocol::{
@@ -53,11 +56,14 @@ macro_rules! zlinkindex {
 pub(crate) struct TransportUnicastConfig {
     pub(crate) manager: TransportManager,
     pub(crate) zid: ZenohId,
+    pub(crate) codec: Zenoh060Version,
     pub(crate) whatami: WhatAmI,
     pub(crate) sn_resolution: ZInt,
     pub(crate) initial_sn_tx: ZInt,
     pub(crate) is_shm: bool,
     pub(crate) is_qos: bool,
+    // Whether the peer stripes its messages over the links, for them to be reordered
+    pub(crate) is_peer_striped: bool,
 }
 
 #[derive(Clone)]
@@ -70,6 +76,8 @@ pub(crate) struct TransportUnicastInner {
     pub(super) conduit_rx: Arc<[TransportConduitRx]>,
     // The links associated to the channel
     pub(super) links: Arc<RwLock<Box<[TransportLinkUnicast]>>>,
+    // The turn of the links when striping the messages over them
+    pub(super) scheduling_turn: Arc<AtomicUsize>,
     // The callback
     pub(super) callback: Arc<RwLock<Option<Arc<dyn TransportPeerEventHandler>>>>,
     // Mutex for notification
@@ -109,6 +117,7 @@ impl TransportUnicastInner {
             conduit_tx: conduit_tx.into_boxed_slice().into(),
             conduit_rx: conduit_rx.into_boxed_slice().into(),
             links: Arc::new(RwLock::new(vec![].into_boxed_slice())),
+            scheduling_turn: Arc::new(AtomicUsize::new(0)),
             callback: Arc::new(RwLock::new(None)),
             alive: Arc::new(AsyncMutex::new(false)),
             #[cfg(feature = "stats")]
@@ -353,7 +362,10 @@ impl TransportUnicastInner {
 
         match target {
             Target::Transport => self.delete().await,
-            Target::Link(stl) => stl.close().await,
+            Target::Link(stl) => {
+                self.flush_pending_frames();
+                stl.close().await
+            }
         }
     }
 }
@@ -370,6 +382,11 @@ impl TransportUnicastInner {
         self.config.whatami
     }
 
+    pub(crate) fn get_version(&self) -> u8 {
+        self.config.codec.version
+    }
+
+    #[inline(always)]
     pub(crate) fn get_sn_resolution(&self) -> ZInt {
         self.config.sn_resolution
     }
@@ -396,6 +413,9 @@ impl TransportUnicastInner {
             .map(|l| l.pipeline.clone())
             .ok_or_else(|| zerror!("Cannot close Link {:?}: not found", link))?;
 
+        // Stop the RX task for it not to delete the link on its own when the peer closes it
+        let _ = self.stop_rx(link);
+
         if let Some(p) = pipeline.take() {
             // Close message to be sent on the target link
             let peer_id = Some(self.config.manager.zid());
@@ -459,4 +479,12 @@ impl TransportUnicastInner {
     pub(crate) fn get_links(&self) -> Vec<LinkUnicast> {
         zread!(self.links).iter().map(|l| l.link.clone()).collect()
     }
+
+    pub(crate) fn get_link_rtts(&self) -> Vec<LinkRtt> {
+        zread!(self.links)
+            .iter()
+            .map(|l| l.rtt.stats(&l.link))
+            .filter(|s| s.rtt.samples > 0)
+            .collect()
+    }
 }
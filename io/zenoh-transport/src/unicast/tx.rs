@@ -11,47 +11,124 @@
 // Contributors:
 //   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
 //
+use super::link::TransportLinkUnicast;
 use super::transport::TransportUnicastInner;
+use std::sync::atomic::Ordering;
 #[cfg(feature = "stats")]
 use zenoh_buffers::SplitBuffer;
+use zenoh_config::LinkSchedulingPolicy;
 use zenoh_core::zread;
+use zenoh_protocol::core::{Locator, Priority};
 #[cfg(feature = "stats")]
 use zenoh_protocol::zenoh::ZenohBody;
 use zenoh_protocol::zenoh::ZenohMessage;
 
+fn locator_matches(locator: &Locator, conf: &Locator) -> bool {
+    let address = locator.address();
+    let conf_address = conf.address();
+    locator.protocol() == conf.protocol()
+        && (address == conf_address
+            || address
+                .as_str()
+                .rsplit_once(':')
+                .map_or(false, |(host, _)| host == conf_address.as_str()))
+}
+
+// Whether the source or destination locator of a link matches a configured locator: same protocol and address,
+// the port being optional (e.g. `tcp/192.168.1.10` matches `tcp/192.168.1.10:7447`, not `tcp/192.168.1.100:7447`)
+fn matches_locator(tl: &TransportLinkUnicast, conf: &Locator) -> bool {
+    locator_matches(tl.link.get_src(), conf) || locator_matches(tl.link.get_dst(), conf)
+}
+
 impl TransportUnicastInner {
-    fn schedule_on_link(&self, msg: ZenohMessage) -> bool {
-        macro_rules! zpush {
-            ($guard:expr, $pipeline:expr, $msg:expr) => {
-                // Drop the guard before the push_zenoh_message since
-                // the link could be congested and this operation could
-                // block for fairly long time
-                let pl = $pipeline.clone();
-                drop($guard);
-                log::trace!("Scheduled: {:?}", $msg);
-                return pl.push_zenoh_message($msg);
-            };
+    fn link_weight(&self, tl: &TransportLinkUnicast) -> usize {
+        self.config
+            .manager
+            .config
+            .unicast
+            .scheduling
+            .weights
+            .iter()
+            .find(|(locator, _)| matches_locator(tl, locator))
+            .map_or(1, |(_, weight)| *weight)
+    }
+
+    // Chooses the link to send a message on, according to the scheduling policy
+    fn select_link<'a>(
+        &self,
+        links: &'a [TransportLinkUnicast],
+        msg: &ZenohMessage,
+    ) -> Option<&'a TransportLinkUnicast> {
+        let scheduling = &self.config.manager.config.unicast.scheduling;
+        let mut candidates: Vec<&TransportLinkUnicast> =
+            links.iter().filter(|tl| tl.pipeline.is_some()).collect();
+        if candidates.len() <= 1 {
+            return candidates.pop();
         }
 
-        let guard = zread!(self.links);
-        // First try to find the best match between msg and link reliability
-        if let Some(pl) = guard
+        // First restrict to the links the priority of the message is pinned to, if any
+        let priority = msg.channel.priority;
+        let pins: Vec<&Locator> = scheduling
+            .priorities
             .iter()
-            .filter_map(|tl| {
-                if msg.is_reliable() == tl.link.is_reliable() {
-                    tl.pipeline.as_ref()
-                } else {
-                    None
-                }
-            })
-            .next()
+            .filter(|(p, _)| *p == priority)
+            .map(|(_, locator)| locator)
+            .collect();
+        if candidates
+            .iter()
+            .any(|tl| pins.iter().any(|pin| matches_locator(tl, pin)))
         {
-            zpush!(guard, pl, msg);
+            candidates.retain(|tl| pins.iter().any(|pin| matches_locator(tl, pin)));
         }
 
-        // No best match found, take the first available link
-        if let Some(pl) = guard.iter().filter_map(|tl| tl.pipeline.as_ref()).next() {
-            zpush!(guard, pl, msg);
+        // Then try to find the best match between msg and link reliability
+        if candidates
+            .iter()
+            .any(|tl| msg.is_reliable() == tl.link.is_reliable())
+        {
+            candidates.retain(|tl| msg.is_reliable() == tl.link.is_reliable());
+        }
+
+        match scheduling.policy {
+            LinkSchedulingPolicy::FirstFit => candidates.first().copied(),
+            LinkSchedulingPolicy::ActiveBackup => candidates
+                .iter()
+                .rev()
+                .max_by_key(|tl| self.link_weight(tl))
+                .copied(),
+            LinkSchedulingPolicy::RoundRobin => {
+                let turn = self.scheduling_turn.fetch_add(1, Ordering::Relaxed);
+                candidates.get(turn % candidates.len()).copied()
+            }
+            LinkSchedulingPolicy::Weighted => {
+                let weights: Vec<usize> =
+                    candidates.iter().map(|tl| self.link_weight(tl)).collect();
+                let total: usize = weights.iter().sum();
+                if total == 0 {
+                    return candidates.first().copied();
+                }
+                let mut turn = self.scheduling_turn.fetch_add(1, Ordering::Relaxed) % total;
+                for (tl, weight) in candidates.iter().zip(weights) {
+                    if turn < weight {
+                        return Some(tl);
+                    }
+                    turn -= weight;
+                }
+                None
+            }
+        }
+    }
+
+    fn schedule_on_link(&self, msg: ZenohMessage) -> bool {
+        let guard = zread!(self.links);
+        if let Some(tl) = self.select_link(&guard, &msg) {
+            // Drop the guard before the push_zenoh_message since
+            // the link could be congested and this operation could
+            // block for fairly long time
+            let pl = tl.pipeline.clone().unwrap();
+            drop(guard);
+            log::trace!("Scheduled: {:?}", msg);
+            return pl.push_zenoh_message(msg);
         }
 
         // No Link found
@@ -100,4 +177,33 @@ impl TransportUnicastInner {
 
         res
     }
+
+    pub(super) fn flush(&self, priority: Option<Priority>) {
+        let pipelines = zread!(self.links)
+            .iter()
+            .filter_map(|tl| tl.pipeline.clone())
+            .collect::<Vec<_>>();
+        for pl in pipelines {
+            pl.flush(priority);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::locator_matches;
+    use zenoh_protocol::core::Locator;
+
+    #[test]
+    fn locator_matching() {
+        let link: Locator = "tcp/127.0.0.10:7447".parse().unwrap();
+        let matches = |conf: &str| locator_matches(&link, &conf.parse().unwrap());
+
+        assert!(matches("tcp/127.0.0.10:7447"));
+        assert!(matches("tcp/127.0.0.10"));
+        assert!(!matches("tcp/127.0.0.1"));
+        assert!(!matches("tcp/127.0.0.1:7447"));
+        assert!(!matches("tcp/127.0.0.10:744"));
+        assert!(!matches("udp/127.0.0.10:7447"));
+    }
 }
@@ -0,0 +1,192 @@
+//
+// Copyright (c) 2022 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+#![cfg(all(feature = "auth_usrpwd", feature = "transport_tcp"))]
+use async_std::{prelude::FutureExt, task};
+use std::{
+    collections::{HashMap, HashSet},
+    convert::TryFrom,
+    iter::FromIterator,
+    sync::Arc,
+    time::Duration,
+};
+use zenoh_config::Config;
+use zenoh_core::zasync_executor_init;
+use zenoh_protocol::core::{EndPoint, WhatAmI, ZenohId};
+use zenoh_result::ZResult;
+use zenoh_transport::unicast::establishment::authenticator::{Password, UserPasswordAuthenticator};
+use zenoh_transport::{
+    DummyTransportPeerEventHandler, TransportEventHandler, TransportManager, TransportMulticast,
+    TransportMulticastEventHandler, TransportPeer, TransportPeerEventHandler, TransportUnicast,
+};
+
+const TIMEOUT: Duration = Duration::from_secs(60);
+const SLEEP: Duration = Duration::from_millis(100);
+
+macro_rules! ztimeout {
+    ($f:expr) => {
+        $f.timeout(TIMEOUT).await.unwrap()
+    };
+}
+
+// Transport Handler
+#[derive(Default)]
+struct SHPeer;
+
+impl TransportEventHandler for SHPeer {
+    fn new_unicast(
+        &self,
+        _peer: TransportPeer,
+        _transport: TransportUnicast,
+    ) -> ZResult<Arc<dyn TransportPeerEventHandler>> {
+        Ok(Arc::new(DummyTransportPeerEventHandler::default()))
+    }
+
+    fn new_multicast(
+        &self,
+        _transport: TransportMulticast,
+    ) -> ZResult<Arc<dyn TransportMulticastEventHandler>> {
+        panic!();
+    }
+}
+
+fn client(zid: ZenohId, user: &str, password: &str) -> TransportManager {
+    let peer_auth = UserPasswordAuthenticator::new(
+        HashMap::new(),
+        Some((user.to_string().into(), password.to_string().into())),
+    );
+    let unicast = TransportManager::config_unicast()
+        .peer_authenticator(HashSet::from_iter(vec![peer_auth.into()]));
+    TransportManager::builder()
+        .whatami(WhatAmI::Client)
+        .zid(zid)
+        .unicast(unicast)
+        .build(Arc::new(SHPeer::default()))
+        .unwrap()
+}
+
+#[test]
+fn credentials_hashed_password() {
+    let _ = env_logger::try_init();
+    task::block_on(async {
+        zasync_executor_init!();
+    });
+
+    // The hashed password can be parsed and printed back
+    let hashed = "$scram-sha3-256$1000$f4c360ef6534b0f9a6f89a633b629072$2fd80a7a97cc5101ba7388fe9d0bcdb24e6b53ad6ef568176538e2dc73aee886";
+    let password: Password = hashed.parse().unwrap();
+    assert_eq!(password.to_string(), hashed);
+    assert!("$scram-sha3-256$1000$00".parse::<Password>().is_err());
+    // The number of iterations is capped, for the peers not to be asked for an unbounded work
+    let hashed = hashed.replacen("$1000$", "$1000000000$", 1);
+    assert!(hashed.parse::<Password>().is_err());
+    assert!(Password::hash(b"password01", 1_000_000_000).is_err());
+
+    // A freshly hashed password is salted
+    let p1 = Password::hash(b"password01", 10).unwrap();
+    let p2 = Password::hash(b"password01", 10).unwrap();
+    assert!(p1 != p2);
+    assert!(p1.to_string().starts_with("$scram-sha3-256$10$"));
+}
+
+#[test]
+fn credentials_reload() {
+    let _ = env_logger::try_init();
+    task::block_on(async {
+        zasync_executor_init!();
+    });
+
+    let endpoint: EndPoint = format!("tcp/127.0.0.1:{}", 17130).parse().unwrap();
+
+    // The dictionary: user01 has a hashed password and user02 a plaintext one
+    let dictionary = std::env::temp_dir().join("zenoh-test-credentials-reload.txt");
+    let user01 = "user01:$scram-sha3-256$1000$f4c360ef6534b0f9a6f89a633b629072$2fd80a7a97cc5101ba7388fe9d0bcdb24e6b53ad6ef568176538e2dc73aee886";
+    let user02 = "user02:password02";
+    std::fs::write(&dictionary, format!("{user01}\n{user02}\n")).unwrap();
+
+    let mut config = Config::default();
+    config
+        .transport
+        .auth
+        .usrpwd
+        .set_dictionary_file(Some(dictionary.to_str().unwrap().to_string()))
+        .unwrap();
+    config
+        .transport
+        .auth
+        .reload
+        .set_close_revoked(true)
+        .unwrap();
+
+    task::block_on(async {
+        /* [ROUTER] */
+        let router_id = ZenohId::try_from([1]).unwrap();
+        let peer_auth = ztimeout!(UserPasswordAuthenticator::from_config(&config))
+            .unwrap()
+            .unwrap();
+        let unicast = TransportManager::config_unicast()
+            .peer_authenticator(HashSet::from_iter(vec![peer_auth.into()]));
+        let router_manager = TransportManager::builder()
+            .whatami(WhatAmI::Router)
+            .zid(router_id)
+            .unicast(unicast)
+            .build(Arc::new(SHPeer::default()))
+            .unwrap();
+        ztimeout!(router_manager.add_listener(endpoint.clone())).unwrap();
+
+        /* [CLIENTS] */
+        let client01_id = ZenohId::try_from([2]).unwrap();
+        let client01_manager = client(client01_id, "user01", "password01");
+        let client02_id = ZenohId::try_from([3]).unwrap();
+        let client02_manager = client(client02_id, "user02", "password02");
+        let client03_id = ZenohId::try_from([4]).unwrap();
+        let client03_manager = client(client03_id, "user01", "invalid");
+        let client04_id = ZenohId::try_from([5]).unwrap();
+        let client04_manager = client(client04_id, "user04", "password04");
+
+        println!("Credentials [1] Opening transports with hashed and plaintext passwords");
+        ztimeout!(client01_manager.open_transport(endpoint.clone())).unwrap();
+        ztimeout!(client02_manager.open_transport(endpoint.clone())).unwrap();
+        assert!(ztimeout!(client03_manager.open_transport(endpoint.clone())).is_err());
+        assert!(ztimeout!(client04_manager.open_transport(endpoint.clone())).is_err());
+        task::sleep(SLEEP).await;
+        assert!(router_manager.get_transport(&client01_id).is_some());
+        assert!(router_manager.get_transport(&client02_id).is_some());
+
+        println!("Credentials [2] Revoking user02");
+        std::fs::write(&dictionary, format!("{user01}\n")).unwrap();
+        let revoked = ztimeout!(router_manager.reload_peer_authenticators(&config)).unwrap();
+        assert_eq!(revoked, vec![client02_id]);
+        task::sleep(SLEEP).await;
+        assert!(router_manager.get_transport(&client01_id).is_some());
+        assert!(router_manager.get_transport(&client02_id).is_none());
+
+        println!("Credentials [3] Reopening a transport with revoked credentials");
+        assert!(ztimeout!(client02_manager.open_transport(endpoint.clone())).is_err());
+
+        println!("Credentials [4] Adding user02 back");
+        std::fs::write(&dictionary, format!("{user01}\n{user02}\n")).unwrap();
+        let revoked = ztimeout!(router_manager.reload_peer_authenticators(&config)).unwrap();
+        assert!(revoked.is_empty());
+        ztimeout!(client02_manager.open_transport(endpoint.clone())).unwrap();
+
+        ztimeout!(client01_manager.close());
+        ztimeout!(client02_manager.close());
+        ztimeout!(client03_manager.close());
+        ztimeout!(client04_manager.close());
+        ztimeout!(router_manager.close());
+        task::sleep(SLEEP).await;
+    });
+
+    let _ = std::fs::remove_file(dictionary);
+}
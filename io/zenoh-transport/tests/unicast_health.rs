@@ -0,0 +1,168 @@
+//
+// Copyright (c) 2022 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+use async_std::io::{ReadExt, WriteExt};
+use async_std::net::{TcpListener, TcpStream};
+use async_std::{prelude::FutureExt, task};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::Instant;
+use std::{convert::TryFrom, sync::Arc, time::Duration};
+use zenoh_core::zasync_executor_init;
+use zenoh_link::EndPoint;
+use zenoh_protocol::core::{WhatAmI, ZenohId};
+use zenoh_result::ZResult;
+use zenoh_transport::{
+    DummyTransportPeerEventHandler, LinkHealth, TransportEventHandler, TransportManager,
+    TransportMulticast, TransportMulticastEventHandler, TransportPeer, TransportPeerEventHandler,
+    TransportUnicast,
+};
+
+const TIMEOUT: Duration = Duration::from_secs(60);
+const SLEEP: Duration = Duration::from_millis(100);
+
+const LEASE: Duration = Duration::from_secs(10);
+const HEALTH: LinkHealth = LinkHealth {
+    interval: Duration::from_millis(100),
+    timeout: Duration::from_millis(500),
+};
+
+macro_rules! ztimeout {
+    ($f:expr) => {
+        $f.timeout(TIMEOUT).await.unwrap()
+    };
+}
+
+// Transport Handler
+#[derive(Default)]
+struct SHPeer;
+
+impl TransportEventHandler for SHPeer {
+    fn new_unicast(
+        &self,
+        _peer: TransportPeer,
+        _transport: TransportUnicast,
+    ) -> ZResult<Arc<dyn TransportPeerEventHandler>> {
+        Ok(Arc::new(DummyTransportPeerEventHandler::default()))
+    }
+
+    fn new_multicast(
+        &self,
+        _transport: TransportMulticast,
+    ) -> ZResult<Arc<dyn TransportMulticastEventHandler>> {
+        panic!();
+    }
+}
+
+// A TCP proxy silently dropping all the traffic once broken,
+// as if the network failed without the links being closed
+async fn proxy(listener: TcpListener, upstream: &'static str, broken: Arc<AtomicBool>) {
+    async fn forward(mut from: TcpStream, mut to: TcpStream, broken: Arc<AtomicBool>) {
+        let mut buffer = vec![0_u8; 65_536];
+        while let Ok(n) = from.read(&mut buffer).await {
+            if n == 0 {
+                break;
+            }
+            if !broken.load(Ordering::SeqCst) && to.write_all(&buffer[..n]).await.is_err() {
+                break;
+            }
+        }
+    }
+
+    while let Ok((downstream, _)) = listener.accept().await {
+        let upstream = TcpStream::connect(upstream).await.unwrap();
+        task::spawn(forward(
+            downstream.clone(),
+            upstream.clone(),
+            broken.clone(),
+        ));
+        task::spawn(forward(upstream, downstream, broken.clone()));
+    }
+}
+
+fn make_manager(zid: u8, whatami: WhatAmI, health: Option<LinkHealth>) -> TransportManager {
+    let unicast = TransportManager::config_unicast()
+        .lease(LEASE)
+        .health(health);
+    TransportManager::builder()
+        .whatami(whatami)
+        .zid(ZenohId::try_from([zid]).unwrap())
+        .unicast(unicast)
+        .build(Arc::new(SHPeer::default()))
+        .unwrap()
+}
+
+async fn health_transport(router: &'static str, proxy_port: u16) {
+    let router_endpoint: EndPoint = format!("tcp/{router}").parse().unwrap();
+    let proxy_endpoint: EndPoint = format!("tcp/127.0.0.1:{proxy_port}").parse().unwrap();
+
+    // The router closes its side of the broken links as well, for the client to reconnect
+    let router_manager = make_manager(1, WhatAmI::Router, Some(HEALTH));
+    ztimeout!(router_manager.add_listener(router_endpoint.clone())).unwrap();
+    let router_id = router_manager.zid();
+
+    let broken = Arc::new(AtomicBool::new(false));
+    let listener = TcpListener::bind(("127.0.0.1", proxy_port)).await.unwrap();
+    let proxy = task::spawn(proxy(listener, router, broken.clone()));
+
+    // Only the first client monitors the health of its link
+    let client01_manager = make_manager(2, WhatAmI::Client, Some(HEALTH));
+    let client02_manager = make_manager(3, WhatAmI::Client, None);
+    for manager in [&client01_manager, &client02_manager] {
+        ztimeout!(manager.open_transport(proxy_endpoint.clone())).unwrap();
+        assert!(manager.get_transport(&router_id).is_some());
+    }
+
+    // The links stay up as long as the traffic goes through, the pings keeping the first one alive
+    task::sleep(HEALTH.timeout * 2).await;
+    assert!(client01_manager.get_transport(&router_id).is_some());
+
+    println!("Breaking the network");
+    broken.store(true, Ordering::SeqCst);
+    let now = Instant::now();
+    ztimeout!(async {
+        while client01_manager.get_transport(&router_id).is_some() {
+            task::sleep(SLEEP).await;
+        }
+    });
+    let elapsed = now.elapsed();
+    println!("Transport closed after {elapsed:?}");
+    assert!(elapsed >= HEALTH.timeout / 2);
+    assert!(elapsed < LEASE / 2);
+
+    // The second client only notices the failure when the lease expires
+    assert!(client02_manager.get_transport(&router_id).is_some());
+
+    // Reconnecting once the network is back
+    broken.store(false, Ordering::SeqCst);
+    ztimeout!(client01_manager.open_transport(proxy_endpoint.clone())).unwrap();
+    assert!(client01_manager.get_transport(&router_id).is_some());
+
+    proxy.cancel().await;
+    ztimeout!(client01_manager.close());
+    ztimeout!(client02_manager.close());
+    ztimeout!(router_manager.close());
+
+    // Wait a little bit
+    task::sleep(SLEEP).await;
+}
+
+#[cfg(feature = "transport_tcp")]
+#[test]
+fn transport_unicast_health_tcp() {
+    let _ = env_logger::try_init();
+    task::block_on(async {
+        zasync_executor_init!();
+    });
+
+    task::block_on(health_transport("127.0.0.1:17160", 17161));
+}
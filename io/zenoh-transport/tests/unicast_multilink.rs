@@ -0,0 +1,447 @@
+//
+// Copyright (c) 2022 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+use async_std::prelude::FutureExt;
+use async_std::task;
+use std::any::Any;
+use std::collections::HashMap;
+use std::convert::{TryFrom, TryInto};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use zenoh_buffers::{SplitBuffer, ZBuf};
+use zenoh_config::{
+    LinkPriorityConf, LinkSchedulingConf, LinkSchedulingPolicy, LinkWeightConf, PriorityConf,
+};
+use zenoh_core::{zasync_executor_init, zlock};
+use zenoh_link::Link;
+use zenoh_protocol::{
+    core::{Channel, CongestionControl, EndPoint, Priority, Reliability, WhatAmI, ZenohId},
+    zenoh::{ZenohBody, ZenohMessage},
+};
+use zenoh_result::ZResult;
+use zenoh_transport::{
+    TransportEventHandler, TransportManager, TransportMulticast, TransportMulticastEventHandler,
+    TransportPeer, TransportPeerEventHandler, TransportUnicast,
+};
+
+const TIMEOUT: Duration = Duration::from_secs(60);
+const SLEEP: Duration = Duration::from_secs(1);
+const SLEEP_COUNT: Duration = Duration::from_millis(10);
+
+const MSG_COUNT: usize = 1_000;
+const MSG_SIZE: usize = 1_024;
+
+macro_rules! ztimeout {
+    ($f:expr) => {
+        $f.timeout(TIMEOUT).await.unwrap()
+    };
+}
+
+// Transport Handler for the router
+#[derive(Default)]
+struct SHRouter {
+    count: Arc<AtomicUsize>,
+    last: Arc<Mutex<HashMap<Priority, u64>>>,
+}
+
+impl SHRouter {
+    fn get_count(&self) -> usize {
+        self.count.load(Ordering::SeqCst)
+    }
+}
+
+impl TransportEventHandler for SHRouter {
+    fn new_unicast(
+        &self,
+        _peer: TransportPeer,
+        _transport: TransportUnicast,
+    ) -> ZResult<Arc<dyn TransportPeerEventHandler>> {
+        let arc = Arc::new(SCRouter {
+            count: self.count.clone(),
+            last: self.last.clone(),
+        });
+        Ok(arc)
+    }
+
+    fn new_multicast(
+        &self,
+        _transport: TransportMulticast,
+    ) -> ZResult<Arc<dyn TransportMulticastEventHandler>> {
+        panic!();
+    }
+}
+
+// Transport Callback for the router, checking that the messages of each priority are received in order
+pub struct SCRouter {
+    count: Arc<AtomicUsize>,
+    last: Arc<Mutex<HashMap<Priority, u64>>>,
+}
+
+impl TransportPeerEventHandler for SCRouter {
+    fn handle_message(&self, message: ZenohMessage) -> ZResult<()> {
+        if let ZenohBody::Data(data) = &message.body {
+            let payload = data.payload.contiguous();
+            let index = u64::from_le_bytes(payload[..8].try_into().unwrap());
+            let mut last = zlock!(self.last);
+            if let Some(previous) = last.insert(message.channel.priority, index) {
+                assert!(
+                    index > previous,
+                    "message {index} received after {previous}"
+                );
+            }
+            self.count.fetch_add(1, Ordering::SeqCst);
+        }
+        Ok(())
+    }
+
+    fn new_link(&self, _link: Link) {}
+    fn del_link(&self, _link: Link) {}
+    fn closing(&self) {}
+    fn closed(&self) {}
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+// Transport Handler for the client
+#[derive(Default)]
+struct SHClient;
+
+impl TransportEventHandler for SHClient {
+    fn new_unicast(
+        &self,
+        _peer: TransportPeer,
+        _transport: TransportUnicast,
+    ) -> ZResult<Arc<dyn TransportPeerEventHandler>> {
+        Ok(Arc::new(SCClient))
+    }
+
+    fn new_multicast(
+        &self,
+        _transport: TransportMulticast,
+    ) -> ZResult<Arc<dyn TransportMulticastEventHandler>> {
+        panic!();
+    }
+}
+
+// Transport Callback for the client
+pub struct SCClient;
+
+impl TransportPeerEventHandler for SCClient {
+    fn handle_message(&self, _message: ZenohMessage) -> ZResult<()> {
+        Ok(())
+    }
+
+    fn new_link(&self, _link: Link) {}
+    fn del_link(&self, _link: Link) {}
+    fn closing(&self) {}
+    fn closed(&self) {}
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+async fn open_transport(
+    endpoints: &[EndPoint],
+    scheduling: LinkSchedulingConf,
+) -> (
+    TransportManager,
+    Arc<SHRouter>,
+    TransportManager,
+    TransportUnicast,
+) {
+    // Define client and router IDs
+    let client_id = ZenohId::try_from([1]).unwrap();
+    let router_id = ZenohId::try_from([2]).unwrap();
+
+    // Create the router transport manager
+    let router_handler = Arc::new(SHRouter::default());
+    let unicast = TransportManager::config_unicast().max_links(endpoints.len());
+    let router_manager = TransportManager::builder()
+        .whatami(WhatAmI::Router)
+        .zid(router_id)
+        .unicast(unicast)
+        .build(router_handler.clone())
+        .unwrap();
+
+    // Create the client transport manager
+    let unicast = TransportManager::config_unicast()
+        .max_links(endpoints.len())
+        .scheduling(scheduling);
+    let client_manager = TransportManager::builder()
+        .whatami(WhatAmI::Client)
+        .zid(client_id)
+        .unicast(unicast)
+        .build(Arc::new(SHClient::default()))
+        .unwrap();
+
+    // Create the listeners on the router
+    for e in endpoints.iter() {
+        println!("Add locator: {e}");
+        let _ = ztimeout!(router_manager.add_listener(e.clone())).unwrap();
+    }
+
+    // Open a link on each endpoint, all of them belonging to the same transport
+    for e in endpoints.iter() {
+        println!("Opening transport with {e}");
+        let _ = ztimeout!(client_manager.open_transport(e.clone())).unwrap();
+    }
+
+    let client_transport = client_manager.get_transport(&router_id).unwrap();
+    assert_eq!(client_transport.get_links().unwrap().len(), endpoints.len());
+
+    (
+        router_manager,
+        router_handler,
+        client_manager,
+        client_transport,
+    )
+}
+
+async fn close_transport(
+    router_manager: TransportManager,
+    client_manager: TransportManager,
+    client_transport: TransportUnicast,
+    endpoints: &[EndPoint],
+) {
+    println!("Closing transport");
+    ztimeout!(client_transport.close()).unwrap();
+
+    ztimeout!(async {
+        while !router_manager.get_transports().is_empty() {
+            task::sleep(SLEEP).await;
+        }
+    });
+
+    // Stop the locators on the manager
+    for e in endpoints.iter() {
+        println!("Del locator: {e}");
+        ztimeout!(router_manager.del_listener(e)).unwrap();
+    }
+
+    // Wait a little bit
+    task::sleep(SLEEP).await;
+
+    ztimeout!(router_manager.close());
+    ztimeout!(client_manager.close());
+
+    // Wait a little bit
+    task::sleep(SLEEP).await;
+}
+
+// Sends the messages with the given indexes, carried by their payload
+fn send(client_transport: &TransportUnicast, priority: Priority, indexes: std::ops::Range<u64>) {
+    let channel = Channel {
+        priority,
+        reliability: Reliability::Reliable,
+    };
+    for index in indexes {
+        let mut payload = vec![0_u8; MSG_SIZE];
+        payload[..8].copy_from_slice(&index.to_le_bytes());
+        let message = ZenohMessage::make_data(
+            "test".into(),
+            ZBuf::from(payload),
+            channel,
+            CongestionControl::Block,
+            None,
+            None,
+            None,
+            None,
+        );
+        client_transport.schedule(message).unwrap();
+    }
+}
+
+async fn wait_count(router_handler: &SHRouter, count: usize) {
+    ztimeout!(async {
+        while router_handler.get_count() != count {
+            task::sleep(SLEEP_COUNT).await;
+        }
+    });
+}
+
+fn weight(endpoint: &EndPoint, weight: usize) -> LinkWeightConf {
+    LinkWeightConf {
+        locator: endpoint.to_string(),
+        weight,
+    }
+}
+
+async fn striping(endpoints: &[EndPoint], scheduling: LinkSchedulingConf) {
+    let (router_manager, router_handler, client_manager, client_transport) =
+        open_transport(endpoints, scheduling).await;
+
+    println!(
+        "Sending {MSG_COUNT} messages striped over {} links",
+        endpoints.len()
+    );
+    send(&client_transport, Priority::default(), 0..MSG_COUNT as u64);
+    wait_count(&router_handler, MSG_COUNT).await;
+
+    close_transport(router_manager, client_manager, client_transport, endpoints).await;
+}
+
+async fn failover(endpoints: &[EndPoint]) {
+    // The last endpoint is the active link
+    let mut scheduling = LinkSchedulingConf::default();
+    scheduling
+        .set_policy(LinkSchedulingPolicy::ActiveBackup)
+        .unwrap();
+    scheduling
+        .set_weights(vec![weight(endpoints.last().unwrap(), 10)])
+        .unwrap();
+    let (router_manager, router_handler, client_manager, client_transport) =
+        open_transport(endpoints, scheduling).await;
+
+    let half = (MSG_COUNT / 2) as u64;
+    println!("Sending {half} messages on the active link");
+    send(&client_transport, Priority::default(), 0..half);
+    wait_count(&router_handler, half as usize).await;
+
+    // Close the active link: the next messages continue on a backup link
+    let links = client_transport.get_links().unwrap();
+    let active = links
+        .iter()
+        .find(|l| l.dst == endpoints.last().unwrap().to_locator())
+        .unwrap();
+    println!("Closing the active link {active}");
+    ztimeout!(client_transport.close_link(active)).unwrap();
+    assert_eq!(
+        client_transport.get_links().unwrap().len(),
+        endpoints.len() - 1
+    );
+
+    println!("Sending {half} messages on a backup link");
+    send(
+        &client_transport,
+        Priority::default(),
+        half..MSG_COUNT as u64,
+    );
+    wait_count(&router_handler, MSG_COUNT).await;
+
+    close_transport(router_manager, client_manager, client_transport, endpoints).await;
+}
+
+async fn pinning(endpoints: &[EndPoint]) {
+    // RealTime is pinned to the first link and Background to the second one
+    let mut scheduling = LinkSchedulingConf::default();
+    scheduling
+        .set_priorities(vec![
+            LinkPriorityConf {
+                priority: PriorityConf::RealTime,
+                locator: endpoints[0].to_string(),
+            },
+            LinkPriorityConf {
+                priority: PriorityConf::Background,
+                locator: endpoints[1].to_string(),
+            },
+        ])
+        .unwrap();
+    let (router_manager, router_handler, client_manager, client_transport) =
+        open_transport(endpoints, scheduling).await;
+
+    let half = (MSG_COUNT / 2) as u64;
+    println!("Sending {MSG_COUNT} messages on pinned links");
+    send(&client_transport, Priority::RealTime, 0..half);
+    send(&client_transport, Priority::Background, 0..half);
+    wait_count(&router_handler, MSG_COUNT).await;
+
+    // Close the link Background is pinned to: it falls back on the remaining link
+    let links = client_transport.get_links().unwrap();
+    let pinned = links
+        .iter()
+        .find(|l| l.dst == endpoints[1].to_locator())
+        .unwrap();
+    println!("Closing the pinned link {pinned}");
+    ztimeout!(client_transport.close_link(pinned)).unwrap();
+
+    send(
+        &client_transport,
+        Priority::Background,
+        half..MSG_COUNT as u64,
+    );
+    wait_count(&router_handler, MSG_COUNT + half as usize).await;
+
+    close_transport(router_manager, client_manager, client_transport, endpoints).await;
+}
+
+#[cfg(feature = "transport_tcp")]
+#[test]
+fn multilink_round_robin_tcp() {
+    let _ = env_logger::try_init();
+    task::block_on(async {
+        zasync_executor_init!();
+    });
+    let endpoints: Vec<EndPoint> = vec![
+        format!("tcp/127.0.0.1:{}", 17000).parse().unwrap(),
+        format!("tcp/127.0.0.1:{}", 17001).parse().unwrap(),
+    ];
+    let mut scheduling = LinkSchedulingConf::default();
+    scheduling
+        .set_policy(LinkSchedulingPolicy::RoundRobin)
+        .unwrap();
+    task::block_on(striping(&endpoints, scheduling));
+}
+
+#[cfg(feature = "transport_tcp")]
+#[test]
+fn multilink_weighted_tcp() {
+    let _ = env_logger::try_init();
+    task::block_on(async {
+        zasync_executor_init!();
+    });
+    let endpoints: Vec<EndPoint> = vec![
+        format!("tcp/127.0.0.1:{}", 17010).parse().unwrap(),
+        format!("tcp/127.0.0.1:{}", 17011).parse().unwrap(),
+        format!("tcp/127.0.0.1:{}", 17012).parse().unwrap(),
+    ];
+    let mut scheduling = LinkSchedulingConf::default();
+    scheduling
+        .set_policy(LinkSchedulingPolicy::Weighted)
+        .unwrap();
+    scheduling
+        .set_weights(vec![weight(&endpoints[0], 3), weight(&endpoints[2], 2)])
+        .unwrap();
+    task::block_on(striping(&endpoints, scheduling));
+}
+
+#[cfg(feature = "transport_tcp")]
+#[test]
+fn multilink_active_backup_tcp() {
+    let _ = env_logger::try_init();
+    task::block_on(async {
+        zasync_executor_init!();
+    });
+    let endpoints: Vec<EndPoint> = vec![
+        format!("tcp/127.0.0.1:{}", 17020).parse().unwrap(),
+        format!("tcp/127.0.0.1:{}", 17021).parse().unwrap(),
+    ];
+    task::block_on(failover(&endpoints));
+}
+
+#[cfg(feature = "transport_tcp")]
+#[test]
+fn multilink_priority_pinning_tcp() {
+    let _ = env_logger::try_init();
+    task::block_on(async {
+        zasync_executor_init!();
+    });
+    let endpoints: Vec<EndPoint> = vec![
+        format!("tcp/127.0.0.1:{}", 17030).parse().unwrap(),
+        format!("tcp/127.0.0.1:{}", 17031).parse().unwrap(),
+    ];
+    task::block_on(pinning(&endpoints));
+}
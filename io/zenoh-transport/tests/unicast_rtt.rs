@@ -0,0 +1,159 @@
+//
+// Copyright (c) 2022 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+use async_std::prelude::FutureExt;
+use async_std::task;
+use std::any::Any;
+use std::convert::TryFrom;
+use std::sync::Arc;
+use std::time::Duration;
+use zenoh_core::zasync_executor_init;
+use zenoh_link::Link;
+use zenoh_protocol::{
+    core::{EndPoint, Priority, WhatAmI, ZenohId},
+    zenoh::ZenohMessage,
+};
+use zenoh_result::ZResult;
+use zenoh_transport::{
+    TransportEventHandler, TransportManager, TransportMulticast, TransportMulticastEventHandler,
+    TransportPeer, TransportPeerEventHandler, TransportUnicast,
+};
+
+const TIMEOUT: Duration = Duration::from_secs(60);
+const SLEEP: Duration = Duration::from_secs(1);
+const RTT_INTERVAL: Duration = Duration::from_millis(100);
+
+macro_rules! ztimeout {
+    ($f:expr) => {
+        $f.timeout(TIMEOUT).await.unwrap()
+    };
+}
+
+// Transport Handler
+#[derive(Default)]
+struct SH;
+
+impl TransportEventHandler for SH {
+    fn new_unicast(
+        &self,
+        _peer: TransportPeer,
+        _transport: TransportUnicast,
+    ) -> ZResult<Arc<dyn TransportPeerEventHandler>> {
+        Ok(Arc::new(SC::default()))
+    }
+
+    fn new_multicast(
+        &self,
+        _transport: TransportMulticast,
+    ) -> ZResult<Arc<dyn TransportMulticastEventHandler>> {
+        panic!();
+    }
+}
+
+// Transport Callback
+#[derive(Default)]
+struct SC;
+
+impl TransportPeerEventHandler for SC {
+    fn handle_message(&self, _message: ZenohMessage) -> ZResult<()> {
+        Ok(())
+    }
+
+    fn new_link(&self, _link: Link) {}
+    fn del_link(&self, _link: Link) {}
+    fn closing(&self) {}
+    fn closed(&self) {}
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+async fn run(endpoint: &EndPoint) {
+    // Define client and router IDs
+    let client_id = ZenohId::try_from([1]).unwrap();
+    let router_id = ZenohId::try_from([2]).unwrap();
+
+    // Create the router transport manager, not measuring the RTT but answering the pings
+    let router_manager = TransportManager::builder()
+        .whatami(WhatAmI::Router)
+        .zid(router_id)
+        .build(Arc::new(SH::default()))
+        .unwrap();
+
+    // Create the client transport manager, measuring the RTT
+    let unicast = TransportManager::config_unicast().rtt_interval(Some(RTT_INTERVAL));
+    let client_manager = TransportManager::builder()
+        .whatami(WhatAmI::Client)
+        .zid(client_id)
+        .unicast(unicast)
+        .build(Arc::new(SH::default()))
+        .unwrap();
+
+    println!("Add locator: {endpoint}");
+    let _ = ztimeout!(router_manager.add_listener(endpoint.clone())).unwrap();
+    println!("Opening transport with {endpoint}");
+    let _ = ztimeout!(client_manager.open_transport(endpoint.clone())).unwrap();
+    let client_transport = client_manager.get_transport(&router_id).unwrap();
+    let router_transport = router_manager.get_transport(&client_id).unwrap();
+
+    // Wait for a few pongs on each priority
+    let rtts = ztimeout!(async {
+        loop {
+            let rtts = client_transport.get_link_rtts().unwrap();
+            if rtts
+                .iter()
+                .any(|r| r.priorities.iter().all(|p| p.samples >= 3))
+            {
+                break rtts;
+            }
+            task::sleep(RTT_INTERVAL).await;
+        }
+    });
+    assert_eq!(rtts.len(), 1);
+    let rtt = &rtts[0];
+    println!("RTT: {rtt:?}");
+    assert_eq!(rtt.priorities.len(), Priority::NUM);
+    assert!(rtt.rtt.samples >= 3 * Priority::NUM);
+    assert!(rtt.rtt.min_us <= rtt.rtt.smoothed_us && rtt.rtt.smoothed_us <= rtt.rtt.max_us);
+    assert!(rtt.rtt.smoothed().unwrap() < TIMEOUT);
+
+    // The router does not measure the RTT
+    assert!(router_transport.get_link_rtts().unwrap().is_empty());
+
+    println!("Closing transport");
+    ztimeout!(client_transport.close()).unwrap();
+    ztimeout!(async {
+        while !router_manager.get_transports().is_empty() {
+            task::sleep(SLEEP).await;
+        }
+    });
+    ztimeout!(router_manager.del_listener(endpoint)).unwrap();
+    ztimeout!(router_manager.close());
+    ztimeout!(client_manager.close());
+
+    // Wait a little bit
+    task::sleep(SLEEP).await;
+}
+
+#[cfg(feature = "transport_tcp")]
+#[test]
+fn rtt_tcp_only() {
+    let _ = env_logger::try_init();
+    task::block_on(async {
+        zasync_executor_init!();
+    });
+
+    let endpoint: EndPoint = format!("tcp/127.0.0.1:{}", 17100).parse().unwrap();
+    task::block_on(run(&endpoint));
+}
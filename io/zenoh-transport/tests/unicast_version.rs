@@ -0,0 +1,255 @@
+//
+// Copyright (c) 2022 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+use async_std::prelude::FutureExt;
+use async_std::task;
+use std::any::Any;
+use std::convert::TryFrom;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+use zenoh_buffers::ZBuf;
+use zenoh_core::zasync_executor_init;
+use zenoh_link::Link;
+use zenoh_protocol::{
+    core::{Channel, CongestionControl, EndPoint, Priority, Reliability, WhatAmI, ZenohId},
+    defaults::{MIN_VERSION, VERSION},
+    zenoh::ZenohMessage,
+};
+use zenoh_result::ZResult;
+use zenoh_transport::{
+    TransportEventHandler, TransportManager, TransportMulticast, TransportMulticastEventHandler,
+    TransportPeer, TransportPeerEventHandler, TransportUnicast,
+};
+
+const TIMEOUT: Duration = Duration::from_secs(60);
+const SLEEP: Duration = Duration::from_secs(1);
+const SLEEP_COUNT: Duration = Duration::from_millis(10);
+const RTT_INTERVAL: Duration = Duration::from_millis(100);
+
+const MSG_COUNT: usize = 100;
+const MSG_SIZE_ALL: [usize; 2] = [1_024, 131_072];
+
+macro_rules! ztimeout {
+    ($f:expr) => {
+        $f.timeout(TIMEOUT).await.unwrap()
+    };
+}
+
+// Transport Handler
+#[derive(Default)]
+struct SH {
+    count: Arc<AtomicUsize>,
+}
+
+impl SH {
+    fn get_count(&self) -> usize {
+        self.count.load(Ordering::SeqCst)
+    }
+
+    fn reset_count(&self) {
+        self.count.store(0, Ordering::SeqCst)
+    }
+}
+
+impl TransportEventHandler for SH {
+    fn new_unicast(
+        &self,
+        _peer: TransportPeer,
+        _transport: TransportUnicast,
+    ) -> ZResult<Arc<dyn TransportPeerEventHandler>> {
+        Ok(Arc::new(SC::new(self.count.clone())))
+    }
+
+    fn new_multicast(
+        &self,
+        _transport: TransportMulticast,
+    ) -> ZResult<Arc<dyn TransportMulticastEventHandler>> {
+        panic!();
+    }
+}
+
+// Transport Callback
+struct SC {
+    count: Arc<AtomicUsize>,
+}
+
+impl SC {
+    fn new(count: Arc<AtomicUsize>) -> Self {
+        Self { count }
+    }
+}
+
+impl TransportPeerEventHandler for SC {
+    fn handle_message(&self, _message: ZenohMessage) -> ZResult<()> {
+        self.count.fetch_add(1, Ordering::SeqCst);
+        Ok(())
+    }
+
+    fn new_link(&self, _link: Link) {}
+    fn del_link(&self, _link: Link) {}
+    fn closing(&self) {}
+    fn closed(&self) {}
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+// Open a transport between a router and a client supporting the given ranges of versions,
+// and verify that the expected version is negotiated (or that the opening fails if none)
+async fn run(
+    endpoint: &EndPoint,
+    router_versions: (u8, u8),
+    client_versions: (u8, u8),
+    expected: Option<u8>,
+) {
+    // Define client and router IDs
+    let client_id = ZenohId::try_from([1]).unwrap();
+    let router_id = ZenohId::try_from([2]).unwrap();
+
+    // Create the router transport manager
+    let router_handler = Arc::new(SH::default());
+    let router_manager = TransportManager::builder()
+        .whatami(WhatAmI::Router)
+        .zid(router_id)
+        .min_version(router_versions.0)
+        .version(router_versions.1)
+        .build(router_handler.clone())
+        .unwrap();
+
+    // Create the client transport manager, measuring the RTT
+    let unicast = TransportManager::config_unicast().rtt_interval(Some(RTT_INTERVAL));
+    let client_manager = TransportManager::builder()
+        .whatami(WhatAmI::Client)
+        .zid(client_id)
+        .min_version(client_versions.0)
+        .version(client_versions.1)
+        .unicast(unicast)
+        .build(Arc::new(SH::default()))
+        .unwrap();
+
+    println!("Add locator: {endpoint}");
+    let _ = ztimeout!(router_manager.add_listener(endpoint.clone())).unwrap();
+    println!("Opening transport with {endpoint}");
+    let res = ztimeout!(client_manager.open_transport(endpoint.clone()));
+
+    match expected {
+        Some(version) => {
+            let client_transport = res.unwrap();
+            let router_transport = router_manager.get_transport(&client_id).unwrap();
+            assert_eq!(client_transport.get_version().unwrap(), version);
+            assert_eq!(router_transport.get_version().unwrap(), version);
+
+            // The messages are exchanged with the negotiated version, fragmented or not
+            for msg_size in MSG_SIZE_ALL {
+                router_handler.reset_count();
+                let message = ZenohMessage::make_data(
+                    "test".into(),
+                    ZBuf::from(vec![0_u8; msg_size]),
+                    Channel {
+                        priority: Priority::default(),
+                        reliability: Reliability::Reliable,
+                    },
+                    CongestionControl::Block,
+                    None,
+                    None,
+                    None,
+                    None,
+                );
+                println!("Sending {MSG_COUNT} messages... {msg_size}");
+                for _ in 0..MSG_COUNT {
+                    client_transport.schedule(message.clone()).unwrap();
+                }
+                ztimeout!(async {
+                    while router_handler.get_count() != MSG_COUNT {
+                        task::sleep(SLEEP_COUNT).await;
+                    }
+                });
+            }
+
+            // The RTT is measured only with the versions having the ping messages
+            if version >= 0x08 {
+                ztimeout!(async {
+                    while client_transport.get_link_rtts().unwrap().is_empty() {
+                        task::sleep(RTT_INTERVAL).await;
+                    }
+                });
+            } else {
+                task::sleep(5 * RTT_INTERVAL).await;
+                assert!(client_transport.get_link_rtts().unwrap().is_empty());
+            }
+
+            println!("Closing transport");
+            ztimeout!(client_transport.close()).unwrap();
+        }
+        None => assert!(res.is_err()),
+    }
+
+    ztimeout!(async {
+        while !router_manager.get_transports().is_empty() {
+            task::sleep(SLEEP).await;
+        }
+    });
+    ztimeout!(router_manager.del_listener(endpoint)).unwrap();
+    ztimeout!(router_manager.close());
+    ztimeout!(client_manager.close());
+
+    // Wait a little bit
+    task::sleep(SLEEP).await;
+}
+
+#[cfg(feature = "transport_tcp")]
+#[test]
+fn version_tcp_only() {
+    let _ = env_logger::try_init();
+    task::block_on(async {
+        zasync_executor_init!();
+    });
+
+    let endpoint: EndPoint = format!("tcp/127.0.0.1:{}", 17140).parse().unwrap();
+    task::block_on(async {
+        // Both peers support the latest version
+        run(
+            &endpoint,
+            (MIN_VERSION, VERSION),
+            (MIN_VERSION, VERSION),
+            Some(VERSION),
+        )
+        .await;
+        // The router only supports the oldest version
+        run(
+            &endpoint,
+            (MIN_VERSION, MIN_VERSION),
+            (MIN_VERSION, VERSION),
+            Some(MIN_VERSION),
+        )
+        .await;
+        // The client only supports the oldest version, as an older zenoh would
+        run(
+            &endpoint,
+            (MIN_VERSION, VERSION),
+            (MIN_VERSION, MIN_VERSION),
+            Some(MIN_VERSION),
+        )
+        .await;
+        // No version in common
+        run(
+            &endpoint,
+            (MIN_VERSION, MIN_VERSION),
+            (VERSION, VERSION),
+            None,
+        )
+        .await;
+    });
+}
@@ -23,6 +23,7 @@ pub struct PluginConfig {
     pub name: String,
     pub required: bool,
     pub backend_search_dirs: Option<Vec<String>>,
+    pub snapshots_dir: Option<String>,
     pub volumes: Vec<VolumeConfig>,
     pub storages: Vec<StorageConfig>,
     #[as_ref]
@@ -49,6 +50,122 @@ pub struct StorageConfig {
     pub volume_cfg: Value,
     // Note: ReplicaConfig is optional. Alignment will be performed only if it is a replica
     pub replica_config: Option<ReplicaConfig>,
+    pub retention: RetentionConfig,
+    pub conflict_resolution: ConflictResolution,
+}
+// Note: All parameters should be same for replicas, else evicted entries might be re-aligned from other replicas
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RetentionConfig {
+    /// The maximum age of the stored values, relative to their timestamp.
+    pub max_age: Option<Duration>,
+    /// The maximum number of stored values, the oldest ones being evicted first.
+    pub max_keys: Option<usize>,
+    /// The maximum total size of the stored payloads, the oldest values being evicted first.
+    pub max_bytes: Option<usize>,
+    /// How long deletions are remembered, to discard the values with an older timestamp received afterwards.
+    pub tombstone_lifetime: Duration,
+}
+
+impl Default for RetentionConfig {
+    fn default() -> Self {
+        Self {
+            max_age: None,
+            max_keys: None,
+            max_bytes: None,
+            tombstone_lifetime: Duration::from_secs(5),
+        }
+    }
+}
+
+impl RetentionConfig {
+    pub fn to_json_value(&self) -> Value {
+        let mut result = serde_json::Map::new();
+        if let Some(max_age) = self.max_age {
+            result.insert("max_age".into(), max_age.as_secs_f64().into());
+        }
+        if let Some(max_keys) = self.max_keys {
+            result.insert("max_keys".into(), max_keys.into());
+        }
+        if let Some(max_bytes) = self.max_bytes {
+            result.insert("max_bytes".into(), max_bytes.into());
+        }
+        result.insert(
+            "tombstone_lifetime".into(),
+            self.tombstone_lifetime.as_secs_f64().into(),
+        );
+        Value::Object(result)
+    }
+    fn try_from(storage_name: &str, config: &Value) -> ZResult<Self> {
+        let config = config.as_object().ok_or_else(|| {
+            zerror!(
+                "`retention` field of storage `{}` must be an object",
+                storage_name
+            )
+        })?;
+        let duration = |field: &str| -> ZResult<Option<Duration>> {
+            match config.get(field) {
+                None => Ok(None),
+                Some(v) => match v.as_f64() {
+                    Some(secs) if secs >= 0.0 => Ok(Some(Duration::from_secs_f64(secs))),
+                    _ => bail!("Invalid value for field `{}` in `retention` of storage `{}`. Only positive numbers of seconds are accepted.", field, storage_name),
+                },
+            }
+        };
+        let size = |field: &str| -> ZResult<Option<usize>> {
+            match config.get(field) {
+                None => Ok(None),
+                Some(v) => match v.as_u64() {
+                    Some(size) => Ok(Some(size as usize)),
+                    None => bail!("Invalid value for field `{}` in `retention` of storage `{}`. Only positive integers are accepted.", field, storage_name),
+                },
+            }
+        };
+        Ok(RetentionConfig {
+            max_age: duration("max_age")?,
+            max_keys: size("max_keys")?,
+            max_bytes: size("max_bytes")?,
+            tombstone_lifetime: duration("tombstone_lifetime")?
+                .unwrap_or_else(|| RetentionConfig::default().tombstone_lifetime),
+        })
+    }
+}
+// Note: The policy should be same for replicas, else they might never converge
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub enum ConflictResolution {
+    /// The value with the latest timestamp replaces the other ones.
+    #[default]
+    LastWriterWins,
+    /// The latest value of each writer is kept, all of them being returned as a single JSON value.
+    Siblings,
+    /// The values of this writer (a zenoh id) are only replaced by newer values of the same writer.
+    PreferWriter(String),
+    /// The values are merged by the conflict resolver of the volume.
+    Volume,
+}
+
+impl ConflictResolution {
+    pub fn to_json_value(&self) -> Value {
+        match self {
+            ConflictResolution::LastWriterWins => "last_writer_wins".into(),
+            ConflictResolution::Siblings => "siblings".into(),
+            ConflictResolution::PreferWriter(zid) => serde_json::json!({ "prefer_writer": zid }),
+            ConflictResolution::Volume => "volume".into(),
+        }
+    }
+    fn try_from(storage_name: &str, config: &Value) -> ZResult<Self> {
+        match config {
+            Value::String(s) if s == "last_writer_wins" => Ok(ConflictResolution::LastWriterWins),
+            Value::String(s) if s == "siblings" => Ok(ConflictResolution::Siblings),
+            Value::String(s) if s == "volume" => Ok(ConflictResolution::Volume),
+            Value::Object(o) if o.len() == 1 => match o.get("prefer_writer") {
+                Some(Value::String(zid)) if !zid.is_empty() => {
+                    Ok(ConflictResolution::PreferWriter(zid.clone()))
+                }
+                _ => bail!("Invalid value for field `conflict_resolution` of storage `{}`. `prefer_writer` must be a zenoh id.", storage_name),
+            },
+            _ => bail!("Invalid value for field `conflict_resolution` of storage `{}`. Only \"last_writer_wins\", \"siblings\", \"volume\" or {{ prefer_writer: <zid> }} are accepted.", storage_name),
+        }
+    }
 }
 // Note: All parameters should be same for replicas, else will result on huge overhead
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -129,6 +246,14 @@ impl<S: Into<String> + AsRef<str>, V: AsObject> TryFrom<(S, &V)> for PluginConfi
             None => None,
             _ => bail!("`backend_search_dirs` field of {}'s configuration must be a string or array of strings", name.as_ref())
         };
+        let snapshots_dir = match value.get("snapshots_dir") {
+            Some(serde_json::Value::String(dir)) => Some(dir.clone()),
+            None => None,
+            _ => bail!(
+                "`snapshots_dir` field of {}'s configuration must be a string",
+                name.as_ref()
+            ),
+        };
         let volumes = match value.get("volumes") {
             Some(configs) => VolumeConfig::try_from(name.as_ref(), configs)?,
             None => Vec::new(),
@@ -155,13 +280,20 @@ impl<S: Into<String> + AsRef<str>, V: AsObject> TryFrom<(S, &V)> for PluginConfi
             name: name.into(),
             required,
             backend_search_dirs,
+            snapshots_dir,
             volumes,
             storages,
             rest: value
                 .into_iter()
                 .filter_map(|(k, v)| {
-                    (!["__required__", "backend_search_dirs", "volumes", "storages"]
-                        .contains(&k.as_str()))
+                    (![
+                        "__required__",
+                        "backend_search_dirs",
+                        "snapshots_dir",
+                        "volumes",
+                        "storages",
+                    ]
+                    .contains(&k.as_str()))
                     .then(|| (k.clone(), v.clone()))
                 })
                 .collect(),
@@ -302,6 +434,15 @@ impl StorageConfig {
                 _ => unreachable!(),
             },
         );
+        if self.retention != RetentionConfig::default() {
+            result.insert("retention".into(), self.retention.to_json_value());
+        }
+        if self.conflict_resolution != ConflictResolution::default() {
+            result.insert(
+                "conflict_resolution".into(),
+                self.conflict_resolution.to_json_value(),
+            );
+        }
         Value::Object(result)
     }
     fn try_from<V: AsObject>(plugin_name: &str, storage_name: &str, config: &V) -> ZResult<Self> {
@@ -415,6 +556,14 @@ impl StorageConfig {
             }
             None => None,
         };
+        let retention = match config.get("retention") {
+            Some(r) => RetentionConfig::try_from(storage_name, r)?,
+            None => RetentionConfig::default(),
+        };
+        let conflict_resolution = match config.get("conflict_resolution") {
+            Some(c) => ConflictResolution::try_from(storage_name, c)?,
+            None => ConflictResolution::default(),
+        };
         Ok(StorageConfig {
             name: storage_name.into(),
             key_expr,
@@ -423,6 +572,8 @@ impl StorageConfig {
             volume_id,
             volume_cfg,
             replica_config,
+            retention,
+            conflict_resolution,
         })
     }
 }
@@ -130,15 +130,24 @@
 //!         // @TODO: get the list of (key, timestamp) in the datastore
 //!         Ok(Vec::new())
 //!     }
+//!
+//!     // When an entry is evicted by the retention policy of the storage
+//!     async fn evict(&mut self, key: OwnedKeyExpr, timestamp: Timestamp) -> ZResult<()> {
+//!         // @TODO: remove the entry (value or deletion) of key if its timestamp is still timestamp
+//!         Ok(())
+//!     }
 //! }
 //! ```
 
 use async_trait::async_trait;
 use std::sync::Arc;
+use std::time::SystemTime;
 use zenoh::prelude::{KeyExpr, OwnedKeyExpr, Sample, Selector};
 use zenoh::queryable::ReplyBuilder;
+use zenoh::selector::TimeRange;
 use zenoh::time::Timestamp;
 pub use zenoh::Result as ZResult;
+use zenoh_result::bail;
 
 pub mod config;
 use config::{StorageConfig, VolumeConfig};
@@ -155,6 +164,64 @@ pub enum StorageInsertionResult {
     Deleted,
 }
 
+/// Whether a storage keeps all the values of each key, or only the latest one.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum History {
+    Latest,
+    All,
+}
+
+/// Whether the content of a storage survives a restart.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Persistence {
+    Volatile,
+    Durable,
+}
+
+/// How the queries on a storage are answered.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum QuerySupport {
+    /// By the storage itself, in [`Storage::on_query`].
+    OnQuery,
+    /// By the storage manager, calling [`Storage::get`] for each stored key matching the query.
+    Get,
+    /// By the storage manager, calling [`Storage::get`] with the key expression of the query,
+    /// which may contain wildcards.
+    GetWithWildcards,
+}
+
+/// The capabilities of a storage, describing how the storage manager can use it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Capability {
+    pub history: History,
+    pub persistence: Persistence,
+    pub queries: QuerySupport,
+    /// Whether [`Storage::evict`] is supported, which is required to enforce a retention policy or to clear the storage.
+    pub eviction: bool,
+}
+
+impl Default for Capability {
+    fn default() -> Self {
+        Capability {
+            history: History::Latest,
+            persistence: Persistence::Volatile,
+            queries: QuerySupport::OnQuery,
+            eviction: false,
+        }
+    }
+}
+
+impl Capability {
+    pub fn to_json_value(&self) -> serde_json::Value {
+        serde_json::json!({
+            "history": format!("{:?}", self.history),
+            "persistence": format!("{:?}", self.persistence),
+            "queries": format!("{:?}", self.queries),
+            "eviction": self.eviction,
+        })
+    }
+}
+
 /// Trait to be implemented by a Backend.
 ///
 #[async_trait]
@@ -173,6 +240,31 @@ pub trait Volume: Send + Sync {
     /// Returns an interceptor that will be called before sending any reply
     /// to a query from a storage created by this backend. `None` can be returned for no interception point.
     fn outgoing_data_interceptor(&self) -> Option<Arc<dyn Fn(Sample) -> Sample + Send + Sync>>;
+
+    /// Returns the resolver used by the storages configured with `conflict_resolution: "volume"`.
+    /// By default: none, such storages failing to start.
+    fn conflict_resolver(&self) -> Option<Arc<dyn ConflictResolver>> {
+        None
+    }
+}
+
+/// Trait to be implemented to merge concurrent updates of a key (e.g. CRDT counters).
+/// It is called by the storage manager for each sample received by a storage, including the ones
+/// retrieved from other replicas while aligning. It must be deterministic and commutative for
+/// the replicas to converge.
+pub trait ConflictResolver: Send + Sync {
+    /// Returns the sample to store given the `stored` value for its key (if any) and the `incoming` one,
+    /// or `None` to drop `incoming`. The returned sample replaces the stored value even if its timestamp
+    /// is not newer, thus it should be the latest timestamp of the two in most cases.
+    fn resolve(&self, stored: Option<&Sample>, incoming: Sample) -> Option<Sample>;
+
+    /// Returns a hash of the content of a sample returned by [`ConflictResolver::resolve`], if its timestamp
+    /// doesn't identify it (e.g. several values merged under the latest timestamp among them).
+    /// It's included in the digests of the replicas, for them to align such samples. By default: `None`.
+    fn content_hash(&self, sample: &Sample) -> Option<u64> {
+        let _ = sample;
+        None
+    }
 }
 
 /// Trait to be implemented by a Storage.
@@ -182,9 +274,26 @@ pub trait Storage: Send + Sync {
     /// on the administration space for this storage.
     fn get_admin_status(&self) -> serde_json::Value;
 
+    /// Returns the capabilities of this storage.
+    /// By default: only the latest value of each key, not persisted, and queries answered by [`Storage::on_query`].
+    fn capabilities(&self) -> Capability {
+        Capability::default()
+    }
+
     /// Function called for each incoming data ([`Sample`]) to be stored in this storage.
     async fn on_sample(&mut self, sample: Sample) -> ZResult<StorageInsertionResult>;
 
+    /// Function called with several incoming data at once (e.g. when aligning with other storages),
+    /// returning the result of the insertion of each of them, in order.
+    /// By default, calls [`Storage::on_sample`] for each of them.
+    async fn on_samples(&mut self, samples: Vec<Sample>) -> Vec<ZResult<StorageInsertionResult>> {
+        let mut results = Vec::with_capacity(samples.len());
+        for sample in samples {
+            results.push(self.on_sample(sample).await);
+        }
+        results
+    }
+
     /// Function called for each incoming query matching this storage's keys exp.
     /// This storage should reply with data matching the query calling [`Query::reply()`].
     async fn on_query(&mut self, query: Query) -> ZResult<()>;
@@ -192,6 +301,31 @@ pub trait Storage: Send + Sync {
     /// Function called to get the list of all storage content (key, timestamp)
     /// The latest Timestamp corresponding to each key is either the timestamp of the delete or put whichever is the latest.
     async fn get_all_entries(&self) -> ZResult<Vec<(OwnedKeyExpr, Timestamp)>>;
+
+    /// Function called by the storage manager to answer the queries, if supported according to [`Storage::capabilities`].
+    /// Returns the timestamped values (not the deletions) stored for the keys matching `key_expr`:
+    /// if `time_range` is set, the values within this range, otherwise at least the latest one for each key.
+    /// The storage manager takes care of the consolidation of the returned values.
+    /// If the storage is configured with a `strip_prefix`, the storage manager strips it from the keys
+    /// passed to this storage (in [`Storage::on_sample`] too) and adds it back to the returned keys.
+    async fn get(
+        &mut self,
+        key_expr: OwnedKeyExpr,
+        time_range: Option<TimeRange<SystemTime>>,
+    ) -> ZResult<Vec<Sample>> {
+        let _ = time_range;
+        bail!("Storage doesn't support get on {}", key_expr)
+    }
+
+    /// Function called by the storage manager to enforce the retention policy of this storage,
+    /// if supported according to [`Storage::capabilities`].
+    /// The entry for `key` (a value or a deletion) must be definitively removed if its timestamp is still `timestamp`.
+    /// By default, eviction isn't supported: the storage manager then refuses a retention policy
+    /// limiting the stored values, and leaves the removal of the deletions to the storage.
+    async fn evict(&mut self, key: OwnedKeyExpr, timestamp: Timestamp) -> ZResult<()> {
+        let _ = timestamp;
+        bail!("Storage doesn't support eviction of {}", key)
+    }
 }
 
 /// A wrapper around the [`zenoh::queryable::Query`] allowing to call the
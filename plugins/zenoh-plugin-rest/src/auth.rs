@@ -0,0 +1,212 @@
+//
+// Copyright (c) 2022 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+use async_std::fs;
+use base64::{engine::general_purpose::STANDARD as b64_std_engine, Engine};
+use sha3::{Digest, Sha3_256};
+use std::collections::HashMap;
+use std::sync::Arc;
+use subtle::ConstantTimeEq;
+use tide::{Middleware, Next, Request, Response, StatusCode};
+use zenoh::prelude::{keyexpr, OwnedKeyExpr};
+use zenoh_cfg_properties::Properties;
+use zenoh_result::{zerror, ZResult};
+use zenoh_transport::unicast::establishment::authenticator::Password;
+
+use crate::config::AuthConf;
+
+const REALM: &str = "zenoh";
+/// The operations of the storages in the admin space: triggered by queries, they modify the storages.
+const STORAGE_OPERATIONS: [&str; 4] = ["snapshot", "restore", "clear", "align"];
+
+/// Whether a key expression is the admin key of an operation of a storage.
+fn is_storage_operation(key_expr: &keyexpr) -> bool {
+    let chunks: Vec<&str> = key_expr.as_str().split('/').collect();
+    matches!(
+        chunks.as_slice(),
+        ["@", "router", _, "status", "plugins", _, "storages", .., operation]
+            if STORAGE_OPERATIONS.contains(operation)
+    )
+}
+
+/// Compares two secrets in a time independent of their content and length.
+fn secret_eq(a: &str, b: &str) -> bool {
+    Sha3_256::digest(a).ct_eq(&Sha3_256::digest(b)).into()
+}
+
+/// The key expressions a user is allowed to read and write.
+#[derive(Debug, Default)]
+pub(crate) struct Permissions {
+    read: Vec<OwnedKeyExpr>,
+    write: Vec<OwnedKeyExpr>,
+}
+
+/// The access granted to the user of a request, set by the [`Authenticator`] middleware.
+/// `None` means an unrestricted access.
+#[derive(Clone, Debug)]
+pub(crate) struct Access(Option<Arc<Permissions>>);
+
+impl Access {
+    pub(crate) fn can_read(&self, key_expr: &keyexpr) -> bool {
+        match &self.0 {
+            Some(p) => p.read.iter().any(|k| k.includes(key_expr)),
+            None => true,
+        }
+    }
+
+    pub(crate) fn can_write(&self, key_expr: &keyexpr) -> bool {
+        match &self.0 {
+            Some(p) => p.write.iter().any(|k| k.includes(key_expr)),
+            None => true,
+        }
+    }
+
+    /// Queries on the operations of the storages also require the write permission.
+    pub(crate) fn can_query(&self, key_expr: &keyexpr) -> bool {
+        self.can_read(key_expr) && (!is_storage_operation(key_expr) || self.can_write(key_expr))
+    }
+}
+
+/// A middleware authenticating the requests with HTTP Basic or bearer token authentication,
+/// using the same `<user>:<password>` dictionary format as the `usrpwd` transport authenticator,
+/// the passwords being either in plaintext or hashed as SCRAM stored keys.
+pub(crate) struct Authenticator {
+    passwords: HashMap<String, Password>,
+    tokens: HashMap<String, String>,
+    permissions: Option<HashMap<String, Arc<Permissions>>>,
+}
+
+async fn load_dictionary(path: &str) -> ZResult<HashMap<String, String>> {
+    let content = fs::read_to_string(path)
+        .await
+        .map_err(|e| zerror!("Invalid dictionary file {}: {}", path, e))?;
+    let mut ps = Properties::from(content);
+    Ok(ps.drain().collect())
+}
+
+/// Parses the permitted key expressions, normalised as the request paths are
+/// (`@/router/local` standing for `@/router/<zid>`).
+fn parse_key_exprs(user: &str, key_exprs: &[String], zid: &str) -> ZResult<Vec<OwnedKeyExpr>> {
+    key_exprs
+        .iter()
+        .map(|k| {
+            crate::path_to_key_expr(k, zid)
+                .map(OwnedKeyExpr::from)
+                .map_err(|e| zerror!("Invalid permission for user {}: {}", user, e).into())
+        })
+        .collect()
+}
+
+impl Authenticator {
+    pub(crate) async fn new(conf: &AuthConf, zid: &str) -> ZResult<Self> {
+        let passwords = match &conf.dictionary_file {
+            Some(path) => load_dictionary(path)
+                .await?
+                .into_iter()
+                .map(|(user, password)| {
+                    let password = password
+                        .parse()
+                        .map_err(|e| zerror!("Invalid password of user {}: {}", user, e))?;
+                    Ok((user, password))
+                })
+                .collect::<ZResult<_>>()?,
+            None => HashMap::new(),
+        };
+        // Tokens are looked up by value
+        let tokens = match &conf.tokens_file {
+            Some(path) => load_dictionary(path)
+                .await?
+                .into_iter()
+                .map(|(user, token)| (token, user))
+                .collect(),
+            None => HashMap::new(),
+        };
+        let permissions = match &conf.permissions {
+            Some(permissions) => {
+                let mut map = HashMap::new();
+                for (user, p) in permissions {
+                    let p = Permissions {
+                        read: parse_key_exprs(user, &p.read, zid)?,
+                        write: parse_key_exprs(user, &p.write, zid)?,
+                    };
+                    map.insert(user.clone(), Arc::new(p));
+                }
+                Some(map)
+            }
+            None => None,
+        };
+        Ok(Authenticator {
+            passwords,
+            tokens,
+            permissions,
+        })
+    }
+
+    /// Returns the user authenticated by the given `Authorization` header, if any.
+    fn authenticate(&self, authorization: &str) -> Option<String> {
+        let (scheme, credentials) = authorization.trim().split_once(' ')?;
+        let credentials = credentials.trim();
+        if scheme.eq_ignore_ascii_case("basic") {
+            let decoded = b64_std_engine.decode(credentials).ok()?;
+            let decoded = String::from_utf8(decoded).ok()?;
+            let (user, password) = decoded.split_once(':')?;
+            match self.passwords.get(user) {
+                Some(p) if p.verify(password.as_bytes()) => Some(user.to_string()),
+                _ => None,
+            }
+        } else if scheme.eq_ignore_ascii_case("bearer") {
+            let mut user = None;
+            for (token, u) in &self.tokens {
+                if secret_eq(token, credentials) {
+                    user = Some(u.clone());
+                }
+            }
+            user
+        } else {
+            None
+        }
+    }
+
+    fn access(&self, user: &str) -> Access {
+        match &self.permissions {
+            Some(permissions) => Access(Some(permissions.get(user).cloned().unwrap_or_default())),
+            None => Access(None),
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl<State: Clone + Send + Sync + 'static> Middleware<State> for Authenticator {
+    async fn handle(&self, mut req: Request<State>, next: Next<'_, State>) -> tide::Result {
+        let user = req
+            .header("authorization")
+            .and_then(|h| self.authenticate(h.last().as_str()));
+        match user {
+            Some(user) => {
+                log::trace!("REST request authenticated as {}", user);
+                req.set_ext(self.access(&user));
+                Ok(next.run(req).await)
+            }
+            None => {
+                log::debug!(
+                    "Unauthenticated REST request: {} {}",
+                    req.method(),
+                    req.url()
+                );
+                Ok(Response::builder(StatusCode::Unauthorized)
+                    .header("WWW-Authenticate", format!(r#"Basic realm="{REALM}""#))
+                    .build())
+            }
+        }
+    }
+}
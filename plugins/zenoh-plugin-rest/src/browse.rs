@@ -0,0 +1,235 @@
+//
+// Copyright (c) 2022 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+
+//! The HTML rendering of queries, for browsing the key space.
+//!
+//! Besides the replies, the page lists the storages, queryables and subscribers declared under the
+//! queried key expression, as reported by the admin space of the routers, with links to drill into keys.
+//! Only the admin space keys the user is allowed to read are taken into account.
+
+use futures::StreamExt;
+use std::collections::BTreeMap;
+use std::convert::TryFrom;
+use zenoh::prelude::r#async::*;
+use zenoh::query::{ConsolidationMode, Reply};
+use zenoh::Session;
+
+use crate::auth::Access;
+
+/// Escapes the HTML special characters of `s`.
+fn escape(s: &str) -> String {
+    let mut escaped = String::with_capacity(s.len());
+    for c in s.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#39;"),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/// A link to the page of the given key expression.
+fn link(key_expr: &str) -> String {
+    let key_expr = escape(key_expr);
+    format!("<a href=\"/{key_expr}\">{key_expr}</a>")
+}
+
+/// Links to the successive prefixes of the given key expression, and to their content.
+fn breadcrumbs(key_expr: &keyexpr) -> String {
+    let mut crumbs = vec!["<a href=\"/**\">**</a>".to_string()];
+    let mut prefix = String::new();
+    for chunk in key_expr.as_str().split('/') {
+        if !prefix.is_empty() {
+            prefix.push('/');
+        }
+        prefix.push_str(chunk);
+        let href = if chunk == "**" {
+            prefix.clone()
+        } else {
+            format!("{prefix}/**")
+        };
+        crumbs.push(format!(
+            "<a href=\"/{}\">{}</a>",
+            escape(&href),
+            escape(chunk)
+        ));
+    }
+    crumbs.join(" / ")
+}
+
+fn result_to_html(result: Result<Sample, Value>) -> String {
+    match result {
+        Ok(sample) => format!(
+            "<dt>{}</dt>\n<dd><pre>{}</pre></dd>\n",
+            link(sample.key_expr.as_str()),
+            escape(&String::from_utf8_lossy(&sample.payload.contiguous()))
+        ),
+        Err(err) => format!(
+            "<dt>ERROR</dt>\n<dd><pre>{}</pre></dd>\n",
+            escape(&String::from_utf8_lossy(&err.payload.contiguous()))
+        ),
+    }
+}
+
+/// The key expression covering everything under `key_expr`.
+fn scope(key_expr: &keyexpr) -> Option<OwnedKeyExpr> {
+    if key_expr.as_str().ends_with("**") {
+        Some(key_expr.into())
+    } else {
+        OwnedKeyExpr::try_from(format!("{key_expr}/**")).ok()
+    }
+}
+
+/// Queries the admin space, returning the replies the user can read as (key, JSON value) pairs.
+async fn admin_query(
+    session: &Session,
+    access: Option<&Access>,
+    selector: &str,
+) -> Vec<(String, serde_json::Value)> {
+    let replies = match session
+        .get(selector)
+        .consolidation(ConsolidationMode::None)
+        .res()
+        .await
+    {
+        Ok(replies) => replies,
+        Err(e) => {
+            log::debug!("Admin space query on {} failed: {}", selector, e);
+            return vec![];
+        }
+    };
+    let mut results = vec![];
+    while let Ok(reply) = replies.recv_async().await {
+        if let Ok(sample) = reply.sample {
+            if !access.map_or(true, |access| access.can_read(&sample.key_expr)) {
+                continue;
+            }
+            let value = serde_json::from_slice(&sample.payload.contiguous())
+                .unwrap_or(serde_json::Value::Null);
+            results.push((sample.key_expr.as_str().to_string(), value));
+        }
+    }
+    results
+}
+
+/// The declarations of the given kind under `scope`, with the routers that reported them.
+async fn declarations(
+    session: &Session,
+    access: Option<&Access>,
+    kind: &str,
+    scope: &keyexpr,
+) -> BTreeMap<String, Vec<String>> {
+    let mut declarations: BTreeMap<String, Vec<String>> = BTreeMap::new();
+    let selector = format!("@/router/*/{kind}/{scope}");
+    for (key, _) in admin_query(session, access, &selector).await {
+        // @/router/<zid>/<kind>/<key_expr>
+        let mut chunks = key.splitn(5, '/');
+        let (zid, declared) = match (chunks.nth(2), chunks.nth(1)) {
+            (Some(zid), Some(declared)) => (zid, declared),
+            _ => continue,
+        };
+        declarations
+            .entry(declared.to_string())
+            .or_default()
+            .push(zid.to_string());
+    }
+    declarations
+}
+
+/// The storages whose key expression intersects `scope`, with the routers hosting them.
+async fn storages(
+    session: &Session,
+    access: Option<&Access>,
+    scope: &keyexpr,
+) -> BTreeMap<String, Vec<String>> {
+    let mut storages: BTreeMap<String, Vec<String>> = BTreeMap::new();
+    let selector = "@/router/*/status/plugins/storage_manager/storages/*";
+    for (key, status) in admin_query(session, access, selector).await {
+        let key_expr = match status["key_expr"]
+            .as_str()
+            .and_then(|ke| keyexpr::new(ke).ok())
+        {
+            Some(key_expr) => key_expr,
+            None => continue,
+        };
+        if key_expr.intersects(scope) {
+            let zid = key.split('/').nth(2).unwrap_or_default();
+            let name = key.rsplit('/').next().unwrap_or_default();
+            storages
+                .entry(key_expr.to_string())
+                .or_default()
+                .push(format!("{name} on {zid}"));
+        }
+    }
+    storages
+}
+
+fn declarations_to_html(title: &str, declarations: &BTreeMap<String, Vec<String>>) -> String {
+    if declarations.is_empty() {
+        return format!("<h2>{title}</h2>\n<p>None</p>\n");
+    }
+    let items = declarations
+        .iter()
+        .map(|(key_expr, hosts)| {
+            format!(
+                "<li>{} <small>({})</small></li>\n",
+                link(key_expr),
+                escape(&hosts.join(", "))
+            )
+        })
+        .collect::<String>();
+    format!("<h2>{title}</h2>\n<ul>\n{items}</ul>\n")
+}
+
+/// Renders the replies to a query on `key_expr` as an HTML page, along with the storages,
+/// queryables and subscribers declared under `key_expr`.
+pub(crate) async fn to_html(
+    session: &Session,
+    access: Option<&Access>,
+    key_expr: &keyexpr,
+    results: flume::Receiver<Reply>,
+) -> String {
+    let values = results
+        .stream()
+        .map(|reply| result_to_html(reply.sample))
+        .collect::<Vec<String>>()
+        .await
+        .join("\n");
+    let mut page = format!(
+        "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n<title>{title}</title>\n</head>\n<body>\n\
+         <h1>{crumbs}</h1>\n<h2>Values</h2>\n<dl>\n{values}\n</dl>\n",
+        title = escape(key_expr.as_str()),
+        crumbs = breadcrumbs(key_expr),
+    );
+    if let Some(scope) = scope(key_expr) {
+        page.push_str(&declarations_to_html(
+            "Storages",
+            &storages(session, access, &scope).await,
+        ));
+        page.push_str(&declarations_to_html(
+            "Queryables",
+            &declarations(session, access, "queryable", &scope).await,
+        ));
+        page.push_str(&declarations_to_html(
+            "Subscribers",
+            &declarations(session, access, "subscriber", &scope).await,
+        ));
+    }
+    page.push_str("</body>\n</html>\n");
+    page
+}
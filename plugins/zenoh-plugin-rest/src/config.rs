@@ -13,19 +13,103 @@
 //
 use serde::de::{Unexpected, Visitor};
 use serde::{de, Deserialize, Deserializer};
+use std::collections::HashMap;
 use std::fmt;
+use zenoh_result::{bail, ZResult};
 
 const DEFAULT_HTTP_INTERFACE: &str = "[::]";
+const DEFAULT_CORS_ORIGIN: &str = "*";
+const DEFAULT_HTTP_QUERYABLE_TIMEOUT_MS: u64 = 10000;
 
 #[derive(Deserialize, serde::Serialize, Clone, Debug)]
 #[serde(deny_unknown_fields)]
 pub struct Config {
     #[serde(deserialize_with = "deserialize_http_port")]
     pub http_port: String,
+    #[serde(default)]
+    pub encodings: Vec<EncodingConf>,
+    pub tls: Option<TlsConf>,
+    pub auth: Option<AuthConf>,
+    #[serde(default = "default_cors_origins")]
+    pub cors_origins: Vec<String>,
+    #[serde(default)]
+    pub http_queryables: Vec<HttpQueryableConf>,
     __path__: Option<String>,
     __required__: Option<bool>,
 }
 
+/// A custom encoding to register in the [`zenoh::encoding::registry`].
+#[derive(Deserialize, serde::Serialize, Clone, Debug)]
+#[serde(deny_unknown_fields)]
+pub struct EncodingConf {
+    pub id: u64,
+    pub mime: String,
+    pub schema: Option<String>,
+}
+
+/// The TLS configuration of the HTTP server.
+#[derive(Deserialize, serde::Serialize, Clone, Debug)]
+#[serde(deny_unknown_fields)]
+pub struct TlsConf {
+    pub server_certificate: String,
+    pub server_private_key: String,
+}
+
+/// The authentication configuration of the HTTP server.
+#[derive(Deserialize, serde::Serialize, Clone, Debug)]
+#[serde(deny_unknown_fields)]
+pub struct AuthConf {
+    /// The path to a `<user>:<password>` dictionary file, for HTTP Basic authentication.
+    pub dictionary_file: Option<String>,
+    /// The path to a `<user>:<token>` dictionary file, for bearer token authentication.
+    pub tokens_file: Option<String>,
+    /// The key expressions each user is allowed to read and write.
+    /// If absent, all the authenticated users can read and write any key.
+    pub permissions: Option<HashMap<String, PermissionsConf>>,
+}
+
+#[derive(Deserialize, serde::Serialize, Clone, Debug, Default)]
+#[serde(deny_unknown_fields)]
+pub struct PermissionsConf {
+    #[serde(default)]
+    pub read: Vec<String>,
+    #[serde(default)]
+    pub write: Vec<String>,
+}
+
+/// A queryable forwarding the zenoh queries on `key_expr` as HTTP requests to `url`.
+#[derive(Deserialize, serde::Serialize, Clone, Debug)]
+#[serde(deny_unknown_fields)]
+pub struct HttpQueryableConf {
+    pub key_expr: String,
+    pub url: String,
+    /// The timeout of the HTTP requests, in milliseconds.
+    #[serde(default = "default_http_queryable_timeout")]
+    pub timeout: u64,
+}
+
+fn default_http_queryable_timeout() -> u64 {
+    DEFAULT_HTTP_QUERYABLE_TIMEOUT_MS
+}
+
+fn default_cors_origins() -> Vec<String> {
+    vec![DEFAULT_CORS_ORIGIN.to_string()]
+}
+
+impl Config {
+    /// The authenticated requests carry credentials, which can't be shared with any origin:
+    /// with `auth`, the allowed origins must be listed explicitly.
+    pub(crate) fn validate_cors(&self) -> ZResult<()> {
+        if self.auth.is_some() && self.cors_origins.iter().any(|o| o == DEFAULT_CORS_ORIGIN) {
+            bail!(
+                "`cors_origins` can't contain `{}` when `auth` is configured: list the allowed origins explicitly",
+                DEFAULT_CORS_ORIGIN
+            );
+        }
+        Ok(())
+    }
+}
+
 impl From<&Config> for serde_json::Value {
     fn from(c: &Config) -> Self {
         serde_json::to_value(c).unwrap()
@@ -0,0 +1,250 @@
+//
+// Copyright (c) 2022 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+
+//! Queryables forwarding zenoh queries as HTTP requests, exposing REST services into the zenoh key space.
+//!
+//! A queryable declared on `api/weather/**` and forwarding to `http://localhost:9000/weather/` answers
+//! a query on `api/weather/paris/today?units=metric` with the response to an HTTP request on
+//! `http://localhost:9000/weather/paris/today?units=metric`: a GET, or a POST if the query carries a value
+//! (sent as body, with its encoding as content type). The selector parameters are appended to the query
+//! string of the configured URL, if any. The response body is replied with the encoding corresponding to
+//! its content type.
+//!
+//! Since replying errors isn't supported yet, the body of a response with an error status is replied
+//! as any other, the status being logged. Queries on key expressions containing wildcards can't be
+//! mapped to a URL, and are ignored. So are the queries on key expressions containing `.` or `..`
+//! chunks, the other chunks being percent-encoded as path segments, and the failed and timed out
+//! requests.
+
+use async_rustls::rustls::{ClientConfig, OwnedTrustAnchor, RootCertStore, ServerName};
+use async_rustls::TlsConnector;
+use async_std::future::timeout;
+use async_std::net::TcpStream;
+use async_std::task;
+use http_types::{Method, Mime, Url};
+use std::convert::TryFrom;
+use std::str::FromStr;
+use std::sync::Arc;
+use std::time::Duration;
+use zenoh::encoding;
+use zenoh::prelude::r#async::*;
+use zenoh::queryable::{Query, Queryable};
+use zenoh::Session;
+use zenoh_result::{bail, zerror, ZResult};
+
+use crate::config::HttpQueryableConf;
+
+struct Forwarder {
+    /// The key expression of the queryable, without its trailing `/**` if any.
+    prefix: OwnedKeyExpr,
+    /// Whether the queryable is declared on `prefix/**`.
+    is_prefix: bool,
+    url: Url,
+    timeout: Duration,
+    tls: TlsConnector,
+}
+
+fn tls_connector() -> TlsConnector {
+    let mut root_cert_store = RootCertStore::empty();
+    root_cert_store.add_server_trust_anchors(webpki_roots::TLS_SERVER_ROOTS.0.iter().map(|ta| {
+        OwnedTrustAnchor::from_subject_spki_name_constraints(
+            ta.subject,
+            ta.spki,
+            ta.name_constraints,
+        )
+    }));
+    let config = ClientConfig::builder()
+        .with_safe_defaults()
+        .with_root_certificates(root_cert_store)
+        .with_no_client_auth();
+    TlsConnector::from(Arc::new(config))
+}
+
+impl Forwarder {
+    fn new(conf: &HttpQueryableConf) -> ZResult<Self> {
+        let (prefix, is_prefix) = match conf.key_expr.strip_suffix("/**") {
+            Some(prefix) => (prefix, true),
+            None => (conf.key_expr.as_str(), false),
+        };
+        let prefix = OwnedKeyExpr::try_from(prefix)?;
+        if prefix.is_wild() {
+            bail!(
+                "HTTP queryable key expression can only contain a trailing `/**` wildcard: {}",
+                conf.key_expr
+            );
+        }
+        let url = Url::parse(&conf.url)
+            .map_err(|e| zerror!("Invalid HTTP queryable URL {}: {}", conf.url, e))?;
+        if url.scheme() != "http" && url.scheme() != "https" {
+            bail!("Unsupported HTTP queryable URL scheme: {}", conf.url);
+        }
+        Ok(Forwarder {
+            prefix,
+            is_prefix,
+            url,
+            timeout: Duration::from_millis(conf.timeout),
+            tls: tls_connector(),
+        })
+    }
+
+    /// The URL corresponding to the given key expression and selector parameters, if any.
+    fn url(&self, key_expr: &keyexpr, parameters: &str) -> Option<Url> {
+        if key_expr.is_wild() {
+            return None;
+        }
+        let suffix = if key_expr == &*self.prefix {
+            ""
+        } else if self.is_prefix {
+            key_expr
+                .as_str()
+                .strip_prefix(self.prefix.as_str())?
+                .strip_prefix('/')?
+        } else {
+            return None;
+        };
+        let mut url = self.url.clone();
+        if !suffix.is_empty() {
+            // Dot segments would let the key expression escape the path of the configured URL.
+            if suffix.split('/').any(|chunk| chunk == "." || chunk == "..") {
+                return None;
+            }
+            url.path_segments_mut()
+                .ok()?
+                .pop_if_empty()
+                .extend(suffix.split('/'));
+        }
+        if !parameters.is_empty() {
+            let query = match self.url.query() {
+                Some(query) if !query.is_empty() => format!("{query}&{parameters}"),
+                _ => parameters.to_string(),
+            };
+            url.set_query(Some(&query));
+        }
+        Some(url)
+    }
+
+    async fn request(&self, url: Url, value: Option<&Value>) -> ZResult<http_types::Response> {
+        let mut req = match value {
+            Some(value) => {
+                let mut req = http_types::Request::new(Method::Post, url.clone());
+                req.set_body(value.payload.contiguous().to_vec());
+                let content_type = encoding::registry().content_type(&value.encoding);
+                if let Ok(mime) = Mime::from_str(&content_type) {
+                    req.set_content_type(mime);
+                }
+                req
+            }
+            None => http_types::Request::new(Method::Get, url.clone()),
+        };
+        req.insert_header("connection", "close");
+        let host = url
+            .host_str()
+            .ok_or_else(|| zerror!("Missing host in {}", url))?;
+        let port = url
+            .port_or_known_default()
+            .ok_or_else(|| zerror!("Missing port in {}", url))?;
+        let stream = TcpStream::connect((host, port))
+            .await
+            .map_err(|e| zerror!("Unable to connect to {}: {}", url, e))?;
+        let res = if url.scheme() == "https" {
+            let server_name = ServerName::try_from(host).map_err(|e| zerror!(e))?;
+            let stream = self
+                .tls
+                .connect(server_name, stream)
+                .await
+                .map_err(|e| zerror!("TLS handshake with {} failed: {}", url, e))?;
+            async_h1::connect(stream, req).await
+        } else {
+            async_h1::connect(stream, req).await
+        };
+        res.map_err(|e| zerror!("HTTP request to {} failed: {}", url, e).into())
+    }
+
+    async fn forward(&self, query: Query) {
+        let url = match self.url(query.key_expr(), query.parameters()) {
+            Some(url) => url,
+            None => {
+                log::debug!(
+                    "Can't forward query on {} as an HTTP request",
+                    query.selector()
+                );
+                return;
+            }
+        };
+        log::trace!("Forwarding query on {} to {}", query.selector(), url);
+        let response = async {
+            let mut res = self.request(url.clone(), query.value()).await?;
+            let body = res
+                .body_bytes()
+                .await
+                .map_err(|e| zerror!("Error reading HTTP response from {}: {}", url, e))?;
+            ZResult::Ok((res, body))
+        };
+        let (res, body) = match timeout(self.timeout, response).await {
+            Ok(Ok(response)) => response,
+            Ok(Err(e)) => {
+                log::warn!("{}", e);
+                return;
+            }
+            Err(_) => {
+                log::warn!("HTTP request to {} timed out", url);
+                return;
+            }
+        };
+        if !res.status().is_success() {
+            log::warn!(
+                "HTTP request to {} failed with status {}",
+                url,
+                res.status()
+            );
+        }
+        let encoding = res
+            .content_type()
+            .map(|mime| encoding::registry().from_mime(mime.essence()))
+            .unwrap_or_default();
+        let sample = Sample::new(
+            query.key_expr().clone(),
+            Value::from(body).encoding(encoding),
+        );
+        if let Err(e) = query.reply(Ok(sample)).res().await {
+            log::warn!("Error replying to query on {}: {}", query.selector(), e);
+        }
+    }
+}
+
+/// Checks the configuration of an HTTP queryable.
+pub(crate) fn validate(conf: &HttpQueryableConf) -> ZResult<()> {
+    Forwarder::new(conf).map(|_| ())
+}
+
+/// Declares a queryable forwarding the queries as HTTP requests, as configured.
+pub(crate) async fn declare(
+    session: &Arc<Session>,
+    conf: &HttpQueryableConf,
+) -> ZResult<Queryable<'static, ()>> {
+    let forwarder = Arc::new(Forwarder::new(conf)?);
+    log::debug!(
+        "Forwarding queries on {} to {}",
+        conf.key_expr,
+        forwarder.url
+    );
+    session
+        .declare_queryable(conf.key_expr.clone())
+        .callback(move |query| {
+            let forwarder = forwarder.clone();
+            task::spawn(async move { forwarder.forward(query).await });
+        })
+        .res()
+        .await
+}
@@ -12,27 +12,50 @@
 //   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
 //
 
+use async_rustls::rustls::{Certificate, PrivateKey, ServerConfig};
+use async_rustls::TlsAcceptor;
+use async_std::net::TcpListener;
 use async_std::prelude::FutureExt;
 use base64::{engine::general_purpose::STANDARD as b64_std_engine, Engine};
-use futures::StreamExt;
+use futures::future::Either;
+use futures::{stream, StreamExt, TryStreamExt};
 use http_types::Method;
 use std::convert::TryFrom;
+use std::io::Cursor;
 use std::str::FromStr;
 use std::sync::Arc;
+use std::time::Duration;
 use tide::http::Mime;
 use tide::sse::Sender;
-use tide::{Request, Response, Server, StatusCode};
+use tide::{Body, Request, Response, Server, StatusCode};
+use zenoh::encoding::{self, CustomEncoding};
 use zenoh::plugins::{Plugin, RunningPluginTrait, ZenohPlugin};
 use zenoh::prelude::r#async::*;
-use zenoh::query::{QueryConsolidation, Reply};
+use zenoh::query::{ConsolidationMode, QueryConsolidation, QueryTarget, Reply};
 use zenoh::runtime::Runtime;
 use zenoh::selector::TIME_RANGE_KEY;
 use zenoh::Session;
 use zenoh_cfg_properties::Properties;
 use zenoh_result::{bail, zerror, ZResult};
 
+mod auth;
+use auth::{Access, Authenticator};
+mod browse;
 mod config;
 pub use config::Config;
+use config::TlsConf;
+mod http_queryable;
+mod websocket;
+
+/// The [`QueryTarget`] of a query: `BEST_MATCHING` (default), `ALL` or `ALL_COMPLETE`.
+const QUERY_TARGET_HEADER: &str = "X-Zenoh-Query-Target";
+/// The [`ConsolidationMode`] of a query: `AUTO`, `NONE`, `MONOTONIC` or `LATEST`.
+/// Defaults to `NONE` for time range queries, and to `LATEST` otherwise.
+const CONSOLIDATION_HEADER: &str = "X-Zenoh-Consolidation";
+/// The timeout of a query, in milliseconds.
+const TIMEOUT_HEADER: &str = "X-Zenoh-Timeout";
+/// The OpenAPI description of the REST API, served on `/@openapi`.
+const OPENAPI: &str = include_str!("openapi.json");
 
 const GIT_VERSION: &str = git_version::git_version!(prefix = "v", cargo_prefix = "v");
 lazy_static::lazy_static! {
@@ -40,6 +63,21 @@ lazy_static::lazy_static! {
 }
 
 fn value_to_json(value: Value) -> String {
+    if let Some(custom) = encoding::registry().resolve(&value.encoding) {
+        let payload = value.payload.contiguous();
+        if custom.mime().ends_with("json") {
+            if let Ok(json) = serde_json::from_slice::<serde_json::Value>(&payload) {
+                return json.to_string();
+            }
+        }
+        return match std::str::from_utf8(&payload) {
+            // not valid JSON despite its encoding: fall back to a string if possible
+            Ok(text) if custom.mime().ends_with("json") || custom.mime().starts_with("text/") => {
+                serde_json::json!(text).to_string()
+            }
+            _ => format!(r#""{}""#, b64_std_engine.encode(&payload)),
+        };
+    }
     // @TODO: transcode to JSON when implemented in Value
     match &value.encoding {
         p if p.starts_with(KnownEncoding::TextPlain) => {
@@ -64,7 +102,7 @@ fn value_to_json(value: Value) -> String {
 }
 
 fn sample_to_json(sample: Sample) -> String {
-    let encoding = sample.value.encoding.to_string();
+    let encoding = encoding::registry().content_type(&sample.value.encoding);
     format!(
         r#"{{ "key": "{}", "value": {}, "encoding": "{}", "time": "{}" }}"#,
         sample.key_expr.as_str(),
@@ -82,7 +120,7 @@ fn result_to_json(sample: Result<Sample, Value>) -> String {
     match sample {
         Ok(sample) => sample_to_json(sample),
         Err(err) => {
-            let encoding = err.encoding.to_string();
+            let encoding = encoding::registry().content_type(&err.encoding);
             format!(
                 r#"{{ "key": "ERROR", "value": {}, "encoding": "{}"}}"#,
                 value_to_json(err),
@@ -92,44 +130,31 @@ fn result_to_json(sample: Result<Sample, Value>) -> String {
     }
 }
 
-async fn to_json(results: flume::Receiver<Reply>) -> String {
-    let values = results
-        .stream()
-        .filter_map(move |reply| async move { Some(result_to_json(reply.sample)) })
-        .collect::<Vec<String>>()
-        .await
-        .join(",\n");
-    format!("[\n{values}\n]\n")
-}
-
-fn sample_to_html(sample: Sample) -> String {
-    format!(
-        "<dt>{}</dt>\n<dd>{}</dd>\n",
-        sample.key_expr.as_str(),
-        String::from_utf8_lossy(&sample.payload.contiguous())
-    )
-}
-
-fn result_to_html(sample: Result<Sample, Value>) -> String {
-    match sample {
-        Ok(sample) => sample_to_html(sample),
-        Err(err) => {
-            format!(
-                "<dt>ERROR</dt>\n<dd>{}</dd>\n",
-                String::from_utf8_lossy(&err.payload.contiguous())
-            )
-        }
-    }
-}
-
-async fn to_html(results: flume::Receiver<Reply>) -> String {
-    let values = results
-        .stream()
-        .filter_map(move |reply| async move { Some(result_to_html(reply.sample)) })
-        .collect::<Vec<String>>()
-        .await
-        .join("\n");
-    format!("<dl>\n{values}\n</dl>\n")
+/// Streams the replies as they are received, either as a JSON array or as JSON lines.
+fn to_json_body(results: flume::Receiver<Reply>, json_lines: bool) -> Body {
+    let mut first = true;
+    let values = results.into_stream().map(move |reply| {
+        let value = result_to_json(reply.sample);
+        let chunk = if json_lines {
+            format!("{value}\n")
+        } else if first {
+            first = false;
+            format!("\n{value}")
+        } else {
+            format!(",\n{value}")
+        };
+        Ok(chunk.into_bytes())
+    });
+    let chunks = if json_lines {
+        Either::Left(values)
+    } else {
+        Either::Right(
+            stream::iter(Some(Ok(b"[".to_vec())))
+                .chain(values)
+                .chain(stream::iter(Some(Ok(b"\n]\n".to_vec())))),
+        )
+    };
+    Body::from_reader(chunks.into_async_read(), None)
 }
 
 fn method_to_kind(method: Method) -> SampleKind {
@@ -143,12 +168,94 @@ fn method_to_kind(method: Method) -> SampleKind {
 fn response(status: StatusCode, content_type: Mime, body: &str) -> Response {
     Response::builder(status)
         .header("content-length", body.len().to_string())
-        .header("Access-Control-Allow-Origin", "*")
         .content_type(content_type)
         .body(body)
         .build()
 }
 
+fn forbidden(key_expr: &keyexpr) -> Response {
+    response(
+        StatusCode::Forbidden,
+        Mime::from_str("text/plain").unwrap(),
+        &format!("Access to {key_expr} is forbidden"),
+    )
+}
+
+fn can_read<State>(req: &Request<State>, key_expr: &keyexpr) -> bool {
+    req.ext::<Access>()
+        .map(|access| access.can_read(key_expr))
+        .unwrap_or(true)
+}
+
+fn can_write<State>(req: &Request<State>, key_expr: &keyexpr) -> bool {
+    req.ext::<Access>()
+        .map(|access| access.can_write(key_expr))
+        .unwrap_or(true)
+}
+
+fn can_query<State>(req: &Request<State>, key_expr: &keyexpr) -> bool {
+    req.ext::<Access>()
+        .map(|access| access.can_query(key_expr))
+        .unwrap_or(true)
+}
+
+fn header<State>(req: &Request<State>, name: &str) -> Option<String> {
+    req.header(name)
+        .map(|h| h.last().as_str().trim().to_string())
+}
+
+pub(crate) fn parse_query_target(target: &str) -> ZResult<QueryTarget> {
+    match target.to_ascii_uppercase().as_str() {
+        "BEST_MATCHING" => Ok(QueryTarget::BestMatching),
+        "ALL" => Ok(QueryTarget::All),
+        "ALL_COMPLETE" => Ok(QueryTarget::AllComplete),
+        _ => bail!(
+            "Invalid query target: {} (expected BEST_MATCHING, ALL or ALL_COMPLETE)",
+            target
+        ),
+    }
+}
+
+pub(crate) fn parse_consolidation(consolidation: &str) -> ZResult<QueryConsolidation> {
+    match consolidation.to_ascii_uppercase().as_str() {
+        "AUTO" => Ok(QueryConsolidation::AUTO),
+        "NONE" => Ok(ConsolidationMode::None.into()),
+        "MONOTONIC" => Ok(ConsolidationMode::Monotonic.into()),
+        "LATEST" => Ok(ConsolidationMode::Latest.into()),
+        _ => bail!(
+            "Invalid consolidation: {} (expected AUTO, NONE, MONOTONIC or LATEST)",
+            consolidation
+        ),
+    }
+}
+
+pub(crate) fn parse_timeout(timeout: &str) -> ZResult<Duration> {
+    match timeout.parse::<u64>() {
+        Ok(ms) => Ok(Duration::from_millis(ms)),
+        Err(e) => bail!(
+            "Invalid timeout: {} (expected milliseconds): {}",
+            timeout,
+            e
+        ),
+    }
+}
+
+/// The value of a query: the body of a POST, or of a GET with a content type.
+async fn query_value(req: &mut Request<(Arc<Session>, String)>) -> tide::Result<Option<Value>> {
+    if req.method() != Method::Post && req.content_type().is_none() {
+        return Ok(None);
+    }
+    let encoding: Encoding = req
+        .content_type()
+        .map(|m| encoding::registry().from_mime(m.essence()))
+        .unwrap_or_default();
+    let bytes = req.body_bytes().await?;
+    if bytes.is_empty() && req.method() != Method::Post {
+        return Ok(None);
+    }
+    Ok(Some(Value::from(bytes).encoding(encoding)))
+}
+
 zenoh_plugin_trait::declare_plugin!(RestPlugin);
 pub struct RestPlugin {}
 #[derive(Clone, Copy, Debug)]
@@ -193,6 +300,27 @@ impl Plugin for RestPlugin {
 
         let conf: Config = serde_json::from_value(plugin_conf.clone())
             .map_err(|e| zerror!("Plugin `{}` configuration error: {}", name, e))?;
+        for e in &conf.encodings {
+            let mut custom = CustomEncoding::new(e.id, e.mime.as_str());
+            if let Some(schema) = &e.schema {
+                custom = custom.schema(OwnedKeyExpr::try_from(schema.as_str())?);
+            }
+            encoding::registry()
+                .register(custom)
+                .map_err(|e| zerror!("Plugin `{}` configuration error: {}", name, e))?;
+        }
+        conf.validate_cors()
+            .map_err(|e| zerror!("Plugin `{}` configuration error: {}", name, e))?;
+        for q in &conf.http_queryables {
+            http_queryable::validate(q).map_err(|e| {
+                zerror!(
+                    "Plugin `{}` configuration error: HTTP queryable on {}: {}",
+                    name,
+                    q.key_expr,
+                    e
+                )
+            })?;
+        }
         async_std::task::spawn(run(runtime.clone(), conf.clone()));
         Ok(Box::new(RunningPlugin(conf)))
     }
@@ -253,8 +381,8 @@ fn with_extended_string<R, F: FnMut(&mut String) -> R>(
     result
 }
 
-async fn query(req: Request<(Arc<Session>, String)>) -> tide::Result<Response> {
-    log::trace!("Incoming GET request: {:?}", req);
+async fn query(mut req: Request<(Arc<Session>, String)>) -> tide::Result<Response> {
+    log::trace!("Incoming {} request: {:?}", req.method(), req);
 
     let first_accept = match req.header("accept") {
         Some(accept) => accept[0]
@@ -268,112 +396,150 @@ async fn query(req: Request<(Arc<Session>, String)>) -> tide::Result<Response> {
             .to_string(),
         None => "application/json".to_string(),
     };
+    let key_expr = match path_to_key_expr(req.url().path(), &req.state().1) {
+        Ok(ke) => ke.into_owned(),
+        Err(e) => {
+            return Ok(response(
+                StatusCode::BadRequest,
+                Mime::from_str("text/plain").unwrap(),
+                &e.to_string(),
+            ))
+        }
+    };
+    let allowed = if first_accept == "text/event-stream" {
+        can_read(&req, &key_expr)
+    } else {
+        can_query(&req, &key_expr)
+    };
+    if !allowed {
+        return Ok(forbidden(&key_expr));
+    }
     if first_accept == "text/event-stream" {
         Ok(tide::sse::upgrade(
             req,
-            move |req: Request<(Arc<Session>, String)>, sender: Sender| async move {
-                let key_expr = match path_to_key_expr(req.url().path(), &req.state().1) {
-                    Ok(ke) => ke.into_owned(),
-                    Err(e) => {
-                        return Err(tide::Error::new(
-                            tide::StatusCode::BadRequest,
-                            anyhow::anyhow!("{}", e),
-                        ))
-                    }
-                };
-                async_std::task::spawn(async move {
-                    log::debug!(
-                        "Subscribe to {} for SSE stream (task {})",
-                        key_expr,
-                        async_std::task::current().id()
-                    );
-                    let sender = &sender;
-                    let sub = req
-                        .state()
-                        .0
-                        .declare_subscriber(&key_expr)
-                        .res()
-                        .await
-                        .unwrap();
-                    loop {
-                        let sample = sub.recv_async().await.unwrap();
-                        match sender
-                            .send(&sample.kind.to_string(), sample_to_json(sample), None)
-                            .timeout(std::time::Duration::new(10, 0))
+            move |req: Request<(Arc<Session>, String)>, sender: Sender| {
+                let key_expr = key_expr.clone();
+                async move {
+                    async_std::task::spawn(async move {
+                        log::debug!(
+                            "Subscribe to {} for SSE stream (task {})",
+                            key_expr,
+                            async_std::task::current().id()
+                        );
+                        let sender = &sender;
+                        let sub = req
+                            .state()
+                            .0
+                            .declare_subscriber(&key_expr)
+                            .res()
                             .await
-                        {
-                            Ok(Ok(_)) => {}
-                            Ok(Err(e)) => {
-                                log::debug!(
-                                    "SSE error ({})! Unsubscribe and terminate (task {})",
-                                    e,
-                                    async_std::task::current().id()
-                                );
-                                if let Err(e) = sub.undeclare().res().await {
-                                    log::error!("Error undeclaring subscriber: {}", e);
+                            .unwrap();
+                        loop {
+                            let sample = sub.recv_async().await.unwrap();
+                            match sender
+                                .send(&sample.kind.to_string(), sample_to_json(sample), None)
+                                .timeout(std::time::Duration::new(10, 0))
+                                .await
+                            {
+                                Ok(Ok(_)) => {}
+                                Ok(Err(e)) => {
+                                    log::debug!(
+                                        "SSE error ({})! Unsubscribe and terminate (task {})",
+                                        e,
+                                        async_std::task::current().id()
+                                    );
+                                    if let Err(e) = sub.undeclare().res().await {
+                                        log::error!("Error undeclaring subscriber: {}", e);
+                                    }
+                                    break;
                                 }
-                                break;
-                            }
-                            Err(_) => {
-                                log::debug!(
-                                    "SSE timeout! Unsubscribe and terminate (task {})",
-                                    async_std::task::current().id()
-                                );
-                                if let Err(e) = sub.undeclare().res().await {
-                                    log::error!("Error undeclaring subscriber: {}", e);
+                                Err(_) => {
+                                    log::debug!(
+                                        "SSE timeout! Unsubscribe and terminate (task {})",
+                                        async_std::task::current().id()
+                                    );
+                                    if let Err(e) = sub.undeclare().res().await {
+                                        log::error!("Error undeclaring subscriber: {}", e);
+                                    }
+                                    break;
                                 }
-                                break;
                             }
                         }
-                    }
-                });
-                Ok(())
+                    });
+                    Ok(())
+                }
             },
         ))
     } else {
-        let url = req.url();
-        let key_expr = match path_to_key_expr(url.path(), &req.state().1) {
-            Ok(ke) => ke,
-            Err(e) => {
-                return Ok(response(
-                    StatusCode::BadRequest,
-                    Mime::from_str("text/plain").unwrap(),
-                    &e.to_string(),
-                ))
-            }
+        let bad_request = |e: &dyn std::fmt::Display| {
+            Ok(response(
+                StatusCode::BadRequest,
+                Mime::from_str("text/plain").unwrap(),
+                &e.to_string(),
+            ))
+        };
+        let value = match query_value(&mut req).await {
+            Ok(value) => value,
+            Err(e) => return bad_request(&e),
+        };
+        let target = match header(&req, QUERY_TARGET_HEADER).map(|t| parse_query_target(&t)) {
+            Some(Ok(target)) => target,
+            Some(Err(e)) => return bad_request(&e),
+            None => QueryTarget::default(),
+        };
+        let timeout = match header(&req, TIMEOUT_HEADER).map(|t| parse_timeout(&t)) {
+            Some(Ok(timeout)) => Some(timeout),
+            Some(Err(e)) => return bad_request(&e),
+            None => None,
         };
+        let url = req.url();
         let query_part = url.query();
         let selector = if let Some(q) = query_part {
-            Selector::from(key_expr).with_parameters(q)
-        } else {
-            key_expr.into()
-        };
-        let consolidation = if selector.decode().any(|(k, _)| k.as_ref() == TIME_RANGE_KEY) {
-            QueryConsolidation::from(zenoh::query::ConsolidationMode::None)
+            Selector::from(key_expr.clone()).with_parameters(q)
         } else {
-            QueryConsolidation::from(zenoh::query::ConsolidationMode::Latest)
+            key_expr.clone().into()
         };
-        match req
+        let consolidation =
+            match header(&req, CONSOLIDATION_HEADER).map(|c| parse_consolidation(&c)) {
+                Some(Ok(consolidation)) => consolidation,
+                Some(Err(e)) => return bad_request(&e),
+                None if selector.decode().any(|(k, _)| k.as_ref() == TIME_RANGE_KEY) => {
+                    QueryConsolidation::from(ConsolidationMode::None)
+                }
+                None => QueryConsolidation::from(ConsolidationMode::Latest),
+            };
+        let mut query = req
             .state()
             .0
             .get(&selector)
-            .consolidation(consolidation)
-            .res()
-            .await
-        {
+            .target(target)
+            .consolidation(consolidation);
+        if let Some(timeout) = timeout {
+            query = query.timeout(timeout);
+        }
+        if let Some(value) = value {
+            query = query.with_value(value);
+        }
+        match query.res().await {
             Ok(receiver) => {
                 if first_accept == "text/html" {
                     Ok(response(
                         StatusCode::Ok,
                         Mime::from_str("text/html").unwrap(),
-                        &to_html(receiver).await,
+                        &browse::to_html(&req.state().0, req.ext::<Access>(), &key_expr, receiver)
+                            .await,
                     ))
                 } else {
-                    Ok(response(
-                        StatusCode::Ok,
-                        Mime::from_str("application/json").unwrap(),
-                        &to_json(receiver).await,
-                    ))
+                    let json_lines = first_accept == "application/x-ndjson";
+                    let content_type = if json_lines {
+                        "application/x-ndjson"
+                    } else {
+                        "application/json"
+                    };
+                    Ok(Response::builder(StatusCode::Ok)
+                        .content_type(Mime::from_str(content_type).unwrap())
+                        .body(to_json_body(receiver, json_lines))
+                        .build())
                 }
             }
             Err(e) => Ok(response(
@@ -385,6 +551,14 @@ async fn query(req: Request<(Arc<Session>, String)>) -> tide::Result<Response> {
     }
 }
 
+async fn openapi(_req: Request<(Arc<Session>, String)>) -> tide::Result<Response> {
+    Ok(response(
+        StatusCode::Ok,
+        Mime::from_str("application/json").unwrap(),
+        OPENAPI,
+    ))
+}
+
 async fn write(mut req: Request<(Arc<Session>, String)>) -> tide::Result<Response> {
     log::trace!("Incoming PUT request: {:?}", req);
     match req.body_bytes().await {
@@ -399,9 +573,12 @@ async fn write(mut req: Request<(Arc<Session>, String)>) -> tide::Result<Respons
                     ))
                 }
             };
+            if !can_write(&req, &key_expr) {
+                return Ok(forbidden(&key_expr));
+            }
             let encoding: Encoding = req
                 .content_type()
-                .map(|m| m.essence().to_owned().into())
+                .map(|m| encoding::registry().from_mime(m.essence()))
                 .unwrap_or_default();
 
             // @TODO: Define the right congestion control value
@@ -436,30 +613,164 @@ pub async fn run(runtime: Runtime, conf: Config) {
     // But cannot be done twice in case of static link.
     let _ = env_logger::try_init();
 
+    if let Err(e) = conf.validate_cors() {
+        log::error!("Unable to configure REST CORS: {}", e);
+        return;
+    }
+
     let zid = runtime.zid.to_string();
     let session = zenoh::init(runtime).res().await.unwrap();
 
-    let mut app = Server::with_state((Arc::new(session), zid));
+    let session = Arc::new(session);
+
+    let mut http_queryables = Vec::with_capacity(conf.http_queryables.len());
+    for http_queryable in &conf.http_queryables {
+        match http_queryable::declare(&session, http_queryable).await {
+            Ok(queryable) => http_queryables.push(queryable),
+            Err(e) => {
+                log::error!(
+                    "Unable to declare HTTP queryable on {}: {}",
+                    http_queryable.key_expr,
+                    e
+                );
+                return;
+            }
+        }
+    }
+
+    let authenticator = match &conf.auth {
+        Some(auth) => match Authenticator::new(auth, &zid).await {
+            Ok(authenticator) => Some(authenticator),
+            Err(e) => {
+                log::error!("Unable to configure REST authentication: {}", e);
+                return;
+            }
+        },
+        None => None,
+    };
+
+    let mut app = Server::with_state((session, zid));
+    // The CORS middleware comes first, so that preflight requests (which carry no credentials)
+    // are answered without authentication and that rejections still carry the CORS headers.
     app.with(
         tide::security::CorsMiddleware::new()
             .allow_methods(
-                "GET, PUT, PATCH, DELETE"
+                "GET, POST, PUT, PATCH, DELETE"
                     .parse::<http_types::headers::HeaderValue>()
                     .unwrap(),
             )
-            .allow_origin(tide::security::Origin::from("*"))
-            .allow_credentials(false),
+            .allow_origin(tide::security::Origin::from(conf.cors_origins.clone()))
+            .allow_credentials(conf.auth.is_some()),
     );
+    if let Some(authenticator) = authenticator {
+        app.with(authenticator);
+    }
 
-    app.at("/").get(query).put(write).patch(write).delete(write);
-    app.at("*").get(query).put(write).patch(write).delete(write);
+    app.at("/@openapi").get(openapi);
+    app.at("/@ws").get(websocket::handle);
+    app.at("/")
+        .get(query)
+        .post(query)
+        .put(write)
+        .patch(write)
+        .delete(write);
+    app.at("*")
+        .get(query)
+        .post(query)
+        .put(write)
+        .patch(write)
+        .delete(write);
 
-    if let Err(e) = app.listen(conf.http_port).await {
+    let result = match &conf.tls {
+        Some(tls) => listen_tls(app, &conf.http_port, tls).await,
+        None => app
+            .listen(conf.http_port)
+            .await
+            .map_err(|e| zerror!(e).into()),
+    };
+    if let Err(e) = result {
         log::error!("Unable to start http server for REST: {:?}", e);
     }
 }
 
-fn path_to_key_expr<'a>(path: &'a str, zid: &str) -> ZResult<KeyExpr<'a>> {
+async fn tls_server_config(conf: &TlsConf) -> ZResult<ServerConfig> {
+    let certificate = async_std::fs::read(&conf.server_certificate)
+        .await
+        .map_err(|e| zerror!("Invalid TLS certificate {}: {}", conf.server_certificate, e))?;
+    let private_key = async_std::fs::read(&conf.server_private_key)
+        .await
+        .map_err(|e| zerror!("Invalid TLS private key {}: {}", conf.server_private_key, e))?;
+
+    let certs: Vec<Certificate> = rustls_pemfile::certs(&mut Cursor::new(&certificate))
+        .map_err(|e| zerror!(e))
+        .map(|mut certs| certs.drain(..).map(Certificate).collect())?;
+    let mut keys: Vec<PrivateKey> =
+        rustls_pemfile::rsa_private_keys(&mut Cursor::new(&private_key))
+            .map_err(|e| zerror!(e))
+            .map(|mut keys| keys.drain(..).map(PrivateKey).collect())?;
+    if keys.is_empty() {
+        keys = rustls_pemfile::pkcs8_private_keys(&mut Cursor::new(&private_key))
+            .map_err(|e| zerror!(e))
+            .map(|mut keys| keys.drain(..).map(PrivateKey).collect())?;
+    }
+    if keys.is_empty() {
+        bail!("No private key found in {}", conf.server_private_key);
+    }
+
+    ServerConfig::builder()
+        .with_safe_defaults()
+        .with_no_client_auth()
+        .with_single_cert(certs, keys.remove(0))
+        .map_err(|e| zerror!(e).into())
+}
+
+/// Serves the app over HTTPS, since tide only supports plain HTTP listeners.
+async fn listen_tls(
+    app: Server<(Arc<Session>, String)>,
+    http_port: &str,
+    conf: &TlsConf,
+) -> ZResult<()> {
+    let acceptor = TlsAcceptor::from(Arc::new(tls_server_config(conf).await?));
+    let listener = TcpListener::bind(http_port)
+        .await
+        .map_err(|e| zerror!("Unable to bind {}: {}", http_port, e))?;
+    log::info!("REST server listening with TLS on {}", http_port);
+    loop {
+        let (stream, peer_addr) = match listener.accept().await {
+            Ok(accepted) => accepted,
+            Err(e) => {
+                log::warn!("Error accepting REST connection: {}", e);
+                continue;
+            }
+        };
+        let acceptor = acceptor.clone();
+        let app = app.clone();
+        async_std::task::spawn(async move {
+            let local_addr = stream.local_addr().ok();
+            let stream = match acceptor.accept(stream).await {
+                Ok(stream) => stream,
+                Err(e) => {
+                    log::debug!("TLS handshake with {} failed: {}", peer_addr, e);
+                    return;
+                }
+            };
+            // async-h1 needs a cloneable stream to read and write concurrently
+            let stream = async_dup::Arc::new(async_dup::Mutex::new(stream));
+            let result = async_h1::accept(stream, |mut req| {
+                req.set_peer_addr(Some(peer_addr));
+                req.set_local_addr(local_addr);
+                let app = app.clone();
+                async move { app.respond(req).await }
+            })
+            .await;
+            if let Err(e) = result {
+                log::debug!("Error serving REST connection from {}: {}", peer_addr, e);
+            }
+        });
+    }
+}
+
+pub(crate) fn path_to_key_expr<'a>(path: &'a str, zid: &str) -> ZResult<KeyExpr<'a>> {
     let path = path.strip_prefix('/').unwrap_or(path);
     if path == "@/router/local" {
         KeyExpr::try_from(format!("@/router/{zid}"))
@@ -0,0 +1,532 @@
+//
+// Copyright (c) 2022 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+
+//! A WebSocket endpoint multiplexing subscriptions, publications, queries and queryables
+//! over a single connection, with a small JSON protocol.
+//!
+//! Each client message is a JSON object with an `op` field:
+//! - `{ "op": "subscribe", "id": 1, "key_expr": "demo/**" }`, acknowledged with
+//!   `{ "op": "ack", "id": 1 }` and followed by `{ "op": "sample", "id": 1, "kind": "PUT", "sample": {...} }`
+//!   messages until `{ "op": "unsubscribe", "id": 1 }`.
+//! - `{ "op": "put", "key_expr": "demo/a", "value": "hello", "encoding": "text/plain" }` and
+//!   `{ "op": "delete", "key_expr": "demo/a" }`.
+//! - `{ "op": "get", "id": 2, "selector": "demo/**", "target": "ALL", "consolidation": "NONE", "timeout": 1000 }`,
+//!   answered by `{ "op": "reply", "id": 2, "reply": {...} }` messages as they are received,
+//!   followed by `{ "op": "end", "id": 2 }`. A `value` and an `encoding` may be sent with the query.
+//! - `{ "op": "declare_queryable", "id": 3, "key_expr": "demo/q", "complete": false }`, acknowledged with
+//!   `{ "op": "ack", "id": 3 }` and followed by `{ "op": "query", "id": 3, "query_id": 1, "selector": "demo/q", ... }`
+//!   messages, that the client answers with `{ "op": "reply", "query_id": 1, "key_expr": "demo/q", "value": ... }`
+//!   messages and a final `{ "op": "reply_final", "query_id": 1 }`, until
+//!   `{ "op": "undeclare_queryable", "id": 3 }`.
+//!
+//! A `value` sent by the client is published as is if it is a string (with the `text/plain` encoding
+//! by default), and as its JSON representation otherwise (with the `application/json` encoding by default).
+//! Errors are reported with `{ "op": "error", "id": 1, "message": "..." }`, the `id` being `null`
+//! when the error is not related to a subscription, query or queryable.
+//!
+//! The messages to send to the client are queued in a bounded channel: a client that doesn't read them
+//! fast enough to keep up is disconnected, rather than slowing down the whole session.
+//! The queries received by its queryables expire if it doesn't finalize them in time.
+
+use async_std::task;
+use async_tungstenite::tungstenite::handshake::derive_accept_key;
+use async_tungstenite::tungstenite::protocol::Role;
+use async_tungstenite::tungstenite::Message;
+use async_tungstenite::WebSocketStream;
+use futures::{SinkExt, StreamExt};
+use http_types::upgrade::Connection;
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::convert::TryFrom;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use stop_token::future::FutureExt;
+use stop_token::StopSource;
+use tide::{Request, Response, StatusCode};
+use zenoh::encoding;
+use zenoh::prelude::r#async::*;
+use zenoh::query::{QueryConsolidation, QueryTarget};
+use zenoh::queryable::{Query, Queryable};
+use zenoh::subscriber::Subscriber;
+use zenoh::Session;
+use zenoh_core::zlock;
+use zenoh_result::{bail, ZResult};
+
+use crate::auth::Access;
+use crate::{
+    parse_consolidation, parse_query_target, result_to_json, sample_to_json, value_to_json,
+};
+
+/// The capacity of the queue of messages to send to a client.
+const OUTGOING_CAPACITY: usize = 256;
+
+/// The queue of messages to send to a client, that disconnects the client when it's full.
+#[derive(Clone)]
+struct Outgoing {
+    tx: flume::Sender<String>,
+    stop: Arc<Mutex<Option<StopSource>>>,
+}
+
+impl Outgoing {
+    /// Queues a message, returning `false` if the client is disconnected.
+    fn send(&self, message: String) -> bool {
+        match self.tx.try_send(message) {
+            Ok(()) => true,
+            Err(flume::TrySendError::Full(_)) => {
+                if zlock!(self.stop).take().is_some() {
+                    log::warn!("WebSocket client too slow to keep up: disconnecting it");
+                }
+                false
+            }
+            Err(flume::TrySendError::Disconnected(_)) => false,
+        }
+    }
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(tag = "op", rename_all = "snake_case")]
+enum ClientMessage {
+    Subscribe {
+        id: u64,
+        key_expr: String,
+    },
+    Unsubscribe {
+        id: u64,
+    },
+    Put {
+        key_expr: String,
+        value: serde_json::Value,
+        encoding: Option<String>,
+    },
+    Delete {
+        key_expr: String,
+    },
+    Get {
+        id: u64,
+        selector: String,
+        target: Option<String>,
+        consolidation: Option<String>,
+        timeout: Option<u64>,
+        value: Option<serde_json::Value>,
+        encoding: Option<String>,
+    },
+    DeclareQueryable {
+        id: u64,
+        key_expr: String,
+        #[serde(default)]
+        complete: bool,
+    },
+    UndeclareQueryable {
+        id: u64,
+    },
+    Reply {
+        query_id: u64,
+        key_expr: String,
+        value: serde_json::Value,
+        encoding: Option<String>,
+    },
+    ReplyFinal {
+        query_id: u64,
+    },
+}
+
+impl ClientMessage {
+    fn id(&self) -> Option<u64> {
+        match self {
+            ClientMessage::Subscribe { id, .. }
+            | ClientMessage::Unsubscribe { id }
+            | ClientMessage::Get { id, .. }
+            | ClientMessage::DeclareQueryable { id, .. }
+            | ClientMessage::UndeclareQueryable { id } => Some(*id),
+            _ => None,
+        }
+    }
+}
+
+fn json_string(s: &str) -> String {
+    serde_json::json!(s).to_string()
+}
+
+fn ack_message(id: u64) -> String {
+    format!(r#"{{ "op": "ack", "id": {id} }}"#)
+}
+
+fn error_message(id: Option<u64>, message: &str) -> String {
+    let id = id.map(|id| id.to_string()).unwrap_or_else(|| "null".into());
+    format!(
+        r#"{{ "op": "error", "id": {}, "message": {} }}"#,
+        id,
+        json_string(message)
+    )
+}
+
+fn sample_message(id: u64, sample: Sample) -> String {
+    let kind = sample.kind;
+    format!(
+        r#"{{ "op": "sample", "id": {}, "kind": "{}", "sample": {} }}"#,
+        id,
+        kind,
+        sample_to_json(sample)
+    )
+}
+
+fn query_message(id: u64, query_id: u64, query: &Query) -> String {
+    let (value, encoding) = match query.value() {
+        Some(value) => (
+            value_to_json(value.clone()),
+            json_string(&encoding::registry().content_type(&value.encoding)),
+        ),
+        None => ("null".to_string(), "null".to_string()),
+    };
+    format!(
+        r#"{{ "op": "query", "id": {}, "query_id": {}, "selector": {}, "value": {}, "encoding": {} }}"#,
+        id,
+        query_id,
+        json_string(&query.selector().to_string()),
+        value,
+        encoding
+    )
+}
+
+/// Converts a JSON value sent by the client to a zenoh [`Value`].
+fn to_value(value: serde_json::Value, encoding: Option<String>) -> Value {
+    let (payload, default_encoding) = match value {
+        serde_json::Value::String(s) => (s, Encoding::TEXT_PLAIN),
+        v => (v.to_string(), Encoding::APP_JSON),
+    };
+    let encoding = encoding
+        .map(|e| encoding::registry().from_mime(&e))
+        .unwrap_or(default_encoding);
+    Value::from(payload).encoding(encoding)
+}
+
+/// Handles a WebSocket upgrade request.
+pub(crate) async fn handle(req: Request<(Arc<Session>, String)>) -> tide::Result<Response> {
+    let is_upgrade = req
+        .header("upgrade")
+        .map(|h| h.last().as_str().eq_ignore_ascii_case("websocket"))
+        .unwrap_or(false);
+    let key = match req.header("sec-websocket-key") {
+        Some(key) if is_upgrade => key.last().as_str().to_string(),
+        _ => {
+            return Ok(Response::builder(StatusCode::UpgradeRequired)
+                .header("upgrade", "websocket")
+                .build())
+        }
+    };
+
+    let mut response = Response::new(StatusCode::SwitchingProtocols);
+    response.insert_header("upgrade", "websocket");
+    response.insert_header("connection", "Upgrade");
+    response.insert_header("sec-websocket-accept", derive_accept_key(key.as_bytes()));
+    let http_response: &mut http_types::Response = response.as_mut();
+    let upgrade = http_response.recv_upgrade().await;
+    let session = req.state().0.clone();
+    let access = req.ext::<Access>().cloned();
+    task::spawn(async move {
+        match upgrade.await {
+            Some(connection) => serve(connection, session, access).await,
+            None => log::debug!("WebSocket upgrade failed"),
+        }
+    });
+    Ok(response)
+}
+
+async fn serve(connection: Connection, session: Arc<Session>, access: Option<Access>) {
+    log::debug!(
+        "WebSocket connection opened (task {})",
+        task::current().id()
+    );
+    let ws = WebSocketStream::from_raw_socket(connection, Role::Server, None).await;
+    let (mut sink, mut stream) = ws.split();
+    let (out_tx, out_rx) = flume::bounded::<String>(OUTGOING_CAPACITY);
+    let stop = StopSource::new();
+    let (reader_stop, writer_stop) = (stop.token(), stop.token());
+    let writer = task::spawn(async move {
+        while let Ok(Ok(message)) = out_rx.recv_async().timeout_at(writer_stop.clone()).await {
+            if let Err(e) = sink.send(Message::Text(message)).await {
+                log::debug!("WebSocket send error: {}", e);
+                break;
+            }
+        }
+        // A disconnected slow client isn't closed gracefully, as it doesn't read anymore
+        let _ = sink.close().timeout_at(writer_stop).await;
+    });
+
+    let query_timeout = session
+        .config()
+        .lock()
+        .queries_default_timeout()
+        .unwrap_or(zenoh::config::defaults::queries_default_timeout);
+    let query_timeout = Duration::from_millis(query_timeout);
+    let mut client = Client {
+        session,
+        access,
+        out: Outgoing {
+            tx: out_tx,
+            stop: Arc::new(Mutex::new(Some(stop))),
+        },
+        subscribers: HashMap::new(),
+        queryables: HashMap::new(),
+        queries: Arc::new(Mutex::new(HashMap::new())),
+        query_counter: Arc::new(AtomicU64::new(0)),
+        query_timeout,
+    };
+    while let Ok(Some(message)) = stream.next().timeout_at(reader_stop.clone()).await {
+        match message {
+            Ok(Message::Text(text)) => client.handle(&text).await,
+            Ok(Message::Close(_)) => break,
+            Ok(_) => {}
+            Err(e) => {
+                log::debug!("WebSocket receive error: {}", e);
+                break;
+            }
+        }
+    }
+
+    // Undeclaring everything disconnects the forwarding tasks, and thus the writer.
+    drop(client);
+    writer.await;
+    log::debug!(
+        "WebSocket connection closed (task {})",
+        task::current().id()
+    );
+}
+
+struct Client {
+    session: Arc<Session>,
+    access: Option<Access>,
+    out: Outgoing,
+    subscribers: HashMap<u64, Subscriber<'static, ()>>,
+    queryables: HashMap<u64, Queryable<'static, ()>>,
+    /// The queries received by the queryables, with their expiration time.
+    queries: Arc<Mutex<HashMap<u64, (Query, Instant)>>>,
+    query_counter: Arc<AtomicU64>,
+    query_timeout: Duration,
+}
+
+impl Client {
+    fn send(&self, message: String) {
+        self.out.send(message);
+    }
+
+    fn check_read(&self, key_expr: &keyexpr) -> ZResult<()> {
+        match &self.access {
+            Some(access) if !access.can_read(key_expr) => {
+                bail!("Access to {} is forbidden", key_expr)
+            }
+            _ => Ok(()),
+        }
+    }
+
+    fn check_query(&self, key_expr: &keyexpr) -> ZResult<()> {
+        match &self.access {
+            Some(access) if !access.can_query(key_expr) => {
+                bail!("Access to {} is forbidden", key_expr)
+            }
+            _ => Ok(()),
+        }
+    }
+
+    fn check_write(&self, key_expr: &keyexpr) -> ZResult<()> {
+        match &self.access {
+            Some(access) if !access.can_write(key_expr) => {
+                bail!("Access to {} is forbidden", key_expr)
+            }
+            _ => Ok(()),
+        }
+    }
+
+    async fn handle(&mut self, text: &str) {
+        let message = match serde_json::from_str::<ClientMessage>(text) {
+            Ok(message) => message,
+            Err(e) => {
+                self.send(error_message(None, &format!("Invalid message: {e}")));
+
+                return;
+            }
+        };
+        log::trace!("WebSocket message: {:?}", message);
+        let id = message.id();
+        if let Err(e) = self.handle_message(message).await {
+            self.send(error_message(id, &e.to_string()));
+        }
+    }
+
+    async fn handle_message(&mut self, message: ClientMessage) -> ZResult<()> {
+        match message {
+            ClientMessage::Subscribe { id, key_expr } => {
+                if self.subscribers.contains_key(&id) {
+                    bail!("Subscription {} already exists", id);
+                }
+                let key_expr = KeyExpr::try_from(key_expr)?;
+                self.check_read(&key_expr)?;
+                // The samples are directly queued to the client, never blocking the session
+                let out = self.out.clone();
+                let subscriber = self
+                    .session
+                    .declare_subscriber(key_expr)
+                    .callback(move |sample| {
+                        out.send(sample_message(id, sample));
+                    })
+                    .res()
+                    .await?;
+                self.subscribers.insert(id, subscriber);
+                self.send(ack_message(id));
+            }
+            ClientMessage::Unsubscribe { id } => match self.subscribers.remove(&id) {
+                Some(subscriber) => subscriber.undeclare().res().await?,
+                None => bail!("Unknown subscription {}", id),
+            },
+            ClientMessage::Put {
+                key_expr,
+                value,
+                encoding,
+            } => {
+                let key_expr = KeyExpr::try_from(key_expr)?;
+                self.check_write(&key_expr)?;
+                self.session
+                    .put(key_expr, to_value(value, encoding))
+                    .res()
+                    .await?;
+            }
+            ClientMessage::Delete { key_expr } => {
+                let key_expr = KeyExpr::try_from(key_expr)?;
+                self.check_write(&key_expr)?;
+                self.session.delete(key_expr).res().await?;
+            }
+            ClientMessage::Get {
+                id,
+                selector,
+                target,
+                consolidation,
+                timeout,
+                value,
+                encoding,
+            } => {
+                let selector = Selector::try_from(selector)?;
+                self.check_query(&selector.key_expr)?;
+                let target = match target {
+                    Some(target) => parse_query_target(&target)?,
+                    None => QueryTarget::default(),
+                };
+                let consolidation = match consolidation {
+                    Some(consolidation) => parse_consolidation(&consolidation)?,
+                    None => QueryConsolidation::default(),
+                };
+                let mut query = self
+                    .session
+                    .get(selector)
+                    .target(target)
+                    .consolidation(consolidation);
+                if let Some(timeout) = timeout {
+                    query = query.timeout(Duration::from_millis(timeout));
+                }
+                if let Some(value) = value {
+                    query = query.with_value(to_value(value, encoding));
+                }
+                let replies = query.res().await?;
+                let out = self.out.clone();
+                task::spawn(async move {
+                    while let Ok(reply) = replies.recv_async().await {
+                        let message = format!(
+                            r#"{{ "op": "reply", "id": {}, "reply": {} }}"#,
+                            id,
+                            result_to_json(reply.sample)
+                        );
+                        if !out.send(message) {
+                            return;
+                        }
+                    }
+                    out.send(format!(r#"{{ "op": "end", "id": {id} }}"#));
+                });
+            }
+            ClientMessage::DeclareQueryable {
+                id,
+                key_expr,
+                complete,
+            } => {
+                if self.queryables.contains_key(&id) {
+                    bail!("Queryable {} already exists", id);
+                }
+                let key_expr = KeyExpr::try_from(key_expr)?;
+                self.check_write(&key_expr)?;
+                let out = self.out.clone();
+                let queries = self.queries.clone();
+                let counter = self.query_counter.clone();
+                let timeout = self.query_timeout;
+                let queryable = self
+                    .session
+                    .declare_queryable(key_expr)
+                    .complete(complete)
+                    .callback(move |query| {
+                        let query_id = counter.fetch_add(1, Ordering::Relaxed);
+                        let message = query_message(id, query_id, &query);
+                        // The query is answered (and finalized when dropped) on the client's request,
+                        // or finalized when it expires.
+                        let expiration = Instant::now() + timeout;
+                        zlock!(queries).insert(query_id, (query, expiration));
+                        let queries = queries.clone();
+                        task::spawn(async move {
+                            task::sleep(timeout).await;
+                            if zlock!(queries).remove(&query_id).is_some() {
+                                log::debug!("WebSocket query {} expired", query_id);
+                            }
+                        });
+                        out.send(message);
+                    })
+                    .res()
+                    .await?;
+                self.queryables.insert(id, queryable);
+                self.send(ack_message(id));
+            }
+            ClientMessage::UndeclareQueryable { id } => match self.queryables.remove(&id) {
+                Some(queryable) => queryable.undeclare().res().await?,
+                None => bail!("Unknown queryable {}", id),
+            },
+            ClientMessage::Reply {
+                query_id,
+                key_expr,
+                value,
+                encoding,
+            } => {
+                let query = zlock!(self.queries).remove(&query_id);
+                match query {
+                    Some((query, expiration)) => {
+                        let key_expr = KeyExpr::try_from(key_expr);
+                        let result = match key_expr {
+                            Ok(key_expr) => {
+                                let sample = Sample::new(key_expr, to_value(value, encoding));
+                                query.reply(Ok(sample)).res().await
+                            }
+                            Err(e) => Err(e),
+                        };
+                        if Instant::now() < expiration {
+                            zlock!(self.queries).insert(query_id, (query, expiration));
+                        }
+                        result?;
+                    }
+                    None => bail!("Unknown query {}", query_id),
+                }
+            }
+            ClientMessage::ReplyFinal { query_id } => {
+                if zlock!(self.queries).remove(&query_id).is_none() {
+                    bail!("Unknown query {}", query_id);
+                }
+            }
+        }
+        Ok(())
+    }
+}
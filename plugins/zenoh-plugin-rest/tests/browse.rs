@@ -0,0 +1,141 @@
+//
+// Copyright (c) 2022 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+use async_std::net::TcpStream;
+use async_std::prelude::FutureExt;
+use async_std::task;
+use futures::io::{AsyncReadExt, AsyncWriteExt};
+use std::time::Duration;
+use zenoh::plugins::PluginsManager;
+use zenoh::prelude::r#async::*;
+use zenoh::prelude::sync::SyncResolve;
+use zenoh::runtime::{AdminSpace, Runtime};
+
+const TIMEOUT: Duration = Duration::from_secs(60);
+const SLEEP: Duration = Duration::from_secs(1);
+
+macro_rules! ztimeout {
+    ($f:expr) => {
+        $f.timeout(TIMEOUT).await.unwrap()
+    };
+}
+
+/// Sends an HTTP/1.1 GET request, returning the status code and the body.
+async fn get(port: u16, path: &str, accept: &str) -> (u16, String) {
+    get_as(port, path, accept, None).await
+}
+
+/// Sends an HTTP/1.1 GET request with the given bearer token, returning the status code and the body.
+async fn get_as(port: u16, path: &str, accept: &str, token: Option<&str>) -> (u16, String) {
+    let mut stream = TcpStream::connect(("127.0.0.1", port)).await.unwrap();
+    let authorization = token
+        .map(|token| format!("Authorization: Bearer {token}\r\n"))
+        .unwrap_or_default();
+    let req = format!(
+        "GET {path} HTTP/1.1\r\nHost: localhost\r\nConnection: close\r\nAccept: {accept}\r\n{authorization}\r\n"
+    );
+    stream.write_all(req.as_bytes()).await.unwrap();
+    let mut res = String::new();
+    ztimeout!(stream.read_to_string(&mut res)).unwrap();
+    let (head, body) = res.split_once("\r\n\r\n").unwrap();
+    let status = head.split(' ').nth(1).unwrap().parse().unwrap();
+    (status, body.to_string())
+}
+
+#[test]
+fn rest_browse() {
+    task::block_on(async {
+        let _ = env_logger::try_init();
+        let port = 18506;
+        let mut config = config::peer();
+        config.scouting.multicast.set_enabled(Some(false)).unwrap();
+        let runtime = ztimeout!(Runtime::new(config)).unwrap();
+        AdminSpace::start(
+            &runtime,
+            PluginsManager::static_plugins_only(),
+            "test".to_string(),
+        )
+        .await;
+        let conf: zplugin_rest::Config =
+            serde_json::from_value(serde_json::json!({ "http_port": port })).unwrap();
+        task::spawn(zplugin_rest::run(runtime.clone(), conf));
+        // A second REST endpoint, with users allowed to read the admin space or not
+        let auth_port = 18510;
+        let dir = std::env::temp_dir().join("zenoh-rest-browse");
+        std::fs::create_dir_all(&dir).unwrap();
+        let tokens = dir.join("tokens.txt");
+        std::fs::write(&tokens, "admin:admin-token\nuser:user-token\n").unwrap();
+        let conf: zplugin_rest::Config = serde_json::from_value(serde_json::json!({
+            "http_port": auth_port,
+            "cors_origins": ["https://example.com"],
+            "auth": {
+                "tokens_file": tokens.to_str().unwrap(),
+                "permissions": {
+                    "admin": { "read": ["test/browse/**", "@/router/**"] },
+                    "user": { "read": ["test/browse/**"] },
+                },
+            },
+        }))
+        .unwrap();
+        task::spawn(zplugin_rest::run(runtime.clone(), conf));
+        task::sleep(SLEEP).await;
+
+        let session = ztimeout!(zenoh::init(runtime).res_async()).unwrap();
+        let _sub = ztimeout!(session
+            .declare_subscriber("test/browse/sub")
+            .callback(|_| {})
+            .res_async())
+        .unwrap();
+        let _queryable = ztimeout!(session
+            .declare_queryable("test/browse/q/**")
+            .callback(|query| {
+                let sample = Sample::new(query.key_expr().clone(), "<b>bold</b>");
+                query.reply(Ok(sample)).res_sync().unwrap();
+            })
+            .res_async())
+        .unwrap();
+        let _elsewhere = ztimeout!(session
+            .declare_subscriber("test/elsewhere")
+            .callback(|_| {})
+            .res_async())
+        .unwrap();
+        task::sleep(SLEEP).await;
+
+        // OpenAPI description
+        let (status, body) = get(port, "/@openapi", "application/json").await;
+        assert_eq!(status, 200);
+        let openapi: serde_json::Value = serde_json::from_str(&body).unwrap();
+        assert_eq!(openapi["openapi"], "3.0.3");
+        assert!(openapi["paths"]["/{key_expr}"]["get"].is_object());
+
+        // HTML browsing
+        let (status, body) = get(port, "/test/browse/**", "text/html").await;
+        assert_eq!(status, 200);
+        assert!(body.contains("<a href=\"/test/browse/q/**\">test/browse/q/**</a>"));
+        assert!(body.contains("<a href=\"/test/browse/sub\">test/browse/sub</a>"));
+        assert!(!body.contains("test/elsewhere"));
+        assert!(body.contains("&lt;b&gt;bold&lt;/b&gt;"));
+        assert!(body.contains("<a href=\"/test/browse/**\">browse</a>"));
+
+        // The declarations are only listed to the users allowed to read the admin space
+        let token = Some("admin-token");
+        let (status, body) = get_as(auth_port, "/test/browse/**", "text/html", token).await;
+        assert_eq!(status, 200);
+        assert!(body.contains("<a href=\"/test/browse/sub\">test/browse/sub</a>"));
+        let token = Some("user-token");
+        let (status, body) = get_as(auth_port, "/test/browse/**", "text/html", token).await;
+        assert_eq!(status, 200);
+        assert!(body.contains("&lt;b&gt;bold&lt;/b&gt;"));
+        assert!(!body.contains("<a href=\"/test/browse/sub\">test/browse/sub</a>"));
+    });
+}
@@ -0,0 +1,167 @@
+//
+// Copyright (c) 2022 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+use async_std::prelude::FutureExt;
+use async_std::task;
+use serde_json::json;
+use std::time::{Duration, Instant};
+use tide::{Request, Response, StatusCode};
+use zenoh::prelude::r#async::*;
+use zenoh::query::Reply;
+use zenoh::runtime::Runtime;
+
+const TIMEOUT: Duration = Duration::from_secs(60);
+const SLEEP: Duration = Duration::from_secs(1);
+
+macro_rules! ztimeout {
+    ($f:expr) => {
+        $f.timeout(TIMEOUT).await.unwrap()
+    };
+}
+
+/// A stand-in HTTP service, echoing the requests it receives.
+async fn weather(mut req: Request<()>) -> tide::Result {
+    if req.url().path() == "/weather/nowhere" {
+        return Ok(Response::builder(StatusCode::NotFound)
+            .body("unknown location")
+            .build());
+    }
+    let body = req.body_string().await?;
+    let content_type = req.content_type().map(|m| m.to_string());
+    Ok(Response::builder(StatusCode::Ok)
+        .body(json!({
+            "method": req.method().to_string(),
+            "path": req.url().path(),
+            "query": req.url().query(),
+            "content_type": content_type,
+            "body": body,
+        }))
+        .build())
+}
+
+/// A stand-in HTTP service, too slow to answer.
+async fn slow(_req: Request<()>) -> tide::Result {
+    task::sleep(Duration::from_secs(30)).await;
+    Ok(Response::new(StatusCode::Ok))
+}
+
+async fn get(session: &Session, selector: &str, value: Option<&str>) -> Vec<Reply> {
+    let mut get = session.get(selector);
+    if let Some(value) = value {
+        get = get.with_value(Value::from(value).encoding(KnownEncoding::TextPlain.into()));
+    }
+    let replies = ztimeout!(get.res_async()).unwrap();
+    let mut result = vec![];
+    while let Ok(reply) = replies.recv_async().await {
+        result.push(reply);
+    }
+    result
+}
+
+#[test]
+fn rest_http_queryable() {
+    task::block_on(async {
+        let _ = env_logger::try_init();
+        let service_port = 18505;
+        let mut service = tide::new();
+        service.at("/weather/").get(weather).post(weather);
+        service.at("/weather/*").get(weather).post(weather);
+        service.at("/slow").get(slow);
+        service.at("/admin").get(weather);
+        task::spawn(service.listen(format!("127.0.0.1:{service_port}")));
+
+        let mut config = config::peer();
+        config.scouting.multicast.set_enabled(Some(false)).unwrap();
+        let runtime = ztimeout!(Runtime::new(config)).unwrap();
+        let conf: zplugin_rest::Config = serde_json::from_value(json!({
+            "http_port": 18504,
+            "http_queryables": [
+                { "key_expr": "api/weather/**", "url": format!("http://127.0.0.1:{service_port}/weather/") },
+                { "key_expr": "api/keyed/**", "url": format!("http://127.0.0.1:{service_port}/weather?key=abc") },
+                { "key_expr": "api/slow", "url": format!("http://127.0.0.1:{service_port}/slow"), "timeout": 500 },
+            ],
+        }))
+        .unwrap();
+        task::spawn(zplugin_rest::run(runtime.clone(), conf));
+        task::sleep(SLEEP).await;
+        let session = ztimeout!(zenoh::init(runtime).res_async()).unwrap();
+
+        // GET with the selector parameters as query string
+        let replies = get(&session, "api/weather/paris/today?units=metric", None).await;
+        assert_eq!(replies.len(), 1);
+        let sample = replies[0].sample.as_ref().unwrap();
+        assert_eq!(sample.key_expr.as_str(), "api/weather/paris/today");
+        assert_eq!(
+            sample.value.encoding,
+            Encoding::Exact(KnownEncoding::AppJson)
+        );
+        let echo: serde_json::Value = serde_json::from_str(&sample.value.to_string()).unwrap();
+        assert_eq!(echo["method"], "GET");
+        assert_eq!(echo["path"], "/weather/paris/today");
+        assert_eq!(echo["query"], "units=metric");
+
+        // The queryable key expression itself
+        let replies = get(&session, "api/weather", None).await;
+        let sample = replies[0].sample.as_ref().unwrap();
+        let echo: serde_json::Value = serde_json::from_str(&sample.value.to_string()).unwrap();
+        assert_eq!(echo["path"], "/weather/");
+        assert_eq!(echo["query"], serde_json::Value::Null);
+
+        // POST with the query value as body
+        let replies = get(&session, "api/weather/paris", Some("rain")).await;
+        let sample = replies[0].sample.as_ref().unwrap();
+        let echo: serde_json::Value = serde_json::from_str(&sample.value.to_string()).unwrap();
+        assert_eq!(echo["method"], "POST");
+        assert_eq!(echo["content_type"], "text/plain");
+        assert_eq!(echo["body"], "rain");
+
+        // The selector parameters are appended to the query string of the URL
+        let replies = get(&session, "api/keyed/paris?units=metric", None).await;
+        let sample = replies[0].sample.as_ref().unwrap();
+        let echo: serde_json::Value = serde_json::from_str(&sample.value.to_string()).unwrap();
+        assert_eq!(echo["path"], "/weather/paris");
+        assert_eq!(echo["query"], "key=abc&units=metric");
+
+        // Error status, that can't be replied as error yet
+        let replies = get(&session, "api/weather/nowhere", None).await;
+        assert_eq!(replies.len(), 1);
+        let sample = replies[0].sample.as_ref().unwrap();
+        assert_eq!(sample.value.to_string(), "unknown location");
+
+        // Timed out request
+        let start = Instant::now();
+        let replies = get(&session, "api/slow", None).await;
+        assert!(replies.is_empty());
+        assert!(start.elapsed() < Duration::from_secs(5));
+
+        // The key expression chunks are percent-encoded path segments
+        let replies = get(&session, "api/weather/new york/a%2Fb", None).await;
+        let sample = replies[0].sample.as_ref().unwrap();
+        let echo: serde_json::Value = serde_json::from_str(&sample.value.to_string()).unwrap();
+        assert_eq!(echo["path"], "/weather/new%20york/a%252Fb");
+
+        // Dot segments can't escape the path of the URL
+        for selector in [
+            "api/weather/../admin",
+            "api/weather/paris/../../admin",
+            "api/weather/.",
+        ] {
+            let replies = get(&session, selector, None).await;
+            assert!(replies.is_empty(), "{} was forwarded", selector);
+        }
+
+        // Wildcard queries can't be forwarded
+        let replies = get(&session, "api/weather/*", None).await;
+        assert!(replies.is_empty());
+    });
+}
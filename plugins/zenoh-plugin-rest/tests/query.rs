@@ -0,0 +1,240 @@
+//
+// Copyright (c) 2022 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+use async_std::net::TcpStream;
+use async_std::prelude::FutureExt;
+use async_std::task;
+use futures::io::{AsyncReadExt, AsyncWriteExt};
+use std::time::{Duration, Instant};
+use zenoh::prelude::r#async::*;
+use zenoh::prelude::sync::SyncResolve;
+use zenoh::queryable::Query;
+use zenoh::runtime::Runtime;
+
+const TIMEOUT: Duration = Duration::from_secs(60);
+const SLEEP: Duration = Duration::from_secs(1);
+
+macro_rules! ztimeout {
+    ($f:expr) => {
+        $f.timeout(TIMEOUT).await.unwrap()
+    };
+}
+
+async fn start_rest(port: u16) -> Runtime {
+    let mut config = config::peer();
+    config.scouting.multicast.set_enabled(Some(false)).unwrap();
+    let runtime = ztimeout!(Runtime::new(config)).unwrap();
+    let conf: zplugin_rest::Config =
+        serde_json::from_value(serde_json::json!({ "http_port": port })).unwrap();
+    task::spawn(zplugin_rest::run(runtime.clone(), conf));
+    task::sleep(SLEEP).await;
+    runtime
+}
+
+/// Decodes a chunked transfer encoded body.
+fn dechunk(mut body: &str) -> String {
+    let mut decoded = String::new();
+    loop {
+        let (size, rest) = body.split_once("\r\n").unwrap();
+        let size = usize::from_str_radix(size.trim(), 16).unwrap();
+        if size == 0 {
+            break decoded;
+        }
+        decoded.push_str(&rest[..size]);
+        body = &rest[size + 2..];
+    }
+}
+
+/// Sends an HTTP/1.1 request, returning the status code, the headers and the decoded body.
+async fn http(
+    port: u16,
+    method: &str,
+    path: &str,
+    headers: &[(&str, &str)],
+    body: &str,
+) -> (u16, String, String) {
+    let mut stream = TcpStream::connect(("127.0.0.1", port)).await.unwrap();
+    let mut req = format!(
+        "{method} {path} HTTP/1.1\r\nHost: localhost\r\nConnection: close\r\nContent-Length: {}\r\n",
+        body.len()
+    );
+    for (name, value) in headers {
+        req.push_str(&format!("{name}: {value}\r\n"));
+    }
+    req.push_str("\r\n");
+    req.push_str(body);
+    stream.write_all(req.as_bytes()).await.unwrap();
+    let mut res = String::new();
+    ztimeout!(stream.read_to_string(&mut res)).unwrap();
+    let (head, body) = res.split_once("\r\n\r\n").unwrap();
+    let head = head.to_lowercase();
+    let status = head.split(' ').nth(1).unwrap().parse().unwrap();
+    let body = if head.contains("transfer-encoding: chunked") {
+        dechunk(body)
+    } else {
+        body.to_string()
+    };
+    (status, head, body)
+}
+
+#[test]
+fn rest_query_options() {
+    task::block_on(async {
+        let _ = env_logger::try_init();
+        let port = 18502;
+        let runtime = start_rest(port).await;
+        let session = ztimeout!(zenoh::init(runtime).res_async()).unwrap();
+
+        // Replies twice on the same key, echoing the query value.
+        let _echo = ztimeout!(session
+            .declare_queryable("test/rest/echo")
+            .callback(|query: Query| {
+                let value = match query.value() {
+                    Some(v) => format!("{}:{}", v.encoding, v),
+                    None => "none".to_string(),
+                };
+                for _ in 0..2 {
+                    let sample = Sample::new(query.key_expr().clone(), value.clone());
+                    query.reply(Ok(sample)).res_sync().unwrap();
+                }
+            })
+            .res_async())
+        .unwrap();
+        // Never replies.
+        let _silent = ztimeout!(session
+            .declare_queryable("test/rest/silent")
+            .callback(|_| {})
+            .res_async())
+        .unwrap();
+        task::sleep(SLEEP).await;
+
+        // Replies are streamed as a JSON array by default, consolidated with LATEST.
+        let (status, head, body) = http(port, "GET", "/test/rest/echo", &[], "").await;
+        assert_eq!(status, 200);
+        assert!(head.contains("transfer-encoding: chunked"));
+        assert!(head.contains("content-type: application/json"));
+        let replies: Vec<serde_json::Value> = serde_json::from_str(&body).unwrap();
+        assert_eq!(replies.len(), 1);
+        assert_eq!(replies[0]["value"], "none");
+
+        let none = ("X-Zenoh-Consolidation", "none");
+        let (_, _, body) = http(port, "GET", "/test/rest/echo", &[none], "").await;
+        let replies: Vec<serde_json::Value> = serde_json::from_str(&body).unwrap();
+        assert_eq!(replies.len(), 2);
+
+        // JSON lines
+        let accept = ("Accept", "application/x-ndjson");
+        let (status, head, body) = http(port, "GET", "/test/rest/echo", &[accept, none], "").await;
+        assert_eq!(status, 200);
+        assert!(head.contains("content-type: application/x-ndjson"));
+        let lines = body.lines().collect::<Vec<_>>();
+        assert_eq!(lines.len(), 2);
+        for line in lines {
+            let reply: serde_json::Value = serde_json::from_str(line).unwrap();
+            assert_eq!(reply["key"], "test/rest/echo");
+        }
+
+        // Query payload, with a POST or with a GET with a content type
+        let text = ("Content-Type", "text/plain");
+        let (_, _, body) = http(port, "POST", "/test/rest/echo", &[text], "hello").await;
+        let replies: Vec<serde_json::Value> = serde_json::from_str(&body).unwrap();
+        assert_eq!(replies[0]["value"], "text/plain:hello");
+        let (_, _, body) = http(port, "GET", "/test/rest/echo", &[text], "hello").await;
+        let replies: Vec<serde_json::Value> = serde_json::from_str(&body).unwrap();
+        assert_eq!(replies[0]["value"], "text/plain:hello");
+
+        // Timeout
+        let start = Instant::now();
+        let timeout = ("X-Zenoh-Timeout", "500");
+        let target = ("X-Zenoh-Query-Target", "ALL");
+        let (status, _, body) =
+            http(port, "GET", "/test/rest/silent", &[timeout, target], "").await;
+        assert_eq!(status, 200);
+        assert!(start.elapsed() < Duration::from_secs(5));
+        let replies: Vec<serde_json::Value> = serde_json::from_str(&body).unwrap();
+        assert!(replies.is_empty());
+
+        // Invalid options
+        for header in [
+            ("X-Zenoh-Timeout", "soon"),
+            ("X-Zenoh-Query-Target", "SOME"),
+            ("X-Zenoh-Consolidation", "best"),
+        ] {
+            let (status, _, _) = http(port, "GET", "/test/rest/echo", &[header], "").await;
+            assert_eq!(status, 400);
+        }
+    });
+}
+
+#[test]
+fn rest_custom_encodings() {
+    task::block_on(async {
+        let _ = env_logger::try_init();
+        let port = 18507;
+        let runtime = start_rest(port).await;
+        let session = ztimeout!(zenoh::init(runtime).res_async()).unwrap();
+
+        let registry = zenoh::encoding::registry();
+        let json = registry
+            .register(zenoh::encoding::CustomEncoding::new(
+                0x7e01,
+                "application/vnd.test+json",
+            ))
+            .unwrap();
+        let text = registry
+            .register(zenoh::encoding::CustomEncoding::new(
+                0x7e02,
+                "text/vnd.test",
+            ))
+            .unwrap();
+        let values = vec![
+            (
+                "json",
+                Value::from(r#"{"a": [1, 2]}"#).encoding(json.clone()),
+            ),
+            ("invalid", Value::from("not json").encoding(json.clone())),
+            ("binary", Value::from(vec![0xff_u8, 0xfe]).encoding(json)),
+            ("text", Value::from("hello").encoding(text)),
+        ];
+        let _queryable = ztimeout!(session
+            .declare_queryable("test/rest/custom/*")
+            .callback(move |query: Query| {
+                for (key, value) in &values {
+                    let sample = Sample::new(
+                        KeyExpr::new(format!("test/rest/custom/{key}")).unwrap(),
+                        value.clone(),
+                    );
+                    query.reply(Ok(sample)).res_sync().unwrap();
+                }
+            })
+            .res_async())
+        .unwrap();
+        task::sleep(SLEEP).await;
+
+        let (status, _, body) = http(port, "GET", "/test/rest/custom/*", &[], "").await;
+        assert_eq!(status, 200);
+        let replies: Vec<serde_json::Value> = serde_json::from_str(&body).unwrap();
+        let value = |key: &str| {
+            replies
+                .iter()
+                .find(|r| r["key"] == format!("test/rest/custom/{key}"))
+                .unwrap()["value"]
+                .clone()
+        };
+        // The JSON is embedded as is, falling back to a string or to base64 if invalid
+        assert_eq!(value("json"), serde_json::json!({"a": [1, 2]}));
+        assert_eq!(value("invalid"), "not json");
+        assert_eq!(value("binary"), "//4=");
+        assert_eq!(value("text"), "hello");
+    });
+}
@@ -0,0 +1,243 @@
+//
+// Copyright (c) 2022 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+use async_std::prelude::FutureExt;
+use async_std::task;
+use async_tungstenite::tungstenite::{client, Message, WebSocket};
+use serde_json::{json, Value as Json};
+use std::net::TcpStream;
+use std::time::{Duration, Instant};
+use zenoh::prelude::r#async::*;
+use zenoh::prelude::sync::SyncResolve;
+use zenoh::runtime::Runtime;
+
+const TIMEOUT: Duration = Duration::from_secs(60);
+const SLEEP: Duration = Duration::from_secs(1);
+
+macro_rules! ztimeout {
+    ($f:expr) => {
+        $f.timeout(TIMEOUT).await.unwrap()
+    };
+}
+
+fn peer_config() -> Config {
+    let mut config = config::peer();
+    config.scouting.multicast.set_enabled(Some(false)).unwrap();
+    config
+}
+
+async fn start_rest(port: u16, config: Config) -> Runtime {
+    let runtime = ztimeout!(Runtime::new(config)).unwrap();
+    let conf: zplugin_rest::Config = serde_json::from_value(json!({ "http_port": port })).unwrap();
+    task::spawn(zplugin_rest::run(runtime.clone(), conf));
+    task::sleep(SLEEP).await;
+    runtime
+}
+
+fn send(ws: &mut WebSocket<TcpStream>, message: Json) {
+    ws.write_message(Message::Text(message.to_string()))
+        .unwrap();
+}
+
+fn connect(port: u16) -> WebSocket<TcpStream> {
+    let stream = TcpStream::connect(("127.0.0.1", port)).unwrap();
+    stream.set_read_timeout(Some(TIMEOUT)).unwrap();
+    let url = format!("ws://127.0.0.1:{port}/@ws");
+    client(url, stream).unwrap().0
+}
+
+fn recv(ws: &mut WebSocket<TcpStream>) -> Json {
+    loop {
+        if let Message::Text(text) = ws.read_message().unwrap() {
+            return serde_json::from_str(&text).unwrap();
+        }
+    }
+}
+
+#[test]
+fn rest_websocket() {
+    task::block_on(async {
+        let _ = env_logger::try_init();
+        let port = 18503;
+        let runtime = start_rest(port, peer_config()).await;
+        let session = ztimeout!(zenoh::init(runtime).res_async())
+            .unwrap()
+            .into_arc();
+
+        let mut ws = connect(port);
+
+        // Subscription and publication
+        send(
+            &mut ws,
+            json!({ "op": "subscribe", "id": 1, "key_expr": "test/ws/**" }),
+        );
+        assert_eq!(recv(&mut ws), json!({ "op": "ack", "id": 1 }));
+        send(
+            &mut ws,
+            json!({ "op": "put", "key_expr": "test/ws/a", "value": "hello" }),
+        );
+        let sample = recv(&mut ws);
+        assert_eq!(sample["op"], "sample");
+        assert_eq!(sample["id"], 1);
+        assert_eq!(sample["kind"], "PUT");
+        assert_eq!(sample["sample"]["key"], "test/ws/a");
+        assert_eq!(sample["sample"]["value"], "hello");
+        ztimeout!(session.put("test/ws/b", "world").res_async()).unwrap();
+        let sample = recv(&mut ws);
+        assert_eq!(sample["sample"]["key"], "test/ws/b");
+        send(&mut ws, json!({ "op": "unsubscribe", "id": 1 }));
+
+        // Queryable answered by the client
+        send(
+            &mut ws,
+            json!({ "op": "declare_queryable", "id": 2, "key_expr": "test/ws/q" }),
+        );
+        assert_eq!(recv(&mut ws), json!({ "op": "ack", "id": 2 }));
+        task::sleep(SLEEP).await;
+        let c_session = session.clone();
+        let querier = task::spawn(async move {
+            let replies = ztimeout!(c_session.get("test/ws/q?x=1").res_async()).unwrap();
+            let mut values = vec![];
+            while let Ok(reply) = replies.recv_async().await {
+                values.push(reply.sample.unwrap().value.to_string());
+            }
+            values
+        });
+        let query = recv(&mut ws);
+        assert_eq!(query["op"], "query");
+        assert_eq!(query["id"], 2);
+        assert_eq!(query["selector"], "test/ws/q?x=1");
+        let query_id = query["query_id"].clone();
+        send(
+            &mut ws,
+            json!({ "op": "reply", "query_id": query_id, "key_expr": "test/ws/q", "value": { "x": 1 } }),
+        );
+        send(
+            &mut ws,
+            json!({ "op": "reply_final", "query_id": query_id }),
+        );
+        assert_eq!(ztimeout!(querier), vec![r#"{"x":1}"#.to_string()]);
+
+        // Query from the client, with streamed replies
+        let _queryable = ztimeout!(session
+            .declare_queryable("test/ws/z")
+            .callback(|query| {
+                let sample = Sample::new(query.key_expr().clone(), "zenoh");
+                query.reply(Ok(sample)).res_sync().unwrap();
+            })
+            .res_async())
+        .unwrap();
+        task::sleep(SLEEP).await;
+        send(
+            &mut ws,
+            json!({ "op": "get", "id": 3, "selector": "test/ws/z", "target": "ALL", "timeout": 5000 }),
+        );
+        let reply = recv(&mut ws);
+        assert_eq!(reply["op"], "reply");
+        assert_eq!(reply["id"], 3);
+        assert_eq!(reply["reply"]["value"], "zenoh");
+        assert_eq!(recv(&mut ws), json!({ "op": "end", "id": 3 }));
+
+        // Errors
+        send(
+            &mut ws,
+            json!({ "op": "subscribe", "id": 4, "key_expr": "test//ws" }),
+        );
+        let error = recv(&mut ws);
+        assert_eq!(error["op"], "error");
+        assert_eq!(error["id"], 4);
+        send(&mut ws, json!({ "op": "unsubscribe", "id": 1 }));
+        let error = recv(&mut ws);
+        assert_eq!(error["op"], "error");
+        assert_eq!(error["id"], 1);
+        send(&mut ws, json!({ "op": "publish" }));
+        let error = recv(&mut ws);
+        assert_eq!(error["op"], "error");
+        assert_eq!(error["id"], Json::Null);
+
+        ws.close(None).unwrap();
+    });
+}
+
+#[test]
+fn rest_websocket_query_expiration() {
+    task::block_on(async {
+        let _ = env_logger::try_init();
+        let port = 18508;
+        let mut config = peer_config();
+        config.set_queries_default_timeout(Some(1000)).unwrap();
+        let runtime = start_rest(port, config).await;
+        let session = ztimeout!(zenoh::init(runtime).res_async()).unwrap();
+
+        let mut ws = connect(port);
+        send(
+            &mut ws,
+            json!({ "op": "declare_queryable", "id": 1, "key_expr": "test/ws/expire" }),
+        );
+        assert_eq!(recv(&mut ws), json!({ "op": "ack", "id": 1 }));
+        task::sleep(SLEEP).await;
+
+        // The query the client never finalizes expires, ending the query
+        let start = Instant::now();
+        let replies = ztimeout!(session
+            .get("test/ws/expire")
+            .timeout(Duration::from_secs(10))
+            .res_async())
+        .unwrap();
+        let query = recv(&mut ws);
+        assert_eq!(query["op"], "query");
+        assert!(ztimeout!(replies.recv_async()).is_err());
+        assert!(start.elapsed() < Duration::from_secs(5));
+
+        // Replying to an expired query fails
+        let query_id = query["query_id"].clone();
+        send(
+            &mut ws,
+            json!({ "op": "reply_final", "query_id": query_id }),
+        );
+        assert_eq!(recv(&mut ws)["op"], "error");
+
+        ws.close(None).unwrap();
+    });
+}
+
+#[test]
+fn rest_websocket_slow_client() {
+    task::block_on(async {
+        let _ = env_logger::try_init();
+        let port = 18509;
+        let runtime = start_rest(port, peer_config()).await;
+        let session = ztimeout!(zenoh::init(runtime).res_async()).unwrap();
+
+        let mut ws = connect(port);
+        send(
+            &mut ws,
+            json!({ "op": "subscribe", "id": 1, "key_expr": "test/ws/slow" }),
+        );
+        assert_eq!(recv(&mut ws), json!({ "op": "ack", "id": 1 }));
+
+        // The client doesn't read the samples fast enough: it's disconnected
+        let count = 4096;
+        let payload = "x".repeat(16 * 1024);
+        for _ in 0..count {
+            ztimeout!(session.put("test/ws/slow", payload.clone()).res_async()).unwrap();
+        }
+        let mut received = 0;
+        while let Ok(message) = ws.read_message() {
+            if message.is_text() {
+                received += 1;
+            }
+        }
+        assert!(received < count);
+    });
+}
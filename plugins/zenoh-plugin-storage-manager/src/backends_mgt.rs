@@ -12,13 +12,15 @@
 //   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
 //
 use super::storages_mgt::*;
+use crate::conflicts;
 use flume::Sender;
 use log::trace;
 use std::sync::Arc;
 use zenoh::prelude::r#async::*;
 use zenoh::Session;
 use zenoh_backend_traits::config::StorageConfig;
-use zenoh_result::ZResult;
+use zenoh_backend_traits::QuerySupport;
+use zenoh_result::{bail, ZResult};
 
 pub(crate) async fn create_and_start_storage(
     admin_key: String,
@@ -29,13 +31,33 @@ pub(crate) async fn create_and_start_storage(
     zenoh: Arc<Session>,
 ) -> ZResult<Sender<StorageMessage>> {
     trace!("Create storage {}", &admin_key);
+    let conflict_resolver = conflicts::resolver(&config, backend.as_ref())?;
     let storage = backend.create_storage(config.clone()).await?;
+    if conflict_resolver.is_some() && storage.capabilities().queries == QuerySupport::OnQuery {
+        bail!(
+            "Storage `{}` doesn't support the `conflict_resolution` policy: its volume `{}` can't get the stored values",
+            config.name,
+            config.volume_id
+        );
+    }
+    let retention = &config.retention;
+    let limited = retention.max_age.is_some()
+        || retention.max_keys.is_some()
+        || retention.max_bytes.is_some();
+    if limited && !storage.capabilities().eviction {
+        bail!(
+            "Storage `{}` doesn't support the `retention` policy: its volume `{}` can't evict the stored values",
+            config.name,
+            config.volume_id
+        );
+    }
     start_storage(
         storage,
         config,
         admin_key,
         in_interceptor,
         out_interceptor,
+        conflict_resolver,
         zenoh,
     )
     .await
@@ -0,0 +1,315 @@
+//
+// Copyright (c) 2022 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+
+// This module provides the built-in conflict resolution policies of the storages.
+// With the `siblings` policy, concurrent values of a key are stored as a JSON object indexed by writer, e.g.:
+// {"<zid1>":{"timestamp":"<time>/<zid1>","encoding":"text/plain","value":"<base64>"},"<zid2>":{...}}
+
+use base64::{engine::general_purpose::STANDARD as b64_std_engine, Engine};
+use crc::{Crc, CRC_64_ECMA_182};
+use log::warn;
+use serde_json::json;
+use std::collections::btree_map::Entry;
+use std::collections::BTreeMap;
+use std::str::FromStr;
+use std::sync::Arc;
+use zenoh::prelude::r#async::*;
+use zenoh::time::Timestamp;
+use zenoh_backend_traits::config::{ConflictResolution, StorageConfig};
+use zenoh_backend_traits::{ConflictResolver, Volume};
+use zenoh_result::{zerror, ZResult};
+
+// Returns the resolver implementing the conflict resolution policy of a storage, if not last writer wins
+pub(crate) fn resolver(
+    config: &StorageConfig,
+    volume: &dyn Volume,
+) -> ZResult<Option<Arc<dyn ConflictResolver>>> {
+    match &config.conflict_resolution {
+        ConflictResolution::LastWriterWins => Ok(None),
+        ConflictResolution::Siblings => Ok(Some(Arc::new(Siblings))),
+        ConflictResolution::PreferWriter(writer) => Ok(Some(Arc::new(PreferWriter {
+            writer: writer.clone(),
+        }))),
+        ConflictResolution::Volume => match volume.conflict_resolver() {
+            Some(resolver) => Ok(Some(resolver)),
+            None => Err(zerror!(
+                "Storage `{}` is configured with `conflict_resolution: \"volume\"`, but its volume `{}` has no conflict resolver",
+                config.name,
+                config.volume_id
+            )
+            .into()),
+        },
+    }
+}
+
+fn writer_of(timestamp: &Timestamp) -> String {
+    timestamp.get_id().to_string()
+}
+
+// The values of the preferred writer are only replaced by its newer values, the other ones by any newer value
+struct PreferWriter {
+    writer: String,
+}
+
+impl PreferWriter {
+    fn is_preferred(&self, sample: &Sample) -> bool {
+        sample
+            .get_timestamp()
+            .map_or(false, |ts| writer_of(ts).eq_ignore_ascii_case(&self.writer))
+    }
+}
+
+impl ConflictResolver for PreferWriter {
+    fn resolve(&self, stored: Option<&Sample>, incoming: Sample) -> Option<Sample> {
+        match stored {
+            Some(stored) => {
+                let stored_preferred = self.is_preferred(stored);
+                let incoming_preferred = self.is_preferred(&incoming);
+                if (incoming_preferred && !stored_preferred)
+                    || (incoming_preferred == stored_preferred
+                        && incoming.timestamp > stored.timestamp)
+                {
+                    Some(incoming)
+                } else {
+                    None
+                }
+            }
+            None => Some(incoming),
+        }
+    }
+}
+
+// The latest value of each writer, kept until a newer deletion of the key
+struct Siblings;
+
+struct Sibling {
+    timestamp: Timestamp,
+    value: Value,
+}
+
+fn siblings_encoding() -> Encoding {
+    Encoding::APP_JSON.with_suffix(";siblings")
+}
+
+impl Siblings {
+    // Returns the siblings stored in a sample, a sample with another encoding being a single sibling
+    fn of(sample: &Sample) -> BTreeMap<String, Sibling> {
+        let mut siblings = BTreeMap::new();
+        let timestamp = match sample.get_timestamp() {
+            Some(ts) => *ts,
+            None => return siblings,
+        };
+        if sample.encoding == siblings_encoding() {
+            match Self::parse(sample) {
+                Ok(parsed) => return parsed,
+                Err(e) => warn!("Invalid siblings on {}: {}", sample.key_expr, e),
+            }
+        }
+        siblings.insert(
+            writer_of(&timestamp),
+            Sibling {
+                timestamp,
+                value: sample.value.clone(),
+            },
+        );
+        siblings
+    }
+
+    fn parse(sample: &Sample) -> ZResult<BTreeMap<String, Sibling>> {
+        let object: serde_json::Map<String, serde_json::Value> =
+            serde_json::from_slice(&sample.payload.contiguous()).map_err(|e| zerror!("{}", e))?;
+        let mut siblings = BTreeMap::new();
+        for (writer, entry) in object {
+            let field = |name: &str| {
+                entry[name]
+                    .as_str()
+                    .ok_or_else(|| zerror!("missing `{}` for writer {}", name, writer))
+            };
+            let timestamp = Timestamp::from_str(field("timestamp")?)
+                .map_err(|e| zerror!("invalid timestamp for writer {}: {:?}", writer, e))?;
+            let payload = b64_std_engine
+                .decode(field("value")?)
+                .map_err(|e| zerror!("invalid value for writer {}: {}", writer, e))?;
+            let encoding = Encoding::from(field("encoding")?.to_string());
+            let value = Value::from(payload).encoding(encoding);
+            siblings.insert(writer, Sibling { timestamp, value });
+        }
+        Ok(siblings)
+    }
+
+    // Returns the sample storing the siblings, with the latest timestamp among them
+    fn to_sample(key_expr: KeyExpr<'static>, siblings: BTreeMap<String, Sibling>) -> Sample {
+        let timestamp = siblings.values().map(|s| s.timestamp).max().unwrap();
+        if siblings.len() == 1 {
+            let sibling = siblings.into_values().next().unwrap();
+            return Sample::new(key_expr, sibling.value).with_timestamp(timestamp);
+        }
+        let mut object = serde_json::Map::new();
+        for (writer, sibling) in siblings {
+            object.insert(
+                writer,
+                json!({
+                    "timestamp": sibling.timestamp.to_string(),
+                    "encoding": sibling.value.encoding.to_string(),
+                    "value": b64_std_engine.encode(sibling.value.payload.contiguous()),
+                }),
+            );
+        }
+        let value = Value::from(serde_json::Value::Object(object).to_string().into_bytes())
+            .encoding(siblings_encoding());
+        Sample::new(key_expr, value).with_timestamp(timestamp)
+    }
+}
+
+impl ConflictResolver for Siblings {
+    fn resolve(&self, stored: Option<&Sample>, incoming: Sample) -> Option<Sample> {
+        let stored = match stored {
+            Some(stored) => stored,
+            None => return Some(incoming),
+        };
+        let mut siblings = Self::of(stored);
+        if incoming.kind == SampleKind::Delete {
+            // a deletion only removes the siblings older than it
+            let deleted = match incoming.get_timestamp() {
+                Some(ts) => *ts,
+                None => return Some(incoming),
+            };
+            let count = siblings.len();
+            siblings.retain(|_, sibling| sibling.timestamp > deleted);
+            return if siblings.is_empty() {
+                Some(incoming)
+            } else if siblings.len() == count {
+                None
+            } else {
+                Some(Self::to_sample(incoming.key_expr, siblings))
+            };
+        }
+        let mut changed = false;
+        for (writer, sibling) in Self::of(&incoming) {
+            match siblings.entry(writer) {
+                Entry::Vacant(v) => {
+                    v.insert(sibling);
+                    changed = true;
+                }
+                Entry::Occupied(mut o) => {
+                    if o.get().timestamp < sibling.timestamp {
+                        o.insert(sibling);
+                        changed = true;
+                    }
+                }
+            }
+        }
+        if !changed {
+            return None;
+        }
+        Some(Self::to_sample(incoming.key_expr, siblings))
+    }
+
+    // the timestamp of several siblings is the latest one: their writers and timestamps are hashed
+    fn content_hash(&self, sample: &Sample) -> Option<u64> {
+        if sample.encoding != siblings_encoding() {
+            return None;
+        }
+        let crc64 = Crc::<u64>::new(&CRC_64_ECMA_182);
+        let mut hasher = crc64.digest();
+        for (writer, sibling) in Self::of(sample) {
+            hasher.update(
+                format!("{}-{};", writer, sibling.timestamp.get_time().as_u64()).as_bytes(),
+            );
+        }
+        Some(hasher.finalize())
+    }
+}
+
+#[cfg(test)]
+pub(crate) fn siblings_resolver() -> Arc<dyn ConflictResolver> {
+    Arc::new(Siblings)
+}
+
+#[cfg(test)]
+fn sample_from(writer: u8, time: u64, value: &str) -> Sample {
+    use std::convert::TryFrom;
+    let id = zenoh::time::TimestampId::try_from([writer]).unwrap();
+    let timestamp = Timestamp::new(
+        zenoh::time::NTP64::from(std::time::Duration::from_secs(time)),
+        id,
+    );
+    Sample::new(KeyExpr::from_str("demo/a").unwrap(), Value::from(value)).with_timestamp(timestamp)
+}
+
+#[test]
+fn test_prefer_writer() {
+    let resolver = PreferWriter {
+        writer: "AA".into(),
+    };
+    let preferred = sample_from(0xaa, 1, "preferred");
+    let newer = sample_from(0xbb, 2, "newer");
+    let newest = sample_from(0xbb, 3, "newest");
+
+    assert!(resolver.resolve(Some(&preferred), newer.clone()).is_none());
+    assert!(resolver.resolve(Some(&newer), preferred.clone()).is_some());
+    assert!(resolver.resolve(Some(&newer), newest.clone()).is_some());
+    assert!(resolver.resolve(Some(&newest), newer.clone()).is_none());
+    assert!(resolver.resolve(None, newer).is_some());
+}
+
+#[test]
+fn test_siblings() {
+    let resolver = Siblings;
+    let a1 = sample_from(0xaa, 1, "a1");
+    let b2 = sample_from(0xbb, 2, "b2");
+    let a3 = sample_from(0xaa, 3, "a3");
+
+    // the result doesn't depend on the order of reception
+    let merged = resolver.resolve(Some(&a1), b2.clone()).unwrap();
+    let merged = resolver.resolve(Some(&merged), a3.clone()).unwrap();
+    let other = resolver.resolve(Some(&a3), b2.clone()).unwrap();
+    assert!(resolver.resolve(Some(&other), a1.clone()).is_none());
+    assert_eq!(merged.encoding, siblings_encoding());
+    assert_eq!(merged.payload.contiguous(), other.payload.contiguous());
+    assert_eq!(merged.timestamp, a3.timestamp);
+
+    let siblings = Siblings::of(&merged);
+    assert_eq!(siblings.len(), 2);
+    assert_eq!(
+        siblings[&writer_of(a3.get_timestamp().unwrap())]
+            .value
+            .payload
+            .contiguous(),
+        a3.payload.contiguous()
+    );
+
+    // the sets of siblings are hashed, unlike single values
+    let hash = resolver.content_hash(&merged);
+    assert!(hash.is_some());
+    assert_eq!(hash, resolver.content_hash(&other));
+    let a1_b2 = resolver.resolve(Some(&a1), b2.clone()).unwrap();
+    assert_ne!(hash, resolver.content_hash(&a1_b2));
+    assert!(resolver.content_hash(&a3).is_none());
+
+    // a deletion only removes the older siblings
+    let delete = |time| {
+        let mut delete = sample_from(0xbb, time, "");
+        delete.kind = SampleKind::Delete;
+        delete
+    };
+    let resolved = resolver.resolve(Some(&merged), delete(2)).unwrap();
+    assert_eq!(resolved.kind, SampleKind::Put);
+    assert_eq!(resolved.payload.contiguous(), a3.payload.contiguous());
+    assert_eq!(resolved.timestamp, a3.timestamp);
+    assert!(resolver.resolve(Some(&merged), delete(1)).is_none());
+    assert!(resolver.resolve(Some(&a3), delete(2)).is_none());
+    let resolved = resolver.resolve(Some(&merged), delete(4)).unwrap();
+    assert_eq!(resolved.kind, SampleKind::Delete);
+}
@@ -25,7 +25,8 @@ use libloading::Library;
 use memory_backend::create_memory_backend;
 use std::collections::HashMap;
 use std::convert::TryFrom;
-use std::path::PathBuf;
+use std::ffi::OsStr;
+use std::path::{Path, PathBuf};
 use std::sync::atomic::AtomicBool;
 use std::sync::Arc;
 use std::sync::Mutex;
@@ -38,13 +39,16 @@ use zenoh_backend_traits::CreateVolume;
 use zenoh_backend_traits::CREATE_VOLUME_FN_NAME;
 use zenoh_backend_traits::{config::*, Volume};
 use zenoh_core::zlock;
-use zenoh_result::{bail, ZResult};
+use zenoh_result::{bail, zerror, ZResult};
 use zenoh_util::LibLoader;
 
 mod backends_mgt;
 use backends_mgt::*;
+mod conflicts;
 mod memory_backend;
 mod replica;
+mod retention;
+mod snapshot;
 mod storages_mgt;
 
 const GIT_VERSION: &str = git_version::git_version!(prefix = "v", cargo_prefix = "v");
@@ -78,6 +82,7 @@ struct StorageRuntimeInner {
     runtime: Runtime,
     session: Arc<Session>,
     lib_loader: LibLoader,
+    snapshots_dir: Option<PathBuf>,
     volumes: HashMap<String, VolumeHandle>,
     storages: HashMap<String, HashMap<String, Sender<StorageMessage>>>,
 }
@@ -96,6 +101,7 @@ impl StorageRuntimeInner {
         let PluginConfig {
             name,
             backend_search_dirs,
+            snapshots_dir,
             volumes,
             storages,
             ..
@@ -110,6 +116,7 @@ impl StorageRuntimeInner {
             runtime,
             session,
             lib_loader,
+            snapshots_dir: snapshots_dir.map(PathBuf::from),
             volumes: Default::default(),
             storages: Default::default(),
         };
@@ -381,6 +388,28 @@ impl RunningPluginTrait for StorageRuntime {
                                 responses.push(zenoh::plugins::Response::new(key.clone(), value))
                             }
                         }
+                        for operation in ["snapshot", "restore", "clear", "align"] {
+                            with_extended_string(key, &["/", operation], |key| {
+                                // operations are only triggered by queries on their exact key
+                                if selector.key_expr.as_str() == key.as_str() {
+                                    let result = storage_operation(
+                                        handle,
+                                        operation,
+                                        selector,
+                                        guard.snapshots_dir.as_deref(),
+                                    );
+                                    responses.push(zenoh::plugins::Response::new(
+                                        key.clone(),
+                                        match result {
+                                            Ok(entries) => {
+                                                serde_json::json!({ "entries": entries })
+                                            }
+                                            Err(e) => serde_json::json!({ "error": e.to_string() }),
+                                        },
+                                    ))
+                                }
+                            })
+                        }
                     })
                 }
             }
@@ -389,6 +418,60 @@ impl RunningPluginTrait for StorageRuntime {
     }
 }
 
+// Performs an admin operation on a storage:
+// - snapshot?file=<name>: writes the content of the storage to the file <name> of the snapshots directory
+// - restore?file=<name>: inserts the content of the file <name> of the snapshots directory in the storage
+// - clear: removes all the entries of the storage
+// - align: forces a full alignment of a replicated storage with the remote storages
+// Returns the number of entries written, restored, cleared or fetched.
+fn storage_operation(
+    handle: &Sender<StorageMessage>,
+    operation: &str,
+    selector: &Selector,
+    snapshots_dir: Option<&Path>,
+) -> ZResult<usize> {
+    // the destructive operations must be explicitly confirmed
+    if (operation == "clear" || operation == "restore")
+        && selector
+            .parameters_stringmap()?
+            .get("confirm")
+            .map(|c| c.as_str())
+            != Some("true")
+    {
+        bail!("missing `confirm=true` parameter");
+    }
+    let (tx, rx) = async_std::channel::bounded(1);
+    let message = if operation == "clear" {
+        StorageMessage::Clear(tx)
+    } else if operation == "align" {
+        StorageMessage::Align(tx)
+    } else {
+        let snapshots_dir = match snapshots_dir {
+            Some(dir) => dir,
+            None => bail!("`snapshots_dir` isn't configured"),
+        };
+        let parameters = selector.parameters_stringmap()?;
+        let file = match parameters.get("file") {
+            Some(file) => file,
+            None => bail!("missing `file` parameter"),
+        };
+        // the snapshots can't be outside of the snapshots directory
+        if Path::new(file).file_name() != Some(OsStr::new(file)) {
+            bail!("invalid snapshot file name: {}", file);
+        }
+        let path = snapshots_dir.join(file);
+        if operation == "snapshot" {
+            StorageMessage::Snapshot(path, tx)
+        } else {
+            StorageMessage::Restore(path, tx)
+        }
+    };
+    if handle.send(message).is_err() {
+        bail!("storage is stopped");
+    }
+    task::block_on(rx.recv()).map_err(|_| zerror!("storage is stopped"))?
+}
+
 const BACKEND_LIB_PREFIX: &str = "zbackend_";
 const MEMORY_BACKEND_NAME: &str = "memory";
 
@@ -405,3 +488,29 @@ fn with_extended_string<R, F: FnMut(&mut String) -> R>(
     prefix.truncate(prefix_len);
     result
 }
+
+#[test]
+fn test_storage_operation_confirm() {
+    let (handle, messages) = flume::unbounded();
+    let dir = std::env::temp_dir();
+    for (operation, selector) in [
+        ("clear", "demo/clear"),
+        ("clear", "demo/clear?confirm=false"),
+        ("restore", "demo/restore?file=a.jsonl"),
+    ] {
+        let selector = Selector::try_from(selector).unwrap();
+        assert!(storage_operation(&handle, operation, &selector, Some(&dir)).is_err());
+    }
+    assert!(messages.try_recv().is_err());
+
+    let storage = std::thread::spawn(move || match messages.recv().unwrap() {
+        StorageMessage::Clear(tx) => task::block_on(tx.send(Ok(3))).unwrap(),
+        _ => panic!("expected a Clear message"),
+    });
+    let selector = Selector::try_from("demo/clear?confirm=true").unwrap();
+    assert_eq!(
+        storage_operation(&handle, "clear", &selector, Some(&dir)).unwrap(),
+        3
+    );
+    storage.join().unwrap();
+}
@@ -17,8 +17,9 @@ use log::{debug, trace};
 use std::collections::hash_map::Entry;
 use std::collections::HashMap;
 use std::sync::Arc;
-use std::time::{Duration, Instant};
+use std::time::{Duration, Instant, SystemTime};
 use zenoh::prelude::r#async::*;
+use zenoh::selector::TimeRange;
 use zenoh::time::Timestamp;
 use zenoh_backend_traits::config::{StorageConfig, VolumeConfig};
 use zenoh_backend_traits::*;
@@ -221,6 +222,15 @@ impl Storage for MemoryStorage {
         }
     }
 
+    fn capabilities(&self) -> Capability {
+        Capability {
+            history: History::Latest,
+            persistence: Persistence::Volatile,
+            queries: QuerySupport::GetWithWildcards,
+            eviction: true,
+        }
+    }
+
     async fn on_query(&mut self, query: Query) -> ZResult<()> {
         trace!("on_query for {}", query.key_expr());
         if !query.key_expr().is_wild() {
@@ -250,6 +260,50 @@ impl Storage for MemoryStorage {
         }
         Ok(result)
     }
+
+    async fn get(
+        &mut self,
+        key_expr: OwnedKeyExpr,
+        time_range: Option<TimeRange<SystemTime>>,
+    ) -> ZResult<Vec<Sample>> {
+        trace!("get for {} (time range: {:?})", key_expr, time_range);
+        // only the latest value of each key is stored: it's returned if it's within the time range
+        let in_range = |ts: &Timestamp| match &time_range {
+            Some(range) => range.contains(ts.get_time().to_system_time()),
+            None => true,
+        };
+        let map = self.map.read().await;
+        if !key_expr.is_wild() {
+            return Ok(match map.get(&key_expr) {
+                Some(Present { sample, ts }) if in_range(ts) => vec![sample.clone()],
+                _ => vec![],
+            });
+        }
+        Ok(map
+            .values()
+            .filter_map(|stored_value| match stored_value {
+                Present { sample, ts } if key_expr.intersects(&sample.key_expr) && in_range(ts) => {
+                    Some(sample.clone())
+                }
+                _ => None,
+            })
+            .collect())
+    }
+
+    async fn evict(&mut self, key: OwnedKeyExpr, timestamp: Timestamp) -> ZResult<()> {
+        if let Entry::Occupied(o) = self.map.write().await.entry(key) {
+            if o.get().ts() == &timestamp {
+                if let Removed {
+                    ts: _,
+                    cleanup_handle,
+                } = o.remove()
+                {
+                    cleanup_handle.defuse();
+                }
+            }
+        }
+        Ok(())
+    }
 }
 
 impl Drop for MemoryStorage {
@@ -272,3 +326,116 @@ impl Timed for TimedCleanup {
         self.map.write().await.remove(&self.key);
     }
 }
+
+#[cfg(test)]
+pub(crate) async fn create_test_storage(
+    key_expr: &str,
+    strip_prefix: Option<&str>,
+) -> Box<dyn Storage> {
+    let mut backend = create_memory_backend(VolumeConfig {
+        name: "memory".into(),
+        backend: None,
+        paths: None,
+        required: false,
+        rest: serde_json::Map::new(),
+    })
+    .unwrap();
+    backend
+        .create_storage(StorageConfig {
+            name: "test".into(),
+            key_expr: OwnedKeyExpr::new(key_expr).unwrap(),
+            complete: false,
+            strip_prefix: strip_prefix.map(|prefix| OwnedKeyExpr::new(prefix).unwrap()),
+            volume_id: "memory".into(),
+            volume_cfg: serde_json::Value::Null,
+            replica_config: None,
+            retention: zenoh_backend_traits::config::RetentionConfig::default(),
+            conflict_resolution: zenoh_backend_traits::config::ConflictResolution::default(),
+        })
+        .await
+        .unwrap()
+}
+
+// A sample timestamped `age` ago
+#[cfg(test)]
+pub(crate) fn test_sample(key_expr: &str, value: &str, age: Duration) -> Sample {
+    use std::convert::TryFrom;
+    let id = zenoh::time::TimestampId::try_from([1]).unwrap();
+    let time = (SystemTime::now() - age)
+        .duration_since(SystemTime::UNIX_EPOCH)
+        .unwrap();
+    Sample::new(OwnedKeyExpr::new(key_expr).unwrap(), Value::from(value))
+        .with_timestamp(Timestamp::new(zenoh::time::NTP64::from(time), id))
+}
+
+#[cfg(test)]
+fn values_of(mut samples: Vec<Sample>) -> Vec<String> {
+    samples.sort_by(|a, b| a.key_expr.as_str().cmp(b.key_expr.as_str()));
+    samples
+        .into_iter()
+        .map(|sample| String::from_utf8(sample.payload.contiguous().into_owned()).unwrap())
+        .collect()
+}
+
+#[test]
+fn test_get_with_wildcards() {
+    async_std::task::block_on(async {
+        let mut storage = create_test_storage("demo/**", None).await;
+        assert_eq!(
+            storage.capabilities().queries,
+            QuerySupport::GetWithWildcards
+        );
+        for (key, value) in [("demo/a/1", "a1"), ("demo/a/2", "a2"), ("demo/b/1", "b1")] {
+            storage
+                .on_sample(test_sample(key, value, Duration::ZERO))
+                .await
+                .unwrap();
+        }
+        let key = |k: &str| OwnedKeyExpr::new(k).unwrap();
+
+        let found = storage.get(key("demo/a/1"), None).await.unwrap();
+        assert_eq!(values_of(found), ["a1"]);
+        let found = storage.get(key("demo/a/*"), None).await.unwrap();
+        assert_eq!(values_of(found), ["a1", "a2"]);
+        let found = storage.get(key("demo/*/1"), None).await.unwrap();
+        assert_eq!(values_of(found), ["a1", "b1"]);
+        let found = storage.get(key("demo/**"), None).await.unwrap();
+        assert_eq!(values_of(found), ["a1", "a2", "b1"]);
+        assert!(storage.get(key("demo/c/*"), None).await.unwrap().is_empty());
+
+        // the deleted keys aren't returned
+        let mut delete = test_sample("demo/a/2", "", Duration::ZERO);
+        delete.kind = SampleKind::Delete;
+        storage.on_sample(delete).await.unwrap();
+        let found = storage.get(key("demo/a/*"), None).await.unwrap();
+        assert_eq!(values_of(found), ["a1"]);
+        assert!(storage.get(key("demo/a/2"), None).await.unwrap().is_empty());
+    })
+}
+
+#[test]
+fn test_get_time_range() {
+    async_std::task::block_on(async {
+        let mut storage = create_test_storage("demo/**", None).await;
+        let hour = Duration::from_secs(3600);
+        storage
+            .on_sample(test_sample("demo/old", "old", 2 * hour))
+            .await
+            .unwrap();
+        storage
+            .on_sample(test_sample("demo/new", "new", Duration::ZERO))
+            .await
+            .unwrap();
+        let key = |k: &str| OwnedKeyExpr::new(k).unwrap();
+        let range = |r: &str| Some(r.parse::<TimeRange>().unwrap().resolve());
+
+        let found = storage.get(key("demo/*"), range("[now(-1h)..]")).await;
+        assert_eq!(values_of(found.unwrap()), ["new"]);
+        let found = storage.get(key("demo/*"), range("[..now(-1h)]")).await;
+        assert_eq!(values_of(found.unwrap()), ["old"]);
+        let found = storage.get(key("demo/old"), range("[now(-1h)..]")).await;
+        assert!(found.unwrap().is_empty());
+        let found = storage.get(key("demo/*"), range("[..]")).await;
+        assert_eq!(values_of(found.unwrap()), ["new", "old"]);
+    })
+}
@@ -12,7 +12,7 @@
 //   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
 //
 
-use super::{Digest, EraType, LogEntry, Snapshotter};
+use super::{Digest, EraType, LogEntry, ReplicaStatus, Snapshotter};
 use super::{CONTENTS, ERA, INTERVALS, SUBINTERVALS};
 use async_std::sync::{Arc, RwLock};
 use flume::{Receiver, Sender};
@@ -32,6 +32,7 @@ pub struct Aligner {
     rx_digest: Receiver<(String, Digest)>,
     tx_sample: Sender<Sample>,
     digests_processed: RwLock<HashSet<u64>>,
+    status: Arc<RwLock<ReplicaStatus>>,
 }
 
 impl Aligner {
@@ -41,6 +42,7 @@ impl Aligner {
         rx_digest: Receiver<(String, Digest)>,
         tx_sample: Sender<Sample>,
         snapshotter: Arc<Snapshotter>,
+        status: Arc<RwLock<ReplicaStatus>>,
     ) {
         let aligner = Aligner {
             session,
@@ -49,6 +51,7 @@ impl Aligner {
             rx_digest,
             tx_sample,
             digests_processed: RwLock::new(HashSet::new()),
+            status,
         };
         aligner.start().await;
     }
@@ -66,6 +69,7 @@ impl Aligner {
                     "[ALIGNER]Skipping matching digest: {}",
                     incoming_digest.checksum
                 );
+                self.status.write().await.aligned_with(&from);
                 continue;
             } else {
                 // process this digest
@@ -87,12 +91,17 @@ impl Aligner {
     async fn process_incoming_digest(&self, other: Digest, from: &str) {
         let checksum = other.checksum;
         let timestamp = other.timestamp;
+        self.status.write().await.alignment_started(from);
         let (missing_content, no_content_err) = self.get_missing_content(&other, from).await;
         trace!("[ALIGNER] Missing content is {:?}", missing_content);
 
         // If missing content is not identified, it showcases some problem
         // The problem will be addressed in the future rounds, hence will not count as processed
-        if !missing_content.is_empty() {
+        if missing_content.is_empty() {
+            if no_content_err {
+                self.status.write().await.aligned_with(from);
+            }
+        } else {
             let (missing_data, no_data_err) = self
                 .get_missing_data(&missing_content, timestamp, from)
                 .await;
@@ -112,6 +121,8 @@ impl Aligner {
             if no_content_err && no_data_err {
                 let mut processed = self.digests_processed.write().await;
                 (*processed).insert(checksum);
+                drop(processed);
+                self.status.write().await.aligned_with(from);
             }
         }
     }
@@ -174,6 +185,10 @@ impl Aligner {
         // get era diff
         let (diff_intervals, no_era_err) =
             self.get_interval_diff(era, this, other, &other_rep).await;
+        self.status
+            .write()
+            .await
+            .misaligned_intervals(&other_rep, diff_intervals.len());
         // get interval diff
         let (diff_subintervals, no_int_err) = self
             .get_subinterval_diff(era, diff_intervals, this, other, &other_rep)
@@ -328,6 +343,7 @@ impl Aligner {
                                 sample.key_expr.as_str(),
                                 sample.value
                             );
+                            self.status.write().await.bytes_fetched += sample.payload.len() as u64;
                             return_val.push(sample);
                         }
                         Err(err) => {
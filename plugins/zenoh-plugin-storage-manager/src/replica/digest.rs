@@ -61,11 +61,18 @@ pub struct SubInterval {
 pub struct LogEntry {
     pub timestamp: Timestamp,
     pub key: OwnedKeyExpr,
+    // hash of the stored value, when its timestamp doesn't identify it (see ConflictResolver::content_hash)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub content_hash: Option<u64>,
 }
 
 impl Ord for LogEntry {
     fn cmp(&self, other: &Self) -> std::cmp::Ordering {
-        self.timestamp.cmp(&other.timestamp)
+        // consistent with Eq, for the sets of entries: ordered by timestamp first
+        self.timestamp
+            .cmp(&other.timestamp)
+            .then_with(|| self.key.as_str().cmp(other.key.as_str()))
+            .then_with(|| self.content_hash.cmp(&other.content_hash))
     }
 }
 
@@ -102,7 +109,10 @@ trait Checksum {
 
 impl Checksum for LogEntry {
     fn format_content(&self) -> String {
-        format!("{}-{}", self.timestamp, self.key)
+        match self.content_hash {
+            Some(hash) => format!("{}-{}-{:x}", self.timestamp, self.key, hash),
+            None => format!("{}-{}", self.timestamp, self.key),
+        }
     }
 }
 
@@ -479,42 +489,26 @@ impl Digest {
                 entry.timestamp,
                 current.config.sub_intervals,
             );
-            subintervals_to_update.insert(subinterval);
             let interval = Digest::get_interval(subinterval, current.config.sub_intervals);
-            intervals_to_update.insert(interval);
             let era = Digest::get_era(&current.config, latest_interval, interval);
-            eras_to_update.insert(era.clone());
 
-            if current.subintervals.contains_key(&subinterval) {
-                current
-                    .subintervals
-                    .get_mut(&subinterval)
-                    .unwrap()
-                    .content
-                    .retain(|x| {
-                        x.timestamp.get_time() != entry.timestamp.get_time()
-                            && x.timestamp.get_id() != entry.timestamp.get_id()
-                            && x.key != entry.key
-                    });
+            // only drop the subinterval (and then the interval) from its parent once empty
+            if let Some(sub) = current.subintervals.get_mut(&subinterval) {
+                sub.content
+                    .retain(|x| x.timestamp != entry.timestamp || x.key != entry.key);
                 subintervals_to_update.insert(subinterval);
-            }
-            if current.intervals.contains_key(&interval) {
-                current
-                    .intervals
-                    .get_mut(&interval)
-                    .unwrap()
-                    .content
-                    .retain(|&x| x != subinterval);
-                intervals_to_update.insert(interval);
-            }
-            if current.eras.contains_key(&era) {
-                current
-                    .eras
-                    .get_mut(&era)
-                    .unwrap()
-                    .content
-                    .retain(|&x| x != interval);
-                eras_to_update.insert(era.clone());
+                if let Some(int) = current.intervals.get_mut(&interval) {
+                    if sub.content.is_empty() {
+                        int.content.retain(|&x| x != subinterval);
+                    }
+                    intervals_to_update.insert(interval);
+                    if let Some(era_content) = current.eras.get_mut(&era) {
+                        if int.content.is_empty() {
+                            era_content.content.retain(|&x| x != interval);
+                        }
+                        eras_to_update.insert(era);
+                    }
+                }
             }
         }
         (
@@ -815,6 +809,7 @@ fn test_create_digest_with_initial_hot() {
         vec![LogEntry {
             timestamp: Timestamp::from_str("2022-12-21T15:00:00.000000000Z/01").unwrap(),
             key: OwnedKeyExpr::from_str("demo/example/a").unwrap(),
+            content_hash: None,
         }],
         1671634800,
     );
@@ -848,6 +843,7 @@ fn test_create_digest_with_initial_hot() {
                 content: BTreeSet::from([LogEntry {
                     timestamp: Timestamp::from_str("2022-12-21T15:00:00.000000000Z/01").unwrap(),
                     key: OwnedKeyExpr::from_str("demo/example/a").unwrap(),
+                    content_hash: None,
                 }]),
             },
         )]),
@@ -871,6 +867,7 @@ fn test_create_digest_with_initial_warm() {
         vec![LogEntry {
             timestamp: Timestamp::from_str("2022-12-21T15:00:00.000000000Z/01").unwrap(),
             key: OwnedKeyExpr::from_str("demo/example/a").unwrap(),
+            content_hash: None,
         }],
         1671634810,
     );
@@ -904,6 +901,7 @@ fn test_create_digest_with_initial_warm() {
                 content: BTreeSet::from([LogEntry {
                     timestamp: Timestamp::from_str("2022-12-21T15:00:00.000000000Z/01").unwrap(),
                     key: OwnedKeyExpr::from_str("demo/example/a").unwrap(),
+                    content_hash: None,
                 }]),
             },
         )]),
@@ -927,6 +925,7 @@ fn test_create_digest_with_initial_cold() {
         vec![LogEntry {
             timestamp: Timestamp::from_str("2022-12-21T15:00:00.000000000Z/01").unwrap(),
             key: OwnedKeyExpr::from_str("demo/example/a").unwrap(),
+            content_hash: None,
         }],
         1671634910,
     );
@@ -960,6 +959,7 @@ fn test_create_digest_with_initial_cold() {
                 content: BTreeSet::from([LogEntry {
                     timestamp: Timestamp::from_str("2022-12-21T15:00:00.000000000Z/01").unwrap(),
                     key: OwnedKeyExpr::from_str("demo/example/a").unwrap(),
+                    content_hash: None,
                 }]),
             },
         )]),
@@ -991,6 +991,7 @@ fn test_update_digest_add_content() {
         HashSet::from([LogEntry {
             timestamp: Timestamp::from_str("2022-12-21T15:00:00.000000000Z/01").unwrap(),
             key: OwnedKeyExpr::from_str("demo/example/a").unwrap(),
+            content_hash: None,
         }]),
         HashSet::new(),
     ));
@@ -1024,6 +1025,7 @@ fn test_update_digest_add_content() {
                 content: BTreeSet::from([LogEntry {
                     timestamp: Timestamp::from_str("2022-12-21T15:00:00.000000000Z/01").unwrap(),
                     key: OwnedKeyExpr::from_str("demo/example/a").unwrap(),
+                    content_hash: None,
                 }]),
             },
         )]),
@@ -1068,6 +1070,7 @@ fn test_update_digest_remove_content() {
                         timestamp: Timestamp::from_str("2022-12-21T15:00:00.000000000Z/01")
                             .unwrap(),
                         key: OwnedKeyExpr::from_str("demo/example/a").unwrap(),
+                        content_hash: None,
                     }]),
                 },
             )]),
@@ -1078,6 +1081,7 @@ fn test_update_digest_remove_content() {
         HashSet::from([LogEntry {
             timestamp: Timestamp::from_str("2022-12-21T15:00:00.000000000Z/01").unwrap(),
             key: OwnedKeyExpr::from_str("demo/example/a").unwrap(),
+            content_hash: None,
         }]),
     ));
     let expected = Digest {
@@ -1119,6 +1123,7 @@ fn test_update_remove_digest() {
         HashSet::from([LogEntry {
             timestamp: Timestamp::from_str("2022-12-21T12:00:00.000000000Z/01").unwrap(),
             key: OwnedKeyExpr::from_str("a/b/c").unwrap(),
+            content_hash: None,
         }]),
         HashSet::new(),
     ));
@@ -1132,6 +1137,7 @@ fn test_update_remove_digest() {
         HashSet::from([LogEntry {
             timestamp: Timestamp::from_str("2022-12-21T12:00:00.000000000Z/01").unwrap(),
             key: OwnedKeyExpr::from_str("a/b/c").unwrap(),
+            content_hash: None,
         }]),
     ));
     assert_eq!(created, removed);
@@ -1143,6 +1149,7 @@ fn test_update_remove_digest() {
         HashSet::from([LogEntry {
             timestamp: Timestamp::from_str("2022-12-21T12:00:00.000000000Z/01").unwrap(),
             key: OwnedKeyExpr::from_str("a/b/c").unwrap(),
+            content_hash: None,
         }]),
         HashSet::new(),
     ));
@@ -20,7 +20,7 @@ use async_std::sync::RwLock;
 use async_std::task::sleep;
 use flume::{Receiver, Sender};
 use futures::{pin_mut, select, FutureExt};
-use log::{debug, error, trace};
+use log::{debug, error, trace, warn};
 use std::collections::{HashMap, HashSet};
 use std::str;
 use std::str::FromStr;
@@ -29,18 +29,21 @@ use urlencoding::encode;
 use zenoh::prelude::r#async::*;
 use zenoh::time::Timestamp;
 use zenoh::Session;
-use zenoh_backend_traits::config::ReplicaConfig;
+use zenoh_backend_traits::config::{ReplicaConfig, RetentionConfig};
 
 pub mod align_queryable;
 pub mod aligner;
 pub mod digest;
 pub mod snapshotter;
+pub mod status;
 pub mod storage;
 
 pub use align_queryable::AlignQueryable;
 pub use aligner::Aligner;
 pub use digest::{Digest, DigestConfig, EraType, LogEntry};
-pub use snapshotter::{ReplicationInfo, Snapshotter};
+pub use snapshotter::{LogUpdate, ReplicationInfo, Snapshotter};
+pub use status::ReplicaStatus;
+pub(crate) use storage::StripPrefix;
 pub use storage::{ReplicationService, StorageService};
 
 const ERA: &str = "era";
@@ -69,14 +72,17 @@ pub struct Replica {
     key_expr: OwnedKeyExpr,
     replica_config: ReplicaConfig,
     digests_published: RwLock<HashSet<u64>>, // checksum of all digests generated and published by this replica
+    status: Arc<RwLock<ReplicaStatus>>,
 }
 
 impl Replica {
     // This function starts the replica by initializing all the components
+    #[allow(clippy::too_many_arguments)]
     pub async fn start(
         config: ReplicaConfig,
+        retention: RetentionConfig,
         session: Arc<Session>,
-        store_intercept: StoreIntercept,
+        mut store_intercept: StoreIntercept,
         key_expr: OwnedKeyExpr,
         complete: bool,
         name: &str,
@@ -90,6 +96,7 @@ impl Replica {
                 return;
             }
         };
+        let startup_log = Replica::startup_log(&mut store_intercept, &startup_entries).await;
 
         let replica = Replica {
             name: name.to_string(),
@@ -97,6 +104,7 @@ impl Replica {
             key_expr,
             replica_config: config,
             digests_published: RwLock::new(HashSet::new()),
+            status: Arc::new(RwLock::new(ReplicaStatus::default())),
         };
 
         // Create channels for communication between components
@@ -106,10 +114,13 @@ impl Replica {
         let (tx_sample, rx_sample) = flume::unbounded();
         // channel for storage to send logging information back
         let (tx_log, rx_log) = flume::unbounded();
+        // channel for storage to send the evicted entries
+        let (tx_eviction, rx_eviction) = flume::unbounded();
 
         let config = replica.replica_config.clone();
         // snapshotter
-        let snapshotter = Arc::new(Snapshotter::new(rx_log, &startup_entries, &config).await);
+        let snapshotter =
+            Arc::new(Snapshotter::new(rx_log, rx_eviction, &startup_log, &config).await);
         // digest sub
         let digest_sub = replica.start_digest_sub(tx_digest).fuse();
         // queryable for alignment
@@ -128,6 +139,7 @@ impl Replica {
             rx_digest,
             tx_sample,
             snapshotter.clone(),
+            replica.status.clone(),
         )
         .fuse();
         // digest pub
@@ -141,6 +153,8 @@ impl Replica {
             empty_start: startup_entries.is_empty(),
             aligner_updates: rx_sample,
             log_propagation: tx_log,
+            log_eviction: tx_eviction,
+            status: replica.status.clone(),
         };
         // channel to pipe the receiver to storage
         let storage_task = StorageService::start(
@@ -151,6 +165,7 @@ impl Replica {
             store_intercept,
             rx,
             Some(replication),
+            retention,
         )
         .fuse();
 
@@ -173,6 +188,32 @@ impl Replica {
         )
     }
 
+    // The log of the entries of the storage at startup, with the hashes of their contents if the conflict
+    // resolver requires them
+    async fn startup_log(
+        store_intercept: &mut StoreIntercept,
+        entries: &[(OwnedKeyExpr, Timestamp)],
+    ) -> Vec<LogUpdate> {
+        let mut log = Vec::with_capacity(entries.len());
+        for (key, timestamp) in entries {
+            let hash = match &store_intercept.conflict_resolver {
+                Some(resolver) => match store_intercept.storage.get(key.clone(), None).await {
+                    Ok(samples) => samples
+                        .iter()
+                        .find(|sample| sample.timestamp.as_ref() == Some(timestamp))
+                        .and_then(|sample| resolver.content_hash(sample)),
+                    Err(e) => {
+                        warn!("[REPLICA] Can't hash the content of {}: {}", key, e);
+                        None
+                    }
+                },
+                None => None,
+            };
+            log.push((key.clone(), *timestamp, hash));
+        }
+        log
+    }
+
     // Create a subscriber to get digests of remote replicas
     // Subscribe on <align_prefix>/<encoded_key_expr>/**
     pub async fn start_digest_sub(&self, tx: Sender<(String, Digest)>) {
@@ -218,6 +259,10 @@ impl Replica {
                 }
             };
             let ts = digest.timestamp;
+            self.status
+                .write()
+                .await
+                .digest_received(from, digest.checksum, &ts);
             let to_be_processed = self
                 .processing_needed(
                     from,
@@ -262,6 +307,7 @@ impl Replica {
             let mut digests_published = self.digests_published.write().await;
             digests_published.insert(digest.checksum);
             drop(digests_published);
+            self.status.write().await.last_digest_checksum = Some(digest.checksum);
             drop(digest);
 
             trace!("[DIGEST_PUB] Putting Digest: {} ...", digest_json);
@@ -325,3 +371,182 @@ impl Replica {
         Replica::get_hot_interval_number(publication_interval, delta) * 5
     }
 }
+
+#[cfg(test)]
+async fn open_test_session(listen: &str, connect: Option<&str>) -> Arc<Session> {
+    let mut config = zenoh::config::peer();
+    config.scouting.multicast.set_enabled(Some(false)).unwrap();
+    config.listen.endpoints = vec![listen.parse().unwrap()];
+    config.connect.endpoints = connect.into_iter().map(|e| e.parse().unwrap()).collect();
+    Arc::new(zenoh::open(config).res().await.unwrap())
+}
+
+#[cfg(test)]
+async fn start_test_replica(
+    name: &'static str,
+    session: Arc<Session>,
+    storage: Box<dyn zenoh_backend_traits::Storage>,
+    conflict_resolver: Option<Arc<dyn zenoh_backend_traits::ConflictResolver>>,
+) -> flume::Sender<StorageMessage> {
+    let (tx, rx) = flume::bounded(1);
+    let config = ReplicaConfig {
+        publication_interval: Duration::from_millis(500),
+        propagation_delay: Duration::from_millis(100),
+        delta: Duration::from_millis(100),
+    };
+    let store_intercept = StoreIntercept {
+        storage,
+        in_interceptor: None,
+        out_interceptor: None,
+        conflict_resolver,
+    };
+    async_std::task::spawn(Replica::start(
+        config,
+        RetentionConfig::default(),
+        session,
+        store_intercept,
+        OwnedKeyExpr::new("test/replica/**").unwrap(),
+        false,
+        name,
+        rx,
+    ));
+    tx
+}
+
+#[test]
+fn test_convergence_after_partition() {
+    use crate::memory_backend::{create_test_storage, test_sample};
+    async_std::task::block_on(async {
+        // the values written on each side while the replicas were partitioned
+        let old = Duration::from_secs(10);
+        let mut storage_a = create_test_storage("test/replica/**", None).await;
+        storage_a
+            .on_sample(test_sample("test/replica/a", "a", old))
+            .await
+            .unwrap();
+        let mut storage_b = create_test_storage("test/replica/**", None).await;
+        storage_b
+            .on_sample(test_sample("test/replica/b", "b", old))
+            .await
+            .unwrap();
+
+        let session_a = open_test_session("tcp/127.0.0.1:17460", None).await;
+        let replica_a = start_test_replica("A", session_a.clone(), storage_a, None).await;
+        let session_b = open_test_session("tcp/127.0.0.1:17461", Some("tcp/127.0.0.1:17460")).await;
+        let replica_b = start_test_replica("B", session_b.clone(), storage_b, None).await;
+
+        let local_keys = |session: Arc<Session>| async move {
+            let replies = session
+                .get("test/replica/**")
+                .allowed_destination(Locality::SessionLocal)
+                .res()
+                .await
+                .unwrap();
+            let mut keys = Vec::new();
+            while let Ok(reply) = replies.recv_async().await {
+                keys.push(reply.sample.unwrap().key_expr.to_string());
+            }
+            keys.sort();
+            keys
+        };
+        let status = |replica: flume::Sender<StorageMessage>| async move {
+            let (tx, rx) = async_std::channel::bounded(1);
+            replica
+                .send_async(StorageMessage::GetStatus(tx))
+                .await
+                .unwrap();
+            rx.recv().await.unwrap()["replication"].clone()
+        };
+        let expected = ["test/replica/a", "test/replica/b"];
+        let mut converged = false;
+        for _ in 0..40 {
+            sleep(Duration::from_millis(250)).await;
+            let status_a = status(replica_a.clone()).await;
+            let status_b = status(replica_b.clone()).await;
+            if local_keys(session_a.clone()).await == expected
+                && local_keys(session_b.clone()).await == expected
+                && status_a["peers"]["B"]["last_alignment"].is_string()
+                && status_b["peers"]["A"]["last_alignment"].is_string()
+                && status_a["pending_intervals"] == 0
+                && status_b["pending_intervals"] == 0
+            {
+                converged = true;
+                break;
+            }
+        }
+        assert!(converged, "the replicas didn't converge");
+
+        // a forced alignment also fetches the content of the other storages of the same session
+        let storage_c = create_test_storage("test/replica/**", None).await;
+        let replica_c = start_test_replica("C", session_b.clone(), storage_c, None).await;
+        sleep(Duration::from_millis(500)).await;
+        let (tx, rx) = async_std::channel::bounded(1);
+        replica_c
+            .send_async(StorageMessage::Align(tx))
+            .await
+            .unwrap();
+        assert_eq!(rx.recv().await.unwrap().unwrap(), 2 * expected.len());
+
+        for replica in [replica_a, replica_b, replica_c] {
+            replica.send_async(StorageMessage::Stop).await.unwrap();
+        }
+    })
+}
+
+#[test]
+fn test_siblings_convergence() {
+    use crate::conflicts::siblings_resolver;
+    use crate::memory_backend::create_test_storage;
+    use std::convert::TryFrom;
+    async_std::task::block_on(async {
+        let sample = |writer: u8, value: &str, time: SystemTime| {
+            let id = zenoh::time::TimestampId::try_from([writer]).unwrap();
+            let time = time.duration_since(SystemTime::UNIX_EPOCH).unwrap();
+            let timestamp = Timestamp::new(zenoh::time::NTP64::from(time), id);
+            Sample::new(
+                OwnedKeyExpr::new("test/replica/k").unwrap(),
+                Value::from(value),
+            )
+            .with_timestamp(timestamp)
+        };
+        // the replicas have the same latest value, but not the same siblings
+        let now = SystemTime::now();
+        let older = sample(1, "older", now - Duration::from_secs(20));
+        let latest = sample(2, "latest", now - Duration::from_secs(10));
+        let resolver = siblings_resolver();
+        let merged = resolver.resolve(Some(&older), latest.clone()).unwrap();
+        let mut storage_a = create_test_storage("test/replica/**", None).await;
+        storage_a.on_sample(merged.clone()).await.unwrap();
+        let mut storage_b = create_test_storage("test/replica/**", None).await;
+        storage_b.on_sample(latest).await.unwrap();
+
+        let session_a = open_test_session("tcp/127.0.0.1:17462", None).await;
+        let replica_a =
+            start_test_replica("A", session_a.clone(), storage_a, Some(resolver.clone())).await;
+        let session_b = open_test_session("tcp/127.0.0.1:17463", Some("tcp/127.0.0.1:17462")).await;
+        let replica_b =
+            start_test_replica("B", session_b.clone(), storage_b, Some(resolver.clone())).await;
+
+        let mut converged = false;
+        for _ in 0..40 {
+            sleep(Duration::from_millis(250)).await;
+            let replies = session_b
+                .get("test/replica/k")
+                .allowed_destination(Locality::SessionLocal)
+                .res()
+                .await
+                .unwrap();
+            if let Ok(reply) = replies.recv_async().await {
+                let sample = reply.sample.unwrap();
+                if sample.payload.contiguous() == merged.payload.contiguous() {
+                    converged = true;
+                    break;
+                }
+            }
+        }
+        assert!(converged, "the siblings weren't aligned");
+
+        replica_a.send_async(StorageMessage::Stop).await.unwrap();
+        replica_b.send_async(StorageMessage::Stop).await.unwrap();
+    })
+}
@@ -16,7 +16,7 @@ use async_std::sync::Arc;
 use async_std::sync::RwLock;
 use async_std::task::sleep;
 use flume::Receiver;
-use futures::join;
+use futures::{join, select};
 use log::trace;
 use std::collections::{HashMap, HashSet};
 use std::convert::TryFrom;
@@ -25,9 +25,17 @@ use zenoh::key_expr::OwnedKeyExpr;
 use zenoh::time::Timestamp;
 use zenoh_backend_traits::config::ReplicaConfig;
 
+// A key updated in the storage, with its timestamp and the hash of its value if needed (see LogEntry)
+pub type LogUpdate = (OwnedKeyExpr, Timestamp, Option<u64>);
+
+// The timestamp and content hash of the latest update of each key
+type Log = HashMap<OwnedKeyExpr, (Timestamp, Option<u64>)>;
+
 pub struct Snapshotter {
     // channel to get updates from the storage
-    storage_update: Receiver<(OwnedKeyExpr, Timestamp)>,
+    storage_update: Receiver<LogUpdate>,
+    // channel to get the entries evicted from the storage
+    storage_eviction: Receiver<(OwnedKeyExpr, Timestamp)>,
     // configuration parameters of the replica
     replica_config: ReplicaConfig,
     // metadata for replication
@@ -36,9 +44,9 @@ pub struct Snapshotter {
 
 pub struct ReplicationInfo {
     // log entries until the snapshot time
-    stable_log: Arc<RwLock<HashMap<OwnedKeyExpr, Timestamp>>>,
+    stable_log: Arc<RwLock<Log>>,
     // log entries after the snapshot time
-    volatile_log: RwLock<HashMap<OwnedKeyExpr, Timestamp>>,
+    volatile_log: RwLock<Log>,
     // the latest snapshot time
     last_snapshot_time: RwLock<Timestamp>,
     // the latest interval
@@ -51,8 +59,9 @@ pub struct ReplicationInfo {
 impl Snapshotter {
     // Initialize the snapshot parameters, logs and digest
     pub async fn new(
-        rx_sample: Receiver<(OwnedKeyExpr, Timestamp)>,
-        initial_entries: &Vec<(OwnedKeyExpr, Timestamp)>,
+        rx_sample: Receiver<LogUpdate>,
+        rx_eviction: Receiver<(OwnedKeyExpr, Timestamp)>,
+        initial_entries: &Vec<LogUpdate>,
         replica_config: &ReplicaConfig,
     ) -> Self {
         // compute snapshot time and snapshot interval to start with
@@ -64,6 +73,7 @@ impl Snapshotter {
         );
         let snapshotter = Snapshotter {
             storage_update: rx_sample,
+            storage_eviction: rx_eviction,
             replica_config: replica_config.clone(),
             content: ReplicationInfo {
                 stable_log: Arc::new(RwLock::new(HashMap::new())),
@@ -104,10 +114,19 @@ impl Snapshotter {
         join!(task, listener);
     }
 
-    // Listen to storage updates
+    // Listen to storage updates and evictions
     async fn listener_log(&self) {
-        while let Ok((key, timestamp)) = self.storage_update.recv_async().await {
-            self.update_log(key, timestamp).await;
+        loop {
+            select!(
+                update = self.storage_update.recv_async() => match update {
+                    Ok((key, timestamp, hash)) => self.update_log(key, timestamp, hash).await,
+                    Err(_) => return,
+                },
+                eviction = self.storage_eviction.recv_async() => match eviction {
+                    Ok((key, timestamp)) => self.remove_log(key, timestamp).await,
+                    Err(_) => return,
+                },
+            );
         }
     }
 
@@ -157,29 +176,29 @@ impl Snapshotter {
     }
 
     // initialize the log from the storage entries at startup
-    async fn initialize_log(&self, log: &Vec<(OwnedKeyExpr, Timestamp)>) {
+    async fn initialize_log(&self, log: &Vec<LogUpdate>) {
         let replica_data = &self.content;
         let last_snapshot_time = replica_data.last_snapshot_time.read().await;
 
         let mut stable_log = replica_data.stable_log.write().await;
         let mut volatile_log = replica_data.volatile_log.write().await;
-        for (key, timestamp) in log {
+        for (key, timestamp, hash) in log {
             // depending on the associated timestamp, either to stable_log or volatile log
             // entries until last_snapshot_time goes to stable
             if *timestamp > *last_snapshot_time {
                 if volatile_log.contains_key(key) {
-                    if *volatile_log.get(key).unwrap() < *timestamp {
-                        (*volatile_log).insert(key.clone(), *timestamp);
+                    if volatile_log.get(key).unwrap().0 < *timestamp {
+                        (*volatile_log).insert(key.clone(), (*timestamp, *hash));
                     }
                 } else {
-                    (*volatile_log).insert(key.clone(), *timestamp);
+                    (*volatile_log).insert(key.clone(), (*timestamp, *hash));
                 }
             } else if stable_log.contains_key(key) {
-                if *stable_log.get(key).unwrap() < *timestamp {
-                    (*stable_log).insert(key.clone(), *timestamp);
+                if stable_log.get(key).unwrap().0 < *timestamp {
+                    (*stable_log).insert(key.clone(), (*timestamp, *hash));
                 }
             } else {
-                (*stable_log).insert(key.clone(), *timestamp);
+                (*stable_log).insert(key.clone(), (*timestamp, *hash));
             }
         }
         drop(volatile_log);
@@ -198,10 +217,11 @@ impl Snapshotter {
         let latest_interval = replica_data.last_interval.read().await;
         let latest_snapshot_time = replica_data.last_snapshot_time.read().await;
         let mut log = Vec::new();
-        for (key, timestamp) in &*log_locked {
+        for (key, (timestamp, hash)) in &*log_locked {
             log.push(LogEntry {
                 timestamp: *timestamp,
                 key: key.clone(),
+                content_hash: *hash,
             });
         }
         let digest = Digest::create_digest(
@@ -230,7 +250,7 @@ impl Snapshotter {
     }
 
     // update log with new entry
-    async fn update_log(&self, key: OwnedKeyExpr, ts: Timestamp) {
+    async fn update_log(&self, key: OwnedKeyExpr, ts: Timestamp, hash: Option<u64>) {
         let replica_data = &self.content;
         let last_snapshot_time = replica_data.last_snapshot_time.read().await;
         let last_interval = replica_data.last_interval.read().await;
@@ -238,19 +258,24 @@ impl Snapshotter {
         let mut new_stable_content = HashSet::new();
         if ts > *last_snapshot_time {
             let mut log = replica_data.volatile_log.write().await;
-            (*log).insert(key, ts);
+            (*log).insert(key, (ts, hash));
             drop(log);
         } else {
             let mut log = replica_data.stable_log.write().await;
-            let deleted = (*log).insert(key.clone(), ts);
-            if let Some(deleted) = deleted {
+            let deleted = (*log).insert(key.clone(), (ts, hash));
+            if let Some((deleted, deleted_hash)) = deleted {
                 deleted_content.insert(LogEntry {
                     timestamp: deleted,
                     key: key.clone(),
+                    content_hash: deleted_hash,
                 });
             }
             drop(log);
-            new_stable_content.insert(LogEntry { timestamp: ts, key });
+            new_stable_content.insert(LogEntry {
+                timestamp: ts,
+                key,
+                content_hash: hash,
+            });
         }
         let mut digest = replica_data.digest.write().await;
         let updated_digest = Digest::update_digest(
@@ -264,6 +289,42 @@ impl Snapshotter {
         *digest = updated_digest;
     }
 
+    // remove an evicted entry from the log, if not superseded yet
+    async fn remove_log(&self, key: OwnedKeyExpr, ts: Timestamp) {
+        let replica_data = &self.content;
+        let last_snapshot_time = replica_data.last_snapshot_time.read().await;
+        let last_interval = replica_data.last_interval.read().await;
+        let mut volatile = replica_data.volatile_log.write().await;
+        if volatile.get(&key).map(|(timestamp, _)| timestamp) == Some(&ts) {
+            volatile.remove(&key);
+            return;
+        }
+        drop(volatile);
+        let mut stable = replica_data.stable_log.write().await;
+        let content_hash = match stable.get(&key) {
+            Some((timestamp, hash)) if *timestamp == ts => *hash,
+            _ => return,
+        };
+        stable.remove(&key);
+        drop(stable);
+        let mut deleted_content = HashSet::new();
+        deleted_content.insert(LogEntry {
+            timestamp: ts,
+            key,
+            content_hash,
+        });
+        let mut digest = replica_data.digest.write().await;
+        let updated_digest = Digest::update_digest(
+            digest.clone(),
+            *last_interval,
+            *last_snapshot_time,
+            HashSet::new(),
+            deleted_content,
+        )
+        .await;
+        *digest = updated_digest;
+    }
+
     // Update stable log based on the snapshot parameters
     async fn update_stable_log(&self) {
         let replica_data = &self.content;
@@ -274,20 +335,22 @@ impl Snapshotter {
         let mut remains_volatile = HashMap::new();
         let mut new_stable = HashSet::new();
         let mut deleted_stable = HashSet::new();
-        for (k, ts) in volatile.clone() {
+        for (k, (ts, hash)) in volatile.clone() {
             if ts > *last_snapshot_time {
-                remains_volatile.insert(k, ts);
+                remains_volatile.insert(k, (ts, hash));
             } else {
-                let deleted = stable.insert(k.clone(), ts);
-                if let Some(deleted) = deleted {
+                let deleted = stable.insert(k.clone(), (ts, hash));
+                if let Some((deleted, deleted_hash)) = deleted {
                     deleted_stable.insert(LogEntry {
                         timestamp: deleted,
                         key: k.clone(),
+                        content_hash: deleted_hash,
                     });
                 }
                 new_stable.insert(LogEntry {
                     timestamp: ts,
                     key: k,
+                    content_hash: hash,
                 });
             }
         }
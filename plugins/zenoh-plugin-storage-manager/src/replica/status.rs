@@ -0,0 +1,139 @@
+//
+// Copyright (c) 2022 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+
+use serde::Serialize;
+use std::collections::HashMap;
+use zenoh::time::Timestamp;
+
+// The alignment state with another replica, according to the latest digest received from it
+#[derive(Clone, Debug, Default, Serialize)]
+pub struct PeerStatus {
+    pub checksum: u64,
+    pub timestamp: String,
+    // number of misaligned intervals found while aligning with this replica, not aligned yet
+    pub pending_intervals: usize,
+    // time of the latest alignment with this replica, or of the latest matching digest received from it
+    pub last_alignment: Option<String>,
+}
+
+// The alignment state of a replica, exposed in the admin space of its storage
+#[derive(Clone, Debug, Default, Serialize)]
+pub struct ReplicaStatus {
+    // checksum of the latest digest published by this replica
+    pub last_digest_checksum: Option<u64>,
+    // the other replicas whose digests were received
+    pub peers: HashMap<String, PeerStatus>,
+    // total size of the replies received while aligning with other replicas
+    pub bytes_fetched: u64,
+    // time of the latest alignment that completed without errors (or found nothing to align)
+    pub last_alignment: Option<String>,
+}
+
+impl ReplicaStatus {
+    pub fn digest_received(&mut self, from: &str, checksum: u64, timestamp: &Timestamp) {
+        let peer = self.peers.entry(from.to_string()).or_default();
+        peer.checksum = checksum;
+        peer.timestamp = timestamp.to_string();
+        if self.last_digest_checksum == Some(checksum) {
+            // the replicas converged
+            self.aligned_with(from);
+        }
+    }
+
+    // An alignment with the replica `from` starts: the intervals found misaligned previously are discarded
+    pub fn alignment_started(&mut self, from: &str) {
+        self.peers
+            .entry(from.to_string())
+            .or_default()
+            .pending_intervals = 0;
+    }
+
+    pub fn misaligned_intervals(&mut self, from: &str, count: usize) {
+        self.peers
+            .entry(from.to_string())
+            .or_default()
+            .pending_intervals += count;
+    }
+
+    // This replica is aligned with the replica `from`
+    pub fn aligned_with(&mut self, from: &str) {
+        let now = Some(zenoh::time::new_reception_timestamp().to_string());
+        let peer = self.peers.entry(from.to_string()).or_default();
+        peer.pending_intervals = 0;
+        peer.last_alignment = now.clone();
+        self.last_alignment = now;
+    }
+
+    // This replica is aligned with all the others (e.g. after a forced alignment)
+    pub fn aligned(&mut self) {
+        let now = Some(zenoh::time::new_reception_timestamp().to_string());
+        for peer in self.peers.values_mut() {
+            peer.pending_intervals = 0;
+            peer.last_alignment = now.clone();
+        }
+        self.last_alignment = now;
+    }
+
+    // number of misaligned intervals, not aligned yet, over all the other replicas
+    pub fn pending_intervals(&self) -> usize {
+        self.peers.values().map(|peer| peer.pending_intervals).sum()
+    }
+
+    pub fn to_json_value(&self) -> serde_json::Value {
+        let mut value = serde_json::to_value(self).unwrap_or_default();
+        if let Some(value) = value.as_object_mut() {
+            value.insert("pending_intervals".into(), self.pending_intervals().into());
+        }
+        value
+    }
+}
+
+#[test]
+fn test_status_json() {
+    let timestamp = zenoh::time::new_reception_timestamp();
+    let mut status = ReplicaStatus {
+        last_digest_checksum: Some(1),
+        ..Default::default()
+    };
+    status.digest_received("a", 2, &timestamp);
+    status.digest_received("b", 3, &timestamp);
+    status.alignment_started("a");
+    status.misaligned_intervals("a", 2);
+    status.misaligned_intervals("a", 1);
+    status.alignment_started("b");
+    status.misaligned_intervals("b", 4);
+
+    let json = status.to_json_value();
+    assert_eq!(json["last_digest_checksum"], 1);
+    assert_eq!(json["pending_intervals"], 7);
+    assert_eq!(json["peers"]["a"]["checksum"], 2);
+    assert_eq!(json["peers"]["a"]["pending_intervals"], 3);
+    assert_eq!(json["peers"]["a"]["timestamp"], timestamp.to_string());
+    assert!(json["last_alignment"].is_null());
+
+    // a new alignment with a replica only discards the intervals pending with this replica
+    status.alignment_started("a");
+    assert_eq!(status.pending_intervals(), 4);
+
+    status.aligned_with("b");
+    let json = status.to_json_value();
+    assert_eq!(json["pending_intervals"], 0);
+    assert!(json["peers"]["b"]["last_alignment"].is_string());
+    assert!(json["peers"]["a"]["last_alignment"].is_null());
+    assert!(json["last_alignment"].is_string());
+
+    // receiving a digest matching the one of this replica shows the convergence
+    status.digest_received("a", 1, &timestamp);
+    assert!(status.to_json_value()["peers"]["a"]["last_alignment"].is_string());
+}
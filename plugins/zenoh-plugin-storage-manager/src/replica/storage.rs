@@ -11,23 +11,41 @@
 // Contributors:
 //   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
 //
+use super::snapshotter::LogUpdate;
+use super::ReplicaStatus;
+use crate::retention::{Retention, MAX_GC_PERIOD};
+use crate::snapshot;
 use crate::storages_mgt::{StorageMessage, StoreIntercept};
 use async_std::sync::Arc;
 use async_std::sync::Mutex;
+use async_std::sync::RwLock;
 use flume::{Receiver, Sender};
-use futures::select;
+use futures::{select, StreamExt};
 use log::{error, trace, warn};
+use std::collections::hash_map::Entry;
+use std::collections::HashMap;
+use std::convert::TryFrom;
+use std::path::{Path, PathBuf};
 use std::str;
+use std::time::SystemTime;
+use urlencoding::encode;
 use zenoh::key_expr::OwnedKeyExpr;
 use zenoh::prelude::r#async::*;
+use zenoh::selector::TimeRange;
 use zenoh::time::Timestamp;
 use zenoh::Session;
-use zenoh_backend_traits::{Query, StorageInsertionResult};
+use zenoh_backend_traits::config::RetentionConfig;
+use zenoh_backend_traits::{
+    Capability, ConflictResolver, Query, QuerySupport, StorageInsertionResult,
+};
+use zenoh_result::{bail, zerror, ZResult};
 
 pub struct ReplicationService {
     pub empty_start: bool,
     pub aligner_updates: Receiver<Sample>,
-    pub log_propagation: Sender<(OwnedKeyExpr, Timestamp)>,
+    pub log_propagation: Sender<LogUpdate>,
+    pub log_eviction: Sender<(OwnedKeyExpr, Timestamp)>,
+    pub status: Arc<RwLock<ReplicaStatus>>,
 }
 
 pub struct StorageService {
@@ -36,29 +54,100 @@ pub struct StorageService {
     complete: bool,
     name: String,
     storage: Mutex<Box<dyn zenoh_backend_traits::Storage>>,
+    // the tracking of the entries, if the storage supports their eviction
+    retention: Mutex<Option<Retention>>,
     in_interceptor: Option<Arc<dyn Fn(Sample) -> Sample + Send + Sync>>,
     out_interceptor: Option<Arc<dyn Fn(Sample) -> Sample + Send + Sync>>,
+    conflict_resolver: Option<Arc<dyn ConflictResolver>>,
     replication: Option<ReplicationService>,
 }
 
+// The selector parameter identifying the storage performing an alignment query
+const ALIGNMENT_ORIGIN: &str = "_alignment_of";
+
+fn is_alignment_of(query: &zenoh::queryable::Query, name: &str) -> bool {
+    query
+        .parameters()
+        .decode()
+        .any(|(key, value)| key == ALIGNMENT_ORIGIN && value == name)
+}
+
+// The key, timestamp, payload size and kind of a sample inserted in the storage
+// (with the hash of its content if the conflict resolver requires it)
+type Update = (OwnedKeyExpr, Timestamp, usize, SampleKind, Option<u64>);
+
+fn update_of(sample: &Sample) -> Update {
+    (
+        OwnedKeyExpr::from(sample.key_expr.clone()),
+        *sample.get_timestamp().unwrap(),
+        sample.payload.len(),
+        sample.kind,
+        None,
+    )
+}
+
+// The entries of a storage at startup, with the kind and the size of their values as tracked by the retention.
+// A storage answering the queries itself can't be asked for its values: its entries are then considered values
+// of unknown size, the max_bytes limit only accounting for the values stored afterwards.
+async fn initial_entries(
+    storage: &mut Box<dyn zenoh_backend_traits::Storage>,
+) -> ZResult<Vec<(OwnedKeyExpr, Timestamp, usize, SampleKind)>> {
+    let entries = storage.get_all_entries().await?;
+    if storage.capabilities().queries == QuerySupport::OnQuery {
+        return Ok(entries
+            .into_iter()
+            .map(|(key, ts)| (key, ts, 0, SampleKind::Put))
+            .collect());
+    }
+    let mut result = Vec::with_capacity(entries.len());
+    for (key, ts) in entries {
+        // only the values are returned: an entry without a value of its timestamp is a deletion
+        let value = storage
+            .get(key.clone(), None)
+            .await?
+            .into_iter()
+            .find(|s| s.timestamp == Some(ts));
+        result.push(match value {
+            Some(sample) => (key, ts, sample.payload.len(), SampleKind::Put),
+            None => (key, ts, 0, SampleKind::Delete),
+        });
+    }
+    Ok(result)
+}
+
 impl StorageService {
+    #[allow(clippy::too_many_arguments)]
     pub async fn start(
         session: Arc<Session>,
         key_expr: OwnedKeyExpr,
         complete: bool,
         name: &str,
-        store_intercept: StoreIntercept,
+        mut store_intercept: StoreIntercept,
         rx: Receiver<StorageMessage>,
         replication: Option<ReplicationService>,
+        retention: RetentionConfig,
     ) {
+        let retention = if store_intercept.storage.capabilities().eviction {
+            match initial_entries(&mut store_intercept.storage).await {
+                Ok(entries) => Some(Retention::new(retention, &entries)),
+                Err(e) => {
+                    error!("Error fetching entries from storage {}: {}", name, e);
+                    return;
+                }
+            }
+        } else {
+            None
+        };
         let storage_service = StorageService {
             session,
             key_expr,
             complete,
             name: name.to_string(),
             storage: Mutex::new(store_intercept.storage),
+            retention: Mutex::new(retention),
             in_interceptor: store_intercept.in_interceptor,
             out_interceptor: store_intercept.out_interceptor,
+            conflict_resolver: store_intercept.conflict_resolver,
             replication,
         };
         storage_service.start_storage_queryable_subscriber(rx).await
@@ -77,20 +166,38 @@ impl StorageService {
         };
 
         // answer to queries on key_expr
-        let storage_queryable = match self
+        let (query_tx, storage_queryable) = flume::bounded(256);
+        let name = self.name.clone();
+        let _queryable = match self
             .session
             .declare_queryable(&self.key_expr)
             .complete(self.complete)
+            .callback(move |query| {
+                // the alignment queries of this storage get no reply from itself:
+                // it's busy aligning, and already has its own content
+                if !is_alignment_of(&query, &name) {
+                    if let Err(e) = query_tx.send(query) {
+                        error!("{}", e)
+                    }
+                }
+            })
             .res()
             .await
         {
-            Ok(storage_queryable) => storage_queryable,
+            Ok(queryable) => queryable,
             Err(e) => {
                 error!("Error starting storage {}: {}", self.name, e);
                 return;
             }
         };
 
+        // periodically evict the entries exceeding the retention policy
+        let gc_period = match self.retention.lock().await.as_ref() {
+            Some(retention) => retention.gc_period(),
+            None => MAX_GC_PERIOD,
+        };
+        let mut gc = async_std::stream::interval(gc_period).fuse();
+
         if self.replication.is_some() {
             let aligner_updates = &self.replication.as_ref().unwrap().aligner_updates;
             loop {
@@ -117,10 +224,19 @@ impl StorageService {
                     query = storage_queryable.recv_async() => {
                         self.reply_query(query).await;
                     },
+                    // on garbage collection tick
+                    _ = gc.select_next_some() => {
+                        self.enforce_retention().await;
+                    },
                     // on aligner update
                     update = aligner_updates.recv_async() => {
                         match update {
-                            Ok(sample) => self.process_sample(sample).await,
+                            Ok(sample) => {
+                                // insert the pending updates at once
+                                let mut samples = vec![sample];
+                                samples.extend(aligner_updates.drain());
+                                self.process_samples(samples).await
+                            }
                             Err(e) => {
                                 error!("Error in receiving aligner update: {}", e);
                             }
@@ -134,9 +250,17 @@ impl StorageService {
                                 return
                             },
                             Ok(StorageMessage::GetStatus(tx)) => {
-                                let storage = self.storage.lock().await;
-                                std::mem::drop(tx.send(storage.get_admin_status()).await);
-                                drop(storage);
+                                std::mem::drop(tx.send(self.admin_status().await).await);
+                            }
+                            Ok(StorageMessage::Snapshot(path, tx)) => self.snapshot(path, tx).await,
+                            Ok(StorageMessage::Restore(path, tx)) => {
+                                std::mem::drop(tx.send(self.restore(&path).await).await);
+                            }
+                            Ok(StorageMessage::Clear(tx)) => {
+                                std::mem::drop(tx.send(self.clear().await).await);
+                            }
+                            Ok(StorageMessage::Align(tx)) => {
+                                std::mem::drop(tx.send(self.align().await).await);
                             }
                             Err(e) => {
                                 error!("Storage Message Channel Error: {}", e);
@@ -164,6 +288,10 @@ impl StorageService {
                     query = storage_queryable.recv_async() => {
                         self.reply_query(query).await;
                     },
+                    // on garbage collection tick
+                    _ = gc.select_next_some() => {
+                        self.enforce_retention().await;
+                    },
                     // on storage handle drop
                     message = rx.recv_async() => {
                         match message {
@@ -172,9 +300,17 @@ impl StorageService {
                                 return
                             },
                             Ok(StorageMessage::GetStatus(tx)) => {
-                                let storage = self.storage.lock().await;
-                                std::mem::drop(tx.send(storage.get_admin_status()).await);
-                                drop(storage);
+                                std::mem::drop(tx.send(self.admin_status().await).await);
+                            }
+                            Ok(StorageMessage::Snapshot(path, tx)) => self.snapshot(path, tx).await,
+                            Ok(StorageMessage::Restore(path, tx)) => {
+                                std::mem::drop(tx.send(self.restore(&path).await).await);
+                            }
+                            Ok(StorageMessage::Clear(tx)) => {
+                                std::mem::drop(tx.send(self.clear().await).await);
+                            }
+                            Ok(StorageMessage::Align(tx)) => {
+                                std::mem::drop(tx.send(self.align().await).await);
                             }
                             Err(e) => {
                                 error!("Storage Message Channel Error: {}", e);
@@ -186,35 +322,230 @@ impl StorageService {
         }
     }
 
+    async fn admin_status(&self) -> serde_json::Value {
+        let storage = self.storage.lock().await;
+        let mut status = storage.get_admin_status();
+        if let Some(status) = status.as_object_mut() {
+            status.insert(
+                "capabilities".into(),
+                storage.capabilities().to_json_value(),
+            );
+            if let Some(replication) = &self.replication {
+                status.insert(
+                    "replication".into(),
+                    replication.status.read().await.to_json_value(),
+                );
+            }
+        }
+        drop(storage);
+        status
+    }
+
     async fn process_sample(&self, sample: Sample) {
-        trace!("[STORAGE] Processing sample: {}", sample);
-        // Call incoming data interceptor (if any)
-        let mut sample = if let Some(ref interceptor) = self.in_interceptor {
-            interceptor(sample)
-        } else {
-            sample
-        };
+        self.process_samples(vec![sample]).await
+    }
+
+    async fn process_samples(&self, samples: Vec<Sample>) {
+        let now = SystemTime::now();
+        let mut retention = self.retention.lock().await;
+        let mut accepted = Vec::with_capacity(samples.len());
+        for sample in samples {
+            trace!("[STORAGE] Processing sample: {}", sample);
+            // Call incoming data interceptor (if any)
+            let mut sample = if let Some(ref interceptor) = self.in_interceptor {
+                interceptor(sample)
+            } else {
+                sample
+            };
+
+            sample.ensure_timestamp();
+            let timestamp = *sample.get_timestamp().unwrap();
 
-        sample.ensure_timestamp();
+            // drop the samples that would be evicted right away (e.g. when aligning with another storage)
+            if matches!(&*retention, Some(r) if r.is_expired(&timestamp, sample.kind, now)) {
+                trace!("[STORAGE] Dropping expired sample: {}", sample);
+                continue;
+            }
+            accepted.push(sample);
+        }
+        if accepted.is_empty() {
+            return;
+        }
 
         let mut storage = self.storage.lock().await;
-        let result = storage.on_sample(sample.clone()).await;
-        if self.replication.is_some()
-            && result.is_ok()
-            && !matches!(result.unwrap(), StorageInsertionResult::Outdated)
+        let results: Vec<(Update, ZResult<StorageInsertionResult>)> = match &self.conflict_resolver
         {
-            let sending = self.replication.as_ref().unwrap().log_propagation.send((
-                OwnedKeyExpr::from(sample.key_expr.clone()),
-                *sample.get_timestamp().unwrap(),
-            ));
-            match sending {
-                Ok(_) => (),
-                Err(e) => {
-                    error!("Error in sending the sample to the log: {}", e);
+            Some(resolver) => {
+                let mut results = Vec::with_capacity(accepted.len());
+                for sample in accepted {
+                    if let Some(result) = self
+                        .resolve_and_insert(&mut storage, resolver.as_ref(), sample)
+                        .await
+                    {
+                        results.push(result);
+                    }
                 }
+                results
+            }
+            None => {
+                let updates: Vec<Update> = accepted.iter().map(update_of).collect();
+                updates
+                    .into_iter()
+                    .zip(storage.on_samples(accepted).await)
+                    .collect()
+            }
+        };
+        for ((key, timestamp, size, kind, hash), result) in results {
+            match result {
+                Ok(StorageInsertionResult::Outdated) => (),
+                Ok(_) => {
+                    if let Some(retention) = retention.as_mut() {
+                        retention.insert(key.clone(), timestamp, size, kind);
+                    }
+                    if let Some(replication) = &self.replication {
+                        let sending = replication.log_propagation.send((key, timestamp, hash));
+                        match sending {
+                            Ok(_) => (),
+                            Err(e) => {
+                                error!("Error in sending the sample to the log: {}", e);
+                            }
+                        }
+                    }
+                }
+                Err(e) => warn!(
+                    "Storage {} raised an error receiving a sample on {}: {}",
+                    self.name, key, e
+                ),
             }
         }
         drop(storage);
+        drop(retention);
+    }
+
+    // Merges the sample with the value stored for its key using the conflict resolver, then stores the result.
+    // Returns None if the resolver dropped the sample.
+    async fn resolve_and_insert(
+        &self,
+        storage: &mut Box<dyn zenoh_backend_traits::Storage>,
+        resolver: &dyn ConflictResolver,
+        sample: Sample,
+    ) -> Option<(Update, ZResult<StorageInsertionResult>)> {
+        let key = OwnedKeyExpr::from(sample.key_expr.clone());
+        let stored = match storage.get(key.clone(), None).await {
+            Ok(samples) => samples.into_iter().max_by_key(|s| s.timestamp),
+            Err(e) => {
+                warn!(
+                    "Storage {} can't resolve the conflicts on {}, storing the sample as is: {}",
+                    self.name, key, e
+                );
+                return Some((update_of(&sample), storage.on_sample(sample).await));
+            }
+        };
+        let mut resolved = match resolver.resolve(stored.as_ref(), sample) {
+            Some(resolved) => resolved,
+            None => {
+                trace!(
+                    "[STORAGE] Sample on {} dropped by the conflict resolver",
+                    key
+                );
+                return None;
+            }
+        };
+        resolved.ensure_timestamp();
+        let mut update = update_of(&resolved);
+        update.4 = resolver.content_hash(&resolved);
+        // the storage would discard a resolved value not newer than the stored one: evict the latter first
+        if let Some(stored_ts) = stored.and_then(|s| s.timestamp) {
+            if update.1 <= stored_ts {
+                if let Err(e) = storage.evict(key, stored_ts).await {
+                    return Some((update, Err(e)));
+                }
+            }
+        }
+        Some((update, storage.on_sample(resolved).await))
+    }
+
+    // Evict the entries exceeding the retention policy from the storage and from the replication log
+    async fn enforce_retention(&self) {
+        let mut retention = self.retention.lock().await;
+        let evictions = match retention.as_mut() {
+            Some(retention) => retention.collect_evictions(SystemTime::now()),
+            None => return,
+        };
+        if evictions.is_empty() {
+            return;
+        }
+        trace!(
+            "[STORAGE] Evicting {} entries from storage {}",
+            evictions.len(),
+            self.name
+        );
+        self.evict(evictions).await;
+        drop(retention);
+    }
+
+    // Evict the given entries from the storage and from the replication log, returning the evicted keys
+    async fn evict(&self, evictions: Vec<(OwnedKeyExpr, Timestamp)>) -> Vec<OwnedKeyExpr> {
+        let mut evicted = Vec::with_capacity(evictions.len());
+        let mut storage = self.storage.lock().await;
+        for (key, timestamp) in evictions {
+            if let Err(e) = storage.evict(key.clone(), timestamp).await {
+                warn!(
+                    "Storage {} raised an error evicting {}: {}",
+                    self.name, key, e
+                );
+                continue;
+            }
+            evicted.push(key.clone());
+            if let Some(replication) = &self.replication {
+                if let Err(e) = replication.log_eviction.send((key, timestamp)) {
+                    error!("Error in sending the eviction to the log: {}", e);
+                }
+            }
+        }
+        drop(storage);
+        evicted
+    }
+
+    // Write a snapshot of the storage to path, replying the number of entries on tx once done.
+    // The values are retrieved querying the storage, hence in a separate task
+    async fn snapshot(&self, path: PathBuf, tx: async_std::channel::Sender<ZResult<usize>>) {
+        let entries = self.storage.lock().await.get_all_entries().await;
+        let session = self.session.clone();
+        let key_expr = self.key_expr.clone();
+        async_std::task::spawn(async move {
+            let result = match entries {
+                Ok(entries) => snapshot::take(&session, &key_expr, entries, &path).await,
+                Err(e) => Err(e),
+            };
+            std::mem::drop(tx.send(result).await);
+        });
+    }
+
+    // Insert the samples of the snapshot at path, as if received by the storage
+    async fn restore(&self, path: &Path) -> ZResult<usize> {
+        let samples = snapshot::load(path).await?;
+        let count = samples.len();
+        self.process_samples(samples).await;
+        Ok(count)
+    }
+
+    // Remove all the entries of the storage
+    async fn clear(&self) -> ZResult<usize> {
+        let mut retention = self.retention.lock().await;
+        let retention = match retention.as_mut() {
+            Some(retention) => retention,
+            None => bail!(
+                "Storage {} doesn't support eviction: it can't be cleared",
+                self.name
+            ),
+        };
+        let entries = self.storage.lock().await.get_all_entries().await?;
+        let cleared = self.evict(entries).await;
+        for key in &cleared {
+            retention.remove(key);
+        }
+        Ok(cleared.len())
     }
 
     async fn reply_query(&self, query: Result<zenoh::queryable::Query, flume::RecvError>) {
@@ -225,11 +556,17 @@ impl StorageService {
                 return;
             }
         };
-        // wrap zenoh::Query in zenoh_backend_traits::Query
-        // with outgoing interceptor
-        let query = Query::new(q, self.out_interceptor.clone());
         let mut storage = self.storage.lock().await;
-        if let Err(e) = storage.on_query(query).await {
+        let result = match storage.capabilities().queries {
+            QuerySupport::OnQuery => {
+                // wrap zenoh::Query in zenoh_backend_traits::Query
+                // with outgoing interceptor
+                let query = Query::new(q, self.out_interceptor.clone());
+                storage.on_query(query).await
+            }
+            queries => self.reply_with_get(&mut storage, &q, queries).await,
+        };
+        if let Err(e) = result {
             warn!(
                 "Storage {} raised an error receiving a query: {}",
                 self.name, e
@@ -238,35 +575,498 @@ impl StorageService {
         drop(storage);
     }
 
+    // Answer a query with the values returned by Storage::get, consolidated here:
+    // all the values within the time range of the query if any, otherwise the latest value of each key
+    async fn reply_with_get(
+        &self,
+        storage: &mut Box<dyn zenoh_backend_traits::Storage>,
+        query: &zenoh::queryable::Query,
+        queries: QuerySupport,
+    ) -> ZResult<()> {
+        let selector = query.selector();
+        let time_range = selector.time_range()?.map(|range| range.resolve());
+        let key_exprs = if queries == QuerySupport::GetWithWildcards || !query.key_expr().is_wild()
+        {
+            vec![OwnedKeyExpr::from(query.key_expr().clone())]
+        } else {
+            storage
+                .get_all_entries()
+                .await?
+                .into_iter()
+                .map(|(key, _)| key)
+                .filter(|key| query.key_expr().intersects(key))
+                .collect()
+        };
+        let mut samples = Vec::new();
+        for key_expr in key_exprs {
+            samples.extend(storage.get(key_expr, time_range).await?);
+        }
+        let samples: Vec<Sample> = match time_range {
+            Some(time_range) => samples
+                .into_iter()
+                .filter(|sample| {
+                    sample
+                        .get_timestamp()
+                        .map(|ts| time_range.contains(ts.get_time().to_system_time()))
+                        .unwrap_or(false)
+                })
+                .collect(),
+            None => {
+                let mut latest: HashMap<OwnedKeyExpr, Sample> = HashMap::new();
+                for sample in samples {
+                    match latest.entry(OwnedKeyExpr::from(sample.key_expr.clone())) {
+                        Entry::Occupied(mut o) => {
+                            if o.get().get_timestamp() < sample.get_timestamp() {
+                                o.insert(sample);
+                            }
+                        }
+                        Entry::Vacant(v) => {
+                            v.insert(sample);
+                        }
+                    }
+                }
+                latest.into_values().collect()
+            }
+        };
+        for sample in samples {
+            let sample = match &self.out_interceptor {
+                Some(interceptor) => interceptor(sample),
+                None => sample,
+            };
+            query.reply(Ok(sample)).res().await?;
+        }
+        Ok(())
+    }
+
     async fn initialize_if_empty(&self) {
         if self.replication.is_some() && self.replication.as_ref().unwrap().empty_start {
-            // align with other storages, querying them on key_expr,
-            // with `_time=[..]` to get historical data (in case of time-series)
-            let replies = match self
-                .session
-                .get(KeyExpr::from(&self.key_expr).with_parameters("_time=[..]"))
-                .target(QueryTarget::All)
-                .consolidation(ConsolidationMode::None)
-                .res()
-                .await
-            {
-                Ok(replies) => replies,
-                Err(e) => {
-                    error!("Error aligning storage {}: {}", self.name, e);
-                    return;
+            if let Err(e) = self.align_with_others().await {
+                error!("Error aligning storage {}: {}", self.name, e);
+            }
+        }
+    }
+
+    // Force a full alignment of the replica with the other storages
+    async fn align(&self) -> ZResult<usize> {
+        if self.replication.is_none() {
+            bail!("storage {} isn't replicated", self.name);
+        }
+        self.align_with_others().await
+    }
+
+    // Align with other storages, querying them on key_expr, with `_time=[..]` to get historical data
+    // (in case of time-series). Returns the number of samples received.
+    async fn align_with_others(&self) -> ZResult<usize> {
+        let parameters = format!("_time=[..]&{}={}", ALIGNMENT_ORIGIN, encode(&self.name));
+        let replies = self
+            .session
+            .get(KeyExpr::from(&self.key_expr).with_parameters(&parameters))
+            .target(QueryTarget::All)
+            .consolidation(ConsolidationMode::None)
+            .res()
+            .await?;
+        let mut samples = Vec::new();
+        let mut bytes = 0;
+        while let Ok(reply) = replies.recv_async().await {
+            match reply.sample {
+                Ok(sample) => {
+                    bytes += sample.payload.len();
+                    samples.push(sample);
                 }
-            };
-            while let Ok(reply) = replies.recv_async().await {
-                match reply.sample {
-                    Ok(sample) => {
-                        self.process_sample(sample).await;
-                    }
-                    Err(e) => warn!(
-                        "Storage {} received an error to align query: {}",
-                        self.name, e
-                    ),
+                Err(e) => warn!(
+                    "Storage {} received an error to align query: {}",
+                    self.name, e
+                ),
+            }
+        }
+        let count = samples.len();
+        self.process_samples(samples).await;
+        if let Some(replication) = &self.replication {
+            let mut status = replication.status.write().await;
+            status.bytes_fetched += bytes as u64;
+            status.aligned();
+        }
+        Ok(count)
+    }
+}
+
+/// Strips the `strip_prefix` of a storage from the keys it's given, and adds it back to the keys it returns,
+/// for the storages answering the queries through [`Storage::get`](zenoh_backend_traits::Storage::get).
+/// The storages answering the queries themselves are in charge of their `strip_prefix`.
+pub(crate) struct StripPrefix {
+    prefix: OwnedKeyExpr,
+    storage: Box<dyn zenoh_backend_traits::Storage>,
+}
+
+impl StripPrefix {
+    pub(crate) fn wrap(
+        storage: Box<dyn zenoh_backend_traits::Storage>,
+        prefix: Option<OwnedKeyExpr>,
+    ) -> Box<dyn zenoh_backend_traits::Storage> {
+        match prefix {
+            Some(prefix) if storage.capabilities().queries != QuerySupport::OnQuery => {
+                Box::new(StripPrefix { prefix, storage })
+            }
+            _ => storage,
+        }
+    }
+
+    // The key without the prefix, or None if it's not under the prefix
+    fn strip(&self, key_expr: &keyexpr) -> Option<OwnedKeyExpr> {
+        let stripped = key_expr
+            .as_str()
+            .strip_prefix(self.prefix.as_str())?
+            .strip_prefix('/')?;
+        OwnedKeyExpr::try_from(stripped).ok()
+    }
+
+    fn prefixed(&self, key_expr: &keyexpr) -> OwnedKeyExpr {
+        &self.prefix / key_expr
+    }
+
+    fn strip_sample(&self, mut sample: Sample) -> ZResult<Sample> {
+        match self.strip(&sample.key_expr) {
+            Some(key_expr) => {
+                sample.key_expr = key_expr.into();
+                Ok(sample)
+            }
+            None => bail!(
+                "{} can't be stored with strip_prefix {}",
+                sample.key_expr,
+                self.prefix
+            ),
+        }
+    }
+
+    fn prefixed_sample(&self, mut sample: Sample) -> Sample {
+        sample.key_expr = self.prefixed(&sample.key_expr).into();
+        sample
+    }
+}
+
+#[async_trait::async_trait]
+impl zenoh_backend_traits::Storage for StripPrefix {
+    fn get_admin_status(&self) -> serde_json::Value {
+        self.storage.get_admin_status()
+    }
+
+    fn capabilities(&self) -> Capability {
+        self.storage.capabilities()
+    }
+
+    async fn on_sample(&mut self, sample: Sample) -> ZResult<StorageInsertionResult> {
+        let sample = self.strip_sample(sample)?;
+        self.storage.on_sample(sample).await
+    }
+
+    async fn on_samples(&mut self, samples: Vec<Sample>) -> Vec<ZResult<StorageInsertionResult>> {
+        let mut results = Vec::with_capacity(samples.len());
+        let mut stripped = Vec::with_capacity(samples.len());
+        for sample in samples {
+            match self.strip_sample(sample) {
+                Ok(sample) => {
+                    stripped.push(sample);
+                    results.push(None);
                 }
+                Err(e) => results.push(Some(Err(e))),
             }
         }
+        let mut inserted = self.storage.on_samples(stripped).await.into_iter();
+        results
+            .into_iter()
+            .map(|result| match result {
+                Some(result) => result,
+                None => inserted
+                    .next()
+                    .unwrap_or_else(|| Err(zerror!("Missing insertion result").into())),
+            })
+            .collect()
+    }
+
+    async fn on_query(&mut self, query: Query) -> ZResult<()> {
+        self.storage.on_query(query).await
+    }
+
+    async fn get_all_entries(&self) -> ZResult<Vec<(OwnedKeyExpr, Timestamp)>> {
+        Ok(self
+            .storage
+            .get_all_entries()
+            .await?
+            .into_iter()
+            .map(|(key, timestamp)| (self.prefixed(&key), timestamp))
+            .collect())
     }
+
+    async fn get(
+        &mut self,
+        key_expr: OwnedKeyExpr,
+        time_range: Option<TimeRange<SystemTime>>,
+    ) -> ZResult<Vec<Sample>> {
+        let keys = match self.strip(&key_expr) {
+            Some(stripped) => vec![stripped],
+            // a key expression not starting with the prefix, but possibly matching keys under it (e.g. `**`)
+            None if key_expr.is_wild() => self
+                .storage
+                .get_all_entries()
+                .await?
+                .into_iter()
+                .map(|(key, _)| key)
+                .filter(|key| key_expr.intersects(&self.prefixed(key)))
+                .collect(),
+            None => vec![],
+        };
+        let mut samples = Vec::new();
+        for key in keys {
+            samples.extend(self.storage.get(key, time_range).await?);
+        }
+        Ok(samples
+            .into_iter()
+            .map(|sample| self.prefixed_sample(sample))
+            .collect())
+    }
+
+    async fn evict(&mut self, key: OwnedKeyExpr, timestamp: Timestamp) -> ZResult<()> {
+        match self.strip(&key) {
+            Some(key) => self.storage.evict(key, timestamp).await,
+            None => Ok(()),
+        }
+    }
+}
+
+#[cfg(test)]
+use crate::memory_backend::{create_test_storage, test_sample};
+
+#[test]
+fn test_strip_prefix_on_samples() {
+    async_std::task::block_on(async {
+        let mut storage = StripPrefix::wrap(
+            create_test_storage("demo/example/**", Some("demo/example")).await,
+            Some(OwnedKeyExpr::new("demo/example").unwrap()),
+        );
+        let now = std::time::Duration::ZERO;
+        let results = storage
+            .on_samples(vec![
+                test_sample("demo/example/a", "a", now),
+                test_sample("demo/other/b", "b", now),
+                test_sample("demo/example/c", "c", now),
+            ])
+            .await;
+        assert_eq!(results.len(), 3);
+        assert!(matches!(results[0], Ok(StorageInsertionResult::Inserted)));
+        assert!(results[1].is_err());
+        assert!(matches!(results[2], Ok(StorageInsertionResult::Inserted)));
+
+        // the keys are stored without the prefix, but returned with it
+        let mut keys: Vec<String> = storage
+            .get_all_entries()
+            .await
+            .unwrap()
+            .into_iter()
+            .map(|(key, _)| key.to_string())
+            .collect();
+        keys.sort();
+        assert_eq!(keys, ["demo/example/a", "demo/example/c"]);
+        for selector in ["demo/example/*", "demo/**", "demo/*/c"] {
+            let found = storage
+                .get(OwnedKeyExpr::new(selector).unwrap(), None)
+                .await
+                .unwrap();
+            assert!(found
+                .iter()
+                .all(|sample| sample.key_expr.starts_with("demo/example/")));
+            assert_eq!(
+                found.len(),
+                if selector == "demo/*/c" { 1 } else { 2 },
+                "get on {selector}"
+            );
+        }
+    })
+}
+
+#[test]
+fn test_initial_entries() {
+    async_std::task::block_on(async {
+        let mut storage = create_test_storage("test/**", None).await;
+        let now = std::time::Duration::ZERO;
+        let mut delete = test_sample("test/b", "", now);
+        delete.kind = SampleKind::Delete;
+        storage
+            .on_samples(vec![
+                test_sample("test/a", "abc", now),
+                test_sample("test/b", "b", std::time::Duration::from_secs(1)),
+                delete,
+            ])
+            .await;
+        let mut entries = initial_entries(&mut storage).await.unwrap();
+        entries.sort_by(|(k1, ..), (k2, ..)| k1.as_str().cmp(k2.as_str()));
+        let entries: Vec<(&str, usize, SampleKind)> = entries
+            .iter()
+            .map(|(key, _, size, kind)| (key.as_str(), *size, *kind))
+            .collect();
+        assert_eq!(
+            entries,
+            [
+                ("test/a", 3, SampleKind::Put),
+                ("test/b", 0, SampleKind::Delete)
+            ]
+        );
+    })
+}
+
+#[test]
+fn test_reply_with_get() {
+    use std::time::Duration;
+    async_std::task::block_on(async {
+        let mut config = zenoh::config::peer();
+        config.scouting.multicast.set_enabled(Some(false)).unwrap();
+        config.listen.endpoints = vec!["tcp/127.0.0.1:0".parse().unwrap()];
+        let session = Arc::new(zenoh::open(config).res().await.unwrap());
+
+        let hour = Duration::from_secs(3600);
+        let mut storage = StripPrefix::wrap(
+            create_test_storage("test/storage/**", Some("test/storage")).await,
+            Some(OwnedKeyExpr::new("test/storage").unwrap()),
+        );
+        for sample in [
+            test_sample("test/storage/a", "a", 2 * hour),
+            test_sample("test/storage/b", "b", Duration::ZERO),
+        ] {
+            storage.on_sample(sample).await.unwrap();
+        }
+        let (tx, rx) = flume::bounded(1);
+        let store_intercept = StoreIntercept {
+            storage,
+            in_interceptor: None,
+            out_interceptor: None,
+            conflict_resolver: None,
+        };
+        let service_session = session.clone();
+        async_std::task::spawn(async move {
+            StorageService::start(
+                service_session,
+                OwnedKeyExpr::new("test/storage/**").unwrap(),
+                false,
+                "test",
+                store_intercept,
+                rx,
+                None,
+                RetentionConfig::default(),
+            )
+            .await
+        });
+        async_std::task::sleep(Duration::from_millis(500)).await;
+
+        let query = |selector: &'static str| {
+            let session = session.clone();
+            async move {
+                let replies = session.get(selector).res().await.unwrap();
+                let mut values = Vec::new();
+                while let Ok(reply) = replies.recv_async().await {
+                    let sample = reply.sample.unwrap();
+                    values.push(format!("{}={}", sample.key_expr, sample.value));
+                }
+                values.sort();
+                values
+            }
+        };
+        assert_eq!(
+            query("test/storage/**").await,
+            ["test/storage/a=a", "test/storage/b=b"]
+        );
+        assert_eq!(
+            query("test/**").await,
+            ["test/storage/a=a", "test/storage/b=b"]
+        );
+        assert_eq!(query("test/storage/a").await, ["test/storage/a=a"]);
+        assert_eq!(
+            query("test/storage/**?_time=[now(-1h)..]").await,
+            ["test/storage/b=b"]
+        );
+        assert_eq!(
+            query("test/storage/**?_time=[..now(-1h)]").await,
+            ["test/storage/a=a"]
+        );
+
+        tx.send_async(StorageMessage::Stop).await.unwrap();
+    })
+}
+
+#[test]
+fn test_siblings_deletion() {
+    use crate::conflicts::siblings_resolver;
+    use std::time::Duration;
+    async_std::task::block_on(async {
+        let mut config = zenoh::config::peer();
+        config.scouting.multicast.set_enabled(Some(false)).unwrap();
+        config.listen.endpoints = vec!["tcp/127.0.0.1:0".parse().unwrap()];
+        let session = Arc::new(zenoh::open(config).res().await.unwrap());
+        let (log_tx, log_rx) = flume::unbounded();
+        let (_, aligner_updates) = flume::unbounded();
+        let service = StorageService {
+            session,
+            key_expr: OwnedKeyExpr::new("test/**").unwrap(),
+            complete: false,
+            name: "test".into(),
+            storage: Mutex::new(create_test_storage("test/**", None).await),
+            retention: Mutex::new(Some(Retention::new(RetentionConfig::default(), &[]))),
+            in_interceptor: None,
+            out_interceptor: None,
+            conflict_resolver: Some(siblings_resolver()),
+            replication: Some(ReplicationService {
+                empty_start: false,
+                aligner_updates,
+                log_propagation: log_tx,
+                log_eviction: flume::unbounded().0,
+                status: Arc::new(RwLock::new(ReplicaStatus::default())),
+            }),
+        };
+        let sample = |writer: u8, value: &str, age: u64| {
+            let mut sample = test_sample("test/k", value, Duration::from_secs(age));
+            let time = *sample.get_timestamp().unwrap().get_time();
+            let id = zenoh::time::TimestampId::try_from([writer]).unwrap();
+            sample.timestamp = Some(Timestamp::new(time, id));
+            sample
+        };
+        let delete = |writer: u8, age: u64| {
+            let mut delete = sample(writer, "", age);
+            delete.kind = SampleKind::Delete;
+            delete
+        };
+        let stored = || async {
+            let mut storage = service.storage.lock().await;
+            storage
+                .get(OwnedKeyExpr::new("test/k").unwrap(), None)
+                .await
+                .unwrap()
+        };
+
+        service
+            .process_samples(vec![sample(1, "a", 3), sample(2, "b", 1)])
+            .await;
+        let siblings = stored().await;
+        assert_eq!(siblings.len(), 1);
+        assert!(siblings[0].encoding.to_string().ends_with(";siblings"));
+        // the sets of siblings are logged with a hash of their content
+        let logged: Vec<LogUpdate> = log_rx.drain().collect();
+        assert!(logged.last().unwrap().2.is_some());
+
+        // (the deletions are more recent than the tombstone lifetime, not to be dropped as expired)
+        // a deletion older than all the siblings is dropped
+        service.process_samples(vec![delete(3, 4)]).await;
+        assert_eq!(
+            stored().await[0].payload.contiguous(),
+            siblings[0].payload.contiguous()
+        );
+
+        // a deletion removes the older siblings only
+        service.process_samples(vec![delete(3, 2)]).await;
+        let remaining = stored().await;
+        assert_eq!(remaining.len(), 1);
+        assert_eq!(remaining[0].payload.contiguous().as_ref(), b"b");
+        assert!(log_rx.drain().last().unwrap().2.is_none());
+
+        // a deletion newer than all the siblings deletes the key
+        service.process_samples(vec![delete(3, 0)]).await;
+        assert!(stored().await.is_empty());
+    })
 }
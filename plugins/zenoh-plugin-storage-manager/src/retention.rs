@@ -0,0 +1,229 @@
+//
+// Copyright (c) 2022 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+
+// This module tracks the entries of a storage to enforce its retention policy, whatever its backend
+// The storage service records every insertion and periodically evicts the entries selected here
+
+use std::cmp::Ordering;
+use std::collections::{BTreeSet, HashMap};
+use std::time::{Duration, SystemTime};
+use zenoh::key_expr::OwnedKeyExpr;
+use zenoh::prelude::SampleKind;
+use zenoh::time::Timestamp;
+use zenoh_backend_traits::config::RetentionConfig;
+
+// The longest period between two garbage collections
+pub const MAX_GC_PERIOD: Duration = Duration::from_secs(1);
+const MIN_GC_PERIOD: Duration = Duration::from_millis(10);
+
+// OwnedKeyExpr isn't Ord: the keys are ordered by their string
+#[derive(Clone, PartialEq, Eq)]
+struct OrderedKey(OwnedKeyExpr);
+
+impl Ord for OrderedKey {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.0.as_str().cmp(other.0.as_str())
+    }
+}
+
+impl PartialOrd for OrderedKey {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+struct Entry {
+    ts: Timestamp,
+    size: usize,
+    deleted: bool,
+}
+
+pub struct Retention {
+    config: RetentionConfig,
+    entries: HashMap<OwnedKeyExpr, Entry>,
+    // the values and the deletions, ordered from the oldest
+    values: BTreeSet<(Timestamp, OrderedKey)>,
+    tombstones: BTreeSet<(Timestamp, OrderedKey)>,
+    // the total size of the values' payloads
+    bytes: usize,
+}
+
+impl Retention {
+    // Tracks the entries already in the storage at startup, with the kind and the size of their values
+    pub fn new(
+        config: RetentionConfig,
+        initial_entries: &[(OwnedKeyExpr, Timestamp, usize, SampleKind)],
+    ) -> Self {
+        let mut retention = Retention {
+            config,
+            entries: HashMap::new(),
+            values: BTreeSet::new(),
+            tombstones: BTreeSet::new(),
+            bytes: 0,
+        };
+        for (key, ts, size, kind) in initial_entries {
+            retention.insert(key.clone(), *ts, *size, *kind);
+        }
+        retention
+    }
+
+    // The period of the garbage collection, short enough to honor the configured lifetimes
+    pub fn gc_period(&self) -> Duration {
+        let lifetime = match self.config.max_age {
+            Some(max_age) => max_age.min(self.config.tombstone_lifetime),
+            None => self.config.tombstone_lifetime,
+        };
+        (lifetime / 2).clamp(MIN_GC_PERIOD, MAX_GC_PERIOD)
+    }
+
+    // Whether an update with this timestamp would be evicted right away
+    pub fn is_expired(&self, ts: &Timestamp, kind: SampleKind, now: SystemTime) -> bool {
+        let lifetime = match kind {
+            SampleKind::Put => match self.config.max_age {
+                Some(max_age) => max_age,
+                None => return false,
+            },
+            SampleKind::Delete => self.config.tombstone_lifetime,
+        };
+        age(ts, now) > lifetime
+    }
+
+    // Records an update accepted by the storage, replacing the previous entry for the key
+    pub fn insert(&mut self, key: OwnedKeyExpr, ts: Timestamp, size: usize, kind: SampleKind) {
+        self.remove(&key);
+        let deleted = kind == SampleKind::Delete;
+        if deleted {
+            self.tombstones.insert((ts, OrderedKey(key.clone())));
+        } else {
+            self.values.insert((ts, OrderedKey(key.clone())));
+            self.bytes += size;
+        }
+        self.entries.insert(key, Entry { ts, size, deleted });
+    }
+
+    // Stops tracking the entry for the key, e.g. when removed from the storage
+    pub fn remove(&mut self, key: &OwnedKeyExpr) {
+        if let Some(entry) = self.entries.remove(key) {
+            let ordered = (entry.ts, OrderedKey(key.clone()));
+            if entry.deleted {
+                self.tombstones.remove(&ordered);
+            } else {
+                self.values.remove(&ordered);
+                self.bytes -= entry.size;
+            }
+        }
+    }
+
+    // Selects the entries to evict (no longer tracked afterwards):
+    // - the deletions older than the tombstone lifetime
+    // - the values older than the max age
+    // - the oldest values, until the max number of keys and max size are honored
+    pub fn collect_evictions(&mut self, now: SystemTime) -> Vec<(OwnedKeyExpr, Timestamp)> {
+        let mut evicted = Vec::new();
+        while let Some((ts, OrderedKey(key))) = self.tombstones.iter().next().cloned() {
+            if age(&ts, now) <= self.config.tombstone_lifetime {
+                break;
+            }
+            self.remove(&key);
+            evicted.push((key, ts));
+        }
+        while let Some((ts, OrderedKey(key))) = self.values.iter().next().cloned() {
+            let too_old = matches!(self.config.max_age, Some(max_age) if age(&ts, now) > max_age);
+            let too_many =
+                matches!(self.config.max_keys, Some(max_keys) if self.values.len() > max_keys);
+            let too_big =
+                matches!(self.config.max_bytes, Some(max_bytes) if self.bytes > max_bytes);
+            if !(too_old || too_many || too_big) {
+                break;
+            }
+            self.remove(&key);
+            evicted.push((key, ts));
+        }
+        evicted
+    }
+}
+
+fn age(ts: &Timestamp, now: SystemTime) -> Duration {
+    now.duration_since(ts.get_time().to_system_time())
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+fn timestamp(time: SystemTime) -> Timestamp {
+    use std::convert::TryFrom;
+    use zenoh::time::{TimestampId, NTP64};
+    let since_epoch = time.duration_since(SystemTime::UNIX_EPOCH).unwrap();
+    Timestamp::new(
+        NTP64::from(since_epoch),
+        TimestampId::try_from([1]).unwrap(),
+    )
+}
+
+#[test]
+fn test_retention_lifetimes() {
+    use std::convert::TryFrom;
+    let now = SystemTime::now();
+    let config = RetentionConfig {
+        max_age: Some(Duration::from_secs(10)),
+        tombstone_lifetime: Duration::from_secs(5),
+        ..Default::default()
+    };
+    let mut retention = Retention::new(config, &[]);
+    let key = |k: &str| OwnedKeyExpr::try_from(k.to_string()).unwrap();
+    let old = timestamp(now - Duration::from_secs(20));
+    let recent = timestamp(now - Duration::from_secs(7));
+    assert!(retention.is_expired(&old, SampleKind::Put, now));
+    assert!(!retention.is_expired(&recent, SampleKind::Put, now));
+    assert!(retention.is_expired(&recent, SampleKind::Delete, now));
+
+    retention.insert(key("a"), old, 1, SampleKind::Put);
+    retention.insert(key("b"), recent, 1, SampleKind::Put);
+    retention.insert(key("c"), recent, 0, SampleKind::Delete);
+    let mut evicted = retention.collect_evictions(now);
+    evicted.sort_by(|(k1, _), (k2, _)| k1.as_str().cmp(k2.as_str()));
+    assert_eq!(evicted, vec![(key("a"), old), (key("c"), recent)]);
+    assert!(retention.collect_evictions(now).is_empty());
+
+    // a deletion replaces the value
+    retention.insert(key("b"), timestamp(now), 0, SampleKind::Delete);
+    assert!(retention.collect_evictions(now).is_empty());
+    assert_eq!(retention.bytes, 0);
+}
+
+#[test]
+fn test_retention_limits() {
+    use std::convert::TryFrom;
+    let now = SystemTime::now();
+    let config = RetentionConfig {
+        max_keys: Some(3),
+        max_bytes: Some(100),
+        ..Default::default()
+    };
+    let mut retention = Retention::new(config, &[]);
+    let key = |i: u64| OwnedKeyExpr::try_from(format!("k/{i}")).unwrap();
+    let ts = |i: u64| timestamp(now - Duration::from_secs(100 - i));
+    for i in 0..5 {
+        retention.insert(key(i), ts(i), 10, SampleKind::Put);
+    }
+    assert_eq!(
+        retention.collect_evictions(now),
+        vec![(key(0), ts(0)), (key(1), ts(1))]
+    );
+    retention.insert(key(5), ts(5), 90, SampleKind::Put);
+    assert_eq!(
+        retention.collect_evictions(now),
+        vec![(key(2), ts(2)), (key(3), ts(3))]
+    );
+    assert_eq!(retention.bytes, 100);
+}
@@ -0,0 +1,163 @@
+//
+// Copyright (c) 2022 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+
+// This module exports the content of a storage to a file, and reads it back to restore the storage
+// A snapshot is a JSON object per line, for each value or deletion of the storage, e.g.:
+// {"key":"demo/a","kind":"PUT","timestamp":"<time>/<id>","encoding":"text/plain","value":"<base64>"}
+// {"key":"demo/b","kind":"DELETE","timestamp":"<time>/<id>"}
+
+use base64::{engine::general_purpose::STANDARD as b64_std_engine, Engine};
+use log::warn;
+use serde_json::json;
+use std::collections::HashMap;
+use std::path::Path;
+use std::str::FromStr;
+use zenoh::prelude::r#async::*;
+use zenoh::time::Timestamp;
+use zenoh::Session;
+use zenoh_result::{bail, zerror, ZResult};
+
+fn to_json(sample: &Sample) -> serde_json::Value {
+    let mut entry = json!({
+        "key": sample.key_expr.as_str(),
+        "kind": sample.kind.to_string(),
+        "timestamp": sample.get_timestamp().map(|ts| ts.to_string()),
+    });
+    if sample.kind == SampleKind::Put {
+        entry["encoding"] = sample.encoding.to_string().into();
+        entry["value"] = b64_std_engine.encode(sample.payload.contiguous()).into();
+    }
+    entry
+}
+
+fn from_json(entry: &serde_json::Value) -> ZResult<Sample> {
+    let field = |name: &str| {
+        entry[name]
+            .as_str()
+            .ok_or_else(|| zerror!("missing `{}` in snapshot entry {}", name, entry))
+    };
+    let key_expr = OwnedKeyExpr::from_str(field("key")?)?;
+    let timestamp = Timestamp::from_str(field("timestamp")?)
+        .map_err(|e| zerror!("invalid timestamp in snapshot entry {}: {:?}", entry, e))?;
+    match field("kind")? {
+        "PUT" => {
+            let payload = b64_std_engine
+                .decode(field("value")?)
+                .map_err(|e| zerror!("invalid value in snapshot entry {}: {}", entry, e))?;
+            let encoding = Encoding::from(field("encoding")?.to_string());
+            Ok(
+                Sample::new(key_expr, Value::from(payload).encoding(encoding))
+                    .with_timestamp(timestamp),
+            )
+        }
+        "DELETE" => {
+            let mut sample = Sample::new(key_expr, Value::empty()).with_timestamp(timestamp);
+            sample.kind = SampleKind::Delete;
+            Ok(sample)
+        }
+        kind => bail!("invalid kind `{}` in snapshot entry {}", kind, entry),
+    }
+}
+
+// Writes a snapshot of the given entries of a storage (as returned by `get_all_entries`) to `path`.
+// The values are retrieved querying the local storages on key_expr, keeping only the replies matching
+// an entry; the remaining entries are the deletions. Returns the number of entries written.
+pub(crate) async fn take(
+    session: &Session,
+    key_expr: &OwnedKeyExpr,
+    entries: Vec<(OwnedKeyExpr, Timestamp)>,
+    path: &Path,
+) -> ZResult<usize> {
+    let mut pending: HashMap<OwnedKeyExpr, Timestamp> = entries.into_iter().collect();
+    let mut samples = Vec::with_capacity(pending.len());
+    let replies = session
+        .get(KeyExpr::from(key_expr).with_parameters("_time=[..]"))
+        .target(QueryTarget::All)
+        .consolidation(ConsolidationMode::None)
+        .allowed_destination(Locality::SessionLocal)
+        .res()
+        .await?;
+    while let Ok(reply) = replies.recv_async().await {
+        match reply.sample {
+            Ok(sample) => {
+                let key = OwnedKeyExpr::from(sample.key_expr.clone());
+                if sample.get_timestamp().is_some() && pending.get(&key) == sample.get_timestamp() {
+                    pending.remove(&key);
+                    samples.push(sample);
+                }
+            }
+            Err(e) => warn!("Error in reply to snapshot query on {}: {}", key_expr, e),
+        }
+    }
+    for (key, timestamp) in pending {
+        let mut sample = Sample::new(key, Value::empty()).with_timestamp(timestamp);
+        sample.kind = SampleKind::Delete;
+        samples.push(sample);
+    }
+    samples.sort_by(|s1, s2| s1.key_expr.as_str().cmp(s2.key_expr.as_str()));
+    write(&samples, path).await?;
+    Ok(samples.len())
+}
+
+async fn write(samples: &[Sample], path: &Path) -> ZResult<()> {
+    let mut content = String::new();
+    for sample in samples {
+        content.push_str(&to_json(sample).to_string());
+        content.push('\n');
+    }
+    async_std::fs::write(path, content)
+        .await
+        .map_err(|e| zerror!("Unable to write snapshot {}: {}", path.display(), e).into())
+}
+
+// Reads the samples of the snapshot at `path`
+pub(crate) async fn load(path: &Path) -> ZResult<Vec<Sample>> {
+    let content = async_std::fs::read_to_string(path)
+        .await
+        .map_err(|e| zerror!("Unable to read snapshot {}: {}", path.display(), e))?;
+    let mut samples = Vec::new();
+    for line in content.lines().filter(|line| !line.trim().is_empty()) {
+        let entry = serde_json::from_str(line)
+            .map_err(|e| zerror!("Invalid snapshot {}: {}", path.display(), e))?;
+        samples.push(from_json(&entry)?);
+    }
+    Ok(samples)
+}
+
+#[test]
+fn test_snapshot_format() {
+    let put = Sample::new(
+        OwnedKeyExpr::from_str("demo/a").unwrap(),
+        Value::from("hello").encoding(KnownEncoding::TextPlain.into()),
+    )
+    .with_timestamp(zenoh::time::new_reception_timestamp());
+    let mut delete = Sample::new(OwnedKeyExpr::from_str("demo/b").unwrap(), Value::empty())
+        .with_timestamp(zenoh::time::new_reception_timestamp());
+    delete.kind = SampleKind::Delete;
+
+    let path = std::env::temp_dir().join(format!("zenoh-snapshot-{}.jsonl", std::process::id()));
+    async_std::task::block_on(write(&[put.clone(), delete.clone()], &path)).unwrap();
+    let samples = async_std::task::block_on(load(&path)).unwrap();
+    std::fs::remove_file(&path).unwrap();
+
+    assert_eq!(samples.len(), 2);
+    assert_eq!(samples[0].key_expr, put.key_expr);
+    assert_eq!(samples[0].kind, SampleKind::Put);
+    assert_eq!(samples[0].encoding, put.encoding);
+    assert_eq!(samples[0].payload.contiguous(), put.payload.contiguous());
+    assert_eq!(samples[0].get_timestamp(), put.get_timestamp());
+    assert_eq!(samples[1].key_expr, delete.key_expr);
+    assert_eq!(samples[1].kind, SampleKind::Delete);
+    assert_eq!(samples[1].get_timestamp(), delete.get_timestamp());
+}
@@ -13,22 +13,31 @@
 //
 use async_std::sync::Arc;
 use log::trace;
+use std::path::PathBuf;
 use zenoh::prelude::r#async::*;
 use zenoh::Session;
 use zenoh_backend_traits::config::StorageConfig;
+use zenoh_backend_traits::ConflictResolver;
 use zenoh_result::ZResult;
 
+use super::replica::StripPrefix;
 pub use super::replica::{Replica, StorageService};
 
 pub enum StorageMessage {
     Stop,
     GetStatus(async_std::channel::Sender<serde_json::Value>),
+    // Snapshot/Restore/Clear/Align reply the number of entries written, restored, cleared or fetched
+    Snapshot(PathBuf, async_std::channel::Sender<ZResult<usize>>),
+    Restore(PathBuf, async_std::channel::Sender<ZResult<usize>>),
+    Clear(async_std::channel::Sender<ZResult<usize>>),
+    Align(async_std::channel::Sender<ZResult<usize>>),
 }
 
 pub struct StoreIntercept {
     pub storage: Box<dyn zenoh_backend_traits::Storage>,
     pub in_interceptor: Option<Arc<dyn Fn(Sample) -> Sample + Send + Sync>>,
     pub out_interceptor: Option<Arc<dyn Fn(Sample) -> Sample + Send + Sync>>,
+    pub conflict_resolver: Option<Arc<dyn ConflictResolver>>,
 }
 
 pub(crate) async fn start_storage(
@@ -37,6 +46,7 @@ pub(crate) async fn start_storage(
     admin_key: String,
     in_interceptor: Option<Arc<dyn Fn(Sample) -> Sample + Send + Sync>>,
     out_interceptor: Option<Arc<dyn Fn(Sample) -> Sample + Send + Sync>>,
+    conflict_resolver: Option<Arc<dyn ConflictResolver>>,
     zenoh: Arc<Session>,
 ) -> ZResult<flume::Sender<StorageMessage>> {
     // Ex: @/router/390CEC11A1E34977A1C609A35BC015E6/status/plugins/storage_manager/storages/demo1 -> 390CEC11A1E34977A1C609A35BC015E6/demo1 (/<type> needed????)
@@ -51,9 +61,10 @@ pub(crate) async fn start_storage(
 
     async_std::task::spawn(async move {
         let store_intercept = StoreIntercept {
-            storage,
+            storage: StripPrefix::wrap(storage, config.strip_prefix),
             in_interceptor,
             out_interceptor,
+            conflict_resolver,
         };
 
         // If a configuration for replica is present, we initialize a replica, else only a storage service
@@ -62,6 +73,7 @@ pub(crate) async fn start_storage(
             Some(replica_config) => {
                 Replica::start(
                     replica_config,
+                    config.retention,
                     zenoh.clone(),
                     store_intercept,
                     config.key_expr,
@@ -80,6 +92,7 @@ pub(crate) async fn start_storage(
                     store_intercept,
                     rx,
                     None,
+                    config.retention,
                 )
                 .await
             }
@@ -13,6 +13,33 @@
 //
 
 //! To manage groups and group memeberships
+//!
+//! # Leader election
+//!
+//! Each member elects as leader the member with the smallest identifier in its current view
+//! of the group. Every change of the elected leader (including the loss of a leader) increments
+//! the member's *term*. Terms are advertised in the keep-alive messages and each member adopts
+//! the highest term it receives, so that terms are monotonically increasing and, once the
+//! views have converged, all members agree on both the leader and the term.
+//!
+//! The term of the [`Leadership`] can be used as a fencing token: a resource protected by the
+//! leader should reject any request carrying a term lower than the highest term it has seen.
+//!
+//! ## Split-brain behaviour
+//!
+//! Failures are detected through lease expiration only, hence a network partition cannot be
+//! distinguished from the failure of the members on the other side. As a consequence:
+//! - Without quorum (the default), each side of a partition elects its own leader once the
+//!   leases of the unreachable members have expired, and [`Group::is_leader`] may return `true`
+//!   for several members at the same time. Concurrent leaders may also share the same term.
+//!   When the partition heals the views merge again, a single leader is elected and the term
+//!   becomes higher than any term used on either side during the partition.
+//! - With a [`Member::quorum`], a member only acknowledges a leader while its view contains at
+//!   least `quorum` members. Setting the quorum to a strict majority of the expected group size
+//!   guarantees that at most one side of a partition has a leader. Note however that a leader
+//!   isolated in a minority only steps down when the leases of the other members expire, so
+//!   the old and the new leader may overlap for up to a lease duration: the new leader always has
+//!   a strictly higher term, which is what fencing relies on.
 
 use async_std::sync::Mutex;
 use async_std::task::JoinHandle;
@@ -72,6 +99,25 @@ enum GroupNetEvent {
     KeepAlive(KeepAliveEvent),
 }
 
+/// Extension appended after an encoded [`GroupNetEvent`]. The members running a version
+/// without it ignore these trailing bytes, and it is missing from the events they send.
+#[derive(Serialize, Deserialize, Debug, Default, PartialEq, Eq)]
+struct GroupNetExt {
+    term: u64,
+}
+
+fn encode_net_event(evt: &GroupNetEvent, ext: &GroupNetExt) -> Vec<u8> {
+    let mut buf = bincode::serialize(evt).unwrap();
+    bincode::serialize_into(&mut buf, ext).unwrap();
+    buf
+}
+
+fn decode_net_event(mut buf: &[u8]) -> bincode::Result<(GroupNetEvent, GroupNetExt)> {
+    let evt = bincode::deserialize_from(&mut buf)?;
+    let ext = bincode::deserialize_from(&mut buf).unwrap_or_default();
+    Ok((evt, ext))
+}
+
 /// Events exposed to the user to be informed for relevant
 /// changes in the group.
 #[derive(Serialize, Deserialize, Debug)]
@@ -97,6 +143,8 @@ pub struct Member {
     refresh_ratio: f32,
     #[serde(skip)]
     priority: Priority,
+    #[serde(skip)]
+    quorum: Option<usize>,
 }
 
 impl Member {
@@ -116,6 +164,7 @@ impl Member {
             lease: DEFAULT_LEASE,
             refresh_ratio: VIEW_REFRESH_LEASE_RATIO,
             priority: DEFAULT_PRIORITY,
+            quorum: None,
         })
     }
 
@@ -150,8 +199,27 @@ impl Member {
         self.priority = p;
         self
     }
+
+    /// The minimum number of members (including itself) this member must see
+    /// in the group to acknowledge a leader. See the [module documentation](self)
+    /// for the split-brain behaviour.
+    pub fn quorum(mut self, q: usize) -> Self {
+        self.quorum = Some(q);
+        self
+    }
 }
 
+/// The leadership of a group as seen by a member.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Leadership {
+    /// The term of this leadership, incremented on each change of leader.
+    pub term: u64,
+    /// The elected leader, or `None` if no leader could be elected (quorum not reached).
+    pub leader: Option<OwnedKeyExpr>,
+}
+
+type LeadershipCallback = Arc<dyn Fn(&Leadership) + Send + Sync>;
+
 struct GroupState {
     gid: String,
     local_member: Member,
@@ -159,6 +227,8 @@ struct GroupState {
     group_publisher: Publisher<'static>,
     user_events_tx: Mutex<Option<Sender<GroupEvent>>>,
     cond: Condition,
+    leadership: Mutex<Leadership>,
+    leadership_callbacks: Mutex<Vec<LeadershipCallback>>,
 }
 
 pub struct Group {
@@ -177,10 +247,14 @@ impl Drop for Group {
     }
 }
 
-async fn keep_alive_task(state: Arc<GroupState>) {
+async fn send_keep_alive(state: &GroupState, term: u64) {
     let mid = state.local_member.mid.clone();
     let evt = GroupNetEvent::KeepAlive(KeepAliveEvent { mid });
-    let buf = bincode::serialize(&evt).unwrap();
+    let buf = encode_net_event(&evt, &GroupNetExt { term });
+    let _ = state.group_publisher.put(buf).res().await;
+}
+
+async fn keep_alive_task(state: Arc<GroupState>) {
     let period = state
         .local_member
         .lease
@@ -188,7 +262,64 @@ async fn keep_alive_task(state: Arc<GroupState>) {
     loop {
         async_std::task::sleep(period).await;
         log::trace!("Sending Keep Alive for: {}", &state.local_member.mid);
-        let _ = state.group_publisher.put(buf.clone()).res().await;
+        let term = state.leadership.lock().await.term;
+        send_keep_alive(&state, term).await;
+    }
+}
+
+fn elect(
+    local_member: &Member,
+    members: &HashMap<OwnedKeyExpr, (Member, Instant)>,
+) -> Option<OwnedKeyExpr> {
+    if let Some(quorum) = local_member.quorum {
+        if members.len() + 1 < quorum {
+            return None;
+        }
+    }
+    members
+        .keys()
+        .chain(std::iter::once(&local_member.mid))
+        .min_by(|a, b| a.as_str().cmp(b.as_str()))
+        .cloned()
+}
+
+/// Re-elects the leader according to the current view, adopting `seen_term` if it is higher
+/// than the local term. The term is incremented and advertised if the leader changed.
+async fn update_leadership(state: &GroupState, seen_term: u64) {
+    let leader = elect(&state.local_member, &*state.members.lock().await);
+    let mut current = state.leadership.lock().await;
+    let mut leadership = Leadership {
+        term: current.term.max(seen_term),
+        leader,
+    };
+    let leader_changed = leadership.leader != current.leader;
+    if leader_changed {
+        leadership.term += 1;
+    }
+    if leadership == *current {
+        return;
+    }
+    log::debug!(
+        "Leadership of {} changed: {:?}",
+        &state.local_member.mid,
+        &leadership
+    );
+    *current = leadership.clone();
+    drop(current);
+
+    if leader_changed {
+        send_keep_alive(state, leadership.term).await;
+        if let Some(mid) = &leadership.leader {
+            let u_evt = &*state.user_events_tx.lock().await;
+            if let Some(tx) = u_evt {
+                let nle = NewLeaderEvent { mid: mid.clone() };
+                tx.send(GroupEvent::NewLeader(nle)).unwrap()
+            }
+        }
+    }
+    let callbacks = state.leadership_callbacks.lock().await.clone();
+    for callback in callbacks {
+        callback(&leadership);
     }
 }
 
@@ -211,13 +342,16 @@ fn spawn_watchdog(s: Arc<GroupState>, period: Duration) -> JoinHandle<()> {
             if !expired_members.is_empty() {
                 log::debug!("Other members list: {:?}", ms.keys());
                 drop(ms);
-                let u_evt = &*s.user_events_tx.lock().await;
-                for e in expired_members {
-                    if let Some(tx) = u_evt {
-                        tx.send(GroupEvent::LeaseExpired(LeaseExpiredEvent { mid: e }))
-                            .unwrap()
+                {
+                    let u_evt = &*s.user_events_tx.lock().await;
+                    for e in expired_members {
+                        if let Some(tx) = u_evt {
+                            tx.send(GroupEvent::LeaseExpired(LeaseExpiredEvent { mid: e }))
+                                .unwrap()
+                        }
                     }
                 }
+                update_leadership(&s, 0).await;
             }
         }
     };
@@ -252,8 +386,8 @@ async fn net_event_handler(z: Arc<Session>, state: Arc<GroupState>) {
         .await
         .unwrap();
     while let Ok(s) = sub.recv_async().await {
-        match bincode::deserialize::<GroupNetEvent>(&(s.value.payload.contiguous())) {
-            Ok(evt) => match evt {
+        match decode_net_event(&s.value.payload.contiguous()) {
+            Ok((evt, ext)) => match evt {
                 GroupNetEvent::Join(je) => {
                     log::debug!("Member join: {:?}", &je.member);
                     let alive_till = Instant::now().add(je.member.lease);
@@ -262,10 +396,10 @@ async fn net_event_handler(z: Arc<Session>, state: Arc<GroupState>) {
                     log::debug!("Other members list: {:?}", ms.keys());
                     state.cond.notify_all();
                     drop(ms);
-                    let u_evt = &*state.user_events_tx.lock().await;
-                    if let Some(tx) = u_evt {
+                    if let Some(tx) = &*state.user_events_tx.lock().await {
                         tx.send(GroupEvent::Join(je)).unwrap()
                     }
+                    update_leadership(&state, 0).await;
                 }
                 GroupNetEvent::Leave(le) => {
                     log::debug!("Member leave: {:?}", &le.mid);
@@ -273,10 +407,10 @@ async fn net_event_handler(z: Arc<Session>, state: Arc<GroupState>) {
                     ms.remove(&le.mid);
                     log::debug!("Other members list: {:?}", ms.keys());
                     drop(ms);
-                    let u_evt = &*state.user_events_tx.lock().await;
-                    if let Some(tx) = u_evt {
+                    if let Some(tx) = &*state.user_events_tx.lock().await {
                         tx.send(GroupEvent::Leave(le)).unwrap()
                     }
+                    update_leadership(&state, 0).await;
                 }
                 GroupNetEvent::KeepAlive(kae) => {
                     log::debug!(
@@ -349,6 +483,8 @@ async fn net_event_handler(z: Arc<Session>, state: Arc<GroupState>) {
                                 state.cond.notify_all();
                             }
                         }
+                        drop(mm);
+                        update_leadership(&state, ext.term).await;
                     } else {
                         log::trace!("KeepAlive from Local Participant -- Ignoring");
                     }
@@ -386,6 +522,11 @@ impl Group {
             group_publisher: publisher,
             user_events_tx: Mutex::new(Default::default()),
             cond: Condition::new(),
+            leadership: Mutex::new(Leadership {
+                term: 0,
+                leader: None,
+            }),
+            leadership_callbacks: Mutex::new(Default::default()),
         });
         let is_auto_liveliness = matches!(with.liveliness, MemberLiveliness::Auto);
 
@@ -394,6 +535,7 @@ impl Group {
         let join_evt = GroupNetEvent::Join(JoinEvent { member: with });
         let buf = bincode::serialize(&join_evt).unwrap();
         let _ = state.group_publisher.put(buf).res().await;
+        update_leadership(&state, 0).await;
 
         // If the liveliness is manual it is the user who has to assert it.
         if is_auto_liveliness {
@@ -472,4 +614,65 @@ impl Group {
         let ms = self.state.members.lock().await;
         ms.len() + 1 // with +1 being the local member
     }
+
+    /// Returns the current leadership of the group as seen by this member.
+    pub async fn leadership(&self) -> Leadership {
+        self.state.leadership.lock().await.clone()
+    }
+
+    /// Returns the current leader of the group as seen by this member, if any.
+    pub async fn leader(&self) -> Option<OwnedKeyExpr> {
+        self.state.leadership.lock().await.leader.clone()
+    }
+
+    /// Returns the current term as seen by this member.
+    pub async fn term(&self) -> u64 {
+        self.state.leadership.lock().await.term
+    }
+
+    /// Returns `true` if this member is the leader of the group in its current view.
+    pub async fn is_leader(&self) -> bool {
+        self.state.leadership.lock().await.leader.as_ref() == Some(&self.state.local_member.mid)
+    }
+
+    /// Registers a callback called on each change of the [`Leadership`] seen by this member,
+    /// either because a new leader was elected or because a higher term was adopted.
+    pub async fn on_leadership_change<F>(&self, callback: F)
+    where
+        F: Fn(&Leadership) + Send + Sync + 'static,
+    {
+        self.state
+            .leadership_callbacks
+            .lock()
+            .await
+            .push(Arc::new(callback));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn keep_alive(mid: &str) -> GroupNetEvent {
+        GroupNetEvent::KeepAlive(KeepAliveEvent {
+            mid: OwnedKeyExpr::new(mid).unwrap(),
+        })
+    }
+
+    #[test]
+    fn net_event_ext_compatibility() {
+        // The events of the members without the extension decode with the default term
+        let legacy = bincode::serialize(&keep_alive("a")).unwrap();
+        let (evt, ext) = decode_net_event(&legacy).unwrap();
+        assert!(matches!(evt, GroupNetEvent::KeepAlive(kae) if kae.mid.as_str() == "a"));
+        assert_eq!(ext, GroupNetExt::default());
+
+        // The members without the extension ignore it
+        let buf = encode_net_event(&keep_alive("a"), &GroupNetExt { term: 42 });
+        assert!(buf.starts_with(&legacy));
+        let evt = bincode::deserialize::<GroupNetEvent>(&buf).unwrap();
+        assert!(matches!(evt, GroupNetEvent::KeepAlive(kae) if kae.mid.as_str() == "a"));
+        let (_, ext) = decode_net_event(&buf).unwrap();
+        assert_eq!(ext, GroupNetExt { term: 42 });
+    }
 }
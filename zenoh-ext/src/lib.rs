@@ -12,11 +12,26 @@
 //   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
 //
 pub mod group;
+mod lock;
 mod publication_cache;
 mod querying_subscriber;
+mod reliable_publisher;
+mod reliable_subscriber;
 mod session_ext;
+mod streaming_publisher;
+mod streaming_subscriber;
 mod subscriber_ext;
+pub use lock::{LockBuilder, LockGuard};
 pub use publication_cache::{PublicationCache, PublicationCacheBuilder};
-pub use querying_subscriber::{QueryingSubscriber, QueryingSubscriberBuilder};
+pub use querying_subscriber::{
+    FetchCallback, FetchingSubscriber, FetchingSubscriberBuilder, QueryingSubscriber,
+    QueryingSubscriberBuilder,
+};
+pub use reliable_publisher::{ReliablePublisher, ReliablePublisherBuilder};
+pub use reliable_subscriber::{ReliableSubscriber, ReliableSubscriberBuilder, SampleMiss};
 pub use session_ext::SessionExt;
+pub use streaming_publisher::{StreamWriter, StreamingPublisher, StreamingPublisherBuilder};
+pub use streaming_subscriber::{
+    IncomingStream, StreamProgress, StreamingSubscriber, StreamingSubscriberBuilder,
+};
 pub use subscriber_ext::SubscriberForward;
@@ -0,0 +1,350 @@
+//
+// Copyright (c) 2022 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+use futures::select;
+use futures::FutureExt;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::convert::TryFrom;
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use zenoh::prelude::r#async::*;
+use zenoh::query::{ConsolidationMode, QueryTarget};
+use zenoh::queryable::{Query, Queryable};
+use zenoh_core::{zlock, AsyncResolve, Resolvable, SyncResolve};
+use zenoh_result::{bail, ZResult};
+use zenoh_util::core::ResolveFuture;
+
+use crate::session_ext::SessionRef;
+
+const LOCK_PREFIX: &str = "zenoh/ext/lock";
+const DEFAULT_LEASE: Duration = Duration::from_secs(10);
+const POLL_LEASE_RATIO: f32 = 0.25f32;
+
+static CANDIDATE_COUNTER: AtomicU64 = AtomicU64::new(0);
+
+/// The state of a candidate to a lock, as replied by its queryable
+/// and published on each change.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+struct Candidate {
+    /// The ticket of the candidate, `None` while it is being chosen.
+    ticket: Option<u64>,
+    lease: Duration,
+}
+
+/// The builder of a lock acquisition, allowing to configure it.
+pub struct LockBuilder<'a, 'b> {
+    session: SessionRef<'a>,
+    key_expr: ZResult<KeyExpr<'b>>,
+    timeout: Option<Duration>,
+    lease: Duration,
+}
+
+impl<'a, 'b> LockBuilder<'a, 'b> {
+    pub(crate) fn new(session: SessionRef<'a>, key_expr: ZResult<KeyExpr<'b>>) -> Self {
+        LockBuilder {
+            session,
+            key_expr,
+            timeout: None,
+            lease: DEFAULT_LEASE,
+        }
+    }
+
+    /// Give up acquiring the lock after the given duration (by default, wait forever).
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    /// Change the lease of this candidate: if it can't be reached by the other candidates
+    /// during this duration (e.g. because its session crashed) it is considered as gone,
+    /// and the lock as released if it was holding it.
+    pub fn lease(mut self, lease: Duration) -> Self {
+        self.lease = lease;
+        self
+    }
+}
+
+impl<'a> Resolvable for LockBuilder<'a, '_> {
+    type To = ZResult<LockGuard<'a>>;
+}
+
+impl SyncResolve for LockBuilder<'_, '_> {
+    fn res_sync(self) -> <Self as Resolvable>::To {
+        async_std::task::block_on(self.res_async())
+    }
+}
+
+impl<'a> AsyncResolve for LockBuilder<'a, '_> {
+    type Future = Pin<Box<dyn Future<Output = Self::To> + Send + 'a>>;
+
+    fn res_async(self) -> Self::Future {
+        let key_expr = self.key_expr.map(|k| k.into_owned());
+        Box::pin(acquire(self.session, key_expr, self.timeout, self.lease))
+    }
+}
+
+/// A distributed lock on a key expression, held until dropped or [released](LockGuard::release).
+///
+/// Candidates are served in the order of their tickets, which are chosen following
+/// the Lamport's bakery algorithm: each candidate takes a ticket greater than the ones
+/// of the candidates it can see, so that the lock is granted in FIFO order.
+///
+/// Each candidate declares a queryable replying its ticket to the other candidates, which poll
+/// it every quarter of their lease: answering these queries renews the candidate's lease.
+/// To let the candidacies propagate, a lock is never acquired before the first of these polls,
+/// even when it is free. A candidate which
+/// can't be reached for longer than its lease (e.g. because its session closed or crashed)
+/// is considered as gone, and the lock is released if it was holding it.
+///
+/// Note that this lock does not provide strict mutual exclusion in presence of network
+/// partitions: a holder isolated for longer than its lease may be superseded by another
+/// candidate while still considering itself as holding the lock.
+pub struct LockGuard<'a> {
+    session: SessionRef<'a>,
+    key_expr: KeyExpr<'static>,
+    candidate_key_expr: KeyExpr<'static>,
+    queryable: Option<Queryable<'a, ()>>,
+}
+
+impl<'a> LockGuard<'a> {
+    /// The key expression locked by this guard.
+    pub fn key_expr(&self) -> &KeyExpr<'static> {
+        &self.key_expr
+    }
+
+    /// Release the lock.
+    pub fn release(mut self) -> impl Resolve<ZResult<()>> + 'a {
+        ResolveFuture::new(async move {
+            if let Some(queryable) = self.queryable.take() {
+                queryable.undeclare().res_async().await?;
+                self.session
+                    .delete(&self.candidate_key_expr)
+                    .res_async()
+                    .await?;
+            }
+            Ok(())
+        })
+    }
+}
+
+impl Drop for LockGuard<'_> {
+    fn drop(&mut self) {
+        if self.queryable.take().is_some() {
+            let _ = self.session.delete(&self.candidate_key_expr).res_sync();
+        }
+    }
+}
+
+fn candidate_id(key_expr: &keyexpr) -> &str {
+    key_expr.as_str().rsplit('/').next().unwrap_or_default()
+}
+
+fn update_candidates(candidates: &mut HashMap<String, (Candidate, Instant)>, sample: &Sample) {
+    let id = candidate_id(&sample.key_expr);
+    match sample.kind {
+        SampleKind::Put => match bincode::deserialize::<Candidate>(&sample.payload.contiguous()) {
+            Ok(c) => {
+                candidates.insert(id.to_string(), (c, Instant::now()));
+            }
+            Err(e) => log::warn!("Unable to deserialize lock candidate {}: {}", id, e),
+        },
+        SampleKind::Delete => {
+            log::trace!("Lock candidate {} left", id);
+            candidates.remove(id);
+        }
+    }
+}
+
+async fn poll_candidates(
+    session: &Session,
+    selector: &str,
+    own_id: &str,
+    candidates: &mut HashMap<String, (Candidate, Instant)>,
+    timeout: Duration,
+) -> ZResult<()> {
+    let replies = session
+        .get(selector)
+        .target(QueryTarget::All)
+        .consolidation(ConsolidationMode::None)
+        .timeout(timeout)
+        .res_async()
+        .await?;
+    while let Ok(reply) = replies.recv_async().await {
+        match reply.sample {
+            Ok(sample) => {
+                if candidate_id(&sample.key_expr) != own_id {
+                    update_candidates(candidates, &sample);
+                }
+            }
+            Err(e) => log::debug!("Error received polling lock candidates: {}", e),
+        }
+    }
+    Ok(())
+}
+
+async fn acquire<'a>(
+    session: SessionRef<'a>,
+    key_expr: ZResult<KeyExpr<'static>>,
+    timeout: Option<Duration>,
+    lease: Duration,
+) -> ZResult<LockGuard<'a>> {
+    let key_expr = key_expr?;
+    if key_expr.is_wild() {
+        bail!(
+            "Lock key expression is not allowed to contain wildcards: {}",
+            key_expr
+        );
+    }
+    let deadline = timeout.map(|t| Instant::now() + t);
+    let poll_period = lease.mul_f32(POLL_LEASE_RATIO);
+    let selector = format!("{}/{}/*", LOCK_PREFIX, key_expr);
+    let own_id = format!(
+        "{}_{}",
+        session.zid(),
+        CANDIDATE_COUNTER.fetch_add(1, Ordering::Relaxed)
+    );
+    let candidate_key_expr = KeyExpr::try_from(format!("{}/{}/{}", LOCK_PREFIX, key_expr, own_id))?;
+    let own = Arc::new(Mutex::new(Candidate {
+        ticket: None,
+        lease,
+    }));
+
+    // Declare the candidacy before choosing a ticket, so that the candidates
+    // choosing concurrently wait for this one to have chosen.
+    let (tx, rx) = flume::unbounded();
+    let subscriber = session
+        .declare_subscriber(&selector)
+        .callback(move |s| {
+            let _ = tx.send(s);
+        })
+        .res_async()
+        .await?;
+    let c_own = own.clone();
+    let c_candidate_key_expr = candidate_key_expr.clone();
+    let queryable_callback = move |query: Query| {
+        let buf = bincode::serialize(&*zlock!(c_own)).unwrap();
+        let sample = Sample::new(c_candidate_key_expr.clone(), buf);
+        if let Err(e) = query.reply(Ok(sample)).res_sync() {
+            log::warn!("Error replying to lock candidates query: {}", e);
+        }
+    };
+    let queryable = match session.clone() {
+        SessionRef::Borrow(session) => {
+            session
+                .declare_queryable(&candidate_key_expr)
+                .callback(queryable_callback)
+                .res_async()
+                .await?
+        }
+        SessionRef::Shared(session) => {
+            session
+                .declare_queryable(&candidate_key_expr)
+                .callback(queryable_callback)
+                .res_async()
+                .await?
+        }
+    };
+    let guard = LockGuard {
+        session: session.clone(),
+        key_expr,
+        candidate_key_expr,
+        queryable: Some(queryable),
+    };
+    let publish = |c: Candidate| {
+        session
+            .put(&guard.candidate_key_expr, bincode::serialize(&c).unwrap())
+            .res_async()
+    };
+    let c = zlock!(own).clone();
+    publish(c).await?;
+
+    // Choose a ticket greater than the ones of the visible candidates.
+    let mut candidates = HashMap::new();
+    poll_candidates(&session, &selector, &own_id, &mut candidates, poll_period).await?;
+    while let Ok(s) = rx.try_recv() {
+        if candidate_id(&s.key_expr) != own_id {
+            update_candidates(&mut candidates, &s);
+        }
+    }
+    let ticket = candidates
+        .values()
+        .filter_map(|(c, _)| c.ticket)
+        .max()
+        .unwrap_or(0)
+        + 1;
+    log::debug!("Lock candidate {} took ticket {}", own_id, ticket);
+    let c = {
+        let mut own = zlock!(own);
+        own.ticket = Some(ticket);
+        own.clone()
+    };
+    publish(c).await?;
+
+    // Wait for the candidates with a lower ticket (or still choosing) to leave.
+    // The lock can't be acquired before a poll period has elapsed since the candidacy
+    // was declared, so that the declarations of concurrent candidates are propagated.
+    let mut next_poll = Instant::now() + poll_period;
+    let mut polled = false;
+    loop {
+        let now = Instant::now();
+        if now >= next_poll {
+            poll_candidates(&session, &selector, &own_id, &mut candidates, poll_period).await?;
+            next_poll = Instant::now() + poll_period;
+            polled = true;
+        }
+        while let Ok(s) = rx.try_recv() {
+            if candidate_id(&s.key_expr) != own_id {
+                update_candidates(&mut candidates, &s);
+            }
+        }
+        let now = Instant::now();
+        candidates.retain(|id, (c, seen)| {
+            let alive = *seen + c.lease > now;
+            if !alive {
+                log::debug!("Lease of lock candidate {} expired", id);
+            }
+            alive
+        });
+        let blocked = candidates.iter().any(|(id, (c, _))| match c.ticket {
+            Some(t) => (t, id.as_str()) < (ticket, own_id.as_str()),
+            None => true,
+        });
+        if polled && !blocked {
+            break;
+        }
+        let mut wake_up = next_poll;
+        if let Some(deadline) = deadline {
+            if now >= deadline {
+                bail!("Timeout acquiring lock on {}", guard.key_expr);
+            }
+            wake_up = wake_up.min(deadline);
+        }
+        select!(
+            s = rx.recv_async().fuse() => {
+                if let Ok(s) = s {
+                    if candidate_id(&s.key_expr) != own_id {
+                        update_candidates(&mut candidates, &s);
+                    }
+                }
+            },
+            _ = async_std::task::sleep(wake_up.saturating_duration_since(now)).fuse() => {},
+        );
+    }
+    log::debug!("Lock on {} acquired by {}", guard.key_expr, own_id);
+    subscriber.undeclare().res_async().await?;
+    Ok(guard)
+}
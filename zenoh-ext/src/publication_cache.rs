@@ -18,6 +18,7 @@ use futures::{FutureExt, StreamExt};
 use std::collections::{HashMap, VecDeque};
 use std::convert::TryInto;
 use std::future::Ready;
+use std::ops::RangeInclusive;
 use zenoh::prelude::r#async::*;
 use zenoh::queryable::{Query, Queryable};
 use zenoh::subscriber::FlumeSubscriber;
@@ -26,6 +27,36 @@ use zenoh_core::{AsyncResolve, Resolvable, SyncResolve};
 use zenoh_result::{bail, ZResult};
 use zenoh_util::core::ResolveFuture;
 
+/// The selector parameter restricting a query on a [`PublicationCache`] to the publications
+/// whose source sequence number is in the given range (e.g. `_sn=10..20`, both bounds included).
+pub(crate) const SN_RANGE_KEY: &str = "_sn";
+
+/// Parses the value of a [`SN_RANGE_KEY`] selector parameter (`<start>..<end>`, where any bound may be omitted).
+pub(crate) fn parse_sn_range(s: &str) -> ZResult<RangeInclusive<ZInt>> {
+    match s.split_once("..") {
+        Some((start, end)) => {
+            let start = if start.is_empty() { 0 } else { start.parse()? };
+            let end = if end.is_empty() {
+                ZInt::MAX
+            } else {
+                end.parse()?
+            };
+            Ok(start..=end)
+        }
+        None => bail!("Invalid sequence number range: {}", s),
+    }
+}
+
+fn sample_in_sn_range(sample: &Sample, sn_range: &Option<RangeInclusive<ZInt>>) -> bool {
+    match sn_range {
+        Some(range) => sample
+            .source_info
+            .source_sn
+            .map_or(false, |sn| range.contains(&sn)),
+        None => true,
+    }
+}
+
 /// The builder of PublicationCache, allowing to configure it.
 pub struct PublicationCacheBuilder<'a, 'b, 'c> {
     session: &'a Session,
@@ -192,9 +223,23 @@ impl<'a> PublicationCache<'a> {
                     // on query, reply with cach content
                     query = quer_recv.recv_async() => {
                         if let Ok(query) = query {
+                            let sn_range = match query.parameters().get_parameters([SN_RANGE_KEY]) {
+                                Ok([Some(s)]) => match parse_sn_range(&s) {
+                                    Ok(range) => Some(range),
+                                    Err(e) => {
+                                        log::warn!("Ignoring query {}: {}", query, e);
+                                        continue;
+                                    }
+                                },
+                                Ok([None]) => None,
+                                Err(e) => {
+                                    log::warn!("Ignoring query {}: {}", query, e);
+                                    continue;
+                                }
+                            };
                             if !query.selector().key_expr.as_str().contains('*') {
                                 if let Some(queue) = cache.get(query.selector().key_expr.as_keyexpr()) {
-                                    for sample in queue {
+                                    for sample in queue.iter().filter(|s| sample_in_sn_range(s, &sn_range)) {
                                         if let Err(e) = query.reply(Ok(sample.clone())).res_async().await {
                                             log::warn!("Error replying to query: {}", e);
                                         }
@@ -203,7 +248,7 @@ impl<'a> PublicationCache<'a> {
                             } else {
                                 for (key_expr, queue) in cache.iter() {
                                     if query.selector().key_expr.intersects(unsafe{ keyexpr::from_str_unchecked(key_expr) }) {
-                                        for sample in queue {
+                                        for sample in queue.iter().filter(|s| sample_in_sn_range(s, &sn_range)) {
                                             if let Err(e) = query.reply(Ok(sample.clone())).res_async().await {
                                                 log::warn!("Error replying to query: {}", e);
                                             }
@@ -11,19 +11,25 @@
 // Contributors:
 //   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
 //
-use std::collections::{btree_map, BTreeMap, VecDeque};
+use async_std::channel::{bounded, Sender};
+use async_std::task;
+use futures::future::{self, BoxFuture};
+use futures::select;
+use futures::{FutureExt, StreamExt};
+use std::collections::{btree_map, BTreeMap, HashMap, VecDeque};
 use std::convert::TryInto;
-use std::future::Ready;
+use std::future::{Future, Ready};
 use std::mem::swap;
 use std::sync::{Arc, Mutex};
-use std::time::Duration;
+use std::time::{Duration, Instant};
 use zenoh::handlers::{locked, DefaultHandler};
 use zenoh::prelude::r#async::*;
 use zenoh::query::{QueryConsolidation, QueryTarget, ReplyKeyExpr};
 use zenoh::subscriber::{Reliability, Subscriber};
 use zenoh::time::Timestamp;
 use zenoh::Result as ZResult;
-use zenoh_core::{zlock, AsyncResolve, Resolvable, SyncResolve};
+use zenoh_core::{zlock, AsyncResolve, Resolvable, ResolveClosure, SyncResolve};
+use zenoh_result::bail;
 
 use crate::session_ext::SessionRef;
 
@@ -37,6 +43,8 @@ pub struct QueryingSubscriberBuilder<'a, 'b, Handler> {
     query_target: QueryTarget,
     query_consolidation: QueryConsolidation,
     query_timeout: Duration,
+    query_period: Option<Duration>,
+    query_on_connect: bool,
     handler: Handler,
 }
 
@@ -61,6 +69,8 @@ impl<'a, 'b> QueryingSubscriberBuilder<'a, 'b, DefaultHandler> {
             query_target,
             query_consolidation,
             query_timeout: Duration::from_secs(10),
+            query_period: None,
+            query_on_connect: false,
             handler: DefaultHandler,
         }
     }
@@ -83,6 +93,8 @@ impl<'a, 'b> QueryingSubscriberBuilder<'a, 'b, DefaultHandler> {
             query_target,
             query_consolidation,
             query_timeout,
+            query_period,
+            query_on_connect,
             handler: _,
         } = self;
         QueryingSubscriberBuilder {
@@ -94,6 +106,8 @@ impl<'a, 'b> QueryingSubscriberBuilder<'a, 'b, DefaultHandler> {
             query_target,
             query_consolidation,
             query_timeout,
+            query_period,
+            query_on_connect,
             handler: callback,
         }
     }
@@ -128,6 +142,8 @@ impl<'a, 'b> QueryingSubscriberBuilder<'a, 'b, DefaultHandler> {
             query_target,
             query_consolidation,
             query_timeout,
+            query_period,
+            query_on_connect,
             handler: _,
         } = self;
         QueryingSubscriberBuilder {
@@ -139,6 +155,8 @@ impl<'a, 'b> QueryingSubscriberBuilder<'a, 'b, DefaultHandler> {
             query_target,
             query_consolidation,
             query_timeout,
+            query_period,
+            query_on_connect,
             handler,
         }
     }
@@ -209,6 +227,33 @@ impl<'a, 'b, Handler> QueryingSubscriberBuilder<'a, 'b, Handler> {
         self
     }
 
+    /// Periodically issue a new query using the configured selector.
+    ///
+    /// Samples with a timestamp are delivered only if they are newer than the last one delivered
+    /// on their key expression, so that the samples retrieved again are not delivered twice.
+    /// The key expressions neither retrieved nor received since the start of the previous query are
+    /// forgotten though, so that a sample that is retrieved again after having been absent from a
+    /// whole query is delivered again.
+    ///
+    /// This requires the QueryingSubscriber to be declared on an `Arc<Session>`.
+    #[inline]
+    pub fn query_period(mut self, query_period: Duration) -> Self {
+        self.query_period = Some(query_period);
+        self
+    }
+
+    /// Issue a new query using the configured selector each time the session gets connected to a new
+    /// peer or router, or gets notified of a new queryable intersecting the selector's key expression,
+    /// so that the publication caches and storages that become reachable are queried.
+    /// This includes the storages appearing behind an already connected router.
+    ///
+    /// This requires the QueryingSubscriber to be declared on an `Arc<Session>`.
+    #[inline]
+    pub fn query_on_connect(mut self, query_on_connect: bool) -> Self {
+        self.query_on_connect = query_on_connect;
+        self
+    }
+
     fn with_static_keys(self) -> QueryingSubscriberBuilder<'a, 'static, Handler> {
         QueryingSubscriberBuilder {
             session: self.session,
@@ -219,6 +264,8 @@ impl<'a, 'b, Handler> QueryingSubscriberBuilder<'a, 'b, Handler> {
             query_target: self.query_target,
             query_consolidation: self.query_consolidation,
             query_timeout: self.query_timeout,
+            query_period: self.query_period,
+            query_on_connect: self.query_on_connect,
             handler: self.handler,
         }
     }
@@ -309,9 +356,275 @@ impl Iterator for MergeQueueValues {
     }
 }
 
+// The timestamp of the latest sample delivered per key expression, when re-querying:
+// the samples a re-query retrieves again are not delivered twice.
+//
+// Only the key expressions seen since the start of the previous queries are kept, the other
+// ones being unlikely to be retrieved again: the timestamps don't pile up for all the key
+// expressions ever seen, but only for the ones retrieved by a query or published in between.
+#[derive(Default)]
+struct Latest {
+    // the key expressions seen since the start of the last queries
+    current: HashMap<KeyExpr<'static>, Timestamp>,
+    // the key expressions seen between the start of the previous queries and of the last ones
+    previous: HashMap<KeyExpr<'static>, Timestamp>,
+}
+
+impl Latest {
+    // Whether the timestamp is newer than the latest one on the key expression.
+    fn is_new(&mut self, key_expr: &KeyExpr<'static>, ts: Timestamp) -> bool {
+        let latest = match self.current.get(key_expr) {
+            Some(t) => Some(*t),
+            None => self.previous.remove(key_expr),
+        };
+        match latest {
+            Some(t) if t >= ts => {
+                self.current.insert(key_expr.clone().into_owned(), t);
+                false
+            }
+            _ => {
+                self.current.insert(key_expr.clone().into_owned(), ts);
+                true
+            }
+        }
+    }
+
+    // Forgets the key expressions that were not seen since the start of the previous queries.
+    fn rotate(&mut self) {
+        self.previous = std::mem::take(&mut self.current);
+    }
+}
+
 struct InnerState {
     pending_queries: u64,
     merge_queue: MergeQueue,
+    latest: Option<Latest>,
+}
+
+impl InnerState {
+    fn new(requery: bool) -> Self {
+        InnerState {
+            pending_queries: 0,
+            merge_queue: MergeQueue::new(),
+            latest: requery.then(Latest::default),
+        }
+    }
+
+    // Whether the sample is newer than the latest one delivered on its key expression.
+    fn is_new(&mut self, sample: &Sample) -> bool {
+        match (&mut self.latest, sample.timestamp) {
+            (Some(latest), Some(ts)) => latest.is_new(&sample.key_expr, ts),
+            _ => true,
+        }
+    }
+
+    // Counts a query in progress, until its RepliesHandler is dropped.
+    fn start_query(&mut self) {
+        if self.pending_queries == 0 {
+            if let Some(latest) = &mut self.latest {
+                latest.rotate();
+            }
+        }
+        self.pending_queries += 1;
+    }
+}
+
+// Returns the callback of a subscriber that delivers the received publications,
+// or that pushes them to the merge_queue while queries are in progress.
+fn merging_callback(
+    state: Arc<Mutex<InnerState>>,
+    callback: Arc<dyn Fn(Sample) + Send + Sync + 'static>,
+) -> impl Fn(Sample) + Send + Sync + 'static {
+    move |mut s| {
+        let state = &mut zlock!(state);
+        if state.pending_queries == 0 {
+            if state.is_new(&s) {
+                callback(s);
+            }
+        } else {
+            log::trace!("Sample received while query in progress: push it to merge_queue");
+            // ensure the sample has a timestamp, thus it will always be sorted into the MergeQueue
+            // after any timestamped Sample possibly coming from a query reply.
+            s.ensure_timestamp();
+            state.merge_queue.push(s);
+        }
+    }
+}
+
+// Issues a query, whose replies are merged with the publications received by the subscriber
+// on `sub_key_expr` until all the pending queries are done.
+#[allow(clippy::too_many_arguments)]
+fn query_on_selector<'c>(
+    session: &'c Session,
+    sub_key_expr: &KeyExpr<'_>,
+    state: &Arc<Mutex<InnerState>>,
+    callback: &Arc<dyn Fn(Sample) + Send + Sync + 'static>,
+    selector: Selector<'c>,
+    target: QueryTarget,
+    consolidation: QueryConsolidation,
+    timeout: Duration,
+) -> impl Resolve<ZResult<()>> + 'c {
+    zlock!(state).start_query();
+    // pending queries will be decremented in RepliesHandler drop()
+    let handler = RepliesHandler {
+        state: state.clone(),
+        callback: callback.clone(),
+    };
+
+    // if selector for query is different than subscription keyexpr: accept Any reply
+    let query_accept_replies = if selector.key_expr != *sub_key_expr {
+        ReplyKeyExpr::Any
+    } else {
+        ReplyKeyExpr::MatchingQuery
+    };
+
+    log::debug!("Start query on {}", selector);
+    session
+        .get(selector)
+        .accept_replies(query_accept_replies)
+        .target(target)
+        .consolidation(consolidation)
+        .timeout(timeout)
+        .callback(move |r| {
+            let mut state = zlock!(handler.state);
+            match r.sample {
+                Ok(s) => {
+                    log::trace!("Reply received: push it to merge_queue");
+                    state.merge_queue.push(s)
+                }
+                Err(v) => log::debug!("Received error {}", v),
+            }
+        })
+}
+
+// The delay before re-querying once connected to a new peer or router,
+// to let its declarations be received.
+const CONNECT_QUERY_DELAY: Duration = Duration::from_secs(1);
+
+// The subscribers to the connectivity events a QueryingSubscriber re-queries on, i.e. the new
+// transports and the new queryables (storages, publication caches...) on the queried key expression.
+struct ConnectEvents {
+    _subscribers: Vec<Subscriber<'static, ()>>,
+    receiver: flume::Receiver<Sample>,
+}
+
+impl ConnectEvents {
+    fn declare(session: &Arc<Session>, key_expr: &KeyExpr<'_>) -> ZResult<Self> {
+        let (sender, receiver) = flume::unbounded();
+        let subscribers = vec![
+            format!("@/session/{}/transport/unicast/*", session.zid()),
+            format!("@/session/{}/queryable/{}", session.zid(), key_expr),
+        ]
+        .into_iter()
+        .map(|events| {
+            let sender = sender.clone();
+            session
+                .declare_subscriber(events)
+                .callback(move |s| {
+                    let _ = sender.send(s);
+                })
+                .res_sync()
+        })
+        .collect::<ZResult<_>>()?;
+        Ok(ConnectEvents {
+            _subscribers: subscribers,
+            receiver,
+        })
+    }
+}
+
+// The re-queries that are automatically issued by a QueryingSubscriber.
+struct Requery {
+    session: Arc<Session>,
+    sub_key_expr: KeyExpr<'static>,
+    selector: Selector<'static>,
+    target: QueryTarget,
+    consolidation: QueryConsolidation,
+    timeout: Duration,
+    state: Arc<Mutex<InnerState>>,
+    callback: Arc<dyn Fn(Sample) + Send + Sync + 'static>,
+}
+
+impl Requery {
+    async fn query(&self) {
+        let res = query_on_selector(
+            &self.session,
+            &self.sub_key_expr,
+            &self.state,
+            &self.callback,
+            self.selector.clone(),
+            self.target,
+            self.consolidation,
+            self.timeout,
+        )
+        .res_async()
+        .await;
+        if let Err(e) = res {
+            log::warn!("Error re-querying {}: {}", self.selector, e);
+        }
+    }
+
+    // Spawns the task issuing a new query periodically and/or on new connectivity events,
+    // stopped when the returned Sender is dropped.
+    fn spawn(
+        self,
+        period: Option<Duration>,
+        connect_events: Option<ConnectEvents>,
+    ) -> Sender<bool> {
+        let (stoptx, mut stoprx) = bounded::<bool>(1);
+        task::spawn(async move {
+            let mut next_periodic = period.map(|p| Instant::now() + p);
+            let mut next_on_connect: Option<Instant> = None;
+            loop {
+                let deadline = match (next_periodic, next_on_connect) {
+                    (Some(p), Some(c)) => Some(p.min(c)),
+                    (p, c) => p.or(c),
+                };
+                let timer = async move {
+                    match deadline {
+                        Some(deadline) => {
+                            task::sleep(deadline.saturating_duration_since(Instant::now())).await
+                        }
+                        None => future::pending().await,
+                    }
+                };
+                let event = async {
+                    match &connect_events {
+                        Some(events) => events.receiver.recv_async().await,
+                        None => future::pending().await,
+                    }
+                };
+                select!(
+                    _ = timer.fuse() => {
+                        let now = Instant::now();
+                        if next_periodic.map_or(false, |t| t <= now) {
+                            next_periodic = period.map(|p| now + p);
+                        }
+                        if next_on_connect.map_or(false, |t| t <= now) {
+                            next_on_connect = None;
+                        }
+                        self.query().await;
+                    },
+
+                    // on new transport or queryable, re-query once the declarations had time to arrive
+                    event = event.fuse() => match event {
+                        Ok(sample) if sample.kind == SampleKind::Put => {
+                            log::debug!("New connectivity event {}: re-query on {}", sample.key_expr, self.selector);
+                            next_on_connect = Some(Instant::now() + CONNECT_QUERY_DELAY);
+                        }
+                        Ok(_) => {}
+                        Err(_) => return,
+                    },
+
+                    // When stoptx is dropped, stop the task
+                    _ = stoprx.next().fuse() => {
+                        return
+                    }
+                );
+            }
+        });
+        stoptx
+    }
 }
 
 pub struct QueryingSubscriber<'a, Receiver> {
@@ -324,6 +637,7 @@ pub struct QueryingSubscriber<'a, Receiver> {
     subscriber: Subscriber<'a, ()>,
     callback: Arc<dyn Fn(Sample) + Send + Sync + 'static>,
     state: Arc<Mutex<InnerState>>,
+    _requery_stoptx: Option<Sender<bool>>,
     receiver: Receiver,
 }
 impl<Receiver> std::ops::Deref for QueryingSubscriber<'_, Receiver> {
@@ -343,28 +657,12 @@ impl<'a, Receiver> QueryingSubscriber<'a, Receiver> {
     where
         Handler: IntoCallbackReceiverPair<'static, Sample, Receiver = Receiver> + Send,
     {
-        let state = Arc::new(Mutex::new(InnerState {
-            pending_queries: 0,
-            merge_queue: MergeQueue::new(),
-        }));
+        let state = Arc::new(Mutex::new(InnerState::new(
+            conf.query_period.is_some() || conf.query_on_connect,
+        )));
         let (callback, receiver) = conf.handler.into_cb_receiver_pair();
 
-        let sub_callback = {
-            let state = state.clone();
-            let callback = callback.clone();
-            move |mut s| {
-                let state = &mut zlock!(state);
-                if state.pending_queries == 0 {
-                    callback(s);
-                } else {
-                    log::trace!("Sample received while query in progress: push it to merge_queue");
-                    // ensure the sample has a timestamp, thus it will always be sorted into the MergeQueue
-                    // after any timestamped Sample possibly coming from a query reply.
-                    s.ensure_timestamp();
-                    state.merge_queue.push(s);
-                }
-            }
-        };
+        let sub_callback = merging_callback(state.clone(), callback.clone());
 
         let key_expr = conf.key_expr?;
         let (key_selector, parameters) = match conf.query_selector {
@@ -374,6 +672,20 @@ impl<'a, Receiver> QueryingSubscriber<'a, Receiver> {
         }
         .split();
 
+        // automatic re-queries are issued from a task, which requires to share the session
+        let requery_session = match (
+            conf.query_period.is_some() || conf.query_on_connect,
+            &conf.session,
+        ) {
+            (false, _) => None,
+            (true, SessionRef::Shared(session)) => Some(session.clone()),
+            (true, SessionRef::Borrow(_)) => bail!(
+                "Failed requirement for QueryingSubscriber on {}: \
+                     periodic or on connect queries require the Session to be shared in an Arc",
+                key_expr
+            ),
+        };
+
         // declare subscriber at first
         let subscriber = match conf.session.clone() {
             SessionRef::Borrow(session) => session
@@ -390,6 +702,31 @@ impl<'a, Receiver> QueryingSubscriber<'a, Receiver> {
                 .res_sync()?,
         };
 
+        let requery_stoptx = match requery_session {
+            Some(session) => {
+                let connect_events = if conf.query_on_connect {
+                    Some(ConnectEvents::declare(&session, &key_selector)?)
+                } else {
+                    None
+                };
+                let requery = Requery {
+                    session,
+                    sub_key_expr: key_expr.clone().into_owned(),
+                    selector: key_selector
+                        .clone()
+                        .into_owned()
+                        .with_owned_parameters(parameters.to_string()),
+                    target: conf.query_target,
+                    consolidation: conf.query_consolidation,
+                    timeout: conf.query_timeout,
+                    state: state.clone(),
+                    callback: callback.clone(),
+                };
+                Some(requery.spawn(conf.query_period, connect_events))
+            }
+            None => None,
+        };
+
         let mut query_subscriber = QueryingSubscriber {
             session: conf.session,
             query_key_expr: key_selector,
@@ -400,6 +737,7 @@ impl<'a, Receiver> QueryingSubscriber<'a, Receiver> {
             subscriber,
             callback,
             state,
+            _requery_stoptx: requery_stoptx,
             receiver,
         };
 
@@ -426,7 +764,11 @@ impl<'a, Receiver> QueryingSubscriber<'a, Receiver> {
     /// Issue a new query using the configured selector.
     #[inline]
     pub fn query(&mut self) -> impl Resolve<ZResult<()>> + '_ {
-        self.query_on_selector(
+        query_on_selector(
+            &self.session,
+            self.subscriber.key_expr(),
+            &self.state,
+            &self.callback,
             self.query_key_expr
                 .clone()
                 .with_owned_parameters(self.query_parameters.to_owned()),
@@ -449,48 +791,16 @@ impl<'a, Receiver> QueryingSubscriber<'a, Receiver> {
         IntoSelector: Into<Selector<'c>>,
         IntoQueryConsolidation: Into<QueryConsolidation>,
     {
-        self.query_on_selector(selector.into(), target, consolidation.into(), timeout)
-    }
-
-    #[inline]
-    fn query_on_selector<'c>(
-        &'c mut self,
-        selector: Selector<'c>,
-        target: QueryTarget,
-        consolidation: QueryConsolidation,
-        timeout: Duration,
-    ) -> impl Resolve<ZResult<()>> + 'c {
-        zlock!(self.state).pending_queries += 1;
-        // pending queries will be decremented in RepliesHandler drop()
-        let handler = RepliesHandler {
-            state: self.state.clone(),
-            callback: self.callback.clone(),
-        };
-
-        // if selector for query is different than subscription keyexpr: accept Any reply
-        let query_accept_replies = if selector.key_expr != *self.subscriber.key_expr() {
-            ReplyKeyExpr::Any
-        } else {
-            ReplyKeyExpr::MatchingQuery
-        };
-
-        log::debug!("Start query on {}", selector);
-        self.session
-            .get(selector)
-            .accept_replies(query_accept_replies)
-            .target(target)
-            .consolidation(consolidation)
-            .timeout(timeout)
-            .callback(move |r| {
-                let mut state = zlock!(handler.state);
-                match r.sample {
-                    Ok(s) => {
-                        log::trace!("Reply received: push it to merge_queue");
-                        state.merge_queue.push(s)
-                    }
-                    Err(v) => log::debug!("Received error {}", v),
-                }
-            })
+        query_on_selector(
+            &self.session,
+            self.subscriber.key_expr(),
+            &self.state,
+            &self.callback,
+            selector.into(),
+            target,
+            consolidation.into(),
+            timeout,
+        )
     }
 }
 
@@ -513,8 +823,278 @@ impl Drop for RepliesHandler {
                 state.merge_queue.len()
             );
             for s in state.merge_queue.drain() {
-                (self.callback)(s);
+                if state.is_new(&s) {
+                    (self.callback)(s);
+                }
             }
         }
     }
 }
+
+/// The callback given to the fetch function of a [`FetchingSubscriber`] to provide the fetched samples.
+pub type FetchCallback = Arc<dyn Fn(Sample) + Send + Sync + 'static>;
+
+type Fetch = Arc<dyn Fn(FetchCallback) -> BoxFuture<'static, ZResult<()>> + Send + Sync + 'static>;
+
+/// The builder of FetchingSubscriber, allowing to configure it.
+pub struct FetchingSubscriberBuilder<'a, 'b, Handler> {
+    session: SessionRef<'a>,
+    key_expr: ZResult<KeyExpr<'b>>,
+    reliability: Reliability,
+    origin: Locality,
+    fetch: Fetch,
+    handler: Handler,
+}
+
+impl<'a, 'b> FetchingSubscriberBuilder<'a, 'b, DefaultHandler> {
+    pub(crate) fn new<FetchFn, FetchFut>(
+        session: SessionRef<'a>,
+        key_expr: ZResult<KeyExpr<'b>>,
+        fetch: FetchFn,
+    ) -> FetchingSubscriberBuilder<'a, 'b, DefaultHandler>
+    where
+        FetchFn: Fn(FetchCallback) -> FetchFut + Send + Sync + 'static,
+        FetchFut: Future<Output = ZResult<()>> + Send + 'static,
+    {
+        FetchingSubscriberBuilder {
+            session,
+            key_expr,
+            reliability: Reliability::default(),
+            origin: Locality::default(),
+            fetch: Arc::new(move |cb| Box::pin(fetch(cb))),
+            handler: DefaultHandler,
+        }
+    }
+
+    /// Add callback to FetchingSubscriber.
+    #[inline]
+    pub fn callback<Callback>(
+        self,
+        callback: Callback,
+    ) -> FetchingSubscriberBuilder<'a, 'b, Callback>
+    where
+        Callback: Fn(Sample) + Send + Sync + 'static,
+    {
+        self.with(callback)
+    }
+
+    /// Add callback to `FetchingSubscriber`.
+    ///
+    /// Using this guarantees that your callback will never be called concurrently.
+    /// If your callback is also accepted by the [`callback`](FetchingSubscriberBuilder::callback) method, we suggest you use it instead of `callback_mut`
+    #[inline]
+    pub fn callback_mut<CallbackMut>(
+        self,
+        callback: CallbackMut,
+    ) -> FetchingSubscriberBuilder<'a, 'b, impl Fn(Sample) + Send + Sync + 'static>
+    where
+        CallbackMut: FnMut(Sample) + Send + Sync + 'static,
+    {
+        self.callback(locked(callback))
+    }
+
+    /// Make the built FetchingSubscriber a [`FetchingSubscriber`](FetchingSubscriber).
+    #[inline]
+    pub fn with<Handler>(self, handler: Handler) -> FetchingSubscriberBuilder<'a, 'b, Handler>
+    where
+        Handler: zenoh::prelude::IntoCallbackReceiverPair<'static, Sample>,
+    {
+        let FetchingSubscriberBuilder {
+            session,
+            key_expr,
+            reliability,
+            origin,
+            fetch,
+            handler: _,
+        } = self;
+        FetchingSubscriberBuilder {
+            session,
+            key_expr,
+            reliability,
+            origin,
+            fetch,
+            handler,
+        }
+    }
+}
+
+impl<'a, 'b, Handler> FetchingSubscriberBuilder<'a, 'b, Handler> {
+    /// Change the subscription reliability.
+    #[inline]
+    pub fn reliability(mut self, reliability: Reliability) -> Self {
+        self.reliability = reliability;
+        self
+    }
+
+    /// Change the subscription reliability to Reliable.
+    #[inline]
+    pub fn reliable(mut self) -> Self {
+        self.reliability = Reliability::Reliable;
+        self
+    }
+
+    /// Change the subscription reliability to BestEffort.
+    #[inline]
+    pub fn best_effort(mut self) -> Self {
+        self.reliability = Reliability::BestEffort;
+        self
+    }
+
+    /// Restrict the matching publications that will be receive by this [`FetchingSubscriber`]
+    /// to the ones that have the given [`Locality`](zenoh::prelude::Locality).
+    #[zenoh_core::unstable]
+    #[inline]
+    pub fn allowed_origin(mut self, origin: Locality) -> Self {
+        self.origin = origin;
+        self
+    }
+
+    fn with_static_keys(self) -> FetchingSubscriberBuilder<'a, 'static, Handler> {
+        FetchingSubscriberBuilder {
+            session: self.session,
+            key_expr: self.key_expr.map(|s| s.into_owned()),
+            reliability: self.reliability,
+            origin: self.origin,
+            fetch: self.fetch,
+            handler: self.handler,
+        }
+    }
+}
+
+impl<'a, Handler> Resolvable for FetchingSubscriberBuilder<'a, '_, Handler>
+where
+    Handler: IntoCallbackReceiverPair<'static, Sample>,
+    Handler::Receiver: Send,
+{
+    type To = ZResult<FetchingSubscriber<'a, Handler::Receiver>>;
+}
+
+impl<Handler> SyncResolve for FetchingSubscriberBuilder<'_, '_, Handler>
+where
+    Handler: IntoCallbackReceiverPair<'static, Sample> + Send,
+    Handler::Receiver: Send,
+{
+    fn res_sync(self) -> <Self as Resolvable>::To {
+        FetchingSubscriber::new(self.with_static_keys())
+    }
+}
+
+impl<'a, Handler> AsyncResolve for FetchingSubscriberBuilder<'a, '_, Handler>
+where
+    Handler: IntoCallbackReceiverPair<'static, Sample> + Send,
+    Handler::Receiver: Send,
+{
+    type Future = Ready<Self::To>;
+
+    fn res_async(self) -> Self::Future {
+        std::future::ready(self.res_sync())
+    }
+}
+
+/// A subscriber whose initial state is provided by a user-defined fetch function
+/// (e.g. reading a file or calling a REST service), merged with the received publications
+/// the same way a [`QueryingSubscriber`] merges its query replies.
+///
+/// The fetch function is given a [`FetchCallback`] to provide the fetched samples, and returns a future
+/// whose completion marks the end of the fetch: the callback must not be called after that.
+pub struct FetchingSubscriber<'a, Receiver> {
+    subscriber: Subscriber<'a, ()>,
+    fetch: Fetch,
+    callback: Arc<dyn Fn(Sample) + Send + Sync + 'static>,
+    state: Arc<Mutex<InnerState>>,
+    receiver: Receiver,
+}
+impl<Receiver> std::ops::Deref for FetchingSubscriber<'_, Receiver> {
+    type Target = Receiver;
+    fn deref(&self) -> &Self::Target {
+        &self.receiver
+    }
+}
+impl<Receiver> std::ops::DerefMut for FetchingSubscriber<'_, Receiver> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.receiver
+    }
+}
+
+impl<'a, Receiver> FetchingSubscriber<'a, Receiver> {
+    fn new<Handler>(conf: FetchingSubscriberBuilder<'a, 'a, Handler>) -> ZResult<Self>
+    where
+        Handler: IntoCallbackReceiverPair<'static, Sample, Receiver = Receiver> + Send,
+    {
+        let state = Arc::new(Mutex::new(InnerState::new(false)));
+        let (callback, receiver) = conf.handler.into_cb_receiver_pair();
+
+        let sub_callback = merging_callback(state.clone(), callback.clone());
+
+        let key_expr = conf.key_expr?;
+
+        // declare subscriber at first
+        let subscriber = match conf.session {
+            SessionRef::Borrow(session) => session
+                .declare_subscriber(&key_expr)
+                .callback(sub_callback)
+                .reliability(conf.reliability)
+                .allowed_origin(conf.origin)
+                .res_sync()?,
+            SessionRef::Shared(session) => session
+                .declare_subscriber(&key_expr)
+                .callback(sub_callback)
+                .reliability(conf.reliability)
+                .allowed_origin(conf.origin)
+                .res_sync()?,
+        };
+
+        let fetch_subscriber = FetchingSubscriber {
+            subscriber,
+            fetch: conf.fetch,
+            callback,
+            state,
+            receiver,
+        };
+
+        // start fetch
+        fetch_subscriber.fetch().res_sync()?;
+
+        Ok(fetch_subscriber)
+    }
+
+    /// Close this FetchingSubscriber
+    #[inline]
+    pub fn close(self) -> impl Resolve<ZResult<()>> + 'a {
+        self.subscriber.undeclare()
+    }
+
+    pub fn key_expr(&self) -> &KeyExpr<'static> {
+        self.subscriber.key_expr()
+    }
+
+    /// Run the fetch function again, merging the fetched samples with the received publications.
+    #[inline]
+    pub fn fetch(&self) -> impl Resolve<ZResult<()>> + '_ {
+        let state = self.state.clone();
+        let callback = self.callback.clone();
+        let fetch = self.fetch.clone();
+        let key_expr = self.subscriber.key_expr();
+        ResolveClosure::new(move || {
+            zlock!(state).start_query();
+            // pending queries will be decremented in RepliesHandler drop()
+            let handler = RepliesHandler {
+                state: state.clone(),
+                callback,
+            };
+            let fetch_callback: FetchCallback = Arc::new(move |s| {
+                log::trace!("Fetched sample: push it to merge_queue");
+                zlock!(state).merge_queue.push(s)
+            });
+            log::debug!("Start fetch for {}", key_expr);
+            let fut = fetch(fetch_callback);
+            task::spawn(async move {
+                if let Err(e) = fut.await {
+                    log::warn!("Error fetching samples: {}", e);
+                }
+                drop(handler);
+            });
+            Ok(())
+        })
+    }
+}
@@ -0,0 +1,167 @@
+//
+// Copyright (c) 2022 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+use std::future::Ready;
+use std::sync::atomic::{AtomicU64, Ordering};
+use zenoh::prelude::r#async::*;
+use zenoh::publication::{Publication, Publisher};
+use zenoh::sample::SourceInfo;
+use zenoh::Session;
+use zenoh_core::{AsyncResolve, Resolvable, SyncResolve};
+use zenoh_result::ZResult;
+use zenoh_util::core::ResolveFuture;
+
+use crate::{PublicationCache, PublicationCacheBuilder};
+
+/// The builder of ReliablePublisher, allowing to configure it.
+pub struct ReliablePublisherBuilder<'a, 'b> {
+    session: &'a Session,
+    pub_key_expr: ZResult<KeyExpr<'b>>,
+    history: usize,
+    congestion_control: CongestionControl,
+    priority: Priority,
+}
+
+impl<'a, 'b> ReliablePublisherBuilder<'a, 'b> {
+    pub(crate) fn new(
+        session: &'a Session,
+        pub_key_expr: ZResult<KeyExpr<'b>>,
+    ) -> ReliablePublisherBuilder<'a, 'b> {
+        ReliablePublisherBuilder {
+            session,
+            pub_key_expr,
+            history: 1024,
+            congestion_control: CongestionControl::default(),
+            priority: Priority::default(),
+        }
+    }
+
+    /// Change the number of past publications kept available for recovery by
+    /// [`ReliableSubscriber`](super::ReliableSubscriber)s.
+    pub fn history(mut self, history: usize) -> Self {
+        self.history = history;
+        self
+    }
+
+    /// Change the `congestion_control` to apply when routing the data.
+    pub fn congestion_control(mut self, congestion_control: CongestionControl) -> Self {
+        self.congestion_control = congestion_control;
+        self
+    }
+
+    /// Change the priority of the written data.
+    pub fn priority(mut self, priority: Priority) -> Self {
+        self.priority = priority;
+        self
+    }
+}
+
+impl<'a> Resolvable for ReliablePublisherBuilder<'a, '_> {
+    type To = ZResult<ReliablePublisher<'a>>;
+}
+
+impl SyncResolve for ReliablePublisherBuilder<'_, '_> {
+    fn res_sync(self) -> <Self as Resolvable>::To {
+        ReliablePublisher::new(self)
+    }
+}
+
+impl<'a> AsyncResolve for ReliablePublisherBuilder<'a, '_> {
+    type Future = Ready<Self::To>;
+
+    fn res_async(self) -> Self::Future {
+        std::future::ready(self.res_sync())
+    }
+}
+
+/// A publisher stamping each publication with its source id and a sequence number,
+/// and keeping the last publications in a [`PublicationCache`] so that
+/// [`ReliableSubscriber`](super::ReliableSubscriber)s can detect and recover missed samples.
+///
+/// Note that the sequence number is consumed when the [`Publication`] is created:
+/// a publication which is dropped without being resolved will be reported as missed.
+pub struct ReliablePublisher<'a> {
+    publisher: Publisher<'a>,
+    cache: PublicationCache<'a>,
+    source_id: ZenohId,
+    sn: AtomicU64,
+}
+
+impl<'a> ReliablePublisher<'a> {
+    fn new(conf: ReliablePublisherBuilder<'a, '_>) -> ZResult<ReliablePublisher<'a>> {
+        let key_expr = conf.pub_key_expr?.into_owned();
+        let cache = PublicationCacheBuilder::new(conf.session, Ok(key_expr.clone()))
+            .history(conf.history)
+            .res_sync()?;
+        let publisher = conf
+            .session
+            .declare_publisher(key_expr)
+            .congestion_control(conf.congestion_control)
+            .priority(conf.priority)
+            .res_sync()?;
+        Ok(ReliablePublisher {
+            publisher,
+            cache,
+            source_id: conf.session.zid(),
+            sn: AtomicU64::new(0),
+        })
+    }
+
+    fn next_source_info(&self) -> SourceInfo {
+        SourceInfo {
+            source_id: Some(self.source_id),
+            source_sn: Some(self.sn.fetch_add(1, Ordering::Relaxed)),
+        }
+    }
+
+    /// Send data with [`kind`](SampleKind) (Put or Delete).
+    pub fn write<IntoValue>(&self, kind: SampleKind, value: IntoValue) -> Publication<'_>
+    where
+        IntoValue: Into<Value>,
+    {
+        self.publisher
+            .write(kind, value)
+            .with_source_info(self.next_source_info())
+    }
+
+    /// Put data.
+    #[inline]
+    pub fn put<IntoValue>(&self, value: IntoValue) -> Publication<'_>
+    where
+        IntoValue: Into<Value>,
+    {
+        self.write(SampleKind::Put, value)
+    }
+
+    /// Delete data.
+    #[inline]
+    pub fn delete(&self) -> Publication<'_> {
+        self.write(SampleKind::Delete, Value::empty())
+    }
+
+    pub fn key_expr(&self) -> &KeyExpr<'a> {
+        self.publisher.key_expr()
+    }
+
+    /// Close this ReliablePublisher
+    #[inline]
+    pub fn close(self) -> impl Resolve<ZResult<()>> + 'a {
+        ResolveFuture::new(async move {
+            let ReliablePublisher {
+                publisher, cache, ..
+            } = self;
+            publisher.undeclare().res_async().await?;
+            cache.close().res_async().await
+        })
+    }
+}
@@ -0,0 +1,675 @@
+//
+// Copyright (c) 2022 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+use std::collections::hash_map::Entry;
+use std::collections::{BTreeMap, HashMap, VecDeque};
+use std::future::Ready;
+use std::ops::RangeInclusive;
+use std::sync::{Arc, Mutex, Weak};
+use std::time::Duration;
+use zenoh::handlers::{locked, DefaultHandler};
+use zenoh::prelude::r#async::*;
+use zenoh::query::{QueryConsolidation, QueryTarget};
+use zenoh::subscriber::{Reliability, Subscriber};
+use zenoh::Result as ZResult;
+use zenoh_core::{zlock, AsyncResolve, Resolvable, SyncResolve};
+use zenoh_result::bail;
+
+use crate::publication_cache::SN_RANGE_KEY;
+use crate::session_ext::SessionRef;
+
+/// A range of consecutive samples from a same source that were not received
+/// by a [`ReliableSubscriber`], and that could not be recovered.
+#[derive(Debug, Clone)]
+pub struct SampleMiss {
+    /// The [`ZenohId`] of the source of the missed samples.
+    pub source_id: ZenohId,
+    /// The key expression on which the missed samples were published.
+    pub key_expr: KeyExpr<'static>,
+    /// The sequence numbers of the missed samples.
+    pub sns: RangeInclusive<ZInt>,
+}
+
+impl SampleMiss {
+    /// Returns the number of missed samples.
+    pub fn count(&self) -> ZInt {
+        self.sns.end() - self.sns.start() + 1
+    }
+}
+
+/// The builder of ReliableSubscriber, allowing to configure it.
+pub struct ReliableSubscriberBuilder<'a, 'b, Handler> {
+    session: SessionRef<'a>,
+    key_expr: ZResult<KeyExpr<'b>>,
+    reliability: Reliability,
+    origin: Locality,
+    recovery: bool,
+    query_timeout: Duration,
+    miss_callback: Option<Arc<dyn Fn(SampleMiss) + Send + Sync + 'static>>,
+    handler: Handler,
+}
+
+impl<'a, 'b> ReliableSubscriberBuilder<'a, 'b, DefaultHandler> {
+    pub(crate) fn new(
+        session: SessionRef<'a>,
+        key_expr: ZResult<KeyExpr<'b>>,
+    ) -> ReliableSubscriberBuilder<'a, 'b, DefaultHandler> {
+        ReliableSubscriberBuilder {
+            session,
+            key_expr,
+            reliability: Reliability::default(),
+            origin: Locality::default(),
+            recovery: false,
+            query_timeout: Duration::from_secs(10),
+            miss_callback: None,
+            handler: DefaultHandler,
+        }
+    }
+
+    /// Add callback to ReliableSubscriber.
+    #[inline]
+    pub fn callback<Callback>(
+        self,
+        callback: Callback,
+    ) -> ReliableSubscriberBuilder<'a, 'b, Callback>
+    where
+        Callback: Fn(Sample) + Send + Sync + 'static,
+    {
+        let ReliableSubscriberBuilder {
+            session,
+            key_expr,
+            reliability,
+            origin,
+            recovery,
+            query_timeout,
+            miss_callback,
+            handler: _,
+        } = self;
+        ReliableSubscriberBuilder {
+            session,
+            key_expr,
+            reliability,
+            origin,
+            recovery,
+            query_timeout,
+            miss_callback,
+            handler: callback,
+        }
+    }
+
+    /// Add callback to `ReliableSubscriber`.
+    ///
+    /// Using this guarantees that your callback will never be called concurrently.
+    /// If your callback is also accepted by the [`callback`](ReliableSubscriberBuilder::callback) method, we suggest you use it instead of `callback_mut`
+    #[inline]
+    pub fn callback_mut<CallbackMut>(
+        self,
+        callback: CallbackMut,
+    ) -> ReliableSubscriberBuilder<'a, 'b, impl Fn(Sample) + Send + Sync + 'static>
+    where
+        CallbackMut: FnMut(Sample) + Send + Sync + 'static,
+    {
+        self.callback(locked(callback))
+    }
+
+    /// Make the built ReliableSubscriber a [`ReliableSubscriber`](ReliableSubscriber).
+    #[inline]
+    pub fn with<Handler>(self, handler: Handler) -> ReliableSubscriberBuilder<'a, 'b, Handler>
+    where
+        Handler: zenoh::prelude::IntoCallbackReceiverPair<'static, Sample>,
+    {
+        let ReliableSubscriberBuilder {
+            session,
+            key_expr,
+            reliability,
+            origin,
+            recovery,
+            query_timeout,
+            miss_callback,
+            handler: _,
+        } = self;
+        ReliableSubscriberBuilder {
+            session,
+            key_expr,
+            reliability,
+            origin,
+            recovery,
+            query_timeout,
+            miss_callback,
+            handler,
+        }
+    }
+}
+
+impl<'a, 'b, Handler> ReliableSubscriberBuilder<'a, 'b, Handler> {
+    /// Change the subscription reliability.
+    #[inline]
+    pub fn reliability(mut self, reliability: Reliability) -> Self {
+        self.reliability = reliability;
+        self
+    }
+
+    /// Change the subscription reliability to Reliable.
+    #[inline]
+    pub fn reliable(mut self) -> Self {
+        self.reliability = Reliability::Reliable;
+        self
+    }
+
+    /// Change the subscription reliability to BestEffort.
+    #[inline]
+    pub fn best_effort(mut self) -> Self {
+        self.reliability = Reliability::BestEffort;
+        self
+    }
+
+    /// Restrict the matching publications that will be receive by this [`ReliableSubscriber`]
+    /// to the ones that have the given [`Locality`](zenoh::prelude::Locality).
+    #[zenoh_core::unstable]
+    #[inline]
+    pub fn allowed_origin(mut self, origin: Locality) -> Self {
+        self.origin = origin;
+        self
+    }
+
+    /// Enable or disable the recovery of missed samples.
+    ///
+    /// When enabled, missed samples are queried from the [`PublicationCache`](super::PublicationCache)
+    /// of their source (e.g. the one of a [`ReliablePublisher`](super::ReliablePublisher)), and the samples
+    /// received in the meantime are held back, so that samples of a same source are still delivered in order.
+    /// Only the samples that could not be recovered are reported as missed.
+    ///
+    /// Recovery requires the ReliableSubscriber to be declared on an `Arc<Session>`.
+    #[inline]
+    pub fn recovery(mut self, recovery: bool) -> Self {
+        self.recovery = recovery;
+        self
+    }
+
+    /// Change the timeout to be used for recovery queries.
+    #[inline]
+    pub fn query_timeout(mut self, query_timeout: Duration) -> Self {
+        self.query_timeout = query_timeout;
+        self
+    }
+
+    /// Set a callback to be notified of the samples that were missed.
+    #[inline]
+    pub fn sample_miss_callback<MissCallback>(mut self, callback: MissCallback) -> Self
+    where
+        MissCallback: Fn(SampleMiss) + Send + Sync + 'static,
+    {
+        self.miss_callback = Some(Arc::new(callback));
+        self
+    }
+
+    fn with_static_keys(self) -> ReliableSubscriberBuilder<'a, 'static, Handler> {
+        ReliableSubscriberBuilder {
+            session: self.session,
+            key_expr: self.key_expr.map(|s| s.into_owned()),
+            reliability: self.reliability,
+            origin: self.origin,
+            recovery: self.recovery,
+            query_timeout: self.query_timeout,
+            miss_callback: self.miss_callback,
+            handler: self.handler,
+        }
+    }
+}
+
+impl<'a, Handler> Resolvable for ReliableSubscriberBuilder<'a, '_, Handler>
+where
+    Handler: IntoCallbackReceiverPair<'static, Sample>,
+    Handler::Receiver: Send,
+{
+    type To = ZResult<ReliableSubscriber<'a, Handler::Receiver>>;
+}
+
+impl<Handler> SyncResolve for ReliableSubscriberBuilder<'_, '_, Handler>
+where
+    Handler: IntoCallbackReceiverPair<'static, Sample> + Send,
+    Handler::Receiver: Send,
+{
+    fn res_sync(self) -> <Self as Resolvable>::To {
+        ReliableSubscriber::new(self.with_static_keys())
+    }
+}
+
+impl<'a, Handler> AsyncResolve for ReliableSubscriberBuilder<'a, '_, Handler>
+where
+    Handler: IntoCallbackReceiverPair<'static, Sample> + Send,
+    Handler::Receiver: Send,
+{
+    type Future = Ready<Self::To>;
+
+    fn res_async(self) -> Self::Future {
+        std::future::ready(self.res_sync())
+    }
+}
+
+// The sequence number tracking of a single source, i.e. of a publisher identified
+// by the ZenohId of its session and its key expression.
+struct SourceState {
+    // the sequence number of the last delivered (or declared as missed) sample
+    last_sn: ZInt,
+    // the samples received out of order, waiting for the gap before them to be recovered
+    pending: BTreeMap<ZInt, Sample>,
+    // the last sequence number requested by the recovery query in progress, if any
+    recovering: Option<ZInt>,
+    // the notifications waiting to be delivered, in order
+    queue: VecDeque<Delivery>,
+    // whether a thread is delivering the queued notifications, the other ones only queueing theirs
+    delivering: bool,
+}
+
+type SourceKey = (ZenohId, KeyExpr<'static>);
+
+struct InnerState {
+    sources: HashMap<SourceKey, SourceState>,
+}
+
+// A notification to the user's callbacks, made once the state is unlocked
+enum Delivery {
+    Sample(Sample),
+    Miss(SourceKey, RangeInclusive<ZInt>),
+}
+
+#[derive(Clone)]
+struct Handlers {
+    state: Arc<Mutex<InnerState>>,
+    callback: Arc<dyn Fn(Sample) + Send + Sync + 'static>,
+    miss_callback: Option<Arc<dyn Fn(SampleMiss) + Send + Sync + 'static>>,
+    // the session is not owned, as it owns the subscriber holding these handlers
+    recovery: Option<(Weak<Session>, Duration)>,
+}
+
+impl Handlers {
+    // Delivers the queued notifications of a source until its queue is empty, if no other
+    // thread is already doing so: the recovery replies and the live samples being received
+    // concurrently, this keeps the notifications of a same source ordered and sequential.
+    fn drain(&self, source: &SourceKey) {
+        loop {
+            let deliveries: Vec<Delivery> = {
+                let mut state = zlock!(self.state);
+                match state.sources.get_mut(source) {
+                    Some(source_state) if !source_state.queue.is_empty() => {
+                        source_state.queue.drain(..).collect()
+                    }
+                    Some(source_state) => {
+                        source_state.delivering = false;
+                        return;
+                    }
+                    None => return,
+                }
+            };
+            self.deliver(deliveries);
+        }
+    }
+
+    fn deliver(&self, deliveries: Vec<Delivery>) {
+        for delivery in deliveries {
+            match delivery {
+                Delivery::Sample(sample) => (self.callback)(sample),
+                Delivery::Miss(source, sns) => {
+                    log::debug!("Missed samples {:?} from {} on {}", sns, source.0, source.1);
+                    if let Some(miss_callback) = &self.miss_callback {
+                        miss_callback(SampleMiss {
+                            source_id: source.0,
+                            key_expr: source.1,
+                            sns,
+                        });
+                    }
+                }
+            }
+        }
+    }
+
+    // Delivers the pending samples following the last delivered one, and returns the
+    // range of sequence numbers which is still missing, if any.
+    fn flush(
+        state: &mut SourceState,
+        deliveries: &mut Vec<Delivery>,
+    ) -> Option<RangeInclusive<ZInt>> {
+        while let Some(&sn) = state.pending.keys().next() {
+            if sn != state.last_sn + 1 {
+                return Some(state.last_sn + 1..=sn - 1);
+            }
+            state.last_sn = sn;
+            deliveries.push(Delivery::Sample(state.pending.remove(&sn).unwrap()));
+        }
+        None
+    }
+
+    // Declares as missed all the gaps up to the `up_to` sequence number, delivering
+    // the pending samples in between, and returns the remaining gap, if any.
+    fn give_up(
+        source: &SourceKey,
+        state: &mut SourceState,
+        up_to: ZInt,
+        deliveries: &mut Vec<Delivery>,
+    ) -> Option<RangeInclusive<ZInt>> {
+        let mut gap = Self::flush(state, deliveries);
+        while let Some(sns) = gap.take() {
+            if *sns.start() > up_to {
+                return Some(sns);
+            }
+            state.last_sn = *sns.end();
+            deliveries.push(Delivery::Miss(source.clone(), sns));
+            gap = Self::flush(state, deliveries);
+        }
+        None
+    }
+
+    fn on_sample(&self, sample: Sample, from_recovery: bool) {
+        let (source_id, sn) = match (sample.source_info.source_id, sample.source_info.source_sn) {
+            (Some(source_id), Some(sn)) => (source_id, sn),
+            _ => {
+                // no sequencing information: nothing to check
+                if !from_recovery {
+                    (self.callback)(sample);
+                }
+                return;
+            }
+        };
+        let source = (source_id, sample.key_expr.clone());
+        let mut deliveries = vec![];
+        let mut gap = None;
+        let mut state = zlock!(self.state);
+        let source_state = match state.sources.entry(source.clone()) {
+            Entry::Occupied(entry) => {
+                let source_state = entry.into_mut();
+                gap = self.track(
+                    &source,
+                    source_state,
+                    sn,
+                    sample,
+                    from_recovery,
+                    &mut deliveries,
+                );
+                source_state
+            }
+            Entry::Vacant(_) if from_recovery => return,
+            Entry::Vacant(entry) => {
+                // first sample received from this source
+                deliveries.push(Delivery::Sample(sample));
+                entry.insert(SourceState::new(sn))
+            }
+        };
+        let is_delivering = source_state.enqueue(deliveries);
+        drop(state);
+        if let (Some(sns), Some((session, timeout))) = (gap, &self.recovery) {
+            self.recover(session.clone(), *timeout, source.clone(), sns);
+        }
+        if is_delivering {
+            self.drain(&source);
+        }
+    }
+
+    // Checks the sequence number of a sample from a known source, adding the resulting
+    // notifications to the deliveries, and returns the range of sequence numbers to recover, if any.
+    fn track(
+        &self,
+        source: &SourceKey,
+        source_state: &mut SourceState,
+        sn: ZInt,
+        sample: Sample,
+        from_recovery: bool,
+        deliveries: &mut Vec<Delivery>,
+    ) -> Option<RangeInclusive<ZInt>> {
+        if sn == 0 && source_state.last_sn > 0 && !from_recovery {
+            // the source restarted its sequence, e.g. a ReliablePublisher re-created on the same session
+            log::debug!("Source {} restarted on {}", source.0, source.1);
+            source_state.restart(sn);
+            deliveries.push(Delivery::Sample(sample));
+            return None;
+        }
+        if sn <= source_state.last_sn || source_state.pending.contains_key(&sn) {
+            log::trace!("Duplicate sample {} from {} on {}", sn, source.0, source.1);
+            return None;
+        }
+        source_state.pending.insert(sn, sample);
+        let gap = Self::flush(source_state, deliveries);
+        if source_state.recovering.is_some() {
+            return None;
+        }
+        match (gap, &self.recovery) {
+            (Some(sns), Some(_)) => {
+                source_state.recovering = Some(*sns.end());
+                Some(sns)
+            }
+            (Some(_), None) => {
+                Self::give_up(source, source_state, ZInt::MAX, deliveries);
+                None
+            }
+            (None, _) => None,
+        }
+    }
+
+    fn recover(
+        &self,
+        session: Weak<Session>,
+        timeout: Duration,
+        source: SourceKey,
+        sns: RangeInclusive<ZInt>,
+    ) {
+        let selector = source.1.clone().with_owned_parameters(format!(
+            "{}={}..{}",
+            SN_RANGE_KEY,
+            sns.start(),
+            sns.end()
+        ));
+        // pending recovery will be completed in RecoveryHandler drop()
+        let handler = RecoveryHandler {
+            handlers: self.clone(),
+            source,
+        };
+        let session = match session.upgrade() {
+            Some(session) => session,
+            // the session is being closed
+            None => return,
+        };
+        log::debug!(
+            "Recovering missed samples from {} with {}",
+            handler.source.0,
+            selector
+        );
+        // the query is issued from a separate task, as we may be called from within a callback
+        async_std::task::spawn(async move {
+            let replies_handler = handler.handlers.clone();
+            let source_id = handler.source.0;
+            let res = session
+                .get(selector)
+                .target(QueryTarget::All)
+                .consolidation(QueryConsolidation::from(ConsolidationMode::None))
+                .timeout(timeout)
+                .callback(move |r| {
+                    let _ = &handler;
+                    match r.sample {
+                        Ok(s) if s.source_info.source_id == Some(source_id) => {
+                            replies_handler.on_sample(s, true)
+                        }
+                        Ok(_) => {}
+                        Err(v) => log::debug!("Received error {}", v),
+                    }
+                })
+                .res_async()
+                .await;
+            if let Err(e) = res {
+                log::warn!("Failed to recover missed samples: {}", e);
+            }
+        });
+    }
+}
+
+impl SourceState {
+    fn new(last_sn: ZInt) -> Self {
+        SourceState {
+            last_sn,
+            pending: BTreeMap::new(),
+            recovering: None,
+            queue: VecDeque::new(),
+            delivering: false,
+        }
+    }
+
+    // Tracks a restarted sequence, the queued notifications of the previous one being still delivered
+    fn restart(&mut self, last_sn: ZInt) {
+        self.last_sn = last_sn;
+        self.pending.clear();
+        self.recovering = None;
+    }
+
+    // Queues notifications to be delivered, and returns whether the caller is in charge of
+    // delivering them, no other thread doing so.
+    fn enqueue(&mut self, deliveries: Vec<Delivery>) -> bool {
+        self.queue.extend(deliveries);
+        if self.delivering || self.queue.is_empty() {
+            false
+        } else {
+            self.delivering = true;
+            true
+        }
+    }
+}
+
+struct RecoveryHandler {
+    handlers: Handlers,
+    source: SourceKey,
+}
+
+impl Drop for RecoveryHandler {
+    fn drop(&mut self) {
+        let mut deliveries = vec![];
+        let mut state = zlock!(self.handlers.state);
+        let source_state = match state.sources.get_mut(&self.source) {
+            Some(source_state) => source_state,
+            None => return,
+        };
+        let up_to = match source_state.recovering.take() {
+            Some(up_to) => up_to,
+            None => return,
+        };
+        log::trace!("Recovery done for {} on {}", self.source.0, self.source.1);
+        // all that could be recovered has been: give up on the queried range
+        let gap = Handlers::give_up(&self.source, source_state, up_to, &mut deliveries);
+        // some other samples were missed while recovering
+        let gap = match (gap, &self.handlers.recovery) {
+            (Some(sns), Some((session, timeout))) => {
+                source_state.recovering = Some(*sns.end());
+                Some((session.clone(), *timeout, sns))
+            }
+            _ => None,
+        };
+        let is_delivering = source_state.enqueue(deliveries);
+        drop(state);
+        if let Some((session, timeout, sns)) = gap {
+            self.handlers
+                .recover(session, timeout, self.source.clone(), sns);
+        }
+        if is_delivering {
+            self.handlers.drain(&self.source);
+        }
+    }
+}
+
+/// A subscriber that detects the samples it missed, relying on the source id and sequence number
+/// stamped in the samples by their publisher (e.g. a [`ReliablePublisher`](super::ReliablePublisher)).
+///
+/// Sequence numbers are tracked per source, i.e. per couple of the publishing session's [`ZenohId`]
+/// and of the publication's key expression. The first sample received from a source is always accepted,
+/// the following ones are checked to be in sequence: duplicates are discarded, and gaps are reported
+/// through the [`sample_miss_callback`](ReliableSubscriberBuilder::sample_miss_callback),
+/// after an attempt to recover them if [`recovery`](ReliableSubscriberBuilder::recovery) is enabled.
+///
+/// A sample with sequence number 0 received after later ones marks the restart of its source
+/// (e.g. a [`ReliablePublisher`](super::ReliablePublisher) re-created on the same session),
+/// whose sequence numbers are then tracked anew.
+///
+/// Samples without source id or sequence number are delivered as is.
+pub struct ReliableSubscriber<'a, Receiver> {
+    subscriber: Subscriber<'a, ()>,
+    receiver: Receiver,
+}
+
+impl<Receiver> std::ops::Deref for ReliableSubscriber<'_, Receiver> {
+    type Target = Receiver;
+    fn deref(&self) -> &Self::Target {
+        &self.receiver
+    }
+}
+
+impl<Receiver> std::ops::DerefMut for ReliableSubscriber<'_, Receiver> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.receiver
+    }
+}
+
+impl<'a, Receiver> ReliableSubscriber<'a, Receiver> {
+    fn new<Handler>(conf: ReliableSubscriberBuilder<'a, 'a, Handler>) -> ZResult<Self>
+    where
+        Handler: IntoCallbackReceiverPair<'static, Sample, Receiver = Receiver> + Send,
+    {
+        let key_expr = conf.key_expr?;
+        let recovery = match (conf.recovery, &conf.session) {
+            (false, _) => None,
+            (true, SessionRef::Shared(session)) => {
+                Some((Arc::downgrade(session), conf.query_timeout))
+            }
+            (true, SessionRef::Borrow(_)) => bail!(
+                "Failed requirement for ReliableSubscriber on {}: \
+                     recovery requires the Session to be shared in an Arc",
+                key_expr
+            ),
+        };
+        let (callback, receiver) = conf.handler.into_cb_receiver_pair();
+        let handlers = Handlers {
+            state: Arc::new(Mutex::new(InnerState {
+                sources: HashMap::new(),
+            })),
+            callback,
+            miss_callback: conf.miss_callback,
+            recovery,
+        };
+
+        let sub_callback = move |s| handlers.on_sample(s, false);
+        let subscriber = match conf.session {
+            SessionRef::Borrow(session) => session
+                .declare_subscriber(&key_expr)
+                .callback(sub_callback)
+                .reliability(conf.reliability)
+                .allowed_origin(conf.origin)
+                .res_sync()?,
+            SessionRef::Shared(session) => session
+                .declare_subscriber(&key_expr)
+                .callback(sub_callback)
+                .reliability(conf.reliability)
+                .allowed_origin(conf.origin)
+                .res_sync()?,
+        };
+
+        Ok(ReliableSubscriber {
+            subscriber,
+            receiver,
+        })
+    }
+
+    /// Close this ReliableSubscriber
+    #[inline]
+    pub fn close(self) -> impl Resolve<ZResult<()>> + 'a {
+        self.subscriber.undeclare()
+    }
+
+    pub fn key_expr(&self) -> &KeyExpr<'static> {
+        self.subscriber.key_expr()
+    }
+}
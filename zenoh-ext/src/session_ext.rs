@@ -11,9 +11,14 @@
 // Contributors:
 //   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
 //
-use super::{PublicationCacheBuilder, QueryingSubscriberBuilder};
+use super::{
+    FetchCallback, FetchingSubscriberBuilder, LockBuilder, PublicationCacheBuilder,
+    QueryingSubscriberBuilder, ReliablePublisherBuilder, ReliableSubscriberBuilder,
+    StreamingPublisherBuilder, StreamingSubscriberBuilder,
+};
 use std::convert::TryInto;
 use std::fmt;
+use std::future::Future;
 use std::ops::Deref;
 use std::sync::Arc;
 use zenoh::handlers::DefaultHandler;
@@ -55,7 +60,9 @@ pub trait SessionExt {
     /// will issue a query on a given key expression (by default it uses the same key expression than it subscribes to).
     /// The results of the query will be merged with the received publications and made available in the receiver.
     /// Later on, new queries can be issued again, calling [`QueryingSubscriber::query()`](super::QueryingSubscriber::query()) or
-    /// [`QueryingSubscriber::query_on()`](super::QueryingSubscriber::query_on()).
+    /// [`QueryingSubscriber::query_on()`](super::QueryingSubscriber::query_on()), or automatically, configuring
+    /// [`QueryingSubscriberBuilder::query_period()`](QueryingSubscriberBuilder::query_period()) or
+    /// [`QueryingSubscriberBuilder::query_on_connect()`](QueryingSubscriberBuilder::query_on_connect()).
     ///
     /// A typical usage of the `QueryingSubscriber` is to retrieve publications that were made in the past, but stored in some zenoh Storage.
     ///
@@ -86,6 +93,49 @@ pub trait SessionExt {
         TryIntoKeyExpr: TryInto<KeyExpr<'b>>,
         <TryIntoKeyExpr as TryInto<KeyExpr<'b>>>::Error: Into<zenoh_result::Error>;
 
+    /// Create a [FetchingSubscriber](super::FetchingSubscriber) with the given key expression and fetch function.
+    ///
+    /// This operation returns a [`FetchingSubscriberBuilder`](FetchingSubscriberBuilder) that can be used to finely configure the subscriber.
+    /// As soon as built, the `FetchingSubscriber` runs the `fetch` function, which provides samples through the given
+    /// [`FetchCallback`](super::FetchCallback) until the future it returns completes (e.g. reading them from a file or
+    /// a REST service). The fetched samples are merged with the received publications and made available in the receiver.
+    /// Later on, the `fetch` function can be run again calling [`FetchingSubscriber::fetch()`](super::FetchingSubscriber::fetch()).
+    ///
+    /// # Arguments
+    /// * `sub_key_expr` - The key expression to subscribe on
+    /// * `fetch` - The function providing the initial samples
+    ///
+    /// # Examples
+    /// ```no_run
+    /// # async_std::task::block_on(async {
+    /// use zenoh::prelude::r#async::*;
+    /// use zenoh_ext::*;
+    ///
+    /// let session = zenoh::open(config::peer()).res().await.unwrap();
+    /// let subscriber = session
+    ///     .declare_fetching_subscriber("key/expr", |cb: FetchCallback| async move {
+    ///         cb(Sample::try_from("key/expr", "initial value").unwrap());
+    ///         Ok(())
+    ///     })
+    ///     .res()
+    ///     .await
+    ///     .unwrap();
+    /// while let Ok(sample) = subscriber.recv_async().await {
+    ///     println!("Received: {:?}", sample);
+    /// }
+    /// # })
+    /// ```
+    fn declare_fetching_subscriber<'a, 'b, TryIntoKeyExpr, FetchFn, FetchFut>(
+        &'a self,
+        sub_key_expr: TryIntoKeyExpr,
+        fetch: FetchFn,
+    ) -> FetchingSubscriberBuilder<'a, 'b, DefaultHandler>
+    where
+        TryIntoKeyExpr: TryInto<KeyExpr<'b>>,
+        <TryIntoKeyExpr as TryInto<KeyExpr<'b>>>::Error: Into<zenoh_result::Error>,
+        FetchFn: Fn(FetchCallback) -> FetchFut + Send + Sync + 'static,
+        FetchFut: Future<Output = zenoh_result::ZResult<()>> + Send + 'static;
+
     fn declare_publication_cache<'a, 'b, 'c, TryIntoKeyExpr>(
         &'a self,
         pub_key_expr: TryIntoKeyExpr,
@@ -93,6 +143,144 @@ pub trait SessionExt {
     where
         TryIntoKeyExpr: TryInto<KeyExpr<'b>>,
         <TryIntoKeyExpr as TryInto<KeyExpr<'b>>>::Error: Into<zenoh_result::Error>;
+
+    /// Create a [ReliableSubscriber](super::ReliableSubscriber) with the given key expression.
+    ///
+    /// This operation returns a [`ReliableSubscriberBuilder`](ReliableSubscriberBuilder) that can be used to finely configure the subscriber.
+    /// The `ReliableSubscriber` tracks the sequence numbers stamped by the publishers (e.g. [ReliablePublisher](super::ReliablePublisher)s)
+    /// in the samples it receives, reports the missed samples through a callback and optionally recovers them
+    /// from the publishers' [PublicationCache](super::PublicationCache)s.
+    ///
+    /// # Arguments
+    /// * `sub_key_expr` - The key expression to subscribe on
+    ///
+    /// # Examples
+    /// ```no_run
+    /// # async_std::task::block_on(async {
+    /// use zenoh::prelude::r#async::*;
+    /// use zenoh_ext::*;
+    ///
+    /// let session = zenoh::open(config::peer()).res().await.unwrap().into_arc();
+    /// let subscriber = session.declare_reliable_subscriber("key/expr")
+    ///     .recovery(true)
+    ///     .sample_miss_callback(|miss| println!("Missed {} samples", miss.count()))
+    ///     .res()
+    ///     .await
+    ///     .unwrap();
+    /// while let Ok(sample) = subscriber.recv_async().await {
+    ///     println!("Received: {:?}", sample);
+    /// }
+    /// # })
+    /// ```
+    fn declare_reliable_subscriber<'a, 'b, TryIntoKeyExpr>(
+        &'a self,
+        sub_key_expr: TryIntoKeyExpr,
+    ) -> ReliableSubscriberBuilder<'a, 'b, DefaultHandler>
+    where
+        TryIntoKeyExpr: TryInto<KeyExpr<'b>>,
+        <TryIntoKeyExpr as TryInto<KeyExpr<'b>>>::Error: Into<zenoh_result::Error>;
+
+    /// Create a [ReliablePublisher](super::ReliablePublisher) with the given key expression.
+    ///
+    /// The `ReliablePublisher` stamps its publications with a sequence number, and keeps the last ones
+    /// in a [PublicationCache](super::PublicationCache) for [ReliableSubscriber](super::ReliableSubscriber)s to recover them.
+    fn declare_reliable_publisher<'a, 'b, TryIntoKeyExpr>(
+        &'a self,
+        pub_key_expr: TryIntoKeyExpr,
+    ) -> ReliablePublisherBuilder<'a, 'b>
+    where
+        TryIntoKeyExpr: TryInto<KeyExpr<'b>>,
+        <TryIntoKeyExpr as TryInto<KeyExpr<'b>>>::Error: Into<zenoh_result::Error>;
+
+    /// Create a [StreamingSubscriber](super::StreamingSubscriber) with the given key expression.
+    ///
+    /// The `StreamingSubscriber` delivers each stream sent by a [StreamingPublisher](super::StreamingPublisher)
+    /// as an [IncomingStream](super::IncomingStream) yielding its chunks as they are received.
+    ///
+    /// # Examples
+    /// ```no_run
+    /// # async_std::task::block_on(async {
+    /// use zenoh::prelude::r#async::*;
+    /// use zenoh_ext::*;
+    ///
+    /// let session = zenoh::open(config::peer()).res().await.unwrap();
+    /// let subscriber = session.declare_streaming_subscriber("key/expr")
+    ///     .progress_callback(|p| println!("Received {} bytes of stream {}", p.received, p.stream_id))
+    ///     .res()
+    ///     .await
+    ///     .unwrap();
+    /// while let Ok(stream) = subscriber.recv_async().await {
+    ///     while let Ok(Some(chunk)) = stream.recv_async().await {
+    ///         println!("Received a chunk of {} bytes", chunk.len());
+    ///     }
+    /// }
+    /// # })
+    /// ```
+    fn declare_streaming_subscriber<'a, 'b, TryIntoKeyExpr>(
+        &'a self,
+        sub_key_expr: TryIntoKeyExpr,
+    ) -> StreamingSubscriberBuilder<'a, 'b, DefaultHandler>
+    where
+        TryIntoKeyExpr: TryInto<KeyExpr<'b>>,
+        <TryIntoKeyExpr as TryInto<KeyExpr<'b>>>::Error: Into<zenoh_result::Error>;
+
+    /// Create a [StreamingPublisher](super::StreamingPublisher) with the given key expression.
+    ///
+    /// The `StreamingPublisher` sends arbitrarily large payloads in chunks, never materializing them fully.
+    ///
+    /// # Examples
+    /// ```no_run
+    /// # async_std::task::block_on(async {
+    /// use zenoh::prelude::r#async::*;
+    /// use zenoh_ext::*;
+    ///
+    /// let session = zenoh::open(config::peer()).res().await.unwrap();
+    /// let publisher = session.declare_streaming_publisher("key/expr").res().await.unwrap();
+    /// let mut file = std::fs::File::open("pointcloud.bin").unwrap();
+    /// let mut stream = publisher.stream(Some(file.metadata().unwrap().len()));
+    /// std::io::copy(&mut file, &mut stream).unwrap();
+    /// stream.close().unwrap();
+    /// # })
+    /// ```
+    fn declare_streaming_publisher<'a, 'b, TryIntoKeyExpr>(
+        &'a self,
+        pub_key_expr: TryIntoKeyExpr,
+    ) -> StreamingPublisherBuilder<'a, 'b>
+    where
+        TryIntoKeyExpr: TryInto<KeyExpr<'b>>,
+        <TryIntoKeyExpr as TryInto<KeyExpr<'b>>>::Error: Into<zenoh_result::Error>;
+
+    /// Acquire a distributed [lock](super::LockGuard) on the given key expression.
+    ///
+    /// This operation returns a [`LockBuilder`](LockBuilder) that can be used to configure the
+    /// acquisition timeout and the lease of this candidate. The lock is granted to the candidates
+    /// in the order of their requests, and held until the returned [`LockGuard`](super::LockGuard)
+    /// is dropped, or until the holder can't be reached for longer than its lease.
+    ///
+    /// # Arguments
+    /// * `key_expr` - The key expression to lock
+    ///
+    /// # Examples
+    /// ```no_run
+    /// # async_std::task::block_on(async {
+    /// use std::time::Duration;
+    /// use zenoh::prelude::r#async::*;
+    /// use zenoh_ext::*;
+    ///
+    /// let session = zenoh::open(config::peer()).res().await.unwrap();
+    /// let guard = session.lock("jobs/42")
+    ///     .timeout(Duration::from_secs(10))
+    ///     .res()
+    ///     .await
+    ///     .unwrap();
+    /// // ... process job 42 ...
+    /// guard.release().res().await.unwrap();
+    /// # })
+    /// ```
+    fn lock<'a, 'b, TryIntoKeyExpr>(&'a self, key_expr: TryIntoKeyExpr) -> LockBuilder<'a, 'b>
+    where
+        TryIntoKeyExpr: TryInto<KeyExpr<'b>>,
+        <TryIntoKeyExpr as TryInto<KeyExpr<'b>>>::Error: Into<zenoh_result::Error>;
 }
 
 impl SessionExt for Session {
@@ -110,6 +298,24 @@ impl SessionExt for Session {
         )
     }
 
+    fn declare_fetching_subscriber<'a, 'b, TryIntoKeyExpr, FetchFn, FetchFut>(
+        &'a self,
+        sub_key_expr: TryIntoKeyExpr,
+        fetch: FetchFn,
+    ) -> FetchingSubscriberBuilder<'a, 'b, DefaultHandler>
+    where
+        TryIntoKeyExpr: TryInto<KeyExpr<'b>>,
+        <TryIntoKeyExpr as TryInto<KeyExpr<'b>>>::Error: Into<zenoh_result::Error>,
+        FetchFn: Fn(FetchCallback) -> FetchFut + Send + Sync + 'static,
+        FetchFut: Future<Output = zenoh_result::ZResult<()>> + Send + 'static,
+    {
+        FetchingSubscriberBuilder::new(
+            SessionRef::Borrow(self),
+            sub_key_expr.try_into().map_err(Into::into),
+            fetch,
+        )
+    }
+
     fn declare_publication_cache<'a, 'b, 'c, TryIntoKeyExpr>(
         &'a self,
         pub_key_expr: TryIntoKeyExpr,
@@ -120,6 +326,66 @@ impl SessionExt for Session {
     {
         PublicationCacheBuilder::new(self, pub_key_expr.try_into().map_err(Into::into))
     }
+
+    fn declare_reliable_subscriber<'a, 'b, TryIntoKeyExpr>(
+        &'a self,
+        sub_key_expr: TryIntoKeyExpr,
+    ) -> ReliableSubscriberBuilder<'a, 'b, DefaultHandler>
+    where
+        TryIntoKeyExpr: TryInto<KeyExpr<'b>>,
+        <TryIntoKeyExpr as TryInto<KeyExpr<'b>>>::Error: Into<zenoh_result::Error>,
+    {
+        ReliableSubscriberBuilder::new(
+            SessionRef::Borrow(self),
+            sub_key_expr.try_into().map_err(Into::into),
+        )
+    }
+
+    fn declare_reliable_publisher<'a, 'b, TryIntoKeyExpr>(
+        &'a self,
+        pub_key_expr: TryIntoKeyExpr,
+    ) -> ReliablePublisherBuilder<'a, 'b>
+    where
+        TryIntoKeyExpr: TryInto<KeyExpr<'b>>,
+        <TryIntoKeyExpr as TryInto<KeyExpr<'b>>>::Error: Into<zenoh_result::Error>,
+    {
+        ReliablePublisherBuilder::new(self, pub_key_expr.try_into().map_err(Into::into))
+    }
+
+    fn declare_streaming_subscriber<'a, 'b, TryIntoKeyExpr>(
+        &'a self,
+        sub_key_expr: TryIntoKeyExpr,
+    ) -> StreamingSubscriberBuilder<'a, 'b, DefaultHandler>
+    where
+        TryIntoKeyExpr: TryInto<KeyExpr<'b>>,
+        <TryIntoKeyExpr as TryInto<KeyExpr<'b>>>::Error: Into<zenoh_result::Error>,
+    {
+        StreamingSubscriberBuilder::new(
+            SessionRef::Borrow(self),
+            sub_key_expr.try_into().map_err(Into::into),
+        )
+    }
+
+    fn declare_streaming_publisher<'a, 'b, TryIntoKeyExpr>(
+        &'a self,
+        pub_key_expr: TryIntoKeyExpr,
+    ) -> StreamingPublisherBuilder<'a, 'b>
+    where
+        TryIntoKeyExpr: TryInto<KeyExpr<'b>>,
+        <TryIntoKeyExpr as TryInto<KeyExpr<'b>>>::Error: Into<zenoh_result::Error>,
+    {
+        StreamingPublisherBuilder::new(self, pub_key_expr.try_into().map_err(Into::into))
+    }
+    fn lock<'a, 'b, TryIntoKeyExpr>(&'a self, key_expr: TryIntoKeyExpr) -> LockBuilder<'a, 'b>
+    where
+        TryIntoKeyExpr: TryInto<KeyExpr<'b>>,
+        <TryIntoKeyExpr as TryInto<KeyExpr<'b>>>::Error: Into<zenoh_result::Error>,
+    {
+        LockBuilder::new(
+            SessionRef::Borrow(self),
+            key_expr.try_into().map_err(Into::into),
+        )
+    }
 }
 
 impl SessionExt for Arc<Session> {
@@ -137,6 +403,24 @@ impl SessionExt for Arc<Session> {
         )
     }
 
+    fn declare_fetching_subscriber<'a, 'b, TryIntoKeyExpr, FetchFn, FetchFut>(
+        &'a self,
+        sub_key_expr: TryIntoKeyExpr,
+        fetch: FetchFn,
+    ) -> FetchingSubscriberBuilder<'a, 'b, DefaultHandler>
+    where
+        TryIntoKeyExpr: TryInto<KeyExpr<'b>>,
+        <TryIntoKeyExpr as TryInto<KeyExpr<'b>>>::Error: Into<zenoh_result::Error>,
+        FetchFn: Fn(FetchCallback) -> FetchFut + Send + Sync + 'static,
+        FetchFut: Future<Output = zenoh_result::ZResult<()>> + Send + 'static,
+    {
+        FetchingSubscriberBuilder::new(
+            SessionRef::Shared(self.clone()),
+            sub_key_expr.try_into().map_err(Into::into),
+            fetch,
+        )
+    }
+
     fn declare_publication_cache<'a, 'b, 'c, TryIntoKeyExpr>(
         &'a self,
         pub_key_expr: TryIntoKeyExpr,
@@ -147,4 +431,65 @@ impl SessionExt for Arc<Session> {
     {
         PublicationCacheBuilder::new(self, pub_key_expr.try_into().map_err(Into::into))
     }
+
+    fn declare_reliable_subscriber<'a, 'b, TryIntoKeyExpr>(
+        &'a self,
+        sub_key_expr: TryIntoKeyExpr,
+    ) -> ReliableSubscriberBuilder<'a, 'b, DefaultHandler>
+    where
+        TryIntoKeyExpr: TryInto<KeyExpr<'b>>,
+        <TryIntoKeyExpr as TryInto<KeyExpr<'b>>>::Error: Into<zenoh_result::Error>,
+    {
+        ReliableSubscriberBuilder::new(
+            SessionRef::Shared(self.clone()),
+            sub_key_expr.try_into().map_err(Into::into),
+        )
+    }
+
+    fn declare_reliable_publisher<'a, 'b, TryIntoKeyExpr>(
+        &'a self,
+        pub_key_expr: TryIntoKeyExpr,
+    ) -> ReliablePublisherBuilder<'a, 'b>
+    where
+        TryIntoKeyExpr: TryInto<KeyExpr<'b>>,
+        <TryIntoKeyExpr as TryInto<KeyExpr<'b>>>::Error: Into<zenoh_result::Error>,
+    {
+        ReliablePublisherBuilder::new(self, pub_key_expr.try_into().map_err(Into::into))
+    }
+
+    fn declare_streaming_subscriber<'a, 'b, TryIntoKeyExpr>(
+        &'a self,
+        sub_key_expr: TryIntoKeyExpr,
+    ) -> StreamingSubscriberBuilder<'a, 'b, DefaultHandler>
+    where
+        TryIntoKeyExpr: TryInto<KeyExpr<'b>>,
+        <TryIntoKeyExpr as TryInto<KeyExpr<'b>>>::Error: Into<zenoh_result::Error>,
+    {
+        StreamingSubscriberBuilder::new(
+            SessionRef::Shared(self.clone()),
+            sub_key_expr.try_into().map_err(Into::into),
+        )
+    }
+
+    fn declare_streaming_publisher<'a, 'b, TryIntoKeyExpr>(
+        &'a self,
+        pub_key_expr: TryIntoKeyExpr,
+    ) -> StreamingPublisherBuilder<'a, 'b>
+    where
+        TryIntoKeyExpr: TryInto<KeyExpr<'b>>,
+        <TryIntoKeyExpr as TryInto<KeyExpr<'b>>>::Error: Into<zenoh_result::Error>,
+    {
+        StreamingPublisherBuilder::new(self, pub_key_expr.try_into().map_err(Into::into))
+    }
+
+    fn lock<'a, 'b, TryIntoKeyExpr>(&'a self, key_expr: TryIntoKeyExpr) -> LockBuilder<'a, 'b>
+    where
+        TryIntoKeyExpr: TryInto<KeyExpr<'b>>,
+        <TryIntoKeyExpr as TryInto<KeyExpr<'b>>>::Error: Into<zenoh_result::Error>,
+    {
+        LockBuilder::new(
+            SessionRef::Shared(self.clone()),
+            key_expr.try_into().map_err(Into::into),
+        )
+    }
 }
@@ -0,0 +1,348 @@
+//
+// Copyright (c) 2022 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+use std::convert::TryInto;
+use std::future::Ready;
+use std::io;
+use std::sync::atomic::{AtomicU64, Ordering};
+use zenoh::prelude::r#async::*;
+use zenoh::publication::Publisher;
+use zenoh::sample::SourceInfo;
+use zenoh::Session;
+use zenoh_core::{AsyncResolve, Resolvable, SyncResolve};
+use zenoh_result::{bail, ZResult};
+
+// The suffix of the encoding marking the chunks of a stream.
+pub(crate) const STREAM_ENCODING_SUFFIX: &str = ";zenoh-stream";
+
+// Each chunk starts with the random id of its publisher, the id of its stream, its offset
+// in the stream, the total size of the stream (or u64::MAX if unknown) and some flags,
+// followed by the chunk's data.
+pub(crate) const CHUNK_HEADER_SIZE: usize = 8 + 8 + 8 + 8 + 1;
+pub(crate) const FLAG_LAST: u8 = 0x01;
+pub(crate) const FLAG_ABORT: u8 = 0x02;
+const UNKNOWN_SIZE: u64 = u64::MAX;
+
+pub(crate) struct ChunkHeader {
+    pub(crate) publisher_id: u64,
+    pub(crate) stream_id: u64,
+    pub(crate) offset: u64,
+    pub(crate) size: Option<u64>,
+    pub(crate) flags: u8,
+}
+
+impl ChunkHeader {
+    fn write(&self, bytes: &mut Vec<u8>) {
+        bytes.extend_from_slice(&self.publisher_id.to_be_bytes());
+        bytes.extend_from_slice(&self.stream_id.to_be_bytes());
+        bytes.extend_from_slice(&self.offset.to_be_bytes());
+        bytes.extend_from_slice(&self.size.unwrap_or(UNKNOWN_SIZE).to_be_bytes());
+        bytes.push(self.flags);
+    }
+
+    pub(crate) fn read(bytes: &[u8]) -> ZResult<ChunkHeader> {
+        if bytes.len() < CHUNK_HEADER_SIZE {
+            bail!("Invalid stream chunk length: {}", bytes.len());
+        }
+        let u64_at = |i: usize| u64::from_be_bytes(bytes[i..i + 8].try_into().unwrap());
+        let size = u64_at(24);
+        Ok(ChunkHeader {
+            publisher_id: u64_at(0),
+            stream_id: u64_at(8),
+            offset: u64_at(16),
+            size: (size != UNKNOWN_SIZE).then_some(size),
+            flags: bytes[32],
+        })
+    }
+}
+
+pub(crate) fn stream_encoding() -> Encoding {
+    Encoding::APP_OCTET_STREAM.with_suffix(STREAM_ENCODING_SUFFIX)
+}
+
+/// The builder of StreamingPublisher, allowing to configure it.
+pub struct StreamingPublisherBuilder<'a, 'b> {
+    session: &'a Session,
+    pub_key_expr: ZResult<KeyExpr<'b>>,
+    chunk_size: usize,
+    congestion_control: CongestionControl,
+    priority: Priority,
+}
+
+impl<'a, 'b> StreamingPublisherBuilder<'a, 'b> {
+    pub(crate) fn new(
+        session: &'a Session,
+        pub_key_expr: ZResult<KeyExpr<'b>>,
+    ) -> StreamingPublisherBuilder<'a, 'b> {
+        StreamingPublisherBuilder {
+            session,
+            pub_key_expr,
+            chunk_size: 32 * 1_024,
+            congestion_control: CongestionControl::Block,
+            priority: Priority::DataLow,
+        }
+    }
+
+    /// Change the maximum size of the data sent in a chunk.
+    ///
+    /// Chunks small enough to fit in a transport batch are never fragmented by the transport,
+    /// and let the transmission of higher priority messages interleave with the stream.
+    pub fn chunk_size(mut self, chunk_size: usize) -> Self {
+        self.chunk_size = chunk_size;
+        self
+    }
+
+    /// Change the `congestion_control` to apply when routing the chunks.
+    ///
+    /// With [`CongestionControl::Block`] (the default), writing a stream blocks while the
+    /// transmission queues are full, bounding the memory used by the sender.
+    pub fn congestion_control(mut self, congestion_control: CongestionControl) -> Self {
+        self.congestion_control = congestion_control;
+        self
+    }
+
+    /// Change the priority of the chunks (by default [`Priority::DataLow`]).
+    pub fn priority(mut self, priority: Priority) -> Self {
+        self.priority = priority;
+        self
+    }
+}
+
+impl<'a> Resolvable for StreamingPublisherBuilder<'a, '_> {
+    type To = ZResult<StreamingPublisher<'a>>;
+}
+
+impl SyncResolve for StreamingPublisherBuilder<'_, '_> {
+    fn res_sync(self) -> <Self as Resolvable>::To {
+        StreamingPublisher::new(self)
+    }
+}
+
+impl<'a> AsyncResolve for StreamingPublisherBuilder<'a, '_> {
+    type Future = Ready<Self::To>;
+
+    fn res_async(self) -> Self::Future {
+        std::future::ready(self.res_sync())
+    }
+}
+
+/// A publisher sending arbitrarily large payloads as streams of chunks, to be received
+/// by [`StreamingSubscriber`](super::StreamingSubscriber)s.
+///
+/// Contrary to a regular publication, a stream is never fully materialized:
+/// its data is sent chunk by chunk as it is written in a [`StreamWriter`].
+pub struct StreamingPublisher<'a> {
+    publisher: Publisher<'a>,
+    source_id: ZenohId,
+    publisher_id: u64,
+    chunk_size: usize,
+    next_stream_id: AtomicU64,
+}
+
+impl<'a> StreamingPublisher<'a> {
+    fn new(conf: StreamingPublisherBuilder<'a, '_>) -> ZResult<StreamingPublisher<'a>> {
+        if conf.chunk_size == 0 {
+            bail!("Invalid chunk size for StreamingPublisher: 0");
+        }
+        let publisher = conf
+            .session
+            .declare_publisher(conf.pub_key_expr?.into_owned())
+            .congestion_control(conf.congestion_control)
+            .priority(conf.priority)
+            .res_sync()?;
+        Ok(StreamingPublisher {
+            publisher,
+            source_id: conf.session.zid(),
+            // Distinguishes the streams of the publishers of a same session and key expression
+            publisher_id: rand::random(),
+            chunk_size: conf.chunk_size,
+            next_stream_id: AtomicU64::new(0),
+        })
+    }
+
+    /// Returns the random id of this publisher, unique for its session and key expression.
+    pub fn id(&self) -> u64 {
+        self.publisher_id
+    }
+
+    /// Start a new stream, of the given total size if known.
+    pub fn stream(&self, size: Option<u64>) -> StreamWriter<'_> {
+        StreamWriter {
+            publisher: self,
+            stream_id: self.next_stream_id.fetch_add(1, Ordering::Relaxed),
+            size,
+            offset: 0,
+            buffer: Vec::with_capacity(self.chunk_size),
+            closed: false,
+        }
+    }
+
+    /// Send the given bytes as a single stream.
+    pub fn put(&self, bytes: &[u8]) -> ZResult<()> {
+        let mut writer = self.stream(Some(bytes.len() as u64));
+        writer.send(bytes)?;
+        writer.close().map(|_| ())
+    }
+
+    pub fn key_expr(&self) -> &KeyExpr<'a> {
+        self.publisher.key_expr()
+    }
+
+    fn send_chunk(&self, header: ChunkHeader, data: &[u8]) -> ZResult<()> {
+        let mut bytes = Vec::with_capacity(CHUNK_HEADER_SIZE + data.len());
+        header.write(&mut bytes);
+        bytes.extend_from_slice(data);
+        self.publisher
+            .put(Value::new(bytes.into()).encoding(stream_encoding()))
+            .with_source_info(SourceInfo {
+                source_id: Some(self.source_id),
+                source_sn: None,
+            })
+            .res_sync()
+    }
+
+    /// Close this StreamingPublisher
+    #[inline]
+    pub fn close(self) -> impl Resolve<ZResult<()>> + 'a {
+        self.publisher.undeclare()
+    }
+}
+
+/// A stream being written by a [`StreamingPublisher`].
+///
+/// The written data is sent in chunks of the publisher's chunk size. The stream must be
+/// [`close`](StreamWriter::close)d once written: a writer dropped before being closed
+/// aborts its stream.
+///
+/// `StreamWriter` implements [`std::io::Write`], so that e.g. a file can be streamed
+/// with [`std::io::copy`].
+pub struct StreamWriter<'a> {
+    publisher: &'a StreamingPublisher<'a>,
+    stream_id: u64,
+    size: Option<u64>,
+    offset: u64,
+    buffer: Vec<u8>,
+    closed: bool,
+}
+
+impl StreamWriter<'_> {
+    /// Returns the id of this stream, unique for its publisher.
+    pub fn id(&self) -> u64 {
+        self.stream_id
+    }
+
+    /// Returns the number of bytes written so far in this stream.
+    pub fn written(&self) -> u64 {
+        self.offset + self.buffer.len() as u64
+    }
+
+    /// Write the given bytes in this stream, sending the chunks filled by them.
+    pub fn send(&mut self, mut bytes: &[u8]) -> ZResult<()> {
+        if let Some(size) = self.size {
+            if self.written() + bytes.len() as u64 > size {
+                bail!(
+                    "Writing {} bytes past the size of stream {}: {}",
+                    bytes.len(),
+                    self.stream_id,
+                    size
+                );
+            }
+        }
+        let chunk_size = self.publisher.chunk_size;
+        while !bytes.is_empty() {
+            if self.buffer.is_empty() && bytes.len() >= chunk_size {
+                // Send full chunks directly from the given bytes
+                let (chunk, rest) = bytes.split_at(chunk_size);
+                self.send_chunk(chunk, 0)?;
+                bytes = rest;
+            } else {
+                let len = bytes.len().min(chunk_size - self.buffer.len());
+                let (chunk, rest) = bytes.split_at(len);
+                self.buffer.extend_from_slice(chunk);
+                bytes = rest;
+                if self.buffer.len() == chunk_size {
+                    self.send_buffer(0)?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Send the remaining data and close this stream, returning its total size.
+    ///
+    /// Fails if less data than the announced size of the stream was written.
+    pub fn close(mut self) -> ZResult<u64> {
+        self.closed = true;
+        if let Some(size) = self.size {
+            if self.written() != size {
+                let written = self.written();
+                let _ = self.send_buffer(FLAG_ABORT);
+                bail!(
+                    "Closing stream {} after {} bytes out of {}",
+                    self.stream_id,
+                    written,
+                    size
+                );
+            }
+        }
+        self.send_buffer(FLAG_LAST)?;
+        Ok(self.offset)
+    }
+
+    fn send_buffer(&mut self, flags: u8) -> ZResult<()> {
+        let buffer = std::mem::take(&mut self.buffer);
+        let res = self.send_chunk(&buffer, flags);
+        self.buffer = buffer;
+        self.buffer.clear();
+        res
+    }
+
+    fn send_chunk(&mut self, data: &[u8], flags: u8) -> ZResult<()> {
+        let header = ChunkHeader {
+            publisher_id: self.publisher.publisher_id,
+            stream_id: self.stream_id,
+            offset: self.offset,
+            size: self.size,
+            flags,
+        };
+        self.publisher.send_chunk(header, data)?;
+        self.offset += data.len() as u64;
+        Ok(())
+    }
+}
+
+impl io::Write for StreamWriter<'_> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.send(buf)
+            .map_err(|e| io::Error::new(io::ErrorKind::Other, e))?;
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        if self.buffer.is_empty() {
+            return Ok(());
+        }
+        self.send_buffer(0)
+            .map_err(|e| io::Error::new(io::ErrorKind::Other, e))
+    }
+}
+
+impl Drop for StreamWriter<'_> {
+    fn drop(&mut self) {
+        if !self.closed {
+            if let Err(e) = self.send_buffer(FLAG_ABORT) {
+                log::warn!("Failed to abort stream {}: {}", self.stream_id, e);
+            }
+        }
+    }
+}
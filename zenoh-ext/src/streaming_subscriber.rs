@@ -0,0 +1,684 @@
+//
+// Copyright (c) 2022 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+use async_std::prelude::FutureExt;
+use flume::{RecvTimeoutError, TrySendError};
+use std::collections::HashMap;
+use std::future::Ready;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use zenoh::buffers::{ZBuf, ZSlice};
+use zenoh::handlers::{locked, Callback, DefaultHandler};
+use zenoh::prelude::r#async::*;
+use zenoh::subscriber::Subscriber;
+use zenoh::Result as ZResult;
+use zenoh_core::{zlock, AsyncResolve, Resolvable, SyncResolve};
+use zenoh_result::{bail, zerror};
+
+use crate::session_ext::SessionRef;
+use crate::streaming_publisher::{
+    stream_encoding, ChunkHeader, CHUNK_HEADER_SIZE, FLAG_ABORT, FLAG_LAST,
+};
+
+/// The progress of a stream received by a [`StreamingSubscriber`].
+#[derive(Debug, Clone)]
+pub struct StreamProgress {
+    /// The [`ZenohId`] of the publisher of the stream.
+    pub source_id: ZenohId,
+    /// The key expression on which the stream is published.
+    pub key_expr: KeyExpr<'static>,
+    /// The random id of the publisher of the stream, unique for its session and key expression.
+    pub publisher_id: u64,
+    /// The id of the stream, unique for its publisher.
+    pub stream_id: u64,
+    /// The number of bytes received so far.
+    pub received: u64,
+    /// The total size of the stream, if known.
+    pub size: Option<u64>,
+}
+
+impl StreamProgress {
+    /// Returns the received fraction of the stream, if its size is known.
+    pub fn ratio(&self) -> Option<f64> {
+        self.size.map(|size| match size {
+            0 => 1.0,
+            size => self.received as f64 / size as f64,
+        })
+    }
+}
+
+/// The builder of StreamingSubscriber, allowing to configure it.
+pub struct StreamingSubscriberBuilder<'a, 'b, Handler> {
+    session: SessionRef<'a>,
+    key_expr: ZResult<KeyExpr<'b>>,
+    origin: Locality,
+    buffer: usize,
+    max_streams: usize,
+    timeout: Duration,
+    progress_callback: Option<Arc<dyn Fn(StreamProgress) + Send + Sync + 'static>>,
+    handler: Handler,
+}
+
+impl<'a, 'b> StreamingSubscriberBuilder<'a, 'b, DefaultHandler> {
+    pub(crate) fn new(
+        session: SessionRef<'a>,
+        key_expr: ZResult<KeyExpr<'b>>,
+    ) -> StreamingSubscriberBuilder<'a, 'b, DefaultHandler> {
+        StreamingSubscriberBuilder {
+            session,
+            key_expr,
+            origin: Locality::default(),
+            buffer: 256,
+            max_streams: 64,
+            timeout: Duration::from_secs(10),
+            progress_callback: None,
+            handler: DefaultHandler,
+        }
+    }
+
+    /// Add callback to StreamingSubscriber, called with each new incoming stream.
+    #[inline]
+    pub fn callback<Callback>(
+        self,
+        callback: Callback,
+    ) -> StreamingSubscriberBuilder<'a, 'b, Callback>
+    where
+        Callback: Fn(IncomingStream) + Send + Sync + 'static,
+    {
+        let StreamingSubscriberBuilder {
+            session,
+            key_expr,
+            origin,
+            buffer,
+            max_streams,
+            timeout,
+            progress_callback,
+            handler: _,
+        } = self;
+        StreamingSubscriberBuilder {
+            session,
+            key_expr,
+            origin,
+            buffer,
+            max_streams,
+            timeout,
+            progress_callback,
+            handler: callback,
+        }
+    }
+
+    /// Add callback to `StreamingSubscriber`.
+    ///
+    /// Using this guarantees that your callback will never be called concurrently.
+    /// If your callback is also accepted by the [`callback`](StreamingSubscriberBuilder::callback) method, we suggest you use it instead of `callback_mut`
+    #[inline]
+    pub fn callback_mut<CallbackMut>(
+        self,
+        callback: CallbackMut,
+    ) -> StreamingSubscriberBuilder<'a, 'b, impl Fn(IncomingStream) + Send + Sync + 'static>
+    where
+        CallbackMut: FnMut(IncomingStream) + Send + Sync + 'static,
+    {
+        self.callback(locked(callback))
+    }
+
+    /// Make the built StreamingSubscriber a [`StreamingSubscriber`](StreamingSubscriber).
+    #[inline]
+    pub fn with<Handler>(self, handler: Handler) -> StreamingSubscriberBuilder<'a, 'b, Handler>
+    where
+        Handler: zenoh::prelude::IntoCallbackReceiverPair<'static, IncomingStream>,
+    {
+        let StreamingSubscriberBuilder {
+            session,
+            key_expr,
+            origin,
+            buffer,
+            max_streams,
+            timeout,
+            progress_callback,
+            handler: _,
+        } = self;
+        StreamingSubscriberBuilder {
+            session,
+            key_expr,
+            origin,
+            buffer,
+            max_streams,
+            timeout,
+            progress_callback,
+            handler,
+        }
+    }
+}
+
+impl<'a, 'b, Handler> StreamingSubscriberBuilder<'a, 'b, Handler> {
+    /// Restrict the matching publications that will be receive by this [`StreamingSubscriber`]
+    /// to the ones that have the given [`Locality`](zenoh::prelude::Locality).
+    #[zenoh_core::unstable]
+    #[inline]
+    pub fn allowed_origin(mut self, origin: Locality) -> Self {
+        self.origin = origin;
+        self
+    }
+
+    /// Change the number of chunks buffered for each incoming stream (256 by default).
+    ///
+    /// The reception of the chunks never waits for the [`IncomingStream`]s to be read:
+    /// a stream whose buffer is full fails with an overflow error, bounding the memory
+    /// used by the receiver.
+    #[inline]
+    pub fn buffer(mut self, buffer: usize) -> Self {
+        self.buffer = buffer;
+        self
+    }
+
+    /// Change the maximum number of concurrent incoming streams (64 by default).
+    ///
+    /// The streams started beyond it fail at once, bounding the memory used by the receiver.
+    #[inline]
+    pub fn max_streams(mut self, max_streams: usize) -> Self {
+        self.max_streams = max_streams;
+        self
+    }
+
+    /// Change the inactivity timeout of the incoming streams (10 seconds by default).
+    ///
+    /// A stream fails when none of its chunks is received for this duration,
+    /// e.g. because its publisher stopped without closing it.
+    #[inline]
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// Set a callback called with the progress of the streams, on each received chunk.
+    #[inline]
+    pub fn progress_callback<ProgressCallback>(mut self, callback: ProgressCallback) -> Self
+    where
+        ProgressCallback: Fn(StreamProgress) + Send + Sync + 'static,
+    {
+        self.progress_callback = Some(Arc::new(callback));
+        self
+    }
+
+    fn with_static_keys(self) -> StreamingSubscriberBuilder<'a, 'static, Handler> {
+        StreamingSubscriberBuilder {
+            session: self.session,
+            key_expr: self.key_expr.map(|s| s.into_owned()),
+            origin: self.origin,
+            buffer: self.buffer,
+            max_streams: self.max_streams,
+            timeout: self.timeout,
+            progress_callback: self.progress_callback,
+            handler: self.handler,
+        }
+    }
+}
+
+impl<'a, Handler> Resolvable for StreamingSubscriberBuilder<'a, '_, Handler>
+where
+    Handler: IntoCallbackReceiverPair<'static, IncomingStream>,
+    Handler::Receiver: Send,
+{
+    type To = ZResult<StreamingSubscriber<'a, Handler::Receiver>>;
+}
+
+impl<Handler> SyncResolve for StreamingSubscriberBuilder<'_, '_, Handler>
+where
+    Handler: IntoCallbackReceiverPair<'static, IncomingStream> + Send,
+    Handler::Receiver: Send,
+{
+    fn res_sync(self) -> <Self as Resolvable>::To {
+        StreamingSubscriber::new(self.with_static_keys())
+    }
+}
+
+impl<'a, Handler> AsyncResolve for StreamingSubscriberBuilder<'a, '_, Handler>
+where
+    Handler: IntoCallbackReceiverPair<'static, IncomingStream> + Send,
+    Handler::Receiver: Send,
+{
+    type Future = Ready<Self::To>;
+
+    fn res_async(self) -> Self::Future {
+        std::future::ready(self.res_sync())
+    }
+}
+
+enum StreamItem {
+    Chunk(ZSlice),
+    End,
+    Error(zenoh::Error),
+}
+
+/// A stream received by a [`StreamingSubscriber`], yielding the chunks of its data.
+pub struct IncomingStream {
+    source_id: ZenohId,
+    key_expr: KeyExpr<'static>,
+    publisher_id: u64,
+    stream_id: u64,
+    size: Option<u64>,
+    timeout: Duration,
+    receiver: flume::Receiver<StreamItem>,
+    ended: AtomicBool,
+}
+
+impl IncomingStream {
+    /// Returns the [`ZenohId`] of the publisher of this stream.
+    pub fn source_id(&self) -> ZenohId {
+        self.source_id
+    }
+
+    /// Returns the key expression on which this stream is published.
+    pub fn key_expr(&self) -> &KeyExpr<'static> {
+        &self.key_expr
+    }
+
+    /// Returns the random id of the publisher of this stream, unique for its session and key expression.
+    pub fn publisher_id(&self) -> u64 {
+        self.publisher_id
+    }
+
+    /// Returns the id of this stream, unique for its publisher.
+    pub fn id(&self) -> u64 {
+        self.stream_id
+    }
+
+    /// Returns the total size of this stream, if known.
+    pub fn size(&self) -> Option<u64> {
+        self.size
+    }
+
+    /// Receive the next chunk of this stream, or `None` once the stream is complete.
+    ///
+    /// Fails if chunks of the stream were missed, if the stream was aborted by its publisher,
+    /// if its buffer overflowed or if none of its chunks was received for its timeout.
+    pub fn recv(&self) -> ZResult<Option<ZSlice>> {
+        if self.ended.load(Ordering::Relaxed) {
+            return Ok(None);
+        }
+        let item = self.receiver.recv_timeout(self.timeout);
+        self.on_item(item)
+    }
+
+    /// Receive asynchronously the next chunk of this stream, or `None` once the stream is complete.
+    ///
+    /// Fails if chunks of the stream were missed, if the stream was aborted by its publisher,
+    /// if its buffer overflowed or if none of its chunks was received for its timeout.
+    pub async fn recv_async(&self) -> ZResult<Option<ZSlice>> {
+        if self.ended.load(Ordering::Relaxed) {
+            return Ok(None);
+        }
+        let item = match self.receiver.recv_async().timeout(self.timeout).await {
+            Ok(Ok(item)) => Ok(item),
+            Ok(Err(_)) => Err(RecvTimeoutError::Disconnected),
+            Err(_) => Err(RecvTimeoutError::Timeout),
+        };
+        self.on_item(item)
+    }
+
+    fn on_item(&self, item: Result<StreamItem, RecvTimeoutError>) -> ZResult<Option<ZSlice>> {
+        if !matches!(item, Ok(StreamItem::Chunk(_))) {
+            self.ended.store(true, Ordering::Relaxed);
+        }
+        match item {
+            Ok(StreamItem::Chunk(chunk)) => Ok(Some(chunk)),
+            Ok(StreamItem::End) => Ok(None),
+            Ok(StreamItem::Error(e)) => Err(e),
+            Err(RecvTimeoutError::Timeout) => bail!(
+                "Stream {} from {} on {} timed out after {:?} without chunks",
+                self.stream_id,
+                self.source_id,
+                self.key_expr,
+                self.timeout
+            ),
+            Err(RecvTimeoutError::Disconnected) => bail!(
+                "Stream {} from {} on {} interrupted",
+                self.stream_id,
+                self.source_id,
+                self.key_expr
+            ),
+        }
+    }
+}
+
+// A stream is identified by the id of its publishing session, its key expression,
+// the id of its publisher and its id.
+type StreamKey = (ZenohId, KeyExpr<'static>, u64, u64);
+
+struct StreamState {
+    sender: flume::Sender<StreamItem>,
+    received: u64,
+    size: Option<u64>,
+    last: Instant,
+}
+
+#[derive(Clone)]
+struct Handlers {
+    streams: Arc<Mutex<HashMap<StreamKey, StreamState>>>,
+    buffer: usize,
+    max_streams: usize,
+    timeout: Duration,
+    callback: Arc<Callback<'static, IncomingStream>>,
+    progress_callback: Option<Arc<dyn Fn(StreamProgress) + Send + Sync + 'static>>,
+}
+
+impl Handlers {
+    fn on_sample(&self, sample: Sample) {
+        if !sample.value.encoding.starts_with(stream_encoding()) {
+            log::trace!("Ignoring non stream sample on {}", sample.key_expr);
+            return;
+        }
+        let source_id = match sample.source_info.source_id {
+            Some(source_id) => source_id,
+            None => {
+                log::debug!(
+                    "Ignoring stream chunk without source id on {}",
+                    sample.key_expr
+                );
+                return;
+            }
+        };
+        let header = match ChunkHeader::read(&sample.value.payload.contiguous()) {
+            Ok(header) => header,
+            Err(e) => {
+                log::warn!("Ignoring stream chunk on {}: {}", sample.key_expr, e);
+                return;
+            }
+        };
+        let chunk = chunk_data(&sample.value.payload);
+        let key = (
+            source_id,
+            sample.key_expr,
+            header.publisher_id,
+            header.stream_id,
+        );
+
+        let mut streams = zlock!(self.streams);
+        let mut incoming = None;
+        let state = match streams.get_mut(&key) {
+            Some(state) => state,
+            None if header.offset == 0 => {
+                // Forget the inactive streams, their IncomingStreams being interrupted
+                streams.retain(|_, state| state.last.elapsed() < self.timeout);
+                // The last slot of the buffer is reserved to the end or the failure of the stream
+                let (sender, receiver) = flume::bounded(self.buffer + 1);
+                let stream = IncomingStream {
+                    source_id,
+                    key_expr: key.1.clone(),
+                    publisher_id: header.publisher_id,
+                    stream_id: header.stream_id,
+                    size: header.size,
+                    timeout: self.timeout,
+                    receiver,
+                    ended: AtomicBool::new(false),
+                };
+                if streams.len() >= self.max_streams {
+                    drop(streams);
+                    let e = zerror!(
+                        "Stream {} from {} on {} refused: too many concurrent streams (max {})",
+                        header.stream_id,
+                        source_id,
+                        key.1,
+                        self.max_streams
+                    );
+                    log::warn!("{}", e);
+                    let _ = sender.try_send(StreamItem::Error(e.into()));
+                    (self.callback)(stream);
+                    return;
+                }
+                incoming = Some(stream);
+                streams.entry(key.clone()).or_insert(StreamState {
+                    sender,
+                    received: 0,
+                    size: header.size,
+                    last: Instant::now(),
+                })
+            }
+            None => {
+                log::debug!(
+                    "Ignoring chunk at offset {} of stream {} from {} on {}: stream start missed",
+                    header.offset,
+                    header.stream_id,
+                    source_id,
+                    key.1
+                );
+                return;
+            }
+        };
+        state.last = Instant::now();
+
+        let mut progress = None;
+        let item = if header.offset != state.received {
+            streams.remove(&key).map(|state| {
+                let e = zerror!(
+                    "Missed {} bytes at offset {} of stream {} from {} on {}",
+                    header.offset.saturating_sub(state.received),
+                    state.received,
+                    header.stream_id,
+                    source_id,
+                    key.1
+                );
+                (state.sender, vec![StreamItem::Error(e.into())])
+            })
+        } else {
+            state.received += chunk.len() as u64;
+            progress = Some(StreamProgress {
+                source_id,
+                key_expr: key.1.clone(),
+                publisher_id: header.publisher_id,
+                stream_id: header.stream_id,
+                received: state.received,
+                size: state.size,
+            });
+            let mut items = vec![];
+            if !chunk.is_empty() {
+                items.push(StreamItem::Chunk(chunk));
+            }
+            if header.flags & FLAG_ABORT != 0 {
+                items.push(StreamItem::Error(
+                    zerror!(
+                        "Stream {} from {} on {} aborted by its publisher",
+                        header.stream_id,
+                        source_id,
+                        key.1
+                    )
+                    .into(),
+                ));
+            } else if header.flags & FLAG_LAST != 0 {
+                match state.size {
+                    Some(size) if size != state.received => items.push(StreamItem::Error(
+                        zerror!(
+                            "Stream {} from {} on {} ended after {} bytes out of {}",
+                            header.stream_id,
+                            source_id,
+                            key.1,
+                            state.received,
+                            size
+                        )
+                        .into(),
+                    )),
+                    _ => items.push(StreamItem::End),
+                }
+            }
+            let sender = if items.iter().any(|i| !matches!(i, StreamItem::Chunk(_))) {
+                streams.remove(&key).unwrap().sender
+            } else {
+                state.sender.clone()
+            };
+            Some((sender, items))
+        };
+        drop(streams);
+
+        // The callbacks are called out of the lock, for them to be able to block
+        if let Some(incoming) = incoming {
+            (self.callback)(incoming);
+        }
+        if let (Some(progress_callback), Some(progress)) = (&self.progress_callback, progress) {
+            progress_callback(progress);
+        }
+        if let Some((sender, items)) = item {
+            self.send(&key, &sender, items);
+        }
+    }
+
+    // Never wait for the IncomingStream to be read: a stream whose buffer is full fails instead
+    fn send(&self, key: &StreamKey, sender: &flume::Sender<StreamItem>, items: Vec<StreamItem>) {
+        for item in items {
+            let item = match item {
+                StreamItem::Chunk(_) if sender.len() >= self.buffer => {
+                    zlock!(self.streams).remove(key);
+                    StreamItem::Error(
+                        zerror!(
+                            "Stream {} from {} on {} overflowed its buffer of {} chunks",
+                            key.3,
+                            key.0,
+                            key.1,
+                            self.buffer
+                        )
+                        .into(),
+                    )
+                }
+                item => item,
+            };
+            let is_last = !matches!(item, StreamItem::Chunk(_));
+            match sender.try_send(item) {
+                Ok(()) if !is_last => {}
+                Ok(()) => break,
+                Err(TrySendError::Full(_)) => {
+                    log::warn!(
+                        "IncomingStream {} from {} on {} full: dropping its end",
+                        key.3,
+                        key.0,
+                        key.1
+                    );
+                    break;
+                }
+                Err(TrySendError::Disconnected(_)) => {
+                    log::debug!(
+                        "IncomingStream {} from {} on {} dropped: ignoring its next chunks",
+                        key.3,
+                        key.0,
+                        key.1
+                    );
+                    zlock!(self.streams).remove(key);
+                    break;
+                }
+            }
+        }
+    }
+}
+
+// Returns the data of a chunk, without copy if it is received in a single slice.
+fn chunk_data(payload: &ZBuf) -> ZSlice {
+    let mut zslices = payload.zslices();
+    if let (Some(zslice), None) = (zslices.next(), zslices.next()) {
+        let range = zslice.range();
+        if let Ok(chunk) = ZSlice::make(
+            zslice.buf.clone(),
+            range.start + CHUNK_HEADER_SIZE,
+            range.end,
+        ) {
+            return chunk;
+        }
+    }
+    payload.contiguous()[CHUNK_HEADER_SIZE..].to_vec().into()
+}
+
+/// A subscriber receiving the streams sent by [`StreamingPublisher`](super::StreamingPublisher)s.
+///
+/// Each new stream is delivered as an [`IncomingStream`] yielding the chunks of its data as
+/// [`ZSlice`]s, in order, as they are received. A stream whose start was missed is ignored, and a
+/// stream fails as soon as one of its chunks is missed, its buffer overflows or it times out.
+/// The streams started beyond the maximum number of concurrent streams fail at once.
+///
+/// Samples that are not stream chunks are ignored.
+pub struct StreamingSubscriber<'a, Receiver> {
+    subscriber: Subscriber<'a, ()>,
+    receiver: Receiver,
+}
+
+impl<Receiver> std::ops::Deref for StreamingSubscriber<'_, Receiver> {
+    type Target = Receiver;
+    fn deref(&self) -> &Self::Target {
+        &self.receiver
+    }
+}
+
+impl<Receiver> std::ops::DerefMut for StreamingSubscriber<'_, Receiver> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.receiver
+    }
+}
+
+impl<'a, Receiver> StreamingSubscriber<'a, Receiver> {
+    fn new<Handler>(conf: StreamingSubscriberBuilder<'a, 'a, Handler>) -> ZResult<Self>
+    where
+        Handler: IntoCallbackReceiverPair<'static, IncomingStream, Receiver = Receiver> + Send,
+    {
+        let key_expr = conf.key_expr?;
+        if conf.buffer == 0 {
+            bail!(
+                "Invalid buffer size for StreamingSubscriber on {}: 0",
+                key_expr
+            );
+        }
+        if conf.max_streams == 0 {
+            bail!(
+                "Invalid maximum number of streams for StreamingSubscriber on {}: 0",
+                key_expr
+            );
+        }
+        let (callback, receiver) = conf.handler.into_cb_receiver_pair();
+        let handlers = Handlers {
+            streams: Arc::new(Mutex::new(HashMap::new())),
+            buffer: conf.buffer,
+            max_streams: conf.max_streams,
+            timeout: conf.timeout,
+            callback: Arc::new(callback),
+            progress_callback: conf.progress_callback,
+        };
+
+        let sub_callback = move |s| handlers.on_sample(s);
+        let subscriber = match conf.session {
+            SessionRef::Borrow(session) => session
+                .declare_subscriber(&key_expr)
+                .callback(sub_callback)
+                .reliable()
+                .allowed_origin(conf.origin)
+                .res_sync()?,
+            SessionRef::Shared(session) => session
+                .declare_subscriber(&key_expr)
+                .callback(sub_callback)
+                .reliable()
+                .allowed_origin(conf.origin)
+                .res_sync()?,
+        };
+
+        Ok(StreamingSubscriber {
+            subscriber,
+            receiver,
+        })
+    }
+
+    /// Close this StreamingSubscriber
+    #[inline]
+    pub fn close(self) -> impl Resolve<ZResult<()>> + 'a {
+        self.subscriber.undeclare()
+    }
+
+    pub fn key_expr(&self) -> &KeyExpr<'static> {
+        self.subscriber.key_expr()
+    }
+}
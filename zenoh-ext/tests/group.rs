@@ -0,0 +1,252 @@
+//
+// Copyright (c) 2022 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+use async_std::net::{Shutdown, TcpListener, TcpStream};
+use async_std::prelude::FutureExt;
+use async_std::task;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use zenoh::prelude::r#async::*;
+use zenoh_ext::group::*;
+
+const TIMEOUT: Duration = Duration::from_secs(60);
+const SLEEP: Duration = Duration::from_millis(100);
+const LEASE: Duration = Duration::from_secs(2);
+
+macro_rules! ztimeout {
+    ($f:expr) => {
+        $f.timeout(TIMEOUT).await.unwrap()
+    };
+}
+
+async fn open_session(listen: &[&str], connect: &[&str]) -> Arc<Session> {
+    let mut config = config::peer();
+    config.listen.endpoints = listen
+        .iter()
+        .map(|e| e.parse().unwrap())
+        .collect::<Vec<_>>();
+    config.connect.endpoints = connect
+        .iter()
+        .map(|e| e.parse().unwrap())
+        .collect::<Vec<_>>();
+    config.scouting.multicast.set_enabled(Some(false)).unwrap();
+    config.scouting.gossip.set_enabled(Some(false)).unwrap();
+    println!("[  ][01a] Opening session");
+    ztimeout!(zenoh::open(config).res_async())
+        .unwrap()
+        .into_arc()
+}
+
+/// A TCP proxy forwarding connections to `target`, used to simulate network partitions.
+struct Proxy {
+    partitioned: Arc<AtomicBool>,
+    streams: Arc<Mutex<Vec<TcpStream>>>,
+}
+
+impl Proxy {
+    async fn spawn(listen: &str, target: &'static str) -> Proxy {
+        let listener = TcpListener::bind(listen).await.unwrap();
+        let partitioned = Arc::new(AtomicBool::new(false));
+        let streams = Arc::new(Mutex::new(vec![]));
+        let c_partitioned = partitioned.clone();
+        let c_streams = streams.clone();
+        task::spawn(async move {
+            while let Ok((inbound, _)) = listener.accept().await {
+                if c_partitioned.load(Ordering::SeqCst) {
+                    let _ = inbound.shutdown(Shutdown::Both);
+                    continue;
+                }
+                let outbound = match TcpStream::connect(target).await {
+                    Ok(outbound) => outbound,
+                    Err(_) => continue,
+                };
+                c_streams
+                    .lock()
+                    .unwrap()
+                    .extend([inbound.clone(), outbound.clone()]);
+                let (mut ri, mut wi) = (inbound.clone(), inbound);
+                let (mut ro, mut wo) = (outbound.clone(), outbound);
+                task::spawn(async move {
+                    let _ = futures::io::copy(&mut ri, &mut wo).await;
+                });
+                task::spawn(async move {
+                    let _ = futures::io::copy(&mut ro, &mut wi).await;
+                });
+            }
+        });
+        Proxy {
+            partitioned,
+            streams,
+        }
+    }
+
+    fn partition(&self) {
+        self.partitioned.store(true, Ordering::SeqCst);
+        for stream in self.streams.lock().unwrap().drain(..) {
+            let _ = stream.shutdown(Shutdown::Both);
+        }
+    }
+
+    fn heal(&self) {
+        self.partitioned.store(false, Ordering::SeqCst);
+    }
+}
+
+async fn join(z: &Arc<Session>, group: &str, mid: &str, quorum: Option<usize>) -> Group {
+    let mut member = Member::new(mid).unwrap().lease(LEASE);
+    if let Some(q) = quorum {
+        member = member.quorum(q);
+    }
+    ztimeout!(Group::join(z.clone(), group, member)).unwrap()
+}
+
+async fn wait_for_size(group: &Group, size: usize) {
+    ztimeout!(async {
+        while group.size().await != size {
+            task::sleep(SLEEP).await;
+        }
+    });
+}
+
+async fn wait_for_leadership(group: &Group, leader: Option<&str>, min_term: u64) -> Leadership {
+    ztimeout!(async {
+        loop {
+            let leadership = group.leadership().await;
+            if leadership.leader.as_deref().map(|l| l.as_str()) == leader
+                && leadership.term >= min_term
+            {
+                break leadership;
+            }
+            task::sleep(SLEEP).await;
+        }
+    })
+}
+
+/// Waits for all the groups to agree on the same leader and term, and returns the term.
+async fn wait_for_agreement(groups: &[&Group], leader: &str, min_term: u64) -> u64 {
+    ztimeout!(async {
+        loop {
+            let mut terms = vec![];
+            for g in groups {
+                terms.push(wait_for_leadership(g, Some(leader), min_term).await.term);
+            }
+            if terms.iter().all(|t| *t == terms[0]) {
+                break terms[0];
+            }
+            task::sleep(SLEEP).await;
+        }
+    })
+}
+
+#[test]
+fn group_election_split_brain() {
+    task::block_on(async {
+        let _ = env_logger::try_init();
+        let group = "test/group/split_brain";
+        let proxy = Proxy::spawn("127.0.0.1:18470", "127.0.0.1:18469").await;
+        let s1 = open_session(&["tcp/127.0.0.1:18469"], &[]).await;
+        let s2 = open_session(&[], &["tcp/127.0.0.1:18470"]).await;
+
+        let a = join(&s1, group, "a", None).await;
+        let b = join(&s1, group, "b", None).await;
+        let c = join(&s2, group, "c", None).await;
+
+        let changes = Arc::new(AtomicUsize::new(0));
+        let c_changes = changes.clone();
+        c.on_leadership_change(move |_| {
+            c_changes.fetch_add(1, Ordering::SeqCst);
+        })
+        .await;
+
+        for g in [&a, &b, &c] {
+            wait_for_size(g, 3).await;
+        }
+        let term = wait_for_agreement(&[&a, &b, &c], "a", 1).await;
+        assert!(a.is_leader().await);
+        assert!(!b.is_leader().await);
+        assert!(!c.is_leader().await);
+
+        // Without quorum, both sides of the partition elect a leader.
+        proxy.partition();
+        wait_for_size(&c, 1).await;
+        wait_for_size(&a, 2).await;
+        let c_term = wait_for_leadership(&c, Some("c"), term + 1).await.term;
+        assert!(c.is_leader().await);
+        assert!(a.is_leader().await);
+        assert!(changes.load(Ordering::SeqCst) > 0);
+
+        // Once healed, a single leader is elected with a term higher than
+        // any term used during the partition.
+        proxy.heal();
+        for g in [&a, &b, &c] {
+            wait_for_size(g, 3).await;
+        }
+        let healed_term = wait_for_agreement(&[&a, &b, &c], "a", c_term + 1).await;
+        assert!(healed_term > c_term);
+        assert!(a.is_leader().await);
+        assert!(!c.is_leader().await);
+    });
+}
+
+#[test]
+fn group_election_quorum() {
+    task::block_on(async {
+        let _ = env_logger::try_init();
+        let group = "test/group/quorum";
+        let proxy = Proxy::spawn("127.0.0.1:18472", "127.0.0.1:18471").await;
+        let s1 = open_session(&["tcp/127.0.0.1:18471"], &[]).await;
+        let s2 = open_session(&[], &["tcp/127.0.0.1:18472"]).await;
+
+        let a = join(&s1, group, "a", Some(2)).await;
+        assert_eq!(a.leader().await, None);
+        let b = join(&s1, group, "b", Some(2)).await;
+        let c = join(&s2, group, "c", Some(2)).await;
+        let rx = c.subscribe().await;
+        let leaderships = Arc::new(Mutex::new(vec![]));
+        let c_leaderships = leaderships.clone();
+        c.on_leadership_change(move |l| c_leaderships.lock().unwrap().push(l.clone()))
+            .await;
+
+        for g in [&a, &b, &c] {
+            wait_for_size(g, 3).await;
+        }
+        let term = wait_for_agreement(&[&a, &b, &c], "a", 1).await;
+
+        // The minority side loses its leader while the majority keeps it.
+        proxy.partition();
+        wait_for_size(&c, 1).await;
+        let c_term = wait_for_leadership(&c, None, term + 1).await.term;
+        assert!(!c.is_leader().await);
+        assert!(a.is_leader().await);
+        assert_eq!(a.term().await, term);
+
+        proxy.heal();
+        for g in [&a, &b, &c] {
+            wait_for_size(g, 3).await;
+        }
+        let healed_term = wait_for_agreement(&[&a, &b, &c], "a", c_term + 1).await;
+        assert!(healed_term > c_term);
+
+        let mut new_leaders = vec![];
+        while let Ok(evt) = rx.try_recv() {
+            if let GroupEvent::NewLeader(nle) = evt {
+                new_leaders.push(nle.mid.to_string());
+            }
+        }
+        assert_eq!(new_leaders.last().unwrap(), "a");
+        let leaderships = leaderships.lock().unwrap();
+        assert_eq!(leaderships.last().unwrap().term, healed_term);
+        assert!(leaderships.windows(2).all(|w| w[0].term < w[1].term));
+    });
+}
@@ -0,0 +1,163 @@
+//
+// Copyright (c) 2022 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+use async_std::prelude::FutureExt;
+use async_std::task;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use zenoh::prelude::r#async::*;
+use zenoh::scouting::WhatAmI;
+use zenoh_ext::*;
+
+const TIMEOUT: Duration = Duration::from_secs(60);
+const SLEEP: Duration = Duration::from_secs(1);
+const LEASE: Duration = Duration::from_secs(2);
+
+macro_rules! ztimeout {
+    ($f:expr) => {
+        $f.timeout(TIMEOUT).await.unwrap()
+    };
+}
+
+async fn open_session(mode: WhatAmI, listen: &[&str], connect: &[&str]) -> Session {
+    let mut config = config::peer();
+    config.set_mode(Some(mode)).unwrap();
+    config.listen.endpoints = listen
+        .iter()
+        .map(|e| e.parse().unwrap())
+        .collect::<Vec<_>>();
+    config.connect.endpoints = connect
+        .iter()
+        .map(|e| e.parse().unwrap())
+        .collect::<Vec<_>>();
+    config.scouting.multicast.set_enabled(Some(false)).unwrap();
+    println!("[  ][01a] Opening session");
+    ztimeout!(zenoh::open(config).res_async()).unwrap()
+}
+
+#[test]
+fn lock_fairness() {
+    task::block_on(async {
+        let key_expr = "test/lock/fairness";
+        let peer01 = open_session(WhatAmI::Peer, &["tcp/127.0.0.1:18480"], &[])
+            .await
+            .into_arc();
+        let peer02 = open_session(WhatAmI::Peer, &[], &["tcp/127.0.0.1:18480"])
+            .await
+            .into_arc();
+        task::sleep(SLEEP).await;
+
+        let guard = ztimeout!(peer01.lock(key_expr).lease(LEASE).res_async()).unwrap();
+        assert_eq!(guard.key_expr().as_str(), key_expr);
+
+        // A candidate giving up doesn't block the others.
+        assert!(ztimeout!(peer02
+            .lock(key_expr)
+            .lease(LEASE)
+            .timeout(SLEEP)
+            .res_async())
+        .is_err());
+
+        let order = Arc::new(Mutex::new(vec![]));
+        let mut waiters = vec![];
+        for (i, session) in [&peer02, &peer01, &peer02].iter().enumerate() {
+            let session = (*session).clone();
+            let order = order.clone();
+            waiters.push(task::spawn(async move {
+                let guard = ztimeout!(session.lock(key_expr).lease(LEASE).res_async()).unwrap();
+                order.lock().unwrap().push(i);
+                task::sleep(Duration::from_millis(200)).await;
+                ztimeout!(guard.release().res_async()).unwrap();
+            }));
+            task::sleep(SLEEP).await;
+        }
+        assert!(order.lock().unwrap().is_empty());
+
+        ztimeout!(guard.release().res_async()).unwrap();
+        for waiter in waiters {
+            ztimeout!(waiter);
+        }
+        assert_eq!(*order.lock().unwrap(), vec![0, 1, 2]);
+    });
+}
+
+#[test]
+fn lock_holder_crash() {
+    task::block_on(async {
+        let key_expr = "test/lock/crash";
+        let peer01 = open_session(WhatAmI::Peer, &["tcp/127.0.0.1:18481"], &[]).await;
+        let peer02 = open_session(WhatAmI::Peer, &[], &["tcp/127.0.0.1:18481"]).await;
+        task::sleep(SLEEP).await;
+
+        let guard = ztimeout!(peer02.lock(key_expr).lease(LEASE).res_async()).unwrap();
+        assert!(ztimeout!(peer01
+            .lock(key_expr)
+            .lease(LEASE)
+            .timeout(SLEEP)
+            .res_async())
+        .is_err());
+
+        // The holder's session closes without releasing the lock.
+        std::mem::forget(guard);
+        ztimeout!(peer02.close().res_async()).unwrap();
+
+        let guard = ztimeout!(peer01
+            .lock(key_expr)
+            .lease(LEASE)
+            .timeout(LEASE * 4)
+            .res_async())
+        .unwrap();
+        ztimeout!(guard.release().res_async()).unwrap();
+    });
+}
+
+#[test]
+fn lock_router_failover() {
+    task::block_on(async {
+        let key_expr = "test/lock/failover";
+        let lease = Duration::from_secs(10);
+        let router01 = open_session(WhatAmI::Router, &["tcp/127.0.0.1:18482"], &[]).await;
+        let router02 = open_session(WhatAmI::Router, &["tcp/127.0.0.1:18483"], &[]).await;
+        let routers = ["tcp/127.0.0.1:18482", "tcp/127.0.0.1:18483"];
+        let client01 = open_session(WhatAmI::Client, &[], &routers).await;
+        let client02 = open_session(WhatAmI::Client, &[], &routers)
+            .await
+            .into_arc();
+        task::sleep(SLEEP).await;
+
+        let guard = ztimeout!(client01.lock(key_expr).lease(lease).res_async()).unwrap();
+
+        let acquired = Arc::new(AtomicBool::new(false));
+        let c_acquired = acquired.clone();
+        let waiter = task::spawn(async move {
+            let guard = ztimeout!(client02.lock(key_expr).lease(lease).res_async()).unwrap();
+            c_acquired.store(true, Ordering::SeqCst);
+            ztimeout!(guard.release().res_async()).unwrap();
+        });
+        task::sleep(SLEEP).await;
+
+        // Both clients fail over to the second router within the lease:
+        // the lock must remain held.
+        ztimeout!(router01.close().res_async()).unwrap();
+        task::sleep(lease + SLEEP).await;
+        assert!(!acquired.load(Ordering::SeqCst));
+
+        ztimeout!(guard.release().res_async()).unwrap();
+        ztimeout!(waiter);
+        assert!(acquired.load(Ordering::SeqCst));
+
+        ztimeout!(client01.close().res_async()).unwrap();
+        ztimeout!(router02.close().res_async()).unwrap();
+    });
+}
@@ -0,0 +1,246 @@
+//
+// Copyright (c) 2022 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+use async_std::prelude::FutureExt;
+use async_std::task;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use zenoh::prelude::r#async::*;
+use zenoh::scouting::WhatAmI;
+use zenoh_core::SyncResolve;
+use zenoh_ext::*;
+
+const TIMEOUT: Duration = Duration::from_secs(60);
+const SLEEP: Duration = Duration::from_secs(1);
+
+macro_rules! ztimeout {
+    ($f:expr) => {
+        $f.timeout(TIMEOUT).await.unwrap()
+    };
+}
+
+async fn open_session(listen: &[&str], connect: &[&str]) -> Session {
+    let mut config = config::peer();
+    config.listen.endpoints = listen
+        .iter()
+        .map(|e| e.parse().unwrap())
+        .collect::<Vec<_>>();
+    config.connect.endpoints = connect
+        .iter()
+        .map(|e| e.parse().unwrap())
+        .collect::<Vec<_>>();
+    config.scouting.multicast.set_enabled(Some(false)).unwrap();
+    println!("[  ][01a] Opening session");
+    ztimeout!(zenoh::open(config).res_async()).unwrap()
+}
+
+async fn open_mode_session(mode: WhatAmI, listen: &[&str], connect: &[&str]) -> Session {
+    let mut config = config::default();
+    config.set_mode(Some(mode)).unwrap();
+    config.listen.endpoints = listen
+        .iter()
+        .map(|e| e.parse().unwrap())
+        .collect::<Vec<_>>();
+    config.connect.endpoints = connect
+        .iter()
+        .map(|e| e.parse().unwrap())
+        .collect::<Vec<_>>();
+    config.scouting.multicast.set_enabled(Some(false)).unwrap();
+    ztimeout!(zenoh::open(config).res_async()).unwrap()
+}
+
+#[test]
+fn querying_subscriber_period() {
+    task::block_on(async {
+        let key_expr = "test/querying_subscriber/period";
+        let peer = open_session(&[], &[]).await.into_arc();
+
+        let queries = Arc::new(AtomicUsize::new(0));
+        let stored = Arc::new(Mutex::new(
+            Sample::try_from(key_expr, "stored")
+                .unwrap()
+                .with_timestamp(zenoh::time::new_reception_timestamp()),
+        ));
+        let c_queries = queries.clone();
+        let c_stored = stored.clone();
+        let _qbl = ztimeout!(peer
+            .declare_queryable(key_expr)
+            .callback(move |query| {
+                c_queries.fetch_add(1, Ordering::SeqCst);
+                let sample = c_stored.lock().unwrap().clone();
+                query.reply(Ok(sample)).res_sync().unwrap();
+            })
+            .res_async())
+        .unwrap();
+
+        let sub = ztimeout!(peer
+            .declare_querying_subscriber(key_expr)
+            .query_period(Duration::from_millis(500))
+            .res_async())
+        .unwrap();
+
+        let sample = ztimeout!(sub.recv_async()).unwrap();
+        assert_eq!(sample.value.to_string(), "stored");
+
+        // The sample retrieved again by the next queries is not delivered twice
+        task::sleep(SLEEP + SLEEP / 2).await;
+        assert!(queries.load(Ordering::SeqCst) >= 3);
+        assert!(sub.try_recv().is_err());
+
+        // A newer sample is
+        *stored.lock().unwrap() = Sample::try_from(key_expr, "updated")
+            .unwrap()
+            .with_timestamp(zenoh::time::new_reception_timestamp());
+        let sample = ztimeout!(sub.recv_async()).unwrap();
+        assert_eq!(sample.value.to_string(), "updated");
+        task::sleep(SLEEP).await;
+        assert!(sub.try_recv().is_err());
+
+        ztimeout!(sub.close().res_async()).unwrap();
+    });
+}
+
+#[test]
+fn querying_subscriber_on_connect() {
+    task::block_on(async {
+        let key_expr = "test/querying_subscriber/connect";
+        let peer01 = open_session(&["tcp/127.0.0.1:18467"], &[]).await.into_arc();
+
+        let sub = ztimeout!(peer01
+            .declare_querying_subscriber(key_expr)
+            .query_on_connect(true)
+            .res_async())
+        .unwrap();
+        task::sleep(SLEEP).await;
+        assert!(sub.try_recv().is_err());
+
+        // A peer with a queryable gets connected
+        let peer02 = open_session(&[], &["tcp/127.0.0.1:18467"]).await;
+        let qbl = ztimeout!(peer02
+            .declare_queryable(key_expr)
+            .callback(move |query| {
+                query
+                    .reply(Ok(Sample::try_from(key_expr, "stored").unwrap()))
+                    .res_sync()
+                    .unwrap();
+            })
+            .res_async())
+        .unwrap();
+
+        let sample = ztimeout!(sub.recv_async()).unwrap();
+        assert_eq!(sample.value.to_string(), "stored");
+
+        ztimeout!(sub.close().res_async()).unwrap();
+        ztimeout!(qbl.undeclare().res_async()).unwrap();
+        ztimeout!(peer02.close().res_async()).unwrap();
+    });
+}
+
+#[test]
+fn querying_subscriber_on_new_queryable() {
+    task::block_on(async {
+        let key_expr = "test/querying_subscriber/queryable";
+        let router = open_mode_session(WhatAmI::Router, &["tcp/127.0.0.1:18512"], &[]).await;
+        let client01 = open_mode_session(WhatAmI::Client, &[], &["tcp/127.0.0.1:18512"])
+            .await
+            .into_arc();
+        let sub = ztimeout!(client01
+            .declare_querying_subscriber(key_expr)
+            .query_on_connect(true)
+            .res_async())
+        .unwrap();
+        task::sleep(SLEEP).await;
+        assert!(sub.try_recv().is_err());
+
+        // A storage appears behind the router the subscriber is already connected to
+        let client02 = open_mode_session(WhatAmI::Client, &[], &["tcp/127.0.0.1:18512"]).await;
+        let qbl = ztimeout!(client02
+            .declare_queryable(key_expr)
+            .callback(move |query| {
+                query
+                    .reply(Ok(Sample::try_from(key_expr, "stored").unwrap()))
+                    .res_sync()
+                    .unwrap();
+            })
+            .res_async())
+        .unwrap();
+
+        let sample = ztimeout!(sub.recv_async()).unwrap();
+        assert_eq!(sample.value.to_string(), "stored");
+
+        ztimeout!(sub.close().res_async()).unwrap();
+        ztimeout!(qbl.undeclare().res_async()).unwrap();
+        ztimeout!(client02.close().res_async()).unwrap();
+        ztimeout!(router.close().res_async()).unwrap();
+    });
+}
+
+#[test]
+fn querying_subscriber_requery_requires_arc() {
+    task::block_on(async {
+        let peer = open_session(&[], &[]).await;
+        assert!(peer
+            .declare_querying_subscriber("test/querying_subscriber/arc")
+            .query_period(Duration::from_secs(1))
+            .res_async()
+            .await
+            .is_err());
+        ztimeout!(peer.close().res_async()).unwrap();
+    });
+}
+
+#[test]
+fn fetching_subscriber() {
+    task::block_on(async {
+        let key_expr = "test/fetching_subscriber";
+        let peer = open_session(&[], &[]).await;
+
+        let (fetch_tx, fetch_rx) = flume::bounded::<()>(1);
+        let fetch_rx = Arc::new(Mutex::new(fetch_rx));
+        let received = Arc::new(Mutex::new(vec![]));
+        let c_received = received.clone();
+        let sub = ztimeout!(peer
+            .declare_fetching_subscriber(key_expr, move |cb: FetchCallback| {
+                let fetch_rx = fetch_rx.clone();
+                async move {
+                    let ts = zenoh::time::new_reception_timestamp();
+                    cb(Sample::try_from(key_expr, "fetched")
+                        .unwrap()
+                        .with_timestamp(ts));
+                    // wait for the live publication to be received before completing the fetch
+                    let rx = fetch_rx.lock().unwrap().clone();
+                    rx.recv_async().await.unwrap();
+                    Ok(())
+                }
+            })
+            .callback(move |s| c_received.lock().unwrap().push(s.value.to_string()))
+            .res_async())
+        .unwrap();
+
+        ztimeout!(peer.put(key_expr, "live").res_async()).unwrap();
+        task::sleep(SLEEP).await;
+        // publications are held back while fetching
+        assert!(received.lock().unwrap().is_empty());
+
+        fetch_tx.send_async(()).await.unwrap();
+        task::sleep(SLEEP).await;
+        assert_eq!(
+            *received.lock().unwrap(),
+            vec!["fetched".to_string(), "live".to_string()]
+        );
+
+        ztimeout!(sub.close().res_async()).unwrap();
+        ztimeout!(peer.close().res_async()).unwrap();
+    });
+}
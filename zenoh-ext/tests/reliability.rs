@@ -0,0 +1,299 @@
+//
+// Copyright (c) 2022 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+use async_std::prelude::FutureExt;
+use async_std::task;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use zenoh::config::ModeDependentValue;
+use zenoh::prelude::r#async::*;
+use zenoh::sample::SourceInfo;
+use zenoh_ext::*;
+
+const TIMEOUT: Duration = Duration::from_secs(60);
+const SLEEP: Duration = Duration::from_secs(1);
+
+macro_rules! ztimeout {
+    ($f:expr) => {
+        $f.timeout(TIMEOUT).await.unwrap()
+    };
+}
+
+async fn open_session(listen: &[&str], connect: &[&str]) -> Session {
+    let mut config = config::peer();
+    config.listen.endpoints = listen
+        .iter()
+        .map(|e| e.parse().unwrap())
+        .collect::<Vec<_>>();
+    config.connect.endpoints = connect
+        .iter()
+        .map(|e| e.parse().unwrap())
+        .collect::<Vec<_>>();
+    config.scouting.multicast.set_enabled(Some(false)).unwrap();
+    config
+        .timestamping
+        .set_enabled(Some(ModeDependentValue::Unique(true)))
+        .unwrap();
+    println!("[  ][01a] Opening session");
+    ztimeout!(zenoh::open(config).res_async()).unwrap()
+}
+
+async fn put_sn(session: &Session, key_expr: &str, sn: u64, destination: Locality) {
+    ztimeout!(session
+        .put(key_expr, sn.to_string())
+        .allowed_destination(destination)
+        .with_source_info(SourceInfo {
+            source_id: Some(session.zid()),
+            source_sn: Some(sn),
+        })
+        .res_async())
+    .unwrap();
+}
+
+fn received_sns(received: &Mutex<Vec<Sample>>) -> Vec<u64> {
+    received
+        .lock()
+        .unwrap()
+        .iter()
+        .map(|s| s.value.to_string().parse().unwrap())
+        .collect()
+}
+
+#[test]
+fn reliability_sample_miss() {
+    task::block_on(async {
+        let key_expr = "test/reliability/miss";
+
+        let peer01 = open_session(&["tcp/127.0.0.1:18457"], &[]).await;
+        let peer02 = open_session(&[], &["tcp/127.0.0.1:18457"]).await;
+
+        let received = Arc::new(Mutex::new(vec![]));
+        let misses = Arc::new(Mutex::new(vec![]));
+        let c_received = received.clone();
+        let c_misses = misses.clone();
+        let sub = ztimeout!(peer01
+            .declare_reliable_subscriber(key_expr)
+            .callback(move |s| c_received.lock().unwrap().push(s))
+            .sample_miss_callback(move |m| c_misses.lock().unwrap().push(m))
+            .res_async())
+        .unwrap();
+        task::sleep(SLEEP).await;
+
+        for sn in [0, 1, 3, 3, 4, 7] {
+            put_sn(&peer02, key_expr, sn, Locality::Any).await;
+        }
+        task::sleep(SLEEP).await;
+
+        assert_eq!(received_sns(&received), vec![0, 1, 3, 4, 7]);
+        let misses = misses.lock().unwrap().clone();
+        assert_eq!(misses.len(), 2);
+        assert_eq!(misses[0].source_id, peer02.zid());
+        assert_eq!(misses[0].sns, 2..=2);
+        assert_eq!(misses[1].sns, 5..=6);
+        assert_eq!(misses[1].count(), 2);
+
+        ztimeout!(sub.close().res_async()).unwrap();
+        ztimeout!(peer02.close().res_async()).unwrap();
+        ztimeout!(peer01.close().res_async()).unwrap();
+    });
+}
+
+#[test]
+fn reliability_sample_recovery() {
+    task::block_on(async {
+        let key_expr = "test/reliability/recovery";
+
+        let peer01 = open_session(&["tcp/127.0.0.1:18458"], &[]).await.into_arc();
+        let peer02 = open_session(&[], &["tcp/127.0.0.1:18458"]).await;
+
+        // The publication cache of peer02 gets all its publications,
+        // including the ones that are not sent to peer01
+        let cache = ztimeout!(peer02
+            .declare_publication_cache(key_expr)
+            .history(16)
+            .res_async())
+        .unwrap();
+
+        let received = Arc::new(Mutex::new(vec![]));
+        let misses = Arc::new(Mutex::new(vec![]));
+        let c_received = received.clone();
+        let c_misses = misses.clone();
+        let sub = ztimeout!(peer01
+            .declare_reliable_subscriber(key_expr)
+            .recovery(true)
+            .callback(move |s| c_received.lock().unwrap().push(s))
+            .sample_miss_callback(move |m| c_misses.lock().unwrap().push(m))
+            .res_async())
+        .unwrap();
+        task::sleep(SLEEP).await;
+
+        put_sn(&peer02, key_expr, 0, Locality::Any).await;
+        put_sn(&peer02, key_expr, 1, Locality::SessionLocal).await;
+        put_sn(&peer02, key_expr, 2, Locality::SessionLocal).await;
+        put_sn(&peer02, key_expr, 3, Locality::Any).await;
+        // sn 4 is never published
+        put_sn(&peer02, key_expr, 5, Locality::Any).await;
+        task::sleep(SLEEP).await;
+
+        assert_eq!(received_sns(&received), vec![0, 1, 2, 3, 5]);
+        let misses = misses.lock().unwrap().clone();
+        assert_eq!(misses.len(), 1);
+        assert_eq!(misses[0].sns, 4..=4);
+
+        ztimeout!(sub.close().res_async()).unwrap();
+        ztimeout!(cache.close().res_async()).unwrap();
+        ztimeout!(peer02.close().res_async()).unwrap();
+    });
+}
+
+#[test]
+fn reliability_recovery_interleaved() {
+    task::block_on(async {
+        let key_expr = "test/reliability/interleaved";
+
+        let peer01 = open_session(&["tcp/127.0.0.1:18511"], &[]).await.into_arc();
+        let peer02 = open_session(&[], &["tcp/127.0.0.1:18511"]).await;
+        let peer03 = open_session(&[], &["tcp/127.0.0.1:18511"]).await;
+
+        // The recovery replies come from peer03, concurrently with the live samples from peer02
+        let cache = ztimeout!(peer03
+            .declare_publication_cache(key_expr)
+            .history(64)
+            .res_async())
+        .unwrap();
+
+        // A slow callback, noting whether it is ever run concurrently
+        let received = Arc::new(Mutex::new(vec![]));
+        let is_running = Arc::new(AtomicBool::new(false));
+        let overlaps = Arc::new(AtomicUsize::new(0));
+        let c_received = received.clone();
+        let c_is_running = is_running.clone();
+        let c_overlaps = overlaps.clone();
+        let sub = ztimeout!(peer01
+            .declare_reliable_subscriber(key_expr)
+            .recovery(true)
+            .callback(move |s| {
+                if c_is_running.swap(true, Ordering::SeqCst) {
+                    c_overlaps.fetch_add(1, Ordering::SeqCst);
+                }
+                std::thread::sleep(Duration::from_millis(10));
+                c_received.lock().unwrap().push(s);
+                c_is_running.store(false, Ordering::SeqCst);
+            })
+            .res_async())
+        .unwrap();
+        task::sleep(SLEEP).await;
+
+        put_sn(&peer02, key_expr, 0, Locality::Any).await;
+        // sns 1 to 20 of peer02 only reach the cache of peer03
+        for sn in 1..=20 {
+            ztimeout!(peer03
+                .put(key_expr, sn.to_string())
+                .allowed_destination(Locality::SessionLocal)
+                .with_source_info(SourceInfo {
+                    source_id: Some(peer02.zid()),
+                    source_sn: Some(sn),
+                })
+                .res_async())
+            .unwrap();
+        }
+        for sn in 21..=60 {
+            put_sn(&peer02, key_expr, sn, Locality::Any).await;
+            task::sleep(Duration::from_millis(5)).await;
+        }
+        task::sleep(SLEEP).await;
+
+        assert_eq!(received_sns(&received), (0..=60).collect::<Vec<_>>());
+        assert_eq!(overlaps.load(Ordering::SeqCst), 0);
+
+        ztimeout!(sub.close().res_async()).unwrap();
+        ztimeout!(cache.close().res_async()).unwrap();
+        ztimeout!(peer03.close().res_async()).unwrap();
+        ztimeout!(peer02.close().res_async()).unwrap();
+    });
+}
+
+#[test]
+fn reliability_recovery_requires_arc() {
+    task::block_on(async {
+        let peer = open_session(&[], &[]).await;
+        assert!(peer
+            .declare_reliable_subscriber("test/reliability/arc")
+            .recovery(true)
+            .res_async()
+            .await
+            .is_err());
+        ztimeout!(peer.close().res_async()).unwrap();
+    });
+}
+
+#[test]
+fn reliability_reliable_publisher() {
+    task::block_on(async {
+        let key_expr = "test/reliability/publisher";
+
+        let peer01 = open_session(&["tcp/127.0.0.1:18459"], &[]).await.into_arc();
+        let peer02 = open_session(&[], &["tcp/127.0.0.1:18459"]).await;
+
+        let received = Arc::new(Mutex::new(vec![]));
+        let misses = Arc::new(Mutex::new(vec![]));
+        let c_received = received.clone();
+        let c_misses = misses.clone();
+        let sub = ztimeout!(peer01
+            .declare_reliable_subscriber(key_expr)
+            .recovery(true)
+            .callback(move |s| c_received.lock().unwrap().push(s))
+            .sample_miss_callback(move |m| c_misses.lock().unwrap().push(m))
+            .res_async())
+        .unwrap();
+        // Only the subscriber's handle refers to the session, not its callback stored in the session
+        assert_eq!(Arc::strong_count(&peer01), 2);
+        task::sleep(SLEEP).await;
+
+        let publisher = ztimeout!(peer02.declare_reliable_publisher(key_expr).res_async()).unwrap();
+        ztimeout!(publisher.put("0").res_async()).unwrap();
+        ztimeout!(publisher.put("1").res_async()).unwrap();
+        // sn 2 is consumed but never published, so it can't be recovered
+        drop(publisher.put("2"));
+        ztimeout!(publisher.put("3").res_async()).unwrap();
+        task::sleep(SLEEP).await;
+
+        assert_eq!(received_sns(&received), vec![0, 1, 3]);
+        assert!(received
+            .lock()
+            .unwrap()
+            .iter()
+            .all(|s| s.source_info.source_id == Some(peer02.zid())));
+        {
+            let misses = misses.lock().unwrap();
+            assert_eq!(misses.len(), 1);
+            assert_eq!(misses[0].sns, 2..=2);
+        }
+
+        // A publisher re-created on the same session restarts its sequence numbers
+        ztimeout!(publisher.close().res_async()).unwrap();
+        received.lock().unwrap().clear();
+        let publisher = ztimeout!(peer02.declare_reliable_publisher(key_expr).res_async()).unwrap();
+        ztimeout!(publisher.put("0").res_async()).unwrap();
+        ztimeout!(publisher.put("1").res_async()).unwrap();
+        task::sleep(SLEEP).await;
+
+        assert_eq!(received_sns(&received), vec![0, 1]);
+        assert_eq!(misses.lock().unwrap().len(), 1);
+
+        ztimeout!(publisher.close().res_async()).unwrap();
+        ztimeout!(sub.close().res_async()).unwrap();
+        ztimeout!(peer02.close().res_async()).unwrap();
+    });
+}
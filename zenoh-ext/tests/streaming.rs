@@ -0,0 +1,354 @@
+//
+// Copyright (c) 2022 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+use async_std::prelude::FutureExt;
+use async_std::task;
+use std::io::Write;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use zenoh::prelude::r#async::*;
+use zenoh::prelude::sync::SyncResolve;
+use zenoh_ext::*;
+
+const TIMEOUT: Duration = Duration::from_secs(60);
+const SLEEP: Duration = Duration::from_secs(1);
+
+const CHUNK_SIZE: usize = 16 * 1_024;
+const STREAM_SIZE: usize = 1_024 * 1_024 + 123;
+
+macro_rules! ztimeout {
+    ($f:expr) => {
+        $f.timeout(TIMEOUT).await.unwrap()
+    };
+}
+
+async fn open_session(listen: &[&str], connect: &[&str]) -> Session {
+    let mut config = config::peer();
+    config.listen.endpoints = listen
+        .iter()
+        .map(|e| e.parse().unwrap())
+        .collect::<Vec<_>>();
+    config.connect.endpoints = connect
+        .iter()
+        .map(|e| e.parse().unwrap())
+        .collect::<Vec<_>>();
+    config.scouting.multicast.set_enabled(Some(false)).unwrap();
+    println!("[  ][01a] Opening session");
+    ztimeout!(zenoh::open(config).res_async()).unwrap()
+}
+
+fn payload() -> Vec<u8> {
+    (0..STREAM_SIZE).map(|i| (i % 251) as u8).collect()
+}
+
+async fn recv_stream(stream: &IncomingStream) -> zenoh::Result<Vec<u8>> {
+    let mut data = vec![];
+    while let Some(chunk) = ztimeout!(stream.recv_async())? {
+        assert!(chunk.len() <= CHUNK_SIZE);
+        data.extend_from_slice(chunk.as_slice());
+    }
+    Ok(data)
+}
+
+#[test]
+fn streaming_large_payload() {
+    task::block_on(async {
+        let key_expr = "test/streaming/large";
+
+        let peer01 = open_session(&["tcp/127.0.0.1:18491"], &[]).await;
+        let peer02 = open_session(&[], &["tcp/127.0.0.1:18491"]).await.into_arc();
+
+        let progress = Arc::new(Mutex::new(vec![]));
+        let c_progress = progress.clone();
+        // The subscriber buffers the whole stream, its reception never waiting for it to be read
+        let sub = ztimeout!(peer01
+            .declare_streaming_subscriber(key_expr)
+            .buffer(STREAM_SIZE / CHUNK_SIZE + 1)
+            .progress_callback(move |p| c_progress.lock().unwrap().push(p))
+            .res_async())
+        .unwrap();
+        task::sleep(SLEEP).await;
+
+        // The stream is written from a thread of its own: the publisher blocks
+        // while the chunks are not sent
+        let c_peer02 = peer02.clone();
+        let writer = std::thread::spawn(move || {
+            let publisher = c_peer02
+                .declare_streaming_publisher(key_expr)
+                .chunk_size(CHUNK_SIZE)
+                .res_sync()
+                .unwrap();
+            let payload = payload();
+            let mut stream = publisher.stream(Some(payload.len() as u64));
+            for part in payload.chunks(10_000) {
+                stream.write_all(part).unwrap();
+            }
+            assert_eq!(stream.close().unwrap(), STREAM_SIZE as u64);
+
+            // A stream of unknown size
+            let mut stream = publisher.stream(None);
+            stream.write_all(b"Hello ").unwrap();
+            stream.write_all(b"streaming").unwrap();
+            stream.close().unwrap();
+        });
+
+        let stream = ztimeout!(sub.recv_async()).unwrap();
+        assert_eq!(stream.source_id(), peer02.zid());
+        assert_eq!(stream.key_expr().as_str(), key_expr);
+        assert_eq!(stream.size(), Some(STREAM_SIZE as u64));
+        assert!(recv_stream(&stream).await.unwrap() == payload());
+        assert!(ztimeout!(stream.recv_async()).unwrap().is_none());
+
+        let stream = ztimeout!(sub.recv_async()).unwrap();
+        assert_eq!(stream.size(), None);
+        assert_eq!(recv_stream(&stream).await.unwrap(), b"Hello streaming");
+        writer.join().unwrap();
+
+        let progress = progress.lock().unwrap().clone();
+        let first = progress
+            .iter()
+            .filter(|p| p.stream_id == 0)
+            .collect::<Vec<_>>();
+        assert!(first.len() >= STREAM_SIZE / CHUNK_SIZE);
+        let last = first.last().unwrap();
+        assert_eq!(last.received, STREAM_SIZE as u64);
+        assert_eq!(last.ratio(), Some(1.0));
+
+        ztimeout!(sub.close().res_async()).unwrap();
+        ztimeout!(peer01.close().res_async()).unwrap();
+    });
+}
+
+#[test]
+fn streaming_abort() {
+    task::block_on(async {
+        let key_expr = "test/streaming/abort";
+
+        let peer01 = open_session(&["tcp/127.0.0.1:18492"], &[]).await;
+        let peer02 = open_session(&[], &["tcp/127.0.0.1:18492"]).await;
+
+        let sub = ztimeout!(peer01.declare_streaming_subscriber(key_expr).res_async()).unwrap();
+        task::sleep(SLEEP).await;
+
+        // Regular publications are ignored
+        ztimeout!(peer02.put(key_expr, "not a stream").res_async()).unwrap();
+
+        let publisher = ztimeout!(peer02
+            .declare_streaming_publisher(key_expr)
+            .chunk_size(4)
+            .res_async())
+        .unwrap();
+
+        // A stream dropped before being closed is aborted
+        let mut stream = publisher.stream(None);
+        stream.write_all(b"abcdef").unwrap();
+        drop(stream);
+
+        let stream = ztimeout!(sub.recv_async()).unwrap();
+        assert_eq!(
+            ztimeout!(stream.recv_async()).unwrap().unwrap().as_slice(),
+            b"abcd"
+        );
+        assert_eq!(
+            ztimeout!(stream.recv_async()).unwrap().unwrap().as_slice(),
+            b"ef"
+        );
+        assert!(ztimeout!(stream.recv_async()).is_err());
+
+        // A stream can't be written past its size, nor closed before it
+        let mut stream = publisher.stream(Some(4));
+        assert!(stream.send(b"abcdef").is_err());
+        stream.send(b"ab").unwrap();
+        assert!(stream.close().is_err());
+        let stream = ztimeout!(sub.recv_async()).unwrap();
+        assert!(recv_stream(&stream).await.is_err());
+
+        publisher.put(b"complete").unwrap();
+        let stream = ztimeout!(sub.recv_async()).unwrap();
+        assert_eq!(recv_stream(&stream).await.unwrap(), b"complete");
+        assert!(sub.try_recv().is_err());
+
+        ztimeout!(publisher.close().res_async()).unwrap();
+        ztimeout!(sub.close().res_async()).unwrap();
+        ztimeout!(peer02.close().res_async()).unwrap();
+        ztimeout!(peer01.close().res_async()).unwrap();
+    });
+}
+
+#[test]
+fn streaming_concurrent() {
+    task::block_on(async {
+        let key_expr = "test/streaming/concurrent";
+
+        let peer01 = open_session(&["tcp/127.0.0.1:18493"], &[]).await;
+        let peer02 = open_session(&[], &["tcp/127.0.0.1:18493"]).await;
+
+        let sub = ztimeout!(peer01.declare_streaming_subscriber(key_expr).res_async()).unwrap();
+        task::sleep(SLEEP).await;
+
+        // The streams of two publishers of the same session have the same ids
+        let publishers = [
+            ztimeout!(peer02
+                .declare_streaming_publisher(key_expr)
+                .chunk_size(4)
+                .res_async())
+            .unwrap(),
+            ztimeout!(peer02
+                .declare_streaming_publisher(key_expr)
+                .chunk_size(4)
+                .res_async())
+            .unwrap(),
+        ];
+        assert_ne!(publishers[0].id(), publishers[1].id());
+        let mut streams = publishers
+            .iter()
+            .map(|p| p.stream(None))
+            .collect::<Vec<_>>();
+        assert_eq!(streams[0].id(), streams[1].id());
+
+        // Their chunks are interleaved
+        for part in [b"aaaa", b"bbbb", b"cccc"] {
+            for (i, stream) in streams.iter_mut().enumerate() {
+                let mut data = part.to_vec();
+                data[0] = b'0' + i as u8;
+                stream.send(&data).unwrap();
+            }
+        }
+        for stream in streams {
+            stream.close().unwrap();
+        }
+
+        for _ in 0..publishers.len() {
+            let stream = ztimeout!(sub.recv_async()).unwrap();
+            let i = publishers
+                .iter()
+                .position(|p| p.id() == stream.publisher_id())
+                .unwrap();
+            let expected = format!("{i}aaa{i}bbb{i}ccc");
+            assert_eq!(recv_stream(&stream).await.unwrap(), expected.as_bytes());
+        }
+        assert!(sub.try_recv().is_err());
+
+        drop(publishers);
+        ztimeout!(sub.close().res_async()).unwrap();
+        ztimeout!(peer02.close().res_async()).unwrap();
+        ztimeout!(peer01.close().res_async()).unwrap();
+    });
+}
+
+#[test]
+fn streaming_overflow_timeout() {
+    task::block_on(async {
+        let key_expr = "test/streaming/overflow";
+
+        let peer01 = open_session(&["tcp/127.0.0.1:18494"], &[]).await;
+        let peer02 = open_session(&[], &["tcp/127.0.0.1:18494"]).await;
+
+        let sub = ztimeout!(peer01
+            .declare_streaming_subscriber(key_expr)
+            .buffer(2)
+            .timeout(SLEEP)
+            .res_async())
+        .unwrap();
+        task::sleep(SLEEP).await;
+
+        let publisher = ztimeout!(peer02
+            .declare_streaming_publisher(key_expr)
+            .chunk_size(4)
+            .res_async())
+        .unwrap();
+
+        // A stream whose buffer is full fails instead of blocking the reception
+        publisher.put(b"aaaabbbbccccdddd").unwrap();
+        let stream = ztimeout!(sub.recv_async()).unwrap();
+        task::sleep(SLEEP / 2).await;
+        assert_eq!(
+            ztimeout!(stream.recv_async()).unwrap().unwrap().as_slice(),
+            b"aaaa"
+        );
+        assert_eq!(
+            ztimeout!(stream.recv_async()).unwrap().unwrap().as_slice(),
+            b"bbbb"
+        );
+        assert!(ztimeout!(stream.recv_async()).is_err());
+        assert!(ztimeout!(stream.recv_async()).unwrap().is_none());
+
+        // A stream fails when none of its chunks is received for its timeout
+        let mut writer = publisher.stream(None);
+        writer.send(b"eeee").unwrap();
+        let stream = ztimeout!(sub.recv_async()).unwrap();
+        assert_eq!(
+            ztimeout!(stream.recv_async()).unwrap().unwrap().as_slice(),
+            b"eeee"
+        );
+        let now = std::time::Instant::now();
+        assert!(ztimeout!(stream.recv_async()).is_err());
+        assert!(now.elapsed() >= SLEEP);
+        drop(writer);
+
+        ztimeout!(publisher.close().res_async()).unwrap();
+        ztimeout!(sub.close().res_async()).unwrap();
+        ztimeout!(peer02.close().res_async()).unwrap();
+        ztimeout!(peer01.close().res_async()).unwrap();
+    });
+}
+
+#[test]
+fn streaming_max_streams() {
+    task::block_on(async {
+        let key_expr = "test/streaming/max_streams";
+
+        let peer01 = open_session(&["tcp/127.0.0.1:18495"], &[]).await;
+        let peer02 = open_session(&[], &["tcp/127.0.0.1:18495"]).await;
+
+        let sub = ztimeout!(peer01
+            .declare_streaming_subscriber(key_expr)
+            .max_streams(1)
+            .res_async())
+        .unwrap();
+        task::sleep(SLEEP).await;
+
+        let publisher = ztimeout!(peer02
+            .declare_streaming_publisher(key_expr)
+            .chunk_size(4)
+            .res_async())
+        .unwrap();
+
+        // The stream started beyond the maximum fails at once
+        let mut first = publisher.stream(None);
+        first.send(b"aaaa").unwrap();
+        let mut second = publisher.stream(None);
+        second.send(b"bbbb").unwrap();
+        let stream = ztimeout!(sub.recv_async()).unwrap();
+        assert_eq!(stream.id(), first.id());
+        let refused = ztimeout!(sub.recv_async()).unwrap();
+        assert_eq!(refused.id(), second.id());
+        assert!(ztimeout!(refused.recv_async()).is_err());
+        drop(second);
+
+        // The first one is unaffected, and its end frees its slot
+        first.send(b"cccc").unwrap();
+        first.close().unwrap();
+        assert_eq!(recv_stream(&stream).await.unwrap(), b"aaaacccc");
+        let mut third = publisher.stream(None);
+        third.send(b"dddd").unwrap();
+        third.close().unwrap();
+        let stream = ztimeout!(sub.recv_async()).unwrap();
+        assert_eq!(recv_stream(&stream).await.unwrap(), b"dddd");
+
+        ztimeout!(publisher.close().res_async()).unwrap();
+        ztimeout!(sub.close().res_async()).unwrap();
+        ztimeout!(peer02.close().res_async()).unwrap();
+        ztimeout!(peer01.close().res_async()).unwrap();
+    });
+}
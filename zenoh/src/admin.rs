@@ -19,11 +19,11 @@ use std::{
 //
 use crate::{
     keyexpr,
-    prelude::sync::{KeyExpr, Locality},
+    prelude::sync::{KeyExpr, Locality, OwnedKeyExpr},
     queryable::Query,
     Sample, Session, ZResult,
 };
-use zenoh_core::SyncResolve;
+use zenoh_core::{zread, SyncResolve};
 use zenoh_protocol::{
     core::{Encoding, KnownEncoding, SampleKind, WireExpr},
     zenoh::{DataInfo, ZenohMessage},
@@ -41,6 +41,7 @@ lazy_static::lazy_static!(
     static ref KE_PREFIX: &'static keyexpr = ke_for_sure!("@/session");
     static ref KE_TRANSPORT_UNICAST: &'static keyexpr = ke_for_sure!("transport/unicast");
     static ref KE_LINK: &'static keyexpr = ke_for_sure!("link");
+    static ref KE_QUERYABLE: &'static keyexpr = ke_for_sure!("queryable");
 );
 
 pub(crate) fn init(session: &Session) {
@@ -97,6 +98,27 @@ pub(crate) fn on_admin_query(session: &Session, query: Query) {
     }
 }
 
+/// Notifies the declaration (Put) or undeclaration (Delete) of a remote queryable on
+/// `@/session/<zid>/queryable/<key_expr>`, so that the subscribers can react to the
+/// storages and publication caches becoming reachable on the key expressions they intersect.
+pub(crate) fn on_remote_queryable(session: &Session, key_expr: &WireExpr, kind: SampleKind) {
+    let key_expr: OwnedKeyExpr = match zread!(session.state).remote_key_to_expr(key_expr) {
+        Ok(key_expr) => (&*key_expr).into(),
+        Err(e) => {
+            log::error!("Received queryable declaration for unknown key_expr: {}", e);
+            return;
+        }
+    };
+    if let Ok(own_zid) = keyexpr::new(&session.zid().to_string()) {
+        let expr = WireExpr::from(&(*KE_PREFIX / own_zid / *KE_QUERYABLE / &key_expr)).to_owned();
+        let info = DataInfo {
+            kind,
+            ..Default::default()
+        };
+        session.handle_data(true, &expr, Some(info), vec![0u8; 0].into());
+    }
+}
+
 pub(crate) struct Handler {
     pub(crate) session: Arc<Session>,
 }
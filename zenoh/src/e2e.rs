@@ -0,0 +1,195 @@
+//
+// Copyright (c) 2022 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+
+//! End-to-end payload encryption.
+//!
+//! Transport security (TLS, QUIC) only protects data hop by hop: the routers forwarding it
+//! see the payloads in clear. When a [`KeyProvider`] is set on a [`Session`](crate::Session)
+//! with [`Session::set_key_provider`](crate::Session::set_key_provider), the payloads put or
+//! replied on the key expressions it provides a key for are encrypted with an authenticated
+//! cipher before leaving the session, and decrypted transparently when received by the
+//! subscribers, queryables and queries of a session holding the same key.
+//!
+//! Encrypted payloads are marked with the [`Encoding::encrypted`] encoding, their original
+//! encoding being encrypted along with them. The key expression is authenticated too, so
+//! that a payload cannot be replayed on another key expression. Sessions that don't hold
+//! the key (e.g. the storages of a router) receive the encrypted payloads as is, while the
+//! sessions holding it drop the unencrypted payloads received on these key expressions.
+//!
+//! # Examples
+//! ```
+//! # async_std::task::block_on(async {
+//! use std::sync::Arc;
+//! use zenoh::e2e::KeyExprKeys;
+//! use zenoh::prelude::r#async::*;
+//!
+//! let keys = KeyExprKeys::new();
+//! keys.insert(OwnedKeyExpr::new("demo/secret/**").unwrap(), [42; 16]);
+//!
+//! let session = zenoh::open(config::peer()).res().await.unwrap();
+//! session.set_key_provider(Arc::new(keys));
+//! session.put("demo/secret/value", "Encrypted end-to-end").res().await.unwrap();
+//! # })
+//! ```
+
+use rand::SeedableRng;
+use std::collections::HashMap;
+use std::convert::TryFrom;
+use std::sync::{Arc, Mutex, RwLock};
+use zenoh_core::{zlock, zread, zwrite};
+use zenoh_crypto::{AeadCipher, PseudoRng};
+use zenoh_protocol::core::key_expr::{keyexpr, OwnedKeyExpr};
+use zenoh_result::{bail, zerror, ZResult};
+
+use crate::prelude::{Encoding, SplitBuffer, ZInt};
+use crate::value::Value;
+
+/// A key used to encrypt and decrypt payloads end-to-end.
+pub type Key = [u8; AeadCipher::KEY_SIZE];
+
+/// A trait implemented by the providers of the keys used to encrypt payloads end-to-end.
+pub trait KeyProvider: Send + Sync {
+    /// Returns the key of the payloads published on the given key expression,
+    /// or `None` if these payloads are not encrypted.
+    fn key(&self, key_expr: &keyexpr) -> Option<Key>;
+}
+
+/// A [`KeyProvider`] associating keys with key expressions.
+///
+/// The key of a key expression is the key of the first inserted key expression including it.
+#[derive(Default)]
+pub struct KeyExprKeys {
+    keys: RwLock<Vec<(OwnedKeyExpr, Key)>>,
+}
+
+impl KeyExprKeys {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the key of the given key expression, replacing its previous key if any.
+    pub fn insert(&self, key_expr: OwnedKeyExpr, key: Key) {
+        let mut keys = zwrite!(self.keys);
+        match keys.iter_mut().find(|(k, _)| *k == key_expr) {
+            Some((_, k)) => *k = key,
+            None => keys.push((key_expr, key)),
+        }
+    }
+
+    /// Removes the key of the given key expression, returning it if any.
+    pub fn remove(&self, key_expr: &keyexpr) -> Option<Key> {
+        let mut keys = zwrite!(self.keys);
+        let index = keys.iter().position(|(k, _)| k.as_ref() == key_expr)?;
+        Some(keys.remove(index).1)
+    }
+}
+
+impl KeyProvider for KeyExprKeys {
+    fn key(&self, key_expr: &keyexpr) -> Option<Key> {
+        zread!(self.keys)
+            .iter()
+            .find(|(k, _)| k.includes(key_expr))
+            .map(|(_, key)| *key)
+    }
+}
+
+// The encrypted plaintext is the prefix of the original encoding (1 byte),
+// the length of its suffix (2 bytes, big endian), its suffix and the payload.
+const HEADER_SIZE: usize = 3;
+// The maximum number of ciphers kept for the keys recently used
+const CIPHERS_CAPACITY: usize = 64;
+
+pub(crate) struct E2e {
+    provider: Arc<dyn KeyProvider>,
+    prng: Mutex<PseudoRng>,
+    ciphers: RwLock<HashMap<Key, Arc<AeadCipher>>>,
+}
+
+impl E2e {
+    pub(crate) fn new(provider: Arc<dyn KeyProvider>) -> Self {
+        E2e {
+            provider,
+            prng: Mutex::new(PseudoRng::from_entropy()),
+            ciphers: RwLock::new(HashMap::new()),
+        }
+    }
+
+    // The cipher of the given key, derived once as long as the key is in use
+    fn cipher(&self, key: Key) -> ZResult<Arc<AeadCipher>> {
+        if let Some(cipher) = zread!(self.ciphers).get(&key) {
+            return Ok(cipher.clone());
+        }
+        let cipher = Arc::new(AeadCipher::new(key)?);
+        let mut ciphers = zwrite!(self.ciphers);
+        // The keys are expected to be few, the ones no longer used being forgotten at once
+        if ciphers.len() >= CIPHERS_CAPACITY {
+            ciphers.clear();
+        }
+        ciphers.insert(key, cipher.clone());
+        Ok(cipher)
+    }
+
+    /// Encrypts the given value in place if a key is provided for the given key expression.
+    pub(crate) fn encrypt(&self, key_expr: &keyexpr, value: &mut Value) -> ZResult<()> {
+        let key = match self.provider.key(key_expr) {
+            Some(key) => key,
+            None => return Ok(()),
+        };
+        let cipher = self.cipher(key)?;
+
+        let suffix = value.encoding.suffix();
+        let suffix_len = u16::try_from(suffix.len())
+            .map_err(|_| zerror!("Encoding suffix too long to encrypt: {}", suffix.len()))?;
+        let payload = value.payload.contiguous();
+        let mut plaintext = Vec::with_capacity(HEADER_SIZE + suffix.len() + payload.len());
+        plaintext.push(*value.encoding.prefix() as u8);
+        plaintext.extend_from_slice(&suffix_len.to_be_bytes());
+        plaintext.extend_from_slice(suffix.as_bytes());
+        plaintext.extend_from_slice(&payload);
+
+        let bytes = cipher.encrypt(&plaintext, key_expr.as_bytes(), &mut zlock!(self.prng))?;
+        *value = Value::new(bytes.into()).encoding(Encoding::encrypted());
+        Ok(())
+    }
+
+    /// Decrypts the given value in place if a key is provided for the given key expression.
+    /// Fails if the value is not encrypted or cannot be authenticated with this key.
+    pub(crate) fn decrypt(&self, key_expr: &keyexpr, value: &mut Value) -> ZResult<()> {
+        let key = match self.provider.key(key_expr) {
+            Some(key) => key,
+            None => return Ok(()),
+        };
+        if !value.encoding.is_encrypted() {
+            bail!("Unencrypted value on a key expression with a key");
+        }
+        let cipher = self.cipher(key)?;
+
+        let mut plaintext = cipher.decrypt(&value.payload.contiguous(), key_expr.as_bytes())?;
+        if plaintext.len() < HEADER_SIZE {
+            bail!("Invalid decrypted payload length: {}", plaintext.len());
+        }
+        let prefix = plaintext[0] as ZInt;
+        let suffix_len = u16::from_be_bytes([plaintext[1], plaintext[2]]) as usize;
+        if plaintext.len() < HEADER_SIZE + suffix_len {
+            bail!("Invalid decrypted encoding suffix length: {}", suffix_len);
+        }
+        let payload = plaintext.split_off(HEADER_SIZE + suffix_len);
+        let suffix = String::from_utf8(plaintext.split_off(HEADER_SIZE))?;
+        let encoding = Encoding::new(prefix, suffix)
+            .ok_or_else(|| zerror!("Invalid decrypted encoding prefix: {}", prefix))?;
+
+        *value = Value::new(payload.into()).encoding(encoding);
+        Ok(())
+    }
+}
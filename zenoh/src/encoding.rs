@@ -0,0 +1,234 @@
+//
+// Copyright (c) 2022 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+
+//! Custom encodings registry.
+//!
+//! A custom encoding is identified on the wire by a stable numeric id (see [`Encoding::custom`]),
+//! and described locally by a [`CustomEncoding`] registered in the process-wide [`registry`]:
+//! its MIME type, an optional key expression where its schema can be retrieved, and an optional
+//! validator for the payloads.
+//!
+//! # Examples
+//! ```
+//! use zenoh::encoding::{self, CustomEncoding};
+//! use zenoh::prelude::*;
+//!
+//! let encoding = encoding::registry()
+//!     .register(
+//!         CustomEncoding::new(1001, "application/vnd.acme.telemetry+json")
+//!             .schema(OwnedKeyExpr::new("acme/schemas/telemetry").unwrap()),
+//!     )
+//!     .unwrap();
+//! let value = Value::from(r#"{"temperature": 21.5}"#).encoding(encoding.clone());
+//! assert_eq!(
+//!     encoding::registry().content_type(&value.encoding),
+//!     "application/vnd.acme.telemetry+json"
+//! );
+//! ```
+
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::{Arc, RwLock};
+use zenoh_core::{zread, zwrite};
+use zenoh_protocol::core::key_expr::OwnedKeyExpr;
+use zenoh_result::{bail, ZResult};
+
+use crate::prelude::{Encoding, ZInt};
+use crate::value::Value;
+
+type Validator = Arc<dyn Fn(&Value) -> ZResult<()> + Send + Sync>;
+
+/// The description of a custom encoding.
+#[derive(Clone)]
+pub struct CustomEncoding {
+    id: ZInt,
+    mime: String,
+    schema: Option<OwnedKeyExpr>,
+    validator: Option<Validator>,
+}
+
+impl CustomEncoding {
+    /// Creates a custom encoding with the given stable numeric id and MIME type.
+    pub fn new<IntoString>(id: ZInt, mime: IntoString) -> Self
+    where
+        IntoString: Into<String>,
+    {
+        CustomEncoding {
+            id,
+            mime: mime.into(),
+            schema: None,
+            validator: None,
+        }
+    }
+
+    /// Sets the key expression where the schema of this encoding can be retrieved (e.g. from a storage).
+    pub fn schema(mut self, schema: OwnedKeyExpr) -> Self {
+        self.schema = Some(schema);
+        self
+    }
+
+    /// Sets the function used to validate the payloads of this encoding.
+    pub fn validator<F>(mut self, validator: F) -> Self
+    where
+        F: Fn(&Value) -> ZResult<()> + Send + Sync + 'static,
+    {
+        self.validator = Some(Arc::new(validator));
+        self
+    }
+
+    /// The numeric id of this encoding.
+    pub fn id(&self) -> ZInt {
+        self.id
+    }
+
+    /// The MIME type of this encoding.
+    pub fn mime(&self) -> &str {
+        &self.mime
+    }
+
+    /// The key expression where the schema of this encoding can be retrieved, if any.
+    pub fn schema_key_expr(&self) -> Option<&OwnedKeyExpr> {
+        self.schema.as_ref()
+    }
+
+    /// The [`Encoding`] identifying this custom encoding on the wire.
+    pub fn encoding(&self) -> Encoding {
+        Encoding::custom(self.id)
+    }
+
+    /// Validates the given value against this encoding.
+    pub fn validate(&self, value: &Value) -> ZResult<()> {
+        match &self.validator {
+            Some(validator) => validator(value),
+            None => Ok(()),
+        }
+    }
+}
+
+impl fmt::Debug for CustomEncoding {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("CustomEncoding")
+            .field("id", &self.id)
+            .field("mime", &self.mime)
+            .field("schema", &self.schema)
+            .field("validator", &self.validator.is_some())
+            .finish()
+    }
+}
+
+/// A registry of [`CustomEncoding`]s.
+#[derive(Default)]
+pub struct EncodingRegistry {
+    encodings: RwLock<HashMap<ZInt, Arc<CustomEncoding>>>,
+}
+
+impl EncodingRegistry {
+    /// Creates an empty registry.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Registers a custom encoding, returning the [`Encoding`] identifying it on the wire.
+    ///
+    /// Registering again the same id with the same MIME type replaces its schema and validator.
+    /// It's an error to register an id or a MIME type already registered for another encoding,
+    /// or a MIME type of a [`KnownEncoding`](crate::prelude::KnownEncoding).
+    pub fn register(&self, encoding: CustomEncoding) -> ZResult<Encoding> {
+        if Encoding::from(encoding.mime.clone()).suffix().is_empty() {
+            bail!(
+                "Custom encoding {} can't use the MIME type of a known encoding: {}",
+                encoding.id,
+                encoding.mime
+            );
+        }
+        let mut encodings = zwrite!(self.encodings);
+        if let Some(e) = encodings.get(&encoding.id) {
+            if e.mime != encoding.mime {
+                bail!(
+                    "Custom encoding {} already registered with MIME type {}",
+                    e.id,
+                    e.mime
+                );
+            }
+        }
+        if let Some(e) = encodings
+            .values()
+            .find(|e| e.id != encoding.id && e.mime == encoding.mime)
+        {
+            bail!(
+                "MIME type {} already registered for custom encoding {}",
+                e.mime,
+                e.id
+            );
+        }
+        let result = encoding.encoding();
+        encodings.insert(encoding.id, Arc::new(encoding));
+        Ok(result)
+    }
+
+    /// Unregisters the custom encoding with the given id, returning it if it was registered.
+    pub fn unregister(&self, id: ZInt) -> Option<Arc<CustomEncoding>> {
+        zwrite!(self.encodings).remove(&id)
+    }
+
+    /// Returns the custom encoding with the given id, if registered.
+    pub fn get(&self, id: ZInt) -> Option<Arc<CustomEncoding>> {
+        zread!(self.encodings).get(&id).cloned()
+    }
+
+    /// Returns the custom encoding identified by the given [`Encoding`], if registered.
+    pub fn resolve(&self, encoding: &Encoding) -> Option<Arc<CustomEncoding>> {
+        self.get(encoding.custom_id()?)
+    }
+
+    /// Returns the [`Encoding`] corresponding to the given MIME type:
+    /// the one of the registered custom encoding if any, or the one parsed from the MIME type.
+    pub fn from_mime(&self, mime: &str) -> Encoding {
+        match zread!(self.encodings).values().find(|e| e.mime == mime) {
+            Some(e) => e.encoding(),
+            None => Encoding::from(mime.to_string()),
+        }
+    }
+
+    /// Returns the MIME type of the given [`Encoding`]:
+    /// the one of the registered custom encoding if any, or its string representation.
+    pub fn content_type(&self, encoding: &Encoding) -> String {
+        match self.resolve(encoding) {
+            Some(e) => e.mime.clone(),
+            None => encoding.to_string(),
+        }
+    }
+
+    /// Validates the given value against the custom encoding it's encoded with, if any.
+    ///
+    /// It's an error for the value to be encoded with an unregistered custom encoding.
+    pub fn validate(&self, value: &Value) -> ZResult<()> {
+        match value.encoding.custom_id() {
+            Some(id) => match self.get(id) {
+                Some(e) => e.validate(value),
+                None => bail!("Unknown custom encoding: {}", id),
+            },
+            None => Ok(()),
+        }
+    }
+}
+
+lazy_static::lazy_static! {
+    static ref REGISTRY: EncodingRegistry = EncodingRegistry::new();
+}
+
+/// The process-wide registry of custom encodings.
+pub fn registry() -> &'static EncodingRegistry {
+    &REGISTRY
+}
@@ -17,6 +17,7 @@ use crate::SessionRef;
 use std::future::Ready;
 use zenoh_config::{WhatAmI, ZenohId};
 use zenoh_core::{AsyncResolve, Resolvable, SyncResolve};
+pub use zenoh_transport::{LinkRtt, RttStats};
 
 /// A builder retuned by [`SessionInfo::zid()`](SessionInfo::zid) that allows
 /// to access the [`ZenohId`] of the current zenoh [`Session`](crate::Session).
@@ -148,6 +149,49 @@ impl<'a> AsyncResolve for PeersZidBuilder<'a> {
     }
 }
 
+/// A builder returned by [`SessionInfo::links_rtt()`](SessionInfo::links_rtt) that allows
+/// to access the round-trip time of the links to the zenoh nodes this process is currently connected to.
+///
+/// # Examples
+/// ```
+/// # async_std::task::block_on(async {
+/// use zenoh::prelude::r#async::*;
+///
+/// let session = zenoh::open(config::peer()).res().await.unwrap();
+/// let mut links_rtt = session.info().links_rtt().res().await;
+/// while let Some((zid, rtt)) = links_rtt.next() {}
+/// # })
+/// ```
+pub struct LinksRttBuilder<'a> {
+    pub(crate) session: SessionRef<'a>,
+}
+
+impl<'a> Resolvable for LinksRttBuilder<'a> {
+    type To = Box<dyn Iterator<Item = (ZenohId, LinkRtt)> + Send + Sync>;
+}
+
+impl<'a> SyncResolve for LinksRttBuilder<'a> {
+    fn res_sync(self) -> <Self as Resolvable>::To {
+        Box::new(
+            self.session
+                .runtime
+                .manager()
+                .get_transports()
+                .into_iter()
+                .filter_map(|s| Some((s.get_zid().ok()?, s.get_link_rtts().ok()?)))
+                .flat_map(|(zid, rtts)| rtts.into_iter().map(move |rtt| (zid, rtt))),
+        )
+    }
+}
+
+impl<'a> AsyncResolve for LinksRttBuilder<'a> {
+    type Future = Ready<Self::To>;
+
+    fn res_async(self) -> Self::Future {
+        std::future::ready(self.res_sync())
+    }
+}
+
 /// Struct returned by [`Session::info()`](crate::Session::info) which allows
 /// to access informations about the current zenoh [`Session`](crate::Session).
 ///
@@ -219,4 +263,24 @@ impl SessionInfo<'_> {
             session: self.session.clone(),
         }
     }
+
+    /// Return the round-trip time of the links to the zenoh nodes this process is currently connected to,
+    /// along with their [`ZenohId`].
+    /// The round-trip time is only measured when enabled in the `transport/unicast/rtt` configuration.
+    ///
+    /// # Examples
+    /// ```
+    /// # async_std::task::block_on(async {
+    /// use zenoh::prelude::r#async::*;
+    ///
+    /// let session = zenoh::open(config::peer()).res().await.unwrap();
+    /// let mut links_rtt = session.info().links_rtt().res().await;
+    /// while let Some((zid, rtt)) = links_rtt.next() {}
+    /// # })
+    /// ```
+    pub fn links_rtt(&self) -> LinksRttBuilder<'_> {
+        LinksRttBuilder {
+            session: self.session.clone(),
+        }
+    }
 }
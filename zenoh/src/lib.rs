@@ -108,6 +108,8 @@ pub use net::runtime;
 pub mod selector;
 #[deprecated = "This module is now a separate crate. Use the crate directly for shorter compile-times"]
 pub use zenoh_config as config;
+pub mod e2e;
+pub mod encoding;
 pub mod handlers;
 pub mod info;
 pub mod plugins;
@@ -16,6 +16,7 @@ use std::collections::{HashMap, HashSet};
 use std::fmt;
 use std::sync::Arc;
 use std::sync::RwLock;
+use std::time::Duration;
 use zenoh_buffers::ZBuf;
 use zenoh_protocol::{
     core::{
@@ -165,6 +166,13 @@ pub struct Face {
     pub(crate) state: Arc<FaceState>,
 }
 
+impl Face {
+    /// The nodes the data published by this face on the given key expression is routed to.
+    pub(crate) fn get_data_route_zids(&self, key_expr: &WireExpr) -> Vec<ZenohId> {
+        get_data_route_zids(&self.tables, &self.state, key_expr)
+    }
+}
+
 impl Primitives for Face {
     fn decl_resource(&self, expr_id: ZInt, key_expr: &WireExpr) {
         let mut tables = zwrite!(self.tables);
@@ -339,6 +347,8 @@ impl Primitives for Face {
         payload: ZBuf,
         channel: Channel,
         congestion_control: CongestionControl,
+        ttl: Option<Duration>,
+        express: bool,
         data_info: Option<DataInfo>,
         routing_context: Option<RoutingContext>,
     ) {
@@ -348,6 +358,8 @@ impl Primitives for Face {
             key_expr,
             channel,
             congestion_control,
+            ttl,
+            express,
             data_info,
             payload,
             routing_context,
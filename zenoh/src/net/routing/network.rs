@@ -14,6 +14,7 @@
 use super::runtime::Runtime;
 use petgraph::graph::NodeIndex;
 use petgraph::visit::{IntoNodeReferences, VisitMap, Visitable};
+use std::collections::HashMap;
 use std::convert::TryInto;
 use vec_map::VecMap;
 use zenoh_config::whatami::WhatAmIMatcher;
@@ -38,6 +39,8 @@ pub(crate) struct Node {
     pub(crate) locators: Option<Vec<Locator>>,
     pub(crate) sn: ZInt,
     pub(crate) links: Vec<ZenohId>,
+    // The advertised weights of the links, i.e. their RTT in microseconds
+    pub(crate) weights: HashMap<ZenohId, ZInt>,
 }
 
 impl std::fmt::Debug for Node {
@@ -105,6 +108,7 @@ pub(crate) struct Network {
     pub(crate) gossip: bool,
     pub(crate) gossip_multihop: bool,
     pub(crate) autoconnect: WhatAmIMatcher,
+    pub(crate) link_weights: bool,
     pub(crate) idx: NodeIndex,
     pub(crate) links: VecMap<Link>,
     pub(crate) trees: Vec<Tree>,
@@ -124,6 +128,7 @@ impl Network {
         gossip: bool,
         gossip_multihop: bool,
         autoconnect: WhatAmIMatcher,
+        link_weights: bool,
     ) -> Self {
         let mut graph = petgraph::stable_graph::StableGraph::default();
         log::debug!("{} Add node (self) {}", name, zid);
@@ -133,6 +138,7 @@ impl Network {
             locators: None,
             sn: 1,
             links: vec![],
+            weights: HashMap::new(),
         });
         Network {
             name,
@@ -141,6 +147,7 @@ impl Network {
             gossip,
             gossip_multihop,
             autoconnect,
+            link_weights,
             idx,
             links: VecMap::new(),
             trees: vec![Tree {
@@ -218,13 +225,16 @@ impl Network {
     }
 
     fn make_link_state(&self, idx: NodeIndex, details: Details) -> LinkState {
-        let links = if details.links {
+        let links: Vec<(ZInt, ZInt)> = if details.links {
             self.graph[idx]
                 .links
                 .iter()
                 .filter_map(|zid| {
                     if let Some(idx2) = self.get_idx(zid) {
-                        Some(idx2.index().try_into().unwrap())
+                        Some((
+                            idx2.index().try_into().unwrap(),
+                            self.graph[idx].weights.get(zid).copied().unwrap_or(0),
+                        ))
                     } else {
                         log::error!(
                             "{} Internal error building link state: cannot get index of {}",
@@ -238,6 +248,8 @@ impl Network {
         } else {
             vec![]
         };
+        let weights = (self.link_weights && links.iter().any(|(_, w)| *w != 0))
+            .then(|| links.iter().map(|(_, w)| *w).collect());
         LinkState {
             psid: idx.index().try_into().unwrap(),
             sn: self.graph[idx].sn,
@@ -256,7 +268,8 @@ impl Network {
             } else {
                 None
             },
-            links,
+            links: links.into_iter().map(|(l, _)| l).collect(),
+            weights,
         }
     }
 
@@ -316,7 +329,20 @@ impl Network {
             hasher.write(self.graph[idx1].zid.as_slice());
             hasher.write(self.graph[idx2].zid.as_slice());
         }
-        let weight = 100.0 + ((hasher.finish() as u32) as f64) / u32::MAX as f64;
+        let mut weight = 100.0 + ((hasher.finish() as u32) as f64) / u32::MAX as f64;
+        // Add the RTT in milliseconds advertised by both ends of the link, if any
+        let weights = [
+            self.graph[idx1].weights.get(&self.graph[idx2].zid),
+            self.graph[idx2].weights.get(&self.graph[idx1].zid),
+        ];
+        let weights: Vec<ZInt> = weights
+            .iter()
+            .filter_map(|w| w.copied())
+            .filter(|w| *w != 0)
+            .collect();
+        if !weights.is_empty() {
+            weight += weights.iter().sum::<ZInt>() as f64 / weights.len() as f64 / 1000.0;
+        }
         self.graph.update_edge(idx1, idx2, weight);
     }
 
@@ -357,6 +383,7 @@ impl Network {
                         link_state.locators,
                         link_state.sn,
                         link_state.links,
+                        link_state.weights,
                     ))
                 } else {
                     match src_link.get_zid(&link_state.psid) {
@@ -366,6 +393,7 @@ impl Network {
                             link_state.locators,
                             link_state.sn,
                             link_state.links,
+                            link_state.weights,
                         )),
                         None => {
                             log::error!(
@@ -384,12 +412,14 @@ impl Network {
         let src_link = self.get_link_from_zid(&src).unwrap();
         let link_states = link_states
             .into_iter()
-            .map(|(zid, wai, locs, sn, links)| {
-                let links: Vec<ZenohId> = links
+            .map(|(zid, wai, locs, sn, links, weights)| {
+                let weights = weights.unwrap_or_default();
+                let links: Vec<(ZenohId, ZInt)> = links
                     .iter()
-                    .filter_map(|l| {
+                    .enumerate()
+                    .filter_map(|(i, l)| {
                         if let Some(zid) = src_link.get_zid(l) {
-                            Some(*zid)
+                            Some((*zid, weights.get(i).copied().unwrap_or(0)))
                         } else {
                             log::error!(
                                 "{} Received LinkState from {} with unknown link mapping {}",
@@ -401,7 +431,10 @@ impl Network {
                         }
                     })
                     .collect();
-                (zid, wai, locs, sn, links)
+                let weights: HashMap<ZenohId, ZInt> =
+                    links.iter().filter(|(_, w)| *w != 0).copied().collect();
+                let links: Vec<ZenohId> = links.into_iter().map(|(zid, _)| zid).collect();
+                (zid, wai, locs, sn, links, weights)
             })
             .collect::<Vec<_>>();
 
@@ -425,7 +458,7 @@ impl Network {
                 updated_nodes: vec![],
                 removed_nodes: vec![],
             };
-            for (zid, whatami, locators, sn, links) in link_states.into_iter() {
+            for (zid, whatami, locators, sn, links, weights) in link_states.into_iter() {
                 let idx = match self.get_idx(&zid) {
                     None => {
                         let idx = self.add_node(Node {
@@ -434,6 +467,7 @@ impl Network {
                             locators: locators.clone(),
                             sn,
                             links,
+                            weights,
                         });
                         changes.updated_nodes.push((idx, self.graph[idx].clone()));
                         locators.is_some().then_some(idx)
@@ -445,6 +479,7 @@ impl Network {
                             .then(|| {
                                 node.sn = sn;
                                 node.links = links.clone();
+                                node.weights = weights;
                                 changes.updated_nodes.push((idx, node.clone()));
                                 (node.locators != locators && locators.is_some()).then(|| {
                                     node.locators = locators.clone();
@@ -499,13 +534,14 @@ impl Network {
         let mut link_states = link_states
             .into_iter()
             .filter_map(
-                |(zid, whatami, locators, sn, links)| match self.get_idx(&zid) {
+                |(zid, whatami, locators, sn, links, weights)| match self.get_idx(&zid) {
                     Some(idx) => {
                         let node = &mut self.graph[idx];
                         let oldsn = node.sn;
                         if oldsn < sn {
                             node.sn = sn;
                             node.links = links.clone();
+                            node.weights = weights;
                             if locators.is_some() {
                                 node.locators = locators;
                             }
@@ -525,6 +561,7 @@ impl Network {
                             locators,
                             sn,
                             links: links.clone(),
+                            weights,
                         };
                         log::debug!("{} Add node (state) {}", self.name, zid);
                         let idx = self.add_node(node);
@@ -555,6 +592,7 @@ impl Network {
                         locators: None,
                         sn: 0,
                         links: vec![],
+                        weights: HashMap::new(),
                     };
                     log::debug!("{} Add node (reintroduced) {}", self.name, link.clone());
                     let idx = self.add_node(node);
@@ -696,6 +734,7 @@ impl Network {
                             locators: None,
                             sn: 0,
                             links: vec![],
+                            weights: HashMap::new(),
                         }),
                         true,
                     )
@@ -837,6 +876,59 @@ impl Network {
         }
     }
 
+    // Refresh the weights of the links of this node from their measured RTT.
+    // Returns true if they significantly changed, in which case the trees need to be recomputed.
+    pub(crate) fn update_link_weights(&mut self) -> bool {
+        if !self.full_linkstate || !self.link_weights {
+            return false;
+        }
+
+        let weights: HashMap<ZenohId, ZInt> = self
+            .links
+            .values()
+            .filter_map(|link| {
+                link.transport
+                    .get_link_rtts()
+                    .ok()?
+                    .iter()
+                    .map(|rtt| rtt.rtt.smoothed_us.max(1))
+                    .min()
+                    .map(|w| (link.zid, w))
+            })
+            .collect();
+
+        let node = &self.graph[self.idx];
+        let changed = weights.len() != node.weights.len()
+            || weights.iter().any(|(zid, w)| match node.weights.get(zid) {
+                Some(old) => w.abs_diff(*old) > old / 4,
+                None => true,
+            });
+        if !changed {
+            return false;
+        }
+
+        log::trace!("{} Update link weights {:?}", self.name, weights);
+        self.graph[self.idx].weights = weights;
+        self.graph[self.idx].sn += 1;
+        let neighbours: Vec<NodeIndex> = self.graph.neighbors_undirected(self.idx).collect();
+        for idx in neighbours {
+            self.update_edge(self.idx, idx);
+        }
+
+        self.send_on_links(
+            vec![(
+                self.idx,
+                Details {
+                    zid: false,
+                    locators: self.gossip,
+                    links: true,
+                },
+            )],
+            |_| true,
+        );
+        true
+    }
+
     fn remove_detached_nodes(&mut self) -> Vec<(NodeIndex, Node)> {
         let mut dfs_stack = vec![self.idx];
         let mut visit_map = self.graph.visit_map();
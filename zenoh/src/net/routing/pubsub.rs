@@ -21,6 +21,7 @@ use std::collections::{HashMap, HashSet};
 use std::convert::TryFrom;
 use std::sync::Arc;
 use std::sync::RwLock;
+use std::time::Duration;
 use zenoh_buffers::ZBuf;
 use zenoh_core::zread;
 use zenoh_protocol::{
@@ -396,6 +397,24 @@ fn client_subs(res: &Arc<Resource>) -> Vec<Arc<FaceState>> {
         .collect()
 }
 
+/// The key expressions of the subscriptions known to this node, with the ids of the nodes that declared them.
+pub(crate) fn declared_subscriptions(tables: &Tables) -> Vec<(String, Vec<ZenohId>)> {
+    let mut subs = vec![];
+    Resource::for_each(&tables.root_res, &mut |res| {
+        let mut zids = client_subs(res)
+            .iter()
+            .map(|face| face.zid)
+            .collect::<HashSet<_>>();
+        if let Some(ctx) = &res.context {
+            zids.extend(ctx.router_subs.iter().chain(ctx.peer_subs.iter()));
+        }
+        if !zids.is_empty() {
+            subs.push((res.expr(), zids.into_iter().collect()));
+        }
+    });
+    subs
+}
+
 #[inline]
 fn send_forget_sourced_subscription_to_net_childs(
     tables: &Tables,
@@ -1352,6 +1371,8 @@ pub fn full_reentrant_route_data(
     expr: &WireExpr,
     channel: Channel,
     congestion_control: CongestionControl,
+    ttl: Option<Duration>,
+    express: bool,
     info: Option<DataInfo>,
     payload: ZBuf,
     routing_context: Option<RoutingContext>,
@@ -1389,6 +1410,8 @@ pub fn full_reentrant_route_data(
                                 payload,
                                 channel, // @TODO: Need to check the active subscriptions to determine the right reliability value
                                 congestion_control,
+                                ttl,
+                                express,
                                 data_info,
                                 *context,
                             )
@@ -1416,6 +1439,8 @@ pub fn full_reentrant_route_data(
                                     payload.clone(),
                                     channel, // @TODO: Need to check the active subscriptions to determine the right reliability value
                                     congestion_control,
+                                    ttl,
+                                    express,
                                     data_info.clone(),
                                     context,
                                 )
@@ -1429,6 +1454,8 @@ pub fn full_reentrant_route_data(
                                         payload.clone(),
                                         channel, // @TODO: Need to check the active subscriptions to determine the right reliability value
                                         congestion_control,
+                                        ttl,
+                                        express,
                                         data_info.clone(),
                                         *context,
                                     )
@@ -1445,6 +1472,32 @@ pub fn full_reentrant_route_data(
     }
 }
 
+// The nodes the data published by the given face on the given key expression is routed to
+pub(crate) fn get_data_route_zids(
+    tables_ref: &RwLock<Tables>,
+    face: &FaceState,
+    expr: &WireExpr,
+) -> Vec<ZenohId> {
+    let tables = zread!(tables_ref);
+    match tables.get_mapping(face, &expr.scope).cloned() {
+        Some(prefix) => {
+            let mut expr = RoutingExpr::new(&prefix, expr.suffix.as_ref());
+            let res = Resource::get_resource(&prefix, expr.suffix);
+            get_data_route(&tables, face, &res, &mut expr, None)
+                .values()
+                .filter(|(outface, _key_expr, _context)| {
+                    should_route(&tables, face, outface, &mut expr)
+                })
+                .map(|(outface, _key_expr, _context)| outface.zid)
+                .collect()
+        }
+        None => {
+            log::error!("Get data route with unknown scope {}!", expr.scope);
+            vec![]
+        }
+    }
+}
+
 pub fn pull_data(
     tables_ref: &RwLock<Tables>,
     face: &Arc<FaceState>,
@@ -1485,6 +1538,8 @@ pub fn pull_data(
                                         reliability,
                                     },
                                     CongestionControl::default(), // @TODO: Default value for the time being
+                                    None,
+                                    false,
                                     info,
                                     None,
                                 );
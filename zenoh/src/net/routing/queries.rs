@@ -19,7 +19,7 @@ use async_trait::async_trait;
 use ordered_float::OrderedFloat;
 use petgraph::graph::NodeIndex;
 use std::borrow::Cow;
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::convert::TryFrom;
 use std::sync::Arc;
 use std::sync::{RwLock, Weak};
@@ -543,6 +543,24 @@ fn client_qabls(res: &Arc<Resource>) -> Vec<Arc<FaceState>> {
         .collect()
 }
 
+/// The key expressions of the queryables known to this node, with the ids of the nodes that declared them.
+pub(crate) fn declared_queryables(tables: &Tables) -> Vec<(String, Vec<ZenohId>)> {
+    let mut qabls = vec![];
+    Resource::for_each(&tables.root_res, &mut |res| {
+        let mut zids = client_qabls(res)
+            .iter()
+            .map(|face| face.zid)
+            .collect::<HashSet<_>>();
+        if let Some(ctx) = &res.context {
+            zids.extend(ctx.router_qabls.keys().chain(ctx.peer_qabls.keys()));
+        }
+        if !zids.is_empty() {
+            qabls.push((res.expr(), zids.into_iter().collect()));
+        }
+    });
+    qabls
+}
+
 #[inline]
 fn send_forget_sourced_queryable_to_net_childs(
     tables: &Tables,
@@ -147,6 +147,14 @@ impl Resource {
         self.context.as_mut().unwrap()
     }
 
+    /// Calls `f` on the given resource and all its descendants.
+    pub(super) fn for_each(res: &Arc<Resource>, f: &mut dyn FnMut(&Arc<Resource>)) {
+        f(res);
+        for child in res.childs.values() {
+            Resource::for_each(child, f);
+        }
+    }
+
     pub fn nonwild_prefix(res: &Arc<Resource>) -> (Option<Arc<Resource>>, String) {
         match &res.nonwild_prefix {
             None => (Some(res.clone()), "".to_string()),
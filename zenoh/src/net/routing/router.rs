@@ -401,6 +401,7 @@ impl Router {
         gossip: bool,
         gossip_multihop: bool,
         autoconnect: WhatAmIMatcher,
+        rtt_interval: Option<Duration>,
     ) {
         let mut tables = zwrite!(self.tables);
         if router_full_linkstate | gossip {
@@ -413,18 +414,20 @@ impl Router {
                 gossip,
                 gossip_multihop,
                 autoconnect,
+                rtt_interval.is_some(),
             ));
         }
         if peer_full_linkstate | gossip {
             tables.peers_net = Some(Network::new(
                 "[Peers network]".to_string(),
                 tables.zid,
-                runtime,
+                runtime.clone(),
                 peer_full_linkstate,
                 router_peers_failover_brokering,
                 gossip,
                 gossip_multihop,
                 autoconnect,
+                rtt_interval.is_some(),
             ));
         }
         if router_full_linkstate && peer_full_linkstate {
@@ -433,6 +436,32 @@ impl Router {
                 tables.peers_net.as_ref().unwrap(),
             );
         }
+        if let Some(interval) = rtt_interval {
+            if router_full_linkstate || peer_full_linkstate {
+                // Periodically advertise the measured RTT of the links as their weights
+                let tables_ref = self.tables.clone();
+                runtime.spawn(async move {
+                    loop {
+                        async_std::task::sleep(interval).await;
+                        let mut tables = zwrite!(tables_ref);
+                        if let Some(true) = tables
+                            .routers_net
+                            .as_mut()
+                            .map(|net| net.update_link_weights())
+                        {
+                            tables.schedule_compute_trees(tables_ref.clone(), WhatAmI::Router);
+                        }
+                        if let Some(true) = tables
+                            .peers_net
+                            .as_mut()
+                            .map(|net| net.update_link_weights())
+                        {
+                            tables.schedule_compute_trees(tables_ref.clone(), WhatAmI::Peer);
+                        }
+                    }
+                });
+            }
+        }
     }
 
     pub fn new_primitives(&self, primitives: Arc<dyn Primitives + Send + Sync>) -> Arc<Face> {
@@ -11,6 +11,7 @@
 // Contributors:
 //   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
 use super::routing::face::Face;
+use super::routing::{pubsub, queries};
 use super::Runtime;
 use crate::key_expr::KeyExpr;
 use crate::plugins::sealed as plugins;
@@ -22,6 +23,7 @@ use std::collections::HashMap;
 use std::convert::TryInto;
 use std::sync::Arc;
 use std::sync::Mutex;
+use std::time::Duration;
 use zenoh_buffers::{SplitBuffer, ZBuf};
 use zenoh_config::ValidatedMap;
 use zenoh_config::WhatAmI;
@@ -295,6 +297,8 @@ impl Primitives for AdminSpace {
         payload: ZBuf,
         channel: Channel,
         congestion_control: CongestionControl,
+        _ttl: Option<Duration>,
+        _express: bool,
         data_info: Option<DataInfo>,
         _routing_context: Option<RoutingContext>,
     ) {
@@ -410,9 +414,18 @@ impl Primitives for AdminSpace {
         let plugin_key: OwnedKeyExpr = format!("@/router/{}/status/plugins/**", &zid)
             .try_into()
             .unwrap();
+        let declarations_keys: [OwnedKeyExpr; 2] = [
+            format!("@/router/{}/subscriber/**", &zid)
+                .try_into()
+                .unwrap(),
+            format!("@/router/{}/queryable/**", &zid)
+                .try_into()
+                .unwrap(),
+        ];
         let context = self.context.clone();
         let mut matching_handlers = vec![];
         let ask_plugins = plugin_key.intersects(&key_expr);
+        let ask_declarations = declarations_keys.iter().any(|k| k.intersects(&key_expr));
         for (key, handler) in &self.handlers {
             if key_expr.intersects(key) {
                 matching_handlers.push((key.clone(), handler.clone()));
@@ -464,6 +477,22 @@ impl Primitives for AdminSpace {
             } else {
                 handler_tasks.await;
             }
+            if ask_declarations {
+                for (key, value) in declarations_data(&context, &key_expr) {
+                    let data_info = DataInfo {
+                        encoding: Some(KnownEncoding::AppJson.into()),
+                        ..Default::default()
+                    };
+
+                    primitives.send_reply_data(
+                        qid,
+                        zid,
+                        String::from(key).into(),
+                        Some(data_info),
+                        value.to_string().into_bytes().into(),
+                    );
+                }
+            }
 
             primitives.send_reply_final(qid);
         });
@@ -537,7 +566,6 @@ pub async fn router_data(
 
     // transports info
     let transport_to_json = |transport: &TransportUnicast| {
-        #[allow(unused_mut)]
         let mut json = json!({
             "peer": transport.get_zid().map_or_else(|_| "unknown".to_string(), |p| p.to_string()),
             "whatami": transport.get_whatami().map_or_else(|_| "unknown".to_string(), |p| p.to_string()),
@@ -546,6 +574,13 @@ pub async fn router_data(
                 |links| links.iter().map(|link| link.dst.to_string()).collect()
             ),
         });
+        if let Ok(rtts) = transport.get_link_rtts() {
+            if !rtts.is_empty() {
+                json.as_object_mut()
+                    .unwrap()
+                    .insert("rtt".to_string(), json!(rtts));
+            }
+        }
         #[cfg(feature = "stats")]
         {
             let stats = crate::prelude::Parameters::decode(selector)
@@ -604,6 +639,37 @@ pub async fn linkstate_data(
     )
 }
 
+/// The subscribers and queryables known to this node matching the given key expression, as
+/// `@/router/<zid>/subscriber/<key_expr>` and `@/router/<zid>/queryable/<key_expr>` entries
+/// listing the nodes that declared them.
+fn declarations_data(
+    context: &AdminContext,
+    key: &KeyExpr<'_>,
+) -> Vec<(OwnedKeyExpr, serde_json::Value)> {
+    let (subscribers, queryables) = {
+        let tables = zread!(context.runtime.router.tables);
+        (
+            pubsub::declared_subscriptions(&tables),
+            queries::declared_queryables(&tables),
+        )
+    };
+    let mut responses = Vec::new();
+    for (kind, declarations) in [("subscriber", subscribers), ("queryable", queryables)] {
+        for (expr, zids) in declarations {
+            let declaration_key: OwnedKeyExpr =
+                match format!("@/router/{}/{}/{}", context.zid_str, kind, expr).try_into() {
+                    Ok(declaration_key) => declaration_key,
+                    Err(_) => continue,
+                };
+            if key.intersects(&declaration_key) {
+                let nodes: Vec<String> = zids.iter().map(|zid| zid.to_string()).collect();
+                responses.push((declaration_key, json!({ "nodes": nodes })));
+            }
+        }
+    }
+    responses
+}
+
 pub async fn plugins_status(
     context: &AdminContext,
     key: &KeyExpr<'_>,
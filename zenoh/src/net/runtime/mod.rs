@@ -37,7 +37,7 @@ use stop_token::{StopSource, TimedOutError};
 use uhlc::{HLCBuilder, HLC};
 use zenoh_link::{EndPoint, Link};
 use zenoh_protocol::{
-    core::{whatami::WhatAmIMatcher, Locator, WhatAmI, ZenohId},
+    core::{whatami::WhatAmIMatcher, Locator, Priority, WhatAmI, ZenohId},
     zenoh::{ZenohBody, ZenohMessage},
 };
 use zenoh_result::{bail, ZResult};
@@ -156,6 +156,7 @@ impl Runtime {
             gossip,
             gossip_multihop,
             autoconnect,
+            runtime.manager().config.unicast.rtt_interval,
         );
 
         let receiver = config.subscribe();
@@ -168,14 +169,71 @@ impl Runtime {
                         if let Err(e) = runtime2.update_peers().await {
                             log::error!("Error updating peers: {}", e);
                         }
+                    } else if event.starts_with("transport/auth/") {
+                        runtime2.reload_authenticators().await;
                     }
                 }
             }
         });
 
+        let period = *runtime
+            .config
+            .lock()
+            .transport()
+            .auth()
+            .reload()
+            .period_ms();
+        if period > 0 {
+            let runtime2 = runtime.clone();
+            runtime.spawn(async move {
+                runtime2
+                    .watch_credentials(Duration::from_millis(period))
+                    .await
+            });
+        }
+
         Ok(runtime)
     }
 
+    // Reload the credentials when the files they are stored in are modified
+    async fn watch_credentials(&self, period: Duration) {
+        let modified = |runtime: &Runtime| {
+            let config = runtime.config.lock();
+            let auth = config.transport().auth();
+            [
+                auth.usrpwd().dictionary_file(),
+                auth.pubkey().known_keys_file(),
+            ]
+            .iter()
+            .map(|file| {
+                file.as_ref()
+                    .and_then(|f| std::fs::metadata(f).and_then(|m| m.modified()).ok())
+            })
+            .collect::<Vec<_>>()
+        };
+
+        let mut last = modified(self);
+        loop {
+            async_std::task::sleep(period).await;
+            let now = modified(self);
+            if now != last {
+                last = now;
+                self.reload_authenticators().await;
+            }
+        }
+    }
+
+    async fn reload_authenticators(&self) {
+        let config = self.config.lock().clone();
+        match self.manager().reload_peer_authenticators(&config).await {
+            Ok(revoked) if !revoked.is_empty() => {
+                log::info!("Revoked the credentials of peers: {:?}", revoked)
+            }
+            Ok(_) => log::debug!("Reloaded the credentials"),
+            Err(e) => log::error!("Unable to reload the credentials: {}", e),
+        }
+    }
+
     #[inline(always)]
     pub fn manager(&self) -> &TransportManager {
         &self.manager
@@ -192,6 +250,17 @@ impl Runtime {
         Ok(())
     }
 
+    /// Sends right away the messages waiting to be batched in the transmission queues of the
+    /// given priority to the given nodes, or in all their transmission queues if no priority
+    /// is given.
+    pub fn flush(&self, zids: &[ZenohId], priority: Option<Priority>) {
+        for zid in zids {
+            if let Some(transport) = self.manager().get_transport_unicast(zid) {
+                let _ = transport.flush(priority);
+            }
+        }
+    }
+
     pub fn new_timestamp(&self) -> Option<uhlc::Timestamp> {
         self.hlc.as_ref().map(|hlc| hlc.new_timestamp())
     }
@@ -234,7 +303,9 @@ impl TransportEventHandler for RuntimeTransportEventHandler {
                         .collect();
                 Ok(Arc::new(RuntimeSession {
                     runtime: runtime.clone(),
+                    zid: peer.zid,
                     endpoint: std::sync::RwLock::new(None),
+                    scouted: std::sync::RwLock::new(None),
                     main_handler: runtime.router.new_transport_unicast(transport).unwrap(),
                     slave_handlers,
                 }))
@@ -254,7 +325,11 @@ impl TransportEventHandler for RuntimeTransportEventHandler {
 
 pub(super) struct RuntimeSession {
     pub(super) runtime: Runtime,
+    pub(super) zid: ZenohId,
+    // The configured endpoint the session has been established with, if any
     pub(super) endpoint: std::sync::RwLock<Option<EndPoint>>,
+    // The locators of the scouted peer the session has been established with, if any
+    pub(super) scouted: std::sync::RwLock<Option<Vec<Locator>>>,
     pub(super) main_handler: Arc<LinkStateInterceptor>,
     pub(super) slave_handlers: Vec<Arc<dyn TransportPeerEventHandler>>,
 }
@@ -271,6 +346,8 @@ impl TransportPeerEventHandler for RuntimeSession {
                     &data.key,
                     msg.channel,
                     data.congestion_control,
+                    msg.ttl,
+                    msg.express,
                     data.data_info,
                     data.payload,
                     msg.routing_context,
@@ -21,7 +21,7 @@ use std::time::Duration;
 use zenoh_buffers::reader::DidntRead;
 use zenoh_buffers::{reader::HasReader, writer::HasWriter};
 use zenoh_codec::{RCodec, WCodec, Zenoh060};
-use zenoh_config::{unwrap_or_default, EndPoint, ModeDependent};
+use zenoh_config::{unwrap_or_default, ConnectionRetryConf, EndPoint, ModeDependent};
 use zenoh_link::Locator;
 use zenoh_protocol::{
     core::{whatami::WhatAmIMatcher, WhatAmI, ZenohId},
@@ -35,9 +35,6 @@ const SCOUT_INITIAL_PERIOD: Duration = Duration::from_millis(1_000);
 const SCOUT_MAX_PERIOD: Duration = Duration::from_millis(8_000);
 const SCOUT_PERIOD_INCREASE_FACTOR: u32 = 2;
 const CONNECTION_TIMEOUT: Duration = Duration::from_millis(10_000);
-const CONNECTION_RETRY_INITIAL_PERIOD: Duration = Duration::from_millis(1_000);
-const CONNECTION_RETRY_MAX_PERIOD: Duration = Duration::from_millis(4_000);
-const CONNECTION_RETRY_PERIOD_INCREASE_FACTOR: u32 = 2;
 const ROUTER_DEFAULT_LISTENER: &str = "tcp/[::]:7447";
 const PEER_DEFAULT_LISTENER: &str = "tcp/[::]:0";
 
@@ -46,6 +43,50 @@ pub enum Loop {
     Break,
 }
 
+// The attempts to connect to an endpoint, separated by an exponentially growing and jittered delay
+struct Backoff {
+    conf: ConnectionRetryConf,
+    period: Duration,
+    attempts: usize,
+}
+
+impl Backoff {
+    fn new(conf: ConnectionRetryConf) -> Self {
+        Backoff {
+            period: Duration::from_millis(conf.period_init_ms),
+            conf,
+            attempts: 0,
+        }
+    }
+
+    // Count a new attempt, if the maximum number of attempts is not reached yet
+    fn attempt(&mut self) -> bool {
+        if self.conf.max_attempts != 0 && self.attempts >= self.conf.max_attempts {
+            return false;
+        }
+        self.attempts += 1;
+        true
+    }
+
+    // The delay to wait before the next attempt
+    fn delay(&mut self) -> Duration {
+        use rand::Rng;
+
+        let jitter = self.conf.jitter.clamp(0., 1.);
+        let delay = if jitter > 0. {
+            self.period
+                .mul_f64(1. + rand::thread_rng().gen_range(-jitter..=jitter))
+        } else {
+            self.period
+        };
+        self.period = self
+            .period
+            .mul_f64(self.conf.period_increase_factor.max(1.))
+            .min(Duration::from_millis(self.conf.period_max_ms));
+        delay
+    }
+}
+
 impl Runtime {
     pub(crate) async fn start(&mut self) -> ZResult<()> {
         match self.whatami {
@@ -424,11 +465,18 @@ impl Runtime {
     }
 
     async fn peer_connector(&self, peer: EndPoint) {
-        let mut delay = CONNECTION_RETRY_INITIAL_PERIOD;
-        loop {
+        let retry = {
+            self.config
+                .lock()
+                .connect()
+                .retry()
+                .for_endpoint(peer.as_str())
+        };
+        let mut backoff = Backoff::new(retry);
+        while backoff.attempt() {
             log::trace!("Trying to connect to configured peer {}", peer);
             let endpoint = peer.clone();
-            match self
+            let e = match self
                 .manager()
                 .open_transport(endpoint)
                 .timeout(CONNECTION_TIMEOUT)
@@ -445,33 +493,70 @@ impl Runtime {
                     {
                         *zwrite!(orch_transport.endpoint) = Some(peer);
                     }
-                    break;
+                    return;
                 }
-                Ok(Err(e)) => {
-                    log::debug!(
-                        "Unable to connect to configured peer {}! {}. Retry in {:?}.",
-                        peer,
-                        e,
-                        delay
-                    );
-                }
-                Err(e) => {
-                    log::debug!(
-                        "Unable to connect to configured peer {}! {}. Retry in {:?}.",
-                        peer,
-                        e,
-                        delay
-                    );
-                }
-            }
+                Ok(Err(e)) => e.to_string(),
+                Err(e) => e.to_string(),
+            };
+            let delay = backoff.delay();
+            log::debug!(
+                "Unable to connect to configured peer {}! {}. Retry in {:?}.",
+                peer,
+                e,
+                delay
+            );
             async_std::task::sleep(delay).await;
-            delay *= CONNECTION_RETRY_PERIOD_INCREASE_FACTOR;
-            if delay > CONNECTION_RETRY_MAX_PERIOD {
-                delay = CONNECTION_RETRY_MAX_PERIOD;
+        }
+        log::warn!(
+            "Unable to connect to configured peer {}! Giving up after {} attempts.",
+            peer,
+            backoff.attempts
+        );
+    }
+
+    // Reconnect to a scouted peer the session with has been closed
+    async fn scouted_peer_connector(&self, zid: ZenohId, locators: Vec<Locator>) {
+        let retry = { self.config.lock().scouting().retry().clone() };
+        let mut backoff = Backoff::new(retry);
+        loop {
+            async_std::task::sleep(backoff.delay()).await;
+            if self.manager().get_transport(&zid).is_some() {
+                log::trace!("Already reconnected to scouted peer {}", zid);
+                return;
+            }
+            if !backoff.attempt() {
+                log::debug!(
+                    "Unable to reconnect to scouted peer {}! Giving up after {} attempts.",
+                    zid,
+                    backoff.attempts
+                );
+                return;
+            }
+            log::trace!(
+                "Trying to reconnect to scouted peer {} via {:?}",
+                zid,
+                locators
+            );
+            if let Some(transport) = self.connect(&locators).await {
+                log::debug!("Successfully reconnected to scouted peer {}", zid);
+                Runtime::set_scouted(&transport, locators);
+                return;
             }
         }
     }
 
+    fn set_scouted(transport: &TransportUnicast, locators: Vec<Locator>) {
+        if let Some(orch_transport) = transport
+            .get_callback()
+            .ok()
+            .flatten()
+            .as_ref()
+            .and_then(|cb| cb.as_any().downcast_ref::<super::RuntimeSession>())
+        {
+            *zwrite!(orch_transport.scouted) = Some(locators);
+        }
+    }
+
     pub async fn scout<Fut, F>(
         sockets: &[UdpSocket],
         matcher: WhatAmIMatcher,
@@ -587,6 +672,7 @@ impl Runtime {
                         zid,
                         transport
                     );
+                    Runtime::set_scouted(&transport, locators.to_vec());
                 } else {
                     log::warn!(
                         "Unable to connect any locator of scouted peer {}: {:?}",
@@ -753,14 +839,18 @@ impl Runtime {
             WhatAmI::Client => {
                 let runtime = session.runtime.clone();
                 session.runtime.spawn(async move {
-                    let mut delay = CONNECTION_RETRY_INITIAL_PERIOD;
-                    while runtime.start_client().await.is_err() {
-                        async_std::task::sleep(delay).await;
-                        delay *= CONNECTION_RETRY_PERIOD_INCREASE_FACTOR;
-                        if delay > CONNECTION_RETRY_MAX_PERIOD {
-                            delay = CONNECTION_RETRY_MAX_PERIOD;
+                    let retry = { runtime.config.lock().connect().retry().clone() };
+                    let mut backoff = Backoff::new(retry);
+                    while backoff.attempt() {
+                        if runtime.start_client().await.is_ok() {
+                            return;
                         }
+                        async_std::task::sleep(backoff.delay()).await;
                     }
+                    log::error!(
+                        "Unable to reconnect! Giving up after {} attempts.",
+                        backoff.attempts
+                    );
                 });
             }
             _ => {
@@ -773,6 +863,12 @@ impl Runtime {
                             .runtime
                             .spawn(async move { runtime.peer_connector(endpoint).await });
                     }
+                } else if let Some(locators) = zread!(session.scouted).clone() {
+                    let zid = session.zid;
+                    let runtime = session.runtime.clone();
+                    session
+                        .runtime
+                        .spawn(async move { runtime.scouted_peer_connector(zid, locators).await });
                 }
             }
         }
@@ -12,6 +12,7 @@
 //   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
 //
 use crate::net::routing::router::*;
+use std::collections::HashSet;
 use std::convert::{TryFrom, TryInto};
 use std::sync::{Arc, RwLock};
 use std::time::Duration;
@@ -451,6 +452,8 @@ impl Primitives for ClientPrimitives {
         _payload: ZBuf,
         _channel: Channel,
         _congestion_control: CongestionControl,
+        _ttl: Option<Duration>,
+        _express: bool,
         _info: Option<DataInfo>,
         _routing_context: Option<RoutingContext>,
     ) {
@@ -594,6 +597,8 @@ fn client_test() {
         Channel::default(),
         CongestionControl::default(),
         None,
+        false,
+        None,
         ZBuf::default(),
         None,
     );
@@ -620,6 +625,8 @@ fn client_test() {
         Channel::default(),
         CongestionControl::default(),
         None,
+        false,
+        None,
         ZBuf::default(),
         None,
     );
@@ -646,6 +653,8 @@ fn client_test() {
         Channel::default(),
         CongestionControl::default(),
         None,
+        false,
+        None,
         ZBuf::default(),
         None,
     );
@@ -672,6 +681,8 @@ fn client_test() {
         Channel::default(),
         CongestionControl::default(),
         None,
+        false,
+        None,
         ZBuf::default(),
         None,
     );
@@ -698,6 +709,8 @@ fn client_test() {
         Channel::default(),
         CongestionControl::default(),
         None,
+        false,
+        None,
         ZBuf::default(),
         None,
     );
@@ -714,3 +727,58 @@ fn client_test() {
     // mapping strategy check
     // assert_eq!(primitives2.get_last_key().unwrap(), KeyExpr::IdWithSuffix(31, "/z2_pub1".to_string()));
 }
+
+#[test]
+fn data_route_zids_test() {
+    let mut tables = RwLock::new(Tables::new(
+        ZenohId::try_from([1]).unwrap(),
+        WhatAmI::Client,
+        Some(Arc::new(HLC::default())),
+        false,
+        true,
+        Duration::from_millis(ZN_QUERIES_DEFAULT_TIMEOUT_DEFAULT.parse().unwrap()),
+    ));
+
+    let sub_info = SubInfo {
+        reliability: Reliability::Reliable,
+        mode: SubMode::Push,
+    };
+
+    let tables_mutref = tables.get_mut().unwrap();
+    let mut faces = vec![];
+    for (zid, sub) in [
+        (1, None),
+        (2, Some("test/a/**")),
+        (3, Some("test/b/**")),
+        (4, None),
+    ] {
+        let face = tables_mutref
+            .open_face(
+                ZenohId::try_from([zid]).unwrap(),
+                WhatAmI::Client,
+                Arc::new(DummyPrimitives::new()),
+            )
+            .upgrade()
+            .unwrap();
+        if let Some(sub) = sub {
+            declare_client_subscription(tables_mutref, &mut face.clone(), &sub.into(), &sub_info);
+        }
+        faces.push(face);
+    }
+
+    let zids = |expr: &str| {
+        get_data_route_zids(&tables, &faces[0], &expr.into())
+            .into_iter()
+            .collect::<HashSet<ZenohId>>()
+    };
+    let zid = |zid: u8| ZenohId::try_from([zid]).unwrap();
+
+    // Only the faces with matching subscriptions are routed to
+    assert_eq!(zids("test/a/x"), HashSet::from([zid(2)]));
+    assert_eq!(zids("test/**"), HashSet::from([zid(2), zid(3)]));
+    assert_eq!(zids("**"), HashSet::from([zid(2), zid(3)]));
+    assert!(zids("other").is_empty());
+
+    // The data is never routed back to the publishing face
+    assert!(get_data_route_zids(&tables, &faces[1], &"test/a/x".into()).is_empty());
+}
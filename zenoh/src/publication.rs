@@ -16,12 +16,15 @@
 
 use crate::net::transport::Primitives;
 use crate::prelude::*;
+#[zenoh_core::unstable]
+use crate::sample::SourceInfo;
 use crate::subscriber::Reliability;
 use crate::Encoding;
 use crate::SessionRef;
 use crate::Undeclarable;
 use std::future::Ready;
-use zenoh_core::{zread, AsyncResolve, Resolvable, Resolve, SyncResolve};
+use std::time::Duration;
+use zenoh_core::{zread, AsyncResolve, Resolvable, Resolve, ResolveClosure, SyncResolve};
 use zenoh_protocol::{core::Channel, zenoh::DataInfo};
 use zenoh_result::ZResult;
 
@@ -69,6 +72,8 @@ pub struct PutBuilder<'a, 'b> {
     pub(crate) publisher: PublisherBuilder<'a, 'b>,
     pub(crate) value: Value,
     pub(crate) kind: SampleKind,
+    #[cfg(feature = "unstable")]
+    pub(crate) source_info: SourceInfo,
 }
 
 impl PutBuilder<'_, '_> {
@@ -95,6 +100,23 @@ impl PutBuilder<'_, '_> {
         self
     }
 
+    /// Change the time-to-live of the written data in the local transmission queues:
+    /// data still queued for transmission after this time is dropped instead of being sent.
+    #[inline]
+    pub fn time_to_live(mut self, ttl: Duration) -> Self {
+        self.publisher = self.publisher.time_to_live(ttl);
+        self
+    }
+
+    /// Change whether the written data is sent right away, instead of waiting in the local
+    /// transmission queues to be batched with the following data. This is local to this
+    /// session: the routers forwarding the data batch it as usual.
+    #[inline]
+    pub fn express(mut self, express: bool) -> Self {
+        self.publisher = self.publisher.express(express);
+        self
+    }
+
     /// Restrict the matching subscribers that will receive the published data
     /// to the ones that have the given [`Locality`](crate::prelude::Locality).
     #[zenoh_core::unstable]
@@ -108,6 +130,14 @@ impl PutBuilder<'_, '_> {
         self.kind = kind;
         self
     }
+
+    /// Sets the [`SourceInfo`] (source id and sequence number) of the written data.
+    #[zenoh_core::unstable]
+    #[inline]
+    pub fn with_source_info(mut self, source_info: SourceInfo) -> Self {
+        self.source_info = source_info;
+        self
+    }
 }
 
 impl Resolvable for PutBuilder<'_, '_> {
@@ -119,11 +149,16 @@ impl SyncResolve for PutBuilder<'_, '_> {
     fn res_sync(self) -> <Self as Resolvable>::To {
         let PutBuilder {
             publisher,
-            value,
+            mut value,
             kind,
+            #[cfg(feature = "unstable")]
+            source_info,
         } = self;
         let key_expr = publisher.key_expr?;
         log::trace!("write({:?}, [...])", &key_expr);
+        if kind != SampleKind::Delete {
+            publisher.session.encrypt(&key_expr, &mut value)?;
+        }
         let primitives = zread!(publisher.session.state)
             .primitives
             .as_ref()
@@ -138,6 +173,10 @@ impl SyncResolve for PutBuilder<'_, '_> {
                 None
             },
             timestamp: publisher.session.runtime.new_timestamp(),
+            #[cfg(feature = "unstable")]
+            source_id: source_info.source_id,
+            #[cfg(feature = "unstable")]
+            source_sn: source_info.source_sn,
             ..Default::default()
         };
         let data_info = if info != DataInfo::default() {
@@ -155,6 +194,8 @@ impl SyncResolve for PutBuilder<'_, '_> {
                     reliability: Reliability::Reliable, // @TODO: need to check subscriptions to determine the right reliability value
                 },
                 publisher.congestion_control,
+                publisher.ttl,
+                publisher.express,
                 data_info.clone(),
                 None,
             );
@@ -221,6 +262,8 @@ pub struct Publisher<'a> {
     pub(crate) key_expr: KeyExpr<'a>,
     pub(crate) congestion_control: CongestionControl,
     pub(crate) priority: Priority,
+    pub(crate) ttl: Option<Duration>,
+    pub(crate) express: bool,
     pub(crate) destination: Locality,
 }
 
@@ -243,6 +286,23 @@ impl<'a> Publisher<'a> {
         self
     }
 
+    /// Change the time-to-live of the written data in the local transmission queues:
+    /// data still queued for transmission after this time is dropped instead of being sent.
+    #[inline]
+    pub fn time_to_live(mut self, ttl: Duration) -> Self {
+        self.ttl = Some(ttl);
+        self
+    }
+
+    /// Change whether the written data is sent right away, instead of waiting in the local
+    /// transmission queues to be batched with the following data. This is local to this
+    /// session: the routers forwarding the data batch it as usual.
+    #[inline]
+    pub fn express(mut self, express: bool) -> Self {
+        self.express = express;
+        self
+    }
+
     /// Restrict the matching subscribers that will receive the published data
     /// to the ones that have the given [`Locality`](crate::prelude::Locality).
     #[zenoh_core::unstable]
@@ -252,11 +312,43 @@ impl<'a> Publisher<'a> {
         self
     }
 
+    /// Send right away the data of this [`Publisher`] priority waiting to be batched in the
+    /// local transmission queues towards the nodes its data is routed to, instead of waiting
+    /// for the batching backoff to expire.
+    ///
+    /// # Examples
+    /// ```
+    /// # async_std::task::block_on(async {
+    /// use zenoh::prelude::r#async::*;
+    ///
+    /// let session = zenoh::open(config::peer()).res().await.unwrap().into_arc();
+    /// let publisher = session.declare_publisher("key/expression").res().await.unwrap();
+    /// publisher.put("value").res().await.unwrap();
+    /// publisher.flush().res().await.unwrap();
+    /// # })
+    /// ```
+    pub fn flush(&self) -> impl Resolve<ZResult<()>> + '_ {
+        ResolveClosure::new(move || {
+            let primitives = zread!(self.session.state)
+                .primitives
+                .as_ref()
+                .unwrap()
+                .clone();
+            let zids = primitives.get_data_route_zids(&self.key_expr.to_wire(&self.session));
+            self.session
+                .runtime
+                .flush(&zids, Some(self.priority.into()));
+            Ok(())
+        })
+    }
+
     fn _write(&self, kind: SampleKind, value: Value) -> Publication {
         Publication {
             publisher: self,
             value,
             kind,
+            #[cfg(feature = "unstable")]
+            source_info: SourceInfo::empty(),
         }
     }
 
@@ -396,6 +488,18 @@ pub struct Publication<'a> {
     publisher: &'a Publisher<'a>,
     value: Value,
     kind: SampleKind,
+    #[cfg(feature = "unstable")]
+    source_info: SourceInfo,
+}
+
+impl Publication<'_> {
+    /// Sets the [`SourceInfo`] (source id and sequence number) of the published data.
+    #[zenoh_core::unstable]
+    #[inline]
+    pub fn with_source_info(mut self, source_info: SourceInfo) -> Self {
+        self.source_info = source_info;
+        self
+    }
 }
 
 impl Resolvable for Publication<'_> {
@@ -406,10 +510,15 @@ impl SyncResolve for Publication<'_> {
     fn res_sync(self) -> <Self as Resolvable>::To {
         let Publication {
             publisher,
-            value,
+            mut value,
             kind,
+            #[cfg(feature = "unstable")]
+            source_info,
         } = self;
         log::trace!("write({:?}, [...])", publisher.key_expr);
+        if kind != SampleKind::Delete {
+            publisher.session.encrypt(&publisher.key_expr, &mut value)?;
+        }
         let primitives = zread!(publisher.session.state)
             .primitives
             .as_ref()
@@ -424,6 +533,10 @@ impl SyncResolve for Publication<'_> {
                 None
             },
             timestamp: publisher.session.runtime.new_timestamp(),
+            #[cfg(feature = "unstable")]
+            source_id: source_info.source_id,
+            #[cfg(feature = "unstable")]
+            source_sn: source_info.source_sn,
             ..Default::default()
         };
         let data_info = if info != DataInfo::default() {
@@ -441,6 +554,8 @@ impl SyncResolve for Publication<'_> {
                     reliability: Reliability::Reliable, // @TODO: need to check subscriptions to determine the right reliability value
                 },
                 publisher.congestion_control,
+                publisher.ttl,
+                publisher.express,
                 data_info.clone(),
                 None,
             );
@@ -515,6 +630,8 @@ pub struct PublisherBuilder<'a, 'b: 'a> {
     pub(crate) key_expr: ZResult<KeyExpr<'b>>,
     pub(crate) congestion_control: CongestionControl,
     pub(crate) priority: Priority,
+    pub(crate) ttl: Option<Duration>,
+    pub(crate) express: bool,
     pub(crate) destination: Locality,
 }
 
@@ -528,6 +645,8 @@ impl<'a, 'b> Clone for PublisherBuilder<'a, 'b> {
             },
             congestion_control: self.congestion_control,
             priority: self.priority,
+            ttl: self.ttl,
+            express: self.express,
             destination: self.destination,
         }
     }
@@ -548,6 +667,23 @@ impl<'a, 'b> PublisherBuilder<'a, 'b> {
         self
     }
 
+    /// Change the time-to-live of the written data in the local transmission queues:
+    /// data still queued for transmission after this time is dropped instead of being sent.
+    #[inline]
+    pub fn time_to_live(mut self, ttl: Duration) -> Self {
+        self.ttl = Some(ttl);
+        self
+    }
+
+    /// Change whether the written data is sent right away, instead of waiting in the local
+    /// transmission queues to be batched with the following data. This is local to this
+    /// session: the routers forwarding the data batch it as usual.
+    #[inline]
+    pub fn express(mut self, express: bool) -> Self {
+        self.express = express;
+        self
+    }
+
     /// Restrict the matching subscribers that will receive the published data
     /// to the ones that have the given [`Locality`](crate::prelude::Locality).
     #[zenoh_core::unstable]
@@ -601,6 +737,8 @@ impl<'a, 'b> SyncResolve for PublisherBuilder<'a, 'b> {
             key_expr,
             congestion_control: self.congestion_control,
             priority: self.priority,
+            ttl: self.ttl,
+            express: self.express,
             destination: self.destination,
         };
         log::trace!("publish({:?})", publisher.key_expr);
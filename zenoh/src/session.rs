@@ -15,6 +15,7 @@
 use crate::admin;
 use crate::config::Config;
 use crate::config::Notifier;
+use crate::e2e::{E2e, KeyProvider};
 use crate::handlers::{Callback, DefaultHandler};
 use crate::info::*;
 use crate::key_expr::KeyExprInner;
@@ -26,6 +27,8 @@ use crate::prelude::{KeyExpr, Parameters};
 use crate::publication::*;
 use crate::query::*;
 use crate::queryable::*;
+#[zenoh_core::unstable]
+use crate::sample::SourceInfo;
 use crate::selector::TIME_RANGE_KEY;
 use crate::subscriber::*;
 use crate::Id;
@@ -85,6 +88,7 @@ pub(crate) struct SessionState {
     pub(crate) queries: HashMap<ZInt, QueryState>,
     pub(crate) aggregated_subscribers: Vec<OwnedKeyExpr>,
     pub(crate) aggregated_publishers: Vec<OwnedKeyExpr>,
+    pub(crate) e2e: Option<Arc<E2e>>,
 }
 
 impl SessionState {
@@ -105,6 +109,7 @@ impl SessionState {
             queries: HashMap::new(),
             aggregated_subscribers,
             aggregated_publishers,
+            e2e: None,
         }
     }
 }
@@ -408,6 +413,61 @@ impl Session {
         self.runtime.hlc.as_ref().map(Arc::as_ref)
     }
 
+    /// Sets the [`KeyProvider`] of the keys used to encrypt payloads end-to-end.
+    ///
+    /// The payloads put and replied by this session on the key expressions for which the provider
+    /// returns a key are encrypted with this key, and the encrypted payloads received on these key
+    /// expressions are decrypted with it. See the [`e2e`](crate::e2e) module for more details.
+    ///
+    /// # Examples
+    /// ```
+    /// # async_std::task::block_on(async {
+    /// use std::sync::Arc;
+    /// use zenoh::e2e::KeyExprKeys;
+    /// use zenoh::prelude::r#async::*;
+    ///
+    /// let keys = KeyExprKeys::new();
+    /// keys.insert(OwnedKeyExpr::new("key/**").unwrap(), [42; 16]);
+    /// let session = zenoh::open(config::peer()).res().await.unwrap();
+    /// session.set_key_provider(Arc::new(keys));
+    /// # })
+    /// ```
+    pub fn set_key_provider(&self, provider: Arc<dyn KeyProvider>) {
+        zwrite!(self.state).e2e = Some(Arc::new(E2e::new(provider)));
+    }
+
+    /// Encrypts the given value for the given key expression if this session is configured to.
+    pub(crate) fn encrypt(&self, key_expr: &keyexpr, value: &mut Value) -> ZResult<()> {
+        match zread!(self.state).e2e.as_ref() {
+            Some(e2e) => e2e.encrypt(key_expr, value),
+            None => Ok(()),
+        }
+    }
+
+    /// Send right away the data waiting to be batched in the transmission queues of this
+    /// [`Session`](Session) towards the nodes its data is routed to, instead of waiting for
+    /// the batching backoff to expire.
+    ///
+    /// # Examples
+    /// ```
+    /// # async_std::task::block_on(async {
+    /// use zenoh::prelude::r#async::*;
+    ///
+    /// let session = zenoh::open(config::peer()).res().await.unwrap();
+    /// session.put("key/expression", "value").res().await.unwrap();
+    /// session.flush().res().await.unwrap();
+    /// # })
+    /// ```
+    pub fn flush(&self) -> impl Resolve<ZResult<()>> + '_ {
+        ResolveClosure::new(move || {
+            trace!("flush()");
+            let primitives = zread!(self.state).primitives.as_ref().unwrap().clone();
+            let zids = primitives.get_data_route_zids(&"**".into());
+            self.runtime.flush(&zids, None);
+            Ok(())
+        })
+    }
+
     /// Close the zenoh [`Session`](Session).
     ///
     /// Sessions are automatically closed when dropped, but you may want to use this function to handle errors or
@@ -597,6 +657,8 @@ impl Session {
             key_expr: key_expr.try_into().map_err(Into::into),
             congestion_control: CongestionControl::default(),
             priority: Priority::default(),
+            ttl: None,
+            express: false,
             destination: Locality::default(),
         }
     }
@@ -694,6 +756,8 @@ impl Session {
             publisher: self.declare_publisher(key_expr),
             value: value.into(),
             kind: SampleKind::Put,
+            #[cfg(feature = "unstable")]
+            source_info: SourceInfo::empty(),
         }
     }
 
@@ -725,6 +789,8 @@ impl Session {
             publisher: self.declare_publisher(key_expr),
             value: Value::empty(),
             kind: SampleKind::Delete,
+            #[cfg(feature = "unstable")]
+            source_info: SourceInfo::empty(),
         }
     }
     /// Query data from the matching queryables in the system.
@@ -1219,7 +1285,7 @@ impl Session {
         payload: ZBuf,
     ) {
         let mut callbacks = SingleOrVec::default();
-        let sample = {
+        let (mut sample, e2e) = {
             let state = zread!(self.state);
             let sample = if key_expr.suffix.is_empty() {
                 match state.get_res(&key_expr.scope, local) {
@@ -1264,8 +1330,19 @@ impl Session {
                     }
                 }
             };
-            sample
+            (sample, state.e2e.clone())
         };
+        // The deletions carry no value to encrypt
+        if let Some(e2e) = e2e.filter(|_| sample.kind != SampleKind::Delete) {
+            if let Err(e) = e2e.decrypt(&sample.key_expr, &mut sample.value) {
+                log::warn!(
+                    "Unable to decrypt Data for `{}`: {}. Dropping Data.",
+                    sample.key_expr,
+                    e
+                );
+                return;
+            }
+        }
         let zenoh_collections::single_or_vec::IntoIter { drain, last } = callbacks.into_iter();
         for cb in drain {
             cb(sample.clone());
@@ -1294,10 +1371,13 @@ impl Session {
         consolidation: QueryConsolidation,
         destination: Locality,
         timeout: Duration,
-        value: Option<Value>,
+        mut value: Option<Value>,
         callback: Callback<'static, Reply>,
     ) -> ZResult<()> {
         log::trace!("get({}, {:?}, {:?})", selector, target, consolidation);
+        if let Some(value) = value.as_mut() {
+            self.encrypt(&selector.key_expr, value)?;
+        }
         let mut state = zwrite!(self.state);
         let consolidation = match consolidation.mode {
             Mode::Auto => {
@@ -1406,7 +1486,7 @@ impl Session {
         _consolidation: ConsolidationMode,
         body: Option<QueryBody>,
     ) {
-        let (primitives, key_expr, senders) = {
+        let (primitives, key_expr, mut senders, e2e) = {
             let state = zread!(self.state);
             match state.wireexpr_to_keyexpr(key_expr, local) {
                 Ok(key_expr) => {
@@ -1437,6 +1517,7 @@ impl Session {
                         state.primitives.as_ref().unwrap().clone(),
                         key_expr.into_owned(),
                         senders,
+                        state.e2e.clone(),
                     )
                 }
                 Err(err) => {
@@ -1447,6 +1528,20 @@ impl Session {
         };
 
         let parameters = parameters.to_owned();
+        let mut value = body.map(|b| Value {
+            payload: b.payload,
+            encoding: b.data_info.encoding.unwrap_or_default(),
+        });
+        if let (Some(e2e), Some(value)) = (e2e.as_ref(), value.as_mut()) {
+            if let Err(e) = e2e.decrypt(&key_expr, value) {
+                log::warn!(
+                    "Unable to decrypt Query for `{}`: {}. Dropping Query.",
+                    key_expr,
+                    e
+                );
+                senders.clear();
+            }
+        }
         let (rep_sender, rep_receiver) = bounded(*API_REPLY_EMISSION_CHANNEL_SIZE);
 
         let zid = self.runtime.zid; // @TODO build/use prebuilt specific zid
@@ -1456,10 +1551,7 @@ impl Session {
                 key_expr: key_expr.clone().into_owned(),
                 parameters: parameters.clone(),
                 replies_sender: rep_sender.clone(),
-                value: body.as_ref().map(|b| Value {
-                    payload: b.payload.clone(),
-                    encoding: b.data_info.encoding.as_ref().cloned().unwrap_or_default(),
-                }),
+                value: value.clone(),
             });
         }
         drop(rep_sender); // all senders need to be dropped for the channel to close
@@ -1469,7 +1561,13 @@ impl Session {
         if local {
             let this = self.clone();
             task::spawn(async move {
-                while let Some(sample) = rep_receiver.stream().next().await {
+                while let Some(mut sample) = rep_receiver.stream().next().await {
+                    if let Some(e2e) = e2e.as_ref().filter(|_| sample.kind != SampleKind::Delete) {
+                        if let Err(e) = e2e.encrypt(&sample.key_expr, &mut sample.value) {
+                            log::error!("Unable to encrypt reply for `{}`: {}", sample.key_expr, e);
+                            continue;
+                        }
+                    }
                     let (key_expr, payload, data_info) = sample.split();
                     this.send_reply_data(
                         qid,
@@ -1484,7 +1582,13 @@ impl Session {
         } else {
             let this = self.clone();
             task::spawn(async move {
-                while let Some(sample) = rep_receiver.stream().next().await {
+                while let Some(mut sample) = rep_receiver.stream().next().await {
+                    if let Some(e2e) = e2e.as_ref().filter(|_| sample.kind != SampleKind::Delete) {
+                        if let Err(e) = e2e.encrypt(&sample.key_expr, &mut sample.value) {
+                            log::error!("Unable to encrypt reply for `{}`: {}", sample.key_expr, e);
+                            continue;
+                        }
+                    }
                     let (key_expr, payload, data_info) = sample.split();
                     primitives.send_reply_data(
                         qid,
@@ -1618,6 +1722,8 @@ impl SessionDeclarations for Arc<Session> {
             key_expr: key_expr.try_into().map_err(Into::into),
             congestion_control: CongestionControl::default(),
             priority: Priority::default(),
+            ttl: None,
+            express: false,
             destination: Locality::default(),
         }
     }
@@ -1676,15 +1782,17 @@ impl Primitives for Session {
 
     fn decl_queryable(
         &self,
-        _key_expr: &WireExpr,
+        key_expr: &WireExpr,
         _qabl_info: &QueryableInfo,
         _routing_context: Option<RoutingContext>,
     ) {
-        trace!("recv Decl Queryable {:?}", _key_expr);
+        trace!("recv Decl Queryable {:?}", key_expr);
+        admin::on_remote_queryable(self, key_expr, SampleKind::Put);
     }
 
-    fn forget_queryable(&self, _key_expr: &WireExpr, _routing_context: Option<RoutingContext>) {
-        trace!("recv Forget Queryable {:?}", _key_expr);
+    fn forget_queryable(&self, key_expr: &WireExpr, _routing_context: Option<RoutingContext>) {
+        trace!("recv Forget Queryable {:?}", key_expr);
+        admin::on_remote_queryable(self, key_expr, SampleKind::Delete);
     }
 
     fn send_data(
@@ -1693,6 +1801,8 @@ impl Primitives for Session {
         payload: ZBuf,
         channel: Channel,
         congestion_control: CongestionControl,
+        _ttl: Option<Duration>,
+        _express: bool,
         info: Option<DataInfo>,
         _routing_context: Option<RoutingContext>,
     ) {
@@ -1759,6 +1869,7 @@ impl Primitives for Session {
                 return;
             }
         };
+        let e2e = state.e2e.clone();
         match state.queries.get_mut(&qid) {
             Some(query) => {
                 if !matches!(
@@ -1777,8 +1888,20 @@ impl Primitives for Session {
                     );
                     return;
                 }
+                let mut sample = Sample::with_info(key_expr.into_owned(), payload, data_info);
+                if let Some(e2e) = e2e.as_ref().filter(|_| sample.kind != SampleKind::Delete) {
+                    if let Err(e) = e2e.decrypt(&sample.key_expr, &mut sample.value) {
+                        log::warn!(
+                            "Unable to decrypt ReplyData for `{}` from `{:?}`: {}. Dropping ReplyData.",
+                            sample.key_expr,
+                            replier_id,
+                            e
+                        );
+                        return;
+                    }
+                }
                 let new_reply = Reply {
-                    sample: Ok(Sample::with_info(key_expr.into_owned(), payload, data_info)),
+                    sample: Ok(sample),
                     replier_id,
                 };
                 let callback = match query.reception_mode {
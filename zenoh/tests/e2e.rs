@@ -0,0 +1,182 @@
+//
+// Copyright (c) 2022 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+use async_std::prelude::FutureExt;
+use async_std::task;
+use std::sync::Arc;
+use std::time::Duration;
+use zenoh::config::{EndPoint, WhatAmI};
+use zenoh::e2e::{Key, KeyExprKeys};
+use zenoh::prelude::r#async::*;
+use zenoh_core::zasync_executor_init;
+
+const TIMEOUT: Duration = Duration::from_secs(60);
+const SLEEP: Duration = Duration::from_secs(1);
+
+const SECRET: &str = "This is a secret";
+
+macro_rules! ztimeout {
+    ($f:expr) => {
+        $f.timeout(TIMEOUT).await.unwrap()
+    };
+}
+
+async fn open_router(endpoint: &str) -> Session {
+    let mut config = config::default();
+    config.set_mode(Some(WhatAmI::Router)).unwrap();
+    config.listen.endpoints = vec![endpoint.parse().unwrap()];
+    config.scouting.multicast.set_enabled(Some(false)).unwrap();
+    ztimeout!(zenoh::open(config).res_async()).unwrap()
+}
+
+async fn open_client(endpoint: &str, key: Option<Key>) -> Session {
+    let mut config = config::client([endpoint.parse::<EndPoint>().unwrap()]);
+    config.scouting.multicast.set_enabled(Some(false)).unwrap();
+    let session = ztimeout!(zenoh::open(config).res_async()).unwrap();
+    if let Some(key) = key {
+        let keys = KeyExprKeys::new();
+        keys.insert(OwnedKeyExpr::new("test/e2e/secret/**").unwrap(), key);
+        session.set_key_provider(Arc::new(keys));
+    }
+    session
+}
+
+#[test]
+fn zenoh_e2e() {
+    task::block_on(async {
+        zasync_executor_init!();
+
+        let endpoint = "tcp/127.0.0.1:17451";
+        let secret = "test/e2e/secret/value";
+        let clear = "test/e2e/clear";
+
+        println!("[E2E][01] Opening sessions");
+        let router = open_router(endpoint).await;
+        let publisher = open_client(endpoint, Some([1; 16])).await;
+        let subscriber = open_client(endpoint, Some([1; 16])).await;
+        let intruder = open_client(endpoint, Some([2; 16])).await;
+
+        let router_sub = ztimeout!(router.declare_subscriber("test/e2e/**").res_async()).unwrap();
+        let sub = ztimeout!(subscriber.declare_subscriber("test/e2e/**").res_async()).unwrap();
+        let intruder_sub =
+            ztimeout!(intruder.declare_subscriber("test/e2e/**").res_async()).unwrap();
+        task::sleep(SLEEP).await;
+
+        println!("[E2E][02] Putting on a key expression with a key");
+        ztimeout!(publisher
+            .put(secret, SECRET)
+            .encoding(KnownEncoding::TextPlain)
+            .res_async())
+        .unwrap();
+
+        let sample = ztimeout!(sub.recv_async()).unwrap();
+        assert_eq!(sample.key_expr.as_str(), secret);
+        assert_eq!(sample.value.encoding, KnownEncoding::TextPlain.into());
+        assert_eq!(sample.value.to_string(), SECRET);
+
+        // The router doesn't hold the key: it receives the encrypted payload
+        let sample = ztimeout!(router_sub.recv_async()).unwrap();
+        assert!(sample.value.encoding.is_encrypted());
+        let payload = sample.value.payload.contiguous();
+        assert!(!payload
+            .windows(SECRET.len())
+            .any(|w| w == SECRET.as_bytes()));
+
+        println!("[E2E][03] Putting on a key expression without a key");
+        ztimeout!(publisher
+            .put(clear, SECRET)
+            .encoding(KnownEncoding::TextPlain)
+            .res_async())
+        .unwrap();
+
+        let sample = ztimeout!(sub.recv_async()).unwrap();
+        assert_eq!(sample.key_expr.as_str(), clear);
+        assert_eq!(sample.value.to_string(), SECRET);
+        let sample = ztimeout!(router_sub.recv_async()).unwrap();
+        assert_eq!(sample.value.encoding, KnownEncoding::TextPlain.into());
+        assert_eq!(sample.value.to_string(), SECRET);
+
+        // The session holding a wrong key can't authenticate the encrypted payload and drops it
+        let sample = ztimeout!(intruder_sub.recv_async()).unwrap();
+        assert_eq!(sample.key_expr.as_str(), clear);
+        assert!(intruder_sub.try_recv().is_err());
+
+        println!("[E2E][04] Putting an unencrypted value on a key expression with a key");
+        // The router doesn't hold the key: the sessions holding one drop its unencrypted values
+        ztimeout!(router
+            .put(secret, SECRET)
+            .encoding(KnownEncoding::TextPlain)
+            .res_async())
+        .unwrap();
+        task::sleep(SLEEP).await;
+        assert!(sub.try_recv().is_err());
+        assert!(intruder_sub.try_recv().is_err());
+
+        // The deletions carry no value and are not encrypted
+        ztimeout!(publisher.delete(secret).res_async()).unwrap();
+        let sample = ztimeout!(sub.recv_async()).unwrap();
+        assert_eq!(sample.key_expr.as_str(), secret);
+        assert_eq!(sample.kind, SampleKind::Delete);
+
+        println!("[E2E][05] Querying a key expression with a key");
+        let qbl = ztimeout!(publisher.declare_queryable(secret).res_async()).unwrap();
+        task::sleep(SLEEP).await;
+
+        #[cfg(feature = "unstable")]
+        let replies = ztimeout!(subscriber
+            .get(secret)
+            .with_value(Value::from("request").encoding(KnownEncoding::TextPlain.into()))
+            .res_async())
+        .unwrap();
+        #[cfg(not(feature = "unstable"))]
+        let replies = ztimeout!(subscriber.get(secret).res_async()).unwrap();
+        let query = ztimeout!(qbl.recv_async()).unwrap();
+        #[cfg(feature = "unstable")]
+        {
+            let value = query.value().unwrap();
+            assert_eq!(value.encoding, KnownEncoding::TextPlain.into());
+            assert_eq!(value.to_string(), "request");
+        }
+        ztimeout!(query
+            .reply(Ok(Sample::try_from(secret, SECRET).unwrap()))
+            .res_async())
+        .unwrap();
+        drop(query);
+
+        let reply = ztimeout!(replies.recv_async()).unwrap();
+        let sample = reply.sample.unwrap();
+        assert_eq!(sample.key_expr.as_str(), secret);
+        assert_eq!(sample.value.to_string(), SECRET);
+        assert!(ztimeout!(replies.recv_async()).is_err());
+
+        println!("[E2E][06] Querying a key expression with a wrong key");
+        let replies = ztimeout!(intruder.get(secret).res_async()).unwrap();
+        let query = ztimeout!(qbl.recv_async()).unwrap();
+        ztimeout!(query
+            .reply(Ok(Sample::try_from(secret, SECRET).unwrap()))
+            .res_async())
+        .unwrap();
+        drop(query);
+        assert!(ztimeout!(replies.recv_async()).is_err());
+
+        println!("[E2E][07] Closing sessions");
+        ztimeout!(qbl.undeclare().res_async()).unwrap();
+        ztimeout!(sub.undeclare().res_async()).unwrap();
+        ztimeout!(intruder_sub.undeclare().res_async()).unwrap();
+        ztimeout!(router_sub.undeclare().res_async()).unwrap();
+        ztimeout!(publisher.close().res_async()).unwrap();
+        ztimeout!(subscriber.close().res_async()).unwrap();
+        ztimeout!(intruder.close().res_async()).unwrap();
+        ztimeout!(router.close().res_async()).unwrap();
+    });
+}
@@ -0,0 +1,131 @@
+//
+// Copyright (c) 2022 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+use async_std::prelude::FutureExt;
+use async_std::task;
+use std::time::Duration;
+use zenoh::encoding::{CustomEncoding, EncodingRegistry};
+use zenoh::prelude::r#async::*;
+use zenoh_core::zasync_executor_init;
+use zenoh_result::bail;
+
+const TIMEOUT: Duration = Duration::from_secs(60);
+const SLEEP: Duration = Duration::from_secs(1);
+
+macro_rules! ztimeout {
+    ($f:expr) => {
+        $f.timeout(TIMEOUT).await.unwrap()
+    };
+}
+
+#[test]
+fn encoding_registry() {
+    let registry = EncodingRegistry::new();
+    let encoding = registry
+        .register(
+            CustomEncoding::new(1001, "application/vnd.acme.counter")
+                .schema(OwnedKeyExpr::new("acme/schemas/counter").unwrap())
+                .validator(|value| match value.to_string().parse::<u64>() {
+                    Ok(_) => Ok(()),
+                    Err(e) => bail!("Invalid counter: {}", e),
+                }),
+        )
+        .unwrap();
+    assert_eq!(encoding, Encoding::custom(1001));
+    assert_eq!(encoding.custom_id(), Some(1001));
+    assert_eq!(Encoding::APP_CUSTOM.custom_id(), None);
+
+    let custom = registry.resolve(&encoding).unwrap();
+    assert_eq!(custom.mime(), "application/vnd.acme.counter");
+    assert_eq!(
+        custom.schema_key_expr().unwrap().as_str(),
+        "acme/schemas/counter"
+    );
+    assert_eq!(registry.from_mime("application/vnd.acme.counter"), encoding);
+    assert_eq!(registry.from_mime("text/plain"), Encoding::TEXT_PLAIN);
+    assert_eq!(
+        registry.content_type(&encoding),
+        "application/vnd.acme.counter"
+    );
+    assert_eq!(
+        registry.content_type(&Encoding::APP_JSON),
+        "application/json"
+    );
+
+    assert!(registry
+        .validate(&Value::from("42").encoding(encoding.clone()))
+        .is_ok());
+    assert!(registry
+        .validate(&Value::from("forty-two").encoding(encoding.clone()))
+        .is_err());
+    assert!(registry
+        .validate(&Value::from("42").encoding(Encoding::custom(1002)))
+        .is_err());
+
+    // Conflicting registrations
+    assert!(registry
+        .register(CustomEncoding::new(1001, "application/vnd.acme.other"))
+        .is_err());
+    assert!(registry
+        .register(CustomEncoding::new(1002, "application/vnd.acme.counter"))
+        .is_err());
+    assert!(registry
+        .register(CustomEncoding::new(1003, "application/json"))
+        .is_err());
+    assert!(registry
+        .register(CustomEncoding::new(1001, "application/vnd.acme.counter"))
+        .is_ok());
+
+    assert!(registry.unregister(1001).is_some());
+    assert!(registry.resolve(&encoding).is_none());
+    assert_eq!(registry.content_type(&encoding), encoding.to_string());
+}
+
+#[test]
+fn encoding_custom_on_the_wire() {
+    task::block_on(async {
+        zasync_executor_init!();
+        let key_expr = "test/encoding/custom";
+        let encoding = zenoh::encoding::registry()
+            .register(CustomEncoding::new(2001, "application/vnd.acme.wire"))
+            .unwrap();
+
+        let mut config = config::peer();
+        config.listen.endpoints = vec!["tcp/127.0.0.1:18490".parse().unwrap()];
+        config.scouting.multicast.set_enabled(Some(false)).unwrap();
+        let peer01 = ztimeout!(zenoh::open(config).res_async()).unwrap();
+        let mut config = config::peer();
+        config.connect.endpoints = vec!["tcp/127.0.0.1:18490".parse().unwrap()];
+        config.scouting.multicast.set_enabled(Some(false)).unwrap();
+        let peer02 = ztimeout!(zenoh::open(config).res_async()).unwrap();
+
+        let sub = ztimeout!(peer01.declare_subscriber(key_expr).res_async()).unwrap();
+        task::sleep(SLEEP).await;
+
+        ztimeout!(peer02
+            .put(key_expr, "payload")
+            .encoding(encoding.clone())
+            .res_async())
+        .unwrap();
+        let sample = ztimeout!(sub.recv_async()).unwrap();
+        assert_eq!(sample.value.encoding, encoding);
+        let custom = zenoh::encoding::registry()
+            .resolve(&sample.value.encoding)
+            .unwrap();
+        assert_eq!(custom.mime(), "application/vnd.acme.wire");
+
+        ztimeout!(sub.undeclare().res_async()).unwrap();
+        ztimeout!(peer01.close().res_async()).unwrap();
+        ztimeout!(peer02.close().res_async()).unwrap();
+    });
+}
@@ -0,0 +1,211 @@
+//
+// Copyright (c) 2022 ZettaScale Technology
+//
+// This program and the accompanying materials are made available under the
+// terms of the Eclipse Public License 2.0 which is available at
+// http://www.eclipse.org/legal/epl-2.0, or the Apache License, Version 2.0
+// which is available at https://www.apache.org/licenses/LICENSE-2.0.
+//
+// SPDX-License-Identifier: EPL-2.0 OR Apache-2.0
+//
+// Contributors:
+//   ZettaScale Zenoh Team, <zenoh@zettascale.tech>
+//
+use async_std::prelude::FutureExt;
+use async_std::task;
+use std::str::FromStr;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+use zenoh::config::{EndPoint, WhatAmI};
+use zenoh::prelude::r#async::*;
+use zenoh_core::zasync_executor_init;
+
+const TIMEOUT: Duration = Duration::from_secs(60);
+const SLEEP: Duration = Duration::from_secs(1);
+
+const MSG_COUNT: usize = 10;
+
+macro_rules! ztimeout {
+    ($f:expr) => {
+        $f.timeout(TIMEOUT).await.unwrap()
+    };
+}
+
+async fn open_router(endpoint: &str) -> Session {
+    let mut config = config::default();
+    config.set_mode(Some(WhatAmI::Router)).unwrap();
+    config.listen.endpoints = vec![endpoint.parse().unwrap()];
+    config.scouting.multicast.set_enabled(Some(false)).unwrap();
+    ztimeout!(zenoh::open(config).res_async()).unwrap()
+}
+
+async fn open_client(endpoint: &str) -> Session {
+    let mut config = config::client([endpoint.parse::<EndPoint>().unwrap()]);
+    config.scouting.multicast.set_enabled(Some(false)).unwrap();
+    config.connect.retry.period_init_ms = 100;
+    config.connect.retry.period_max_ms = 500;
+    config.connect.retry.jitter = 0.5;
+    ztimeout!(zenoh::open(config).res_async()).unwrap()
+}
+
+async fn open_peer(listen: &str, connect: &[&str]) -> Session {
+    let mut config = config::peer();
+    config.listen.endpoints = vec![listen.parse().unwrap()];
+    config.connect.endpoints = connect.iter().map(|e| e.parse().unwrap()).collect();
+    config.scouting.multicast.set_enabled(Some(false)).unwrap();
+    // The peers only gossip their own locators in multihop mode
+    config.scouting.gossip.set_multihop(Some(true)).unwrap();
+    config.scouting.retry.period_init_ms = 100;
+    config.scouting.retry.period_max_ms = 500;
+    config.scouting.retry.max_attempts = 20;
+    ztimeout!(zenoh::open(config).res_async()).unwrap()
+}
+
+async fn wait_peer(session: &Session, zid: ZenohId) {
+    ztimeout!(async {
+        while !session
+            .info()
+            .peers_zid()
+            .res_async()
+            .await
+            .any(|z| z == zid)
+        {
+            task::sleep(SLEEP / 10).await;
+        }
+    });
+}
+
+#[test]
+fn zenoh_reconnect() {
+    task::block_on(async {
+        zasync_executor_init!();
+
+        let endpoint = "tcp/127.0.0.1:17449";
+        let key_expr = "test/reconnect";
+
+        println!("[RC][01] Opening router session");
+        let mut router = open_router(endpoint).await;
+
+        println!("[RC][02] Opening client sessions");
+        let client01 = open_client(endpoint).await;
+        let client02 = open_client(endpoint).await;
+
+        let msgs = Arc::new(AtomicUsize::new(0));
+        let c_msgs = msgs.clone();
+        let sub = ztimeout!(client01
+            .declare_subscriber(key_expr)
+            .callback(move |_| {
+                c_msgs.fetch_add(1, Ordering::SeqCst);
+            })
+            .res_async())
+        .unwrap();
+        let qbl = ztimeout!(client01
+            .declare_queryable(key_expr)
+            .callback(move |query| {
+                let rep = Sample::try_from(key_expr, vec![0u8; 8]).unwrap();
+                task::block_on(async { ztimeout!(query.reply(Ok(rep)).res_async()).unwrap() });
+            })
+            .res_async())
+        .unwrap();
+
+        for round in 0..2 {
+            // Wait for the declarations to propagate
+            task::sleep(SLEEP).await;
+
+            println!("[RC][03] Round {round}: putting {MSG_COUNT} msgs");
+            msgs.store(0, Ordering::SeqCst);
+            for _ in 0..MSG_COUNT {
+                ztimeout!(client02.put(key_expr, vec![0u8; 8]).res_async()).unwrap();
+            }
+            ztimeout!(async {
+                while msgs.load(Ordering::SeqCst) < MSG_COUNT {
+                    task::sleep(SLEEP / 10).await;
+                }
+            });
+
+            println!("[RC][04] Round {round}: getting");
+            let rs = ztimeout!(client02.get(key_expr).res_async()).unwrap();
+            let mut cnt = 0;
+            while let Ok(s) = ztimeout!(rs.recv_async()) {
+                assert!(s.sample.is_ok());
+                cnt += 1;
+            }
+            assert_eq!(cnt, 1);
+
+            if round == 0 {
+                println!("[RC][05] Killing the router");
+                ztimeout!(router.close().res_async()).unwrap();
+                task::sleep(SLEEP).await;
+
+                println!("[RC][06] Restarting the router");
+                router = open_router(endpoint).await;
+                let zid = router.zid();
+                // Wait for the clients to reconnect
+                ztimeout!(async {
+                    for client in [&client01, &client02] {
+                        while !client
+                            .info()
+                            .routers_zid()
+                            .res_async()
+                            .await
+                            .any(|z| z == zid)
+                        {
+                            task::sleep(SLEEP / 10).await;
+                        }
+                    }
+                });
+            }
+        }
+
+        ztimeout!(sub.undeclare().res_async()).unwrap();
+        ztimeout!(qbl.undeclare().res_async()).unwrap();
+        ztimeout!(client01.close().res_async()).unwrap();
+        ztimeout!(client02.close().res_async()).unwrap();
+        ztimeout!(router.close().res_async()).unwrap();
+    });
+}
+
+#[test]
+fn zenoh_reconnect_scouted_peer() {
+    task::block_on(async {
+        zasync_executor_init!();
+
+        let endpoint_a = "tcp/127.0.0.1:17453";
+        let endpoint_b = "tcp/127.0.0.1:17454";
+        let endpoint_c = "tcp/127.0.0.1:17455";
+        let zid_c = "c0ffee";
+
+        println!("[RS][01] Opening peer sessions");
+        let peer_a = open_peer(endpoint_a, &[]).await;
+        let peer_b = open_peer(endpoint_b, &[endpoint_a]).await;
+        // Only peer B connects to peer C, when scouting it through the gossip of peer A
+        let open_peer_c = |connect: Option<&'static str>| async move {
+            let mut config = config::peer();
+            config.set_id(ZenohId::from_str(zid_c).unwrap()).unwrap();
+            config.listen.endpoints = vec![endpoint_c.parse().unwrap()];
+            config.connect.endpoints = connect.iter().map(|e| e.parse().unwrap()).collect();
+            config.scouting.multicast.set_enabled(Some(false)).unwrap();
+            config.scouting.gossip.set_multihop(Some(true)).unwrap();
+            config
+                .insert_json5("scouting/gossip/autoconnect", r#""router""#)
+                .unwrap();
+            ztimeout!(zenoh::open(config).res_async()).unwrap()
+        };
+        let peer_c = open_peer_c(Some(endpoint_a)).await;
+        let zid_c = peer_c.zid();
+        wait_peer(&peer_b, zid_c).await;
+
+        println!("[RS][02] Restarting peer C, not connecting to any peer");
+        ztimeout!(peer_c.close().res_async()).unwrap();
+        task::sleep(SLEEP).await;
+        let peer_c = open_peer_c(None).await;
+
+        println!("[RS][03] Waiting for peer B to reconnect to the scouted peer C");
+        wait_peer(&peer_b, zid_c).await;
+
+        ztimeout!(peer_c.close().res_async()).unwrap();
+        ztimeout!(peer_b.close().res_async()).unwrap();
+        ztimeout!(peer_a.close().res_async()).unwrap();
+    });
+}